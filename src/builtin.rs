@@ -1,20 +1,34 @@
 pub mod array;
+pub mod boolean;
 pub mod class;
+pub mod complex;
+pub mod dir;
 pub mod enumerator;
+pub mod env;
 pub mod errorobj;
 pub mod fiber;
 pub mod file;
+pub mod fileutils;
 pub mod float;
 pub mod hash;
 pub mod integer;
+pub mod io;
 pub mod kernel;
+pub mod matchdata;
 pub mod math;
 pub mod method;
 pub mod module;
+pub mod nilclass;
+pub mod numeric;
 pub mod object;
+pub mod open3;
 pub mod process;
 pub mod procobj;
+pub mod random;
 pub mod range;
+pub mod rational;
 pub mod regexp;
 pub mod string;
 pub mod structobj;
+pub mod symbol;
+pub mod time;
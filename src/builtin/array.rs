@@ -1,3 +1,4 @@
+use crate::builtin::random::{random_kw_arg, random_upto};
 use crate::error::RubyError;
 use crate::*;
 
@@ -50,6 +51,9 @@ pub fn init_array(globals: &mut Globals) -> Value {
     globals.add_builtin_instance_method(class, "zip", zip);
     globals.add_builtin_instance_method(class, "grep", grep);
     globals.add_builtin_instance_method(class, "sort", sort);
+    globals.add_builtin_instance_method(class, "shuffle", shuffle);
+    globals.add_builtin_instance_method(class, "shuffle!", shuffle_);
+    globals.add_builtin_instance_method(class, "sample", sample);
     globals.add_builtin_class_method(obj, "new", array_new);
     obj
 }
@@ -305,7 +309,7 @@ fn flat_map(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
     let aref = self_val.as_array().unwrap();
     let method = vm.expect_block(args.block)?;
     let mut res = vec![];
-    let param_num = vm.get_iseq(method)?.params.req_params;
+    let param_num = vm.get_block_iseq(method)?.params.req_params;
     let mut arg = Args::new(param_num);
     for elem in &aref.elements {
         if param_num == 0 {
@@ -348,27 +352,9 @@ fn each(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
         }
     };
 
-    let mut arg = Args::new(vm.get_iseq(method)?.params.req_params);
+    // An Array element is expanded into block parameters in Context::from_args().
     for i in &aref.elements {
-        match i.as_array() {
-            Some(aref) if arg.len() != 1 => {
-                for j in 0..arg.len() {
-                    arg[j] = if j < aref.elements.len() {
-                        aref.elements[j]
-                    } else {
-                        Value::nil()
-                    };
-                }
-            }
-            _ => {
-                arg[0] = *i;
-                for j in 1..arg.len() {
-                    arg[j] = Value::nil();
-                }
-            }
-        };
-
-        vm.eval_block(method, &arg)?;
+        vm.eval_block(method, &Args::new1(*i))?;
     }
     Ok(self_val)
 }
@@ -602,13 +588,17 @@ fn pack(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
 
 fn join(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
     vm.check_args_range(args.len(), 0, 1)?;
+    // The default separator is $,.
     let sep = if args.len() == 0 {
-        ""
+        let id = vm.globals.get_ident_id("$,");
+        vm.get_global_var(id)
     } else {
-        match args[0].as_string() {
-            Some(s) => s,
-            None => return Err(vm.error_argument("Seperator must be String.")),
-        }
+        args[0]
+    };
+    let sep = match sep.as_string() {
+        Some(s) => s.to_string(),
+        None if sep.is_nil() => "".to_string(),
+        None => return Err(vm.error_argument("Seperator must be String.")),
     };
     let aref = self_val.as_array().unwrap();
     let mut res = "".to_string();
@@ -617,7 +607,7 @@ fn join(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
         if res.is_empty() {
             res = s.to_owned();
         } else {
-            res = res + sep + s.as_str();
+            res = res + &sep + s.as_str();
         }
     }
     Ok(Value::string(&vm.globals, res))
@@ -712,6 +702,91 @@ fn uniq(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
     Ok(Value::array_from(&vm.globals, v))
 }
 
+/// Shuffle `elements` in place in the same order as CRuby.
+fn shuffle_elements(
+    vm: &mut VM,
+    elements: &mut [Value],
+    rng: Option<Value>,
+) -> Result<(), RubyError> {
+    let mut i = elements.len();
+    while i > 0 {
+        let j = random_upto(vm, rng, i)?;
+        i -= 1;
+        elements.swap(i, j);
+    }
+    Ok(())
+}
+
+/// Array#shuffle(random: Random)
+fn shuffle(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let rng = random_kw_arg(vm, args)?;
+    let mut elements = self_val.as_array().unwrap().elements.clone();
+    shuffle_elements(vm, &mut elements, rng)?;
+    Ok(Value::array_from(&vm.globals, elements))
+}
+
+/// Array#shuffle!(random: Random)
+fn shuffle_(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let rng = random_kw_arg(vm, args)?;
+    let mut aref = self_val.as_array().unwrap();
+    shuffle_elements(vm, &mut aref.elements, rng)?;
+    Ok(self_val)
+}
+
+/// Array#sample(random: Random)
+/// Array#sample(n, random: Random)
+/// Elements are chosen in the same way as CRuby.
+fn sample(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_range(args.len(), 0, 1)?;
+    let rng = random_kw_arg(vm, args)?;
+    let aref = self_val.as_array().unwrap();
+    let len = aref.elements.len();
+    if args.len() == 0 {
+        return match len {
+            0 => Ok(Value::nil()),
+            1 => Ok(aref.elements[0]),
+            _ => {
+                let i = random_upto(vm, rng, len)?;
+                Ok(aref.elements[i])
+            }
+        };
+    }
+    let n = vm.expect_integer(args[0], "Sample number")?;
+    if n < 0 {
+        return Err(vm.error_argument("Negative sample number."));
+    }
+    let n = std::cmp::min(n as usize, len);
+    let mut res = vec![];
+    if n <= 10 {
+        // Pick indices without replacement, keeping them sorted to skip the chosen ones.
+        let mut rnds = vec![];
+        for i in 0..n {
+            rnds.push(random_upto(vm, rng, len - i)?);
+        }
+        let mut sorted: Vec<usize> = vec![];
+        for mut k in rnds {
+            let mut j = 0;
+            while j < sorted.len() && k >= sorted[j] {
+                k += 1;
+                j += 1;
+            }
+            sorted.insert(j, k);
+            res.push(aref.elements[k]);
+        }
+    } else {
+        let mut elements = aref.elements.clone();
+        for i in 0..n {
+            let j = random_upto(vm, rng, len - i)? + i;
+            elements.swap(i, j);
+        }
+        elements.truncate(n);
+        res = elements;
+    }
+    Ok(Value::array_from(&vm.globals, res))
+}
+
 #[cfg(test)]
 mod tests {
     use crate::test::*;
@@ -988,4 +1063,34 @@ mod tests {
         "#;
         assert_script(program);
     }
+
+    #[test]
+    fn shuffle_sample() {
+        let program = r#"
+        a = (1..20).to_a
+        b = a.shuffle(random: Random.new(42))
+        assert(b, a.shuffle(random: Random.new(42)))
+        assert(a, b.sort)
+        assert((1..20).to_a, a)
+        c = a.dup
+        assert(b, c.shuffle!(random: Random.new(42)))
+        assert(b, c)
+        assert([], [].shuffle)
+        assert(nil, [].sample)
+        assert(3, [3].sample)
+        r = Random.new(42)
+        assert(a.sample(random: Random.new(5)), a.sample(random: Random.new(5)))
+        assert(a.sample(3, random: Random.new(5)), a.sample(3, random: Random.new(5)))
+        assert(3, a.sample(3).uniq.size)
+        assert(15, a.sample(15).uniq.size)
+        assert(20, a.sample(30).uniq.size)
+        assert([], a.sample(0))
+        srand(7)
+        x = a.shuffle
+        srand(7)
+        assert(x, a.shuffle)
+        assert_error { a.sample(-1) }
+        "#;
+        assert_script(program);
+    }
 }
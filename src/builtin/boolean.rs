@@ -0,0 +1,90 @@
+use crate::*;
+
+pub fn init_trueclass(globals: &mut Globals) -> Value {
+    let id = globals.get_ident_id("TrueClass");
+    let class = ClassRef::from(id, globals.builtins.object);
+    let obj = Value::class(globals, class);
+    globals.add_builtin_instance_method(class, "to_s", to_s);
+    globals.add_builtin_instance_method(class, "inspect", to_s);
+    globals.add_builtin_instance_method(class, "&", true_and);
+    globals.add_builtin_instance_method(class, "|", true_or);
+    globals.add_builtin_instance_method(class, "^", true_xor);
+    obj
+}
+
+pub fn init_falseclass(globals: &mut Globals) -> Value {
+    let id = globals.get_ident_id("FalseClass");
+    let class = ClassRef::from(id, globals.builtins.object);
+    let obj = Value::class(globals, class);
+    globals.add_builtin_instance_method(class, "to_s", to_s);
+    globals.add_builtin_instance_method(class, "inspect", to_s);
+    globals.add_builtin_instance_method(class, "&", false_and);
+    globals.add_builtin_instance_method(class, "|", false_or);
+    globals.add_builtin_instance_method(class, "^", false_or);
+    obj
+}
+
+fn to_s(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let s = vm.val_to_s(self_val);
+    Ok(Value::string(&vm.globals, s))
+}
+
+fn true_and(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    Ok(Value::bool(vm.val_to_bool(args[0])))
+}
+
+fn true_or(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    Ok(Value::true_val())
+}
+
+fn true_xor(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    Ok(Value::bool(!vm.val_to_bool(args[0])))
+}
+
+fn false_and(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    Ok(Value::false_val())
+}
+
+fn false_or(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    Ok(Value::bool(vm.val_to_bool(args[0])))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::*;
+
+    #[test]
+    fn boolean() {
+        let program = r#"
+        assert(TrueClass, true.class)
+        assert(FalseClass, false.class)
+        assert("true", true.to_s)
+        assert("false", false.inspect)
+        assert(false, true & false)
+        assert(true, true & 1)
+        assert(false, true & nil)
+        assert(true, true | false)
+        assert(false, true ^ true)
+        assert(true, true ^ nil)
+        assert(false, false & true)
+        assert(true, false | 1)
+        assert(false, false | nil)
+        assert(true, false ^ true)
+        assert(true, true.is_a?(TrueClass))
+        assert(true, FalseClass === false)
+        class TrueClass
+          def to_i
+            1
+          end
+        end
+        assert(1, true.to_i)
+        "#;
+        assert_script(program);
+    }
+}
@@ -26,7 +26,7 @@ fn class_new(vm: &mut VM, _: Value, args: &Args) -> VMResult {
         Some(method) => {
             vm.class_push(val);
             let arg = Args::new1(val);
-            vm.eval_method(method, val, Some(vm.context()), &arg)?;
+            vm.eval_block_with_self(method, val, &arg)?;
             vm.class_pop();
         }
         None => {}
@@ -0,0 +1,420 @@
+use crate::builtin::numeric::{coerce_bin_op, to_f64};
+use crate::builtin::rational::to_rational;
+use crate::*;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ComplexInfo {
+    pub re: Value,
+    pub im: Value,
+}
+
+impl ComplexInfo {
+    pub fn new(re: Value, im: Value) -> Self {
+        ComplexInfo { re, im }
+    }
+
+    fn to_s_with(&self, vm: &mut VM, to_s: fn(&mut VM, Value) -> String) -> String {
+        let re = to_s(vm, self.re);
+        let (sign, im) = if is_negative(self.im) {
+            let im = negate(vm, self.im);
+            ("-", to_s(vm, im))
+        } else {
+            ("+", to_s(vm, self.im))
+        };
+        let star = match im.chars().last() {
+            Some(ch) if ch.is_ascii_digit() => "",
+            _ => "*",
+        };
+        format!("{}{}{}{}i", re, sign, im, star)
+    }
+
+    pub fn to_s(&self, vm: &mut VM) -> String {
+        self.to_s_with(vm, |vm, val| vm.val_to_s(val))
+    }
+
+    pub fn inspect(&self, vm: &mut VM) -> String {
+        format!("({})", self.to_s_with(vm, |vm, val| vm.val_inspect(val)))
+    }
+}
+
+pub fn init_complex(globals: &mut Globals) -> Value {
+    let id = globals.get_ident_id("Complex");
+    let class = ClassRef::from(id, globals.builtins.numeric);
+    globals.add_builtin_instance_method(class, "real", real);
+    globals.add_builtin_instance_method(class, "imaginary", imaginary);
+    globals.add_builtin_instance_method(class, "imag", imaginary);
+    globals.add_builtin_instance_method(class, "+", add);
+    globals.add_builtin_instance_method(class, "-", sub);
+    globals.add_builtin_instance_method(class, "*", mul);
+    globals.add_builtin_instance_method(class, "/", div);
+    globals.add_builtin_instance_method(class, "quo", div);
+    globals.add_builtin_instance_method(class, "**", pow);
+    globals.add_builtin_instance_method(class, "==", eq);
+    globals.add_builtin_instance_method(class, "coerce", coerce_);
+    globals.add_builtin_instance_method(class, "abs", abs);
+    globals.add_builtin_instance_method(class, "magnitude", abs);
+    globals.add_builtin_instance_method(class, "abs2", abs2);
+    globals.add_builtin_instance_method(class, "arg", arg);
+    globals.add_builtin_instance_method(class, "angle", arg);
+    globals.add_builtin_instance_method(class, "phase", arg);
+    globals.add_builtin_instance_method(class, "conjugate", conjugate);
+    globals.add_builtin_instance_method(class, "conj", conjugate);
+    globals.add_builtin_instance_method(class, "rectangular", rectangular);
+    globals.add_builtin_instance_method(class, "rect", rectangular);
+    globals.add_builtin_instance_method(class, "polar", polar);
+    globals.add_builtin_instance_method(class, "real?", real_);
+    globals.add_builtin_instance_method(class, "to_s", to_s);
+    globals.add_builtin_instance_method(class, "inspect", inspect);
+    let mut obj = Value::class(globals, class);
+    globals.add_builtin_class_method(obj, "rectangular", complex_rect);
+    globals.add_builtin_class_method(obj, "rect", complex_rect);
+    globals.add_builtin_class_method(obj, "polar", complex_polar);
+    // Complex::I needs the class to be registered.
+    globals.builtins.complex = obj;
+    let id = globals.get_ident_id("I");
+    let i = Value::complex(
+        globals,
+        ComplexInfo::new(Value::fixnum(0), Value::fixnum(1)),
+    );
+    obj.set_var(id, i);
+    obj
+}
+
+// Utils
+
+fn is_real(val: Value) -> bool {
+    val.as_fixnum().is_some() || val.as_flonum().is_some() || val.as_rational().is_some()
+}
+
+fn is_negative(val: Value) -> bool {
+    match val.unpack() {
+        RV::Integer(i) => i < 0,
+        RV::Float(f) => f.is_sign_negative(),
+        _ => match val.as_rational() {
+            Some(r) => r.num < 0,
+            None => false,
+        },
+    }
+}
+
+fn negate(vm: &mut VM, val: Value) -> Value {
+    match val.unpack() {
+        RV::Integer(i) => Value::fixnum(-i),
+        RV::Float(f) => Value::flonum(-f),
+        _ => match val.as_rational() {
+            Some(r) => Value::rational(&vm.globals, RationalInfo::new(-r.num, r.den)),
+            None => val,
+        },
+    }
+}
+
+/// Divide real numbers. Integers are divided into a Rational.
+fn quo(vm: &mut VM, lhs: Value, rhs: Value) -> VMResult {
+    match (to_rational(lhs), to_rational(rhs)) {
+        (Some(lhs), Some(rhs)) => {
+            if rhs.num == 0 {
+                return Err(vm.error_zero_division());
+            }
+            match lhs.div(&rhs) {
+                Some(info) => Ok(Value::rational(&vm.globals, info)),
+                None => Err(vm.error_unimplemented("Rational overflow. (Bignum is not supported)")),
+            }
+        }
+        _ => vm.eval_div_values(lhs, rhs),
+    }
+}
+
+fn new_complex(vm: &mut VM, re: Value, im: Value) -> Value {
+    Value::complex(&vm.globals, ComplexInfo::new(re, im))
+}
+
+fn expect_real(vm: &mut VM, val: Value) -> Result<Value, RubyError> {
+    if is_real(val) {
+        Ok(val)
+    } else {
+        let inspect = vm.val_inspect(val);
+        Err(vm.error_type(format!("Not a real. (given:{})", inspect)))
+    }
+}
+
+fn mul_complex(vm: &mut VM, lhs: ComplexInfo, rhs: ComplexInfo) -> Result<ComplexInfo, RubyError> {
+    let ac = vm.eval_mul_values(lhs.re, rhs.re)?;
+    let bd = vm.eval_mul_values(lhs.im, rhs.im)?;
+    let ad = vm.eval_mul_values(lhs.re, rhs.im)?;
+    let bc = vm.eval_mul_values(lhs.im, rhs.re)?;
+    let re = vm.eval_sub_values(ac, bd)?;
+    let im = vm.eval_add_values(ad, bc)?;
+    Ok(ComplexInfo::new(re, im))
+}
+
+fn div_complex(vm: &mut VM, lhs: ComplexInfo, rhs: ComplexInfo) -> Result<ComplexInfo, RubyError> {
+    let cc = vm.eval_mul_values(rhs.re, rhs.re)?;
+    let dd = vm.eval_mul_values(rhs.im, rhs.im)?;
+    let denom = vm.eval_add_values(cc, dd)?;
+    let ac = vm.eval_mul_values(lhs.re, rhs.re)?;
+    let bd = vm.eval_mul_values(lhs.im, rhs.im)?;
+    let bc = vm.eval_mul_values(lhs.im, rhs.re)?;
+    let ad = vm.eval_mul_values(lhs.re, rhs.im)?;
+    let re = vm.eval_add_values(ac, bd)?;
+    let im = vm.eval_sub_values(bc, ad)?;
+    let re = quo(vm, re, denom)?;
+    let im = quo(vm, im, denom)?;
+    Ok(ComplexInfo::new(re, im))
+}
+
+// Class methods
+
+/// Kernel#Complex(re, im = 0)
+pub fn complex(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    complex_rect(vm, Value::nil(), args)
+}
+
+fn complex_rect(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_range(args.len(), 1, 2)?;
+    if args.len() == 1 && args[0].as_complex().is_some() {
+        return Ok(args[0]);
+    }
+    let re = expect_real(vm, args[0])?;
+    let im = if args.len() == 2 {
+        expect_real(vm, args[1])?
+    } else {
+        Value::fixnum(0)
+    };
+    Ok(new_complex(vm, re, im))
+}
+
+fn complex_polar(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_range(args.len(), 1, 2)?;
+    let r = expect_real(vm, args[0])?;
+    if args.len() == 1 {
+        return Ok(new_complex(vm, r, Value::fixnum(0)));
+    }
+    let theta = expect_real(vm, args[1])?;
+    let (r, theta) = (to_f64(r).unwrap(), to_f64(theta).unwrap());
+    let re = Value::flonum(r * theta.cos());
+    let im = Value::flonum(r * theta.sin());
+    Ok(new_complex(vm, re, im))
+}
+
+// Instance methods
+
+fn real(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    Ok(self_val.as_complex().unwrap().re)
+}
+
+fn imaginary(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    Ok(self_val.as_complex().unwrap().im)
+}
+
+fn real_(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    Ok(Value::false_val())
+}
+
+macro_rules! define_add_sub {
+    ($fname:ident, $op:ident, $name:expr) => {
+        fn $fname(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+            vm.check_args_num(args.len(), 1)?;
+            let lhs = self_val.as_complex().unwrap();
+            let (re, im) = if let Some(rhs) = args[0].as_complex() {
+                let re = vm.$op(lhs.re, rhs.re)?;
+                let im = vm.$op(lhs.im, rhs.im)?;
+                (re, im)
+            } else if is_real(args[0]) {
+                let re = vm.$op(lhs.re, args[0])?;
+                (re, lhs.im)
+            } else {
+                return coerce_bin_op(vm, self_val, args[0], $name);
+            };
+            Ok(new_complex(vm, re, im))
+        }
+    };
+}
+
+define_add_sub!(add, eval_add_values, "+");
+define_add_sub!(sub, eval_sub_values, "-");
+
+fn mul(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let lhs = self_val.as_complex().unwrap();
+    let res = if let Some(rhs) = args[0].as_complex() {
+        mul_complex(vm, lhs, rhs)?
+    } else if is_real(args[0]) {
+        let re = vm.eval_mul_values(lhs.re, args[0])?;
+        let im = vm.eval_mul_values(lhs.im, args[0])?;
+        ComplexInfo::new(re, im)
+    } else {
+        return coerce_bin_op(vm, self_val, args[0], "*");
+    };
+    Ok(Value::complex(&vm.globals, res))
+}
+
+fn div(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let lhs = self_val.as_complex().unwrap();
+    let res = if let Some(rhs) = args[0].as_complex() {
+        div_complex(vm, lhs, rhs)?
+    } else if is_real(args[0]) {
+        let re = quo(vm, lhs.re, args[0])?;
+        let im = quo(vm, lhs.im, args[0])?;
+        ComplexInfo::new(re, im)
+    } else {
+        return coerce_bin_op(vm, self_val, args[0], "/");
+    };
+    Ok(Value::complex(&vm.globals, res))
+}
+
+fn pow(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let lhs = self_val.as_complex().unwrap();
+    if let Some(exp) = args[0].as_fixnum() {
+        // Exponentiation by squaring keeps exact components.
+        let mut res = ComplexInfo::new(Value::fixnum(1), Value::fixnum(0));
+        let mut base = lhs;
+        let mut n = exp.abs();
+        while n > 0 {
+            if n & 1 == 1 {
+                res = mul_complex(vm, res, base)?;
+            }
+            base = mul_complex(vm, base, base)?;
+            n >>= 1;
+        }
+        if exp < 0 {
+            let one = ComplexInfo::new(Value::fixnum(1), Value::fixnum(0));
+            res = div_complex(vm, one, res)?;
+        }
+        return Ok(Value::complex(&vm.globals, res));
+    }
+    let (c, d) = match args[0].as_complex() {
+        Some(rhs) => (to_f64(rhs.re).unwrap(), to_f64(rhs.im).unwrap()),
+        None => match to_f64(args[0]) {
+            Some(f) => (f, 0.0),
+            None => return coerce_bin_op(vm, self_val, args[0], "**"),
+        },
+    };
+    // z ** w = exp(w * log(z))
+    let (a, b) = (to_f64(lhs.re).unwrap(), to_f64(lhs.im).unwrap());
+    let (log_r, theta) = (a.hypot(b).ln(), b.atan2(a));
+    let (re, im) = (c * log_r - d * theta, d * log_r + c * theta);
+    let r = re.exp();
+    Ok(new_complex(
+        vm,
+        Value::flonum(r * im.cos()),
+        Value::flonum(r * im.sin()),
+    ))
+}
+
+fn eq(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    Ok(Value::bool(self_val.equal(args[0])))
+}
+
+/// Complex#coerce(other)
+fn coerce_(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let other = args[0];
+    let other = if other.as_complex().is_some() {
+        other
+    } else if is_real(other) {
+        new_complex(vm, other, Value::fixnum(0))
+    } else {
+        let name = vm.globals.get_class_name(other);
+        return Err(vm.error_type(format!("{} can't be coerced into Complex.", name)));
+    };
+    Ok(Value::array_from(&vm.globals, vec![other, self_val]))
+}
+
+fn abs(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let info = self_val.as_complex().unwrap();
+    let (re, im) = (to_f64(info.re).unwrap(), to_f64(info.im).unwrap());
+    Ok(Value::flonum(re.hypot(im)))
+}
+
+fn abs2(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let info = self_val.as_complex().unwrap();
+    let re = vm.eval_mul_values(info.re, info.re)?;
+    let im = vm.eval_mul_values(info.im, info.im)?;
+    vm.eval_add_values(re, im)
+}
+
+fn arg(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let info = self_val.as_complex().unwrap();
+    let (re, im) = (to_f64(info.re).unwrap(), to_f64(info.im).unwrap());
+    Ok(Value::flonum(im.atan2(re)))
+}
+
+fn conjugate(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let info = self_val.as_complex().unwrap();
+    let im = negate(vm, info.im);
+    Ok(new_complex(vm, info.re, im))
+}
+
+fn rectangular(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let info = self_val.as_complex().unwrap();
+    Ok(Value::array_from(&vm.globals, vec![info.re, info.im]))
+}
+
+fn polar(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    let abs = abs(vm, self_val, args)?;
+    let arg = arg(vm, self_val, args)?;
+    Ok(Value::array_from(&vm.globals, vec![abs, arg]))
+}
+
+fn to_s(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let s = self_val.as_complex().unwrap().to_s(vm);
+    Ok(Value::string(&vm.globals, s))
+}
+
+fn inspect(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let s = self_val.as_complex().unwrap().inspect(vm);
+    Ok(Value::string(&vm.globals, s))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::*;
+
+    #[test]
+    fn complex() {
+        let program = r#"
+            a = Complex(1, 2)
+            b = Complex(3, -4)
+            assert(1, a.real)
+            assert(2, a.imaginary)
+            assert("1+2i", a.to_s)
+            assert("(3-4i)", b.inspect)
+            assert("((1/2)+(1/3)*i)", Complex(Rational(1, 2), Rational(1, 3)).inspect)
+            assert(Complex(4, -2), a + b)
+            assert(Complex(-2, 6), a - b)
+            assert(Complex(11, 2), a * b)
+            assert(Complex(Rational(-1, 5), Rational(2, 5)), a / b)
+            assert(Complex(2, 2), a + 1)
+            assert(Complex(2, 2), 1 + a)
+            assert(Complex(2, 4), 2 * a)
+            assert(Complex(-3, 4), a ** 2)
+            assert(Complex(1, 0), a ** 0)
+            assert(5.0, b.abs)
+            assert(25, b.abs2)
+            assert(Complex(1, -2), a.conjugate)
+            assert([1, 2], a.rectangular)
+            assert(true, Complex(3, 0) == 3)
+            assert(Complex(0, 1), Complex::I)
+            assert(Complex(-1, 0), Complex::I * Complex::I)
+            assert(Complex(0, 2), 2i)
+            assert(Complex(1, 2), 1 + 2i)
+            assert(Complex(0, 2.5), 2.5i)
+            assert(Complex(0, Rational(1, 2)), 0.5ri)
+            assert_error { Complex("a", 1) }
+        "#;
+        assert_script(program);
+    }
+}
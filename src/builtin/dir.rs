@@ -0,0 +1,506 @@
+use std::os::unix::fs::DirBuilderExt;
+use std::path::*;
+//#[macro_use]
+use crate::*;
+
+pub const FNM_NOESCAPE: i32 = 0x01;
+pub const FNM_PATHNAME: i32 = 0x02;
+pub const FNM_DOTMATCH: i32 = 0x04;
+pub const FNM_CASEFOLD: i32 = 0x08;
+pub const FNM_EXTGLOB: i32 = 0x10;
+
+pub fn init_dir(globals: &mut Globals) -> Value {
+    let id = globals.get_ident_id("Dir");
+    let class = ClassRef::from(id, globals.builtins.object);
+    let obj = Value::class(globals, class);
+    globals.add_builtin_class_method(obj, "glob", glob);
+    globals.add_builtin_class_method(obj, "[]", glob);
+    globals.add_builtin_class_method(obj, "entries", entries);
+    globals.add_builtin_class_method(obj, "children", children);
+    globals.add_builtin_class_method(obj, "each_child", each_child);
+    globals.add_builtin_class_method(obj, "mkdir", mkdir);
+    globals.add_builtin_class_method(obj, "rmdir", rmdir);
+    globals.add_builtin_class_method(obj, "delete", rmdir);
+    globals.add_builtin_class_method(obj, "pwd", pwd);
+    globals.add_builtin_class_method(obj, "getwd", pwd);
+    globals.add_builtin_class_method(obj, "chdir", chdir);
+    globals.add_builtin_class_method(obj, "exist?", exist);
+    obj
+}
+
+// Utils
+
+fn string_to_path(vm: &mut VM, string: Value) -> Result<PathBuf, RubyError> {
+    let file = vm.expect_string(&string, "Must be string.")?;
+    Ok(PathBuf::from(file))
+}
+
+fn read_dir(vm: &mut VM, path: &Path) -> Result<Vec<String>, RubyError> {
+    let dir = match std::fs::read_dir(path) {
+        Ok(dir) => dir,
+        Err(err) => {
+            return Err(vm.error_internal(format!("Can not open directory. {:?}\n{:?}", path, err)))
+        }
+    };
+    let mut entries = vec![];
+    for entry in dir {
+        match entry {
+            Ok(entry) => entries.push(entry.file_name().to_string_lossy().into_owned()),
+            Err(err) => return Err(vm.error_internal(format!("{:?}", err))),
+        }
+    }
+    entries.sort();
+    Ok(entries)
+}
+
+/// Expand all brace alternatives in `pattern`. e.g. "a{b,c{d,e}}" => ["ab", "acd", "ace"]
+pub fn expand_braces(pattern: &str, flags: i32) -> Vec<String> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let noescape = flags & FNM_NOESCAPE != 0;
+    let mut depth = 0;
+    let mut start = 0;
+    let mut commas = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' if !noescape => i += 1,
+            '{' => {
+                if depth == 0 {
+                    start = i;
+                    commas.clear();
+                }
+                depth += 1;
+            }
+            ',' if depth == 1 => commas.push(i),
+            '}' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    let prefix: String = chars[..start].iter().collect();
+                    let suffix: String = chars[i + 1..].iter().collect();
+                    let mut res = vec![];
+                    let mut begin = start + 1;
+                    commas.push(i);
+                    for end in &commas {
+                        let alt: String = chars[begin..*end].iter().collect();
+                        let expanded = format!("{}{}{}", prefix, alt, suffix);
+                        res.append(&mut expand_braces(&expanded, flags));
+                        begin = end + 1;
+                    }
+                    return res;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    vec![pattern.to_string()]
+}
+
+/// Match `string` against shell glob `pattern` with File::FNM_* `flags`.
+pub fn fnmatch(pattern: &[char], string: &[char], flags: i32) -> bool {
+    if flags & FNM_DOTMATCH == 0 && string.first() == Some(&'.') && pattern.first() != Some(&'.') {
+        return false;
+    }
+    let noescape = flags & FNM_NOESCAPE != 0;
+    let pathname = flags & FNM_PATHNAME != 0;
+    let eq = |p: char, s: char| {
+        if flags & FNM_CASEFOLD != 0 {
+            p.to_lowercase().eq(s.to_lowercase())
+        } else {
+            p == s
+        }
+    };
+    let mut pi = 0;
+    let mut si = 0;
+    // Position of the last '*' in the pattern and the string position it matched up to.
+    let mut backtrack: Option<(usize, usize)> = None;
+    while si < string.len() {
+        if pi < pattern.len() {
+            let c = string[si];
+            match pattern[pi] {
+                '*' => {
+                    backtrack = Some((pi, si));
+                    pi += 1;
+                    continue;
+                }
+                '?' if !(pathname && c == '/') => {
+                    pi += 1;
+                    si += 1;
+                    continue;
+                }
+                '[' => {
+                    if let Some((matched, next)) = match_bracket(pattern, pi, c, flags) {
+                        if matched && !(pathname && c == '/') {
+                            pi = next;
+                            si += 1;
+                            continue;
+                        }
+                    } else if c == '[' {
+                        pi += 1;
+                        si += 1;
+                        continue;
+                    }
+                }
+                '\\' if !noescape && pi + 1 < pattern.len() => {
+                    if eq(pattern[pi + 1], c) {
+                        pi += 2;
+                        si += 1;
+                        continue;
+                    }
+                }
+                p => {
+                    if eq(p, c) {
+                        pi += 1;
+                        si += 1;
+                        continue;
+                    }
+                }
+            }
+        }
+        match backtrack {
+            Some((bp, bs)) if !(pathname && string[bs] == '/') => {
+                pi = bp + 1;
+                si = bs + 1;
+                backtrack = Some((bp, bs + 1));
+            }
+            _ => return false,
+        }
+    }
+    while pi < pattern.len() && pattern[pi] == '*' {
+        pi += 1;
+    }
+    pi == pattern.len()
+}
+
+/// Match `c` against the bracket expression starting at `pattern[start]`.
+/// Returns whether it matched and the position following the expression,
+/// or None if the bracket is not closed.
+fn match_bracket(pattern: &[char], start: usize, c: char, flags: i32) -> Option<(bool, usize)> {
+    let fold = |c: char| {
+        if flags & FNM_CASEFOLD != 0 {
+            c.to_lowercase().next().unwrap_or(c)
+        } else {
+            c
+        }
+    };
+    let c = fold(c);
+    let mut i = start + 1;
+    let negate = match pattern.get(i) {
+        Some('!') | Some('^') => {
+            i += 1;
+            true
+        }
+        _ => false,
+    };
+    let mut matched = false;
+    let mut first = true;
+    loop {
+        let mut lo = *pattern.get(i)?;
+        if lo == ']' && !first {
+            return Some((matched != negate, i + 1));
+        }
+        first = false;
+        if lo == '\\' && flags & FNM_NOESCAPE == 0 {
+            i += 1;
+            lo = *pattern.get(i)?;
+        }
+        i += 1;
+        let mut hi = lo;
+        if pattern.get(i) == Some(&'-') && pattern.get(i + 1).map_or(false, |ch| *ch != ']') {
+            hi = *pattern.get(i + 1)?;
+            i += 2;
+        }
+        if fold(lo) <= c && c <= fold(hi) {
+            matched = true;
+        }
+    }
+}
+
+fn has_magic(segment: &str) -> bool {
+    segment.contains(|c| c == '*' || c == '?' || c == '[')
+}
+
+fn unescape(segment: &str, flags: i32) -> String {
+    if flags & FNM_NOESCAPE != 0 {
+        return segment.to_string();
+    }
+    let mut res = String::new();
+    let mut chars = segment.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => res.extend(chars.next()),
+            c => res.push(c),
+        }
+    }
+    res
+}
+
+/// Walk the file system from `base` along `segments` of a glob pattern.
+/// `base` is empty (the current directory) or a path ending with '/'.
+fn glob_walk(base: &str, segments: &[&str], flags: i32, res: &mut Vec<String>) {
+    let all = segments;
+    let (recursive, segments) = if segments[0] == "**" && segments.len() > 1 {
+        (true, &segments[1..])
+    } else {
+        (false, segments)
+    };
+    // "**" at the end of the pattern behaves like "*".
+    let segment = if segments[0] == "**" {
+        "*"
+    } else {
+        segments[0]
+    };
+    let last = segments.len() == 1;
+    if !recursive && !has_magic(segment) {
+        let path = format!("{}{}", base, unescape(segment, flags));
+        if last {
+            if Path::new(&path).exists() {
+                res.push(path);
+            }
+        } else if Path::new(&path).is_dir() {
+            glob_walk(&format!("{}/", path), &segments[1..], flags, res);
+        }
+        return;
+    }
+    let dir = if base.is_empty() { "." } else { base };
+    let mut entries = match std::fs::read_dir(dir) {
+        Ok(dir) => dir
+            .filter_map(|entry| entry.ok())
+            .map(|entry| {
+                let is_real_dir = entry.file_type().map_or(false, |t| t.is_dir());
+                (
+                    entry.file_name().to_string_lossy().into_owned(),
+                    is_real_dir,
+                )
+            })
+            .collect::<Vec<_>>(),
+        Err(_) => return,
+    };
+    entries.sort();
+    let pattern: Vec<char> = segment.chars().collect();
+    for (name, is_real_dir) in entries {
+        let path = format!("{}{}", base, name);
+        let name_chars: Vec<char> = name.chars().collect();
+        if fnmatch(&pattern, &name_chars, flags) {
+            if last {
+                res.push(path.clone());
+            } else if Path::new(&path).is_dir() {
+                glob_walk(&format!("{}/", path), &segments[1..], flags, res);
+            }
+        }
+        // "**/" does not follow symbolic links.
+        if recursive && is_real_dir && (flags & FNM_DOTMATCH != 0 || !name.starts_with('.')) {
+            glob_walk(&format!("{}/", path), all, flags, res);
+        }
+    }
+}
+
+pub fn glob_pattern(pattern: &str, flags: i32) -> Vec<String> {
+    let mut res = vec![];
+    for pattern in expand_braces(pattern, flags) {
+        let (base, rest) = if pattern.starts_with('/') {
+            ("/", &pattern[1..])
+        } else {
+            ("", &pattern[..])
+        };
+        let segments: Vec<&str> = rest.split('/').filter(|s| !s.is_empty()).collect();
+        if !segments.is_empty() {
+            glob_walk(base, &segments, flags, &mut res);
+        }
+    }
+    res
+}
+
+// Class methods
+
+/// Dir.glob(pattern, flags = 0) -> Array
+/// Dir.glob(pattern, flags = 0) { |path| ... } -> nil
+fn glob(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_range(args.len(), 1, 2)?;
+    let flags = if args.len() == 2 {
+        vm.expect_integer(args[1], "Flags")? as i32
+    } else {
+        0
+    };
+    let patterns = match args[0].as_array() {
+        Some(aref) => aref.elements.clone(),
+        None => vec![args[0]],
+    };
+    let mut paths = vec![];
+    for pattern in patterns {
+        let pattern = vm.expect_string(&pattern, "Pattern")?.to_string();
+        paths.append(&mut glob_pattern(&pattern, flags));
+    }
+    let paths: Vec<Value> = paths
+        .into_iter()
+        .map(|path| Value::string(&vm.globals, path))
+        .collect();
+    match args.block {
+        Some(method) => {
+            for path in paths {
+                vm.eval_block(method, &Args::new1(path))?;
+            }
+            Ok(Value::nil())
+        }
+        None => Ok(Value::array_from(&vm.globals, paths)),
+    }
+}
+
+fn entries(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let path = string_to_path(vm, args[0])?;
+    let mut entries = vec![".".to_string(), "..".to_string()];
+    entries.append(&mut read_dir(vm, &path)?);
+    let entries = entries
+        .into_iter()
+        .map(|entry| Value::string(&vm.globals, entry))
+        .collect();
+    Ok(Value::array_from(&vm.globals, entries))
+}
+
+fn children(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let path = string_to_path(vm, args[0])?;
+    let entries = read_dir(vm, &path)?
+        .into_iter()
+        .map(|entry| Value::string(&vm.globals, entry))
+        .collect();
+    Ok(Value::array_from(&vm.globals, entries))
+}
+
+fn each_child(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let method = match args.block {
+        Some(method) => method,
+        None => {
+            let id = vm.globals.get_ident_id("each_child");
+            let val = Value::enumerator(&vm.globals, id, self_val, args.clone());
+            return Ok(val);
+        }
+    };
+    if method.is_none() {
+        return children(vm, self_val, args);
+    }
+    let path = string_to_path(vm, args[0])?;
+    for entry in read_dir(vm, &path)? {
+        let entry = Value::string(&vm.globals, entry);
+        vm.eval_block(method, &Args::new1(entry))?;
+    }
+    Ok(Value::nil())
+}
+
+/// Dir.mkdir(path, perm = 0777)
+fn mkdir(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_range(args.len(), 1, 2)?;
+    let path = string_to_path(vm, args[0])?;
+    let mut builder = std::fs::DirBuilder::new();
+    if args.len() == 2 {
+        let perm = args[1].expect_integer(vm, "Permission")?;
+        builder.mode(perm as u32);
+    }
+    match builder.create(&path) {
+        Ok(()) => Ok(Value::fixnum(0)),
+        Err(err) => {
+            Err(vm.error_internal(format!("Can not create directory. {:?}\n{:?}", path, err)))
+        }
+    }
+}
+
+fn rmdir(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let path = string_to_path(vm, args[0])?;
+    match std::fs::remove_dir(&path) {
+        Ok(()) => Ok(Value::fixnum(0)),
+        Err(err) => {
+            Err(vm.error_internal(format!("Can not remove directory. {:?}\n{:?}", path, err)))
+        }
+    }
+}
+
+fn pwd(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    match std::env::current_dir() {
+        Ok(path) => Ok(Value::string(
+            &vm.globals,
+            path.to_string_lossy().into_owned(),
+        )),
+        Err(err) => Err(vm.error_internal(format!("{:?}", err))),
+    }
+}
+
+/// Dir.chdir(path = ENV["HOME"]) -> 0
+/// Dir.chdir(path = ENV["HOME"]) { |path| ... } -> object
+/// With a block, the original working directory is restored when the block terminates.
+fn chdir(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_range(args.len(), 0, 1)?;
+    let path = if args.len() == 1 {
+        string_to_path(vm, args[0])?
+    } else {
+        match std::env::var("HOME") {
+            Ok(home) => PathBuf::from(home),
+            Err(_) => return Err(vm.error_argument("HOME is not set.")),
+        }
+    };
+    let prev = match std::env::current_dir() {
+        Ok(prev) => prev,
+        Err(err) => return Err(vm.error_internal(format!("{:?}", err))),
+    };
+    if let Err(err) = std::env::set_current_dir(&path) {
+        return Err(vm.error_internal(format!("Can not change directory. {:?}\n{:?}", path, err)));
+    };
+    match args.block {
+        Some(method) => {
+            let arg = Value::string(&vm.globals, path.to_string_lossy().into_owned());
+            let res = vm.eval_block(method, &Args::new1(arg));
+            if let Err(err) = std::env::set_current_dir(&prev) {
+                return Err(vm.error_internal(format!("{:?}", err)));
+            };
+            res
+        }
+        None => Ok(Value::fixnum(0)),
+    }
+}
+
+fn exist(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let path = string_to_path(vm, args[0])?;
+    Ok(Value::bool(path.is_dir()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test::*;
+
+    #[test]
+    fn dir() {
+        let program = r#"
+            FileUtils.rm_rf("dir_test")
+            Dir.mkdir("dir_test")
+            FileUtils.mkdir_p("dir_test/a/b")
+            FileUtils.touch(["dir_test/x.rb", "dir_test/a/y.rb", "dir_test/a/b/z.txt", "dir_test/.w.rb"])
+            assert(true, Dir.exist?("dir_test/a"))
+            assert(false, Dir.exist?("dir_test/x.rb"))
+            assert([".", "..", ".w.rb", "a", "x.rb"], Dir.entries("dir_test"))
+            assert([".w.rb", "a", "x.rb"], Dir.children("dir_test"))
+            assert(["dir_test/a/y.rb", "dir_test/x.rb"], Dir.glob("dir_test/**/*.rb"))
+            assert(["dir_test/.w.rb", "dir_test/a/y.rb", "dir_test/x.rb"], Dir.glob("dir_test/**/*.rb", File::FNM_DOTMATCH))
+            assert(["dir_test/x.rb", "dir_test/a/b/z.txt"], Dir.glob("dir_test/{x.rb,a/b/*}"))
+            assert(["dir_test/a/b/z.txt"], Dir["dir_test/*/[a-c]/?.txt"])
+            children = []
+            Dir.each_child("dir_test/a") { |c| children << c }
+            assert(["b", "y.rb"], children)
+            assert(File.expand_path("."), Dir.pwd)
+            assert(true, Dir.exist?("dir_test"))
+            FileUtils.cp("dir_test/x.rb", "dir_test/a")
+            FileUtils.mv("dir_test/a/x.rb", "dir_test/a/b/w.rb")
+            assert(["dir_test/a/b/w.rb", "dir_test/a/b/z.txt"], Dir.glob("dir_test/a/b/*"))
+            assert_error { FileUtils.cp("dir_test/a/b/w.rb", []) }
+            assert_error { FileUtils.mv("dir_test/a/b/w.rb", []) }
+            Dir.mkdir("dir_test/perm", 0700)
+            assert(0700, File.stat("dir_test/perm").mode & 0777)
+            FileUtils.rm_rf("dir_test")
+            assert(false, Dir.exist?("dir_test"))
+        "#;
+        assert_script(program);
+    }
+}
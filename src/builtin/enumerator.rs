@@ -9,7 +9,7 @@ pub struct EnumInfo {
 
 impl EnumInfo {
     pub fn new(method: IdentId, receiver: Value, mut args: Args) -> Self {
-        args.block = Some(MethodRef::from(0));
+        args.block = Some(Block::Block(MethodRef::from(0)));
         EnumInfo {
             method,
             receiver,
@@ -39,6 +39,7 @@ pub fn init_enumerator(globals: &mut Globals) -> Value {
     globals.add_builtin_instance_method(class, "map", map);
     globals.add_builtin_instance_method(class, "collect", map);
     globals.add_builtin_instance_method(class, "with_index", with_index);
+    globals.add_builtin_instance_method(class, "to_a", to_a);
     globals.add_builtin_instance_method(class, "inspect", inspect);
     let class = Value::class(globals, class);
     globals.add_builtin_class_method(class, "new", enum_new);
@@ -140,6 +141,12 @@ fn map(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
     Ok(Value::array_from(&vm.globals, res))
 }
 
+fn to_a(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let eref = vm.expect_enumerator(self_val, "Expect Enumerator.")?;
+    vm.eval_enumerator(eref)
+}
+
 fn with_index(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
     vm.check_args_num(args.len(), 0)?;
     let eref = vm.expect_enumerator(self_val, "Expect Enumerator.")?;
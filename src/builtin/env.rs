@@ -0,0 +1,188 @@
+use crate::*;
+use std::collections::HashMap;
+
+/// ENV is an ordinary object whose singleton methods access the process environment.
+pub fn init_env(globals: &mut Globals) -> Value {
+    let env = Value::ordinary_object(globals.builtins.object);
+    globals.add_builtin_class_method(env, "[]", get);
+    globals.add_builtin_class_method(env, "[]=", set);
+    globals.add_builtin_class_method(env, "store", set);
+    globals.add_builtin_class_method(env, "fetch", fetch);
+    globals.add_builtin_class_method(env, "key?", has_key);
+    globals.add_builtin_class_method(env, "has_key?", has_key);
+    globals.add_builtin_class_method(env, "include?", has_key);
+    globals.add_builtin_class_method(env, "member?", has_key);
+    globals.add_builtin_class_method(env, "delete", delete);
+    globals.add_builtin_class_method(env, "each", each);
+    globals.add_builtin_class_method(env, "each_pair", each);
+    globals.add_builtin_class_method(env, "keys", keys);
+    globals.add_builtin_class_method(env, "values", values);
+    globals.add_builtin_class_method(env, "to_h", to_h);
+    globals.add_builtin_class_method(env, "to_hash", to_h);
+    globals.add_builtin_class_method(env, "to_s", to_s);
+    globals.add_builtin_class_method(env, "inspect", inspect);
+    env
+}
+
+// Utils
+
+fn expect_name(vm: &mut VM, val: Value) -> Result<String, RubyError> {
+    let name = vm.expect_string(&val, "Environment variable name")?;
+    if name.is_empty() || name.contains('=') || name.contains('\0') {
+        return Err(vm.error_argument(format!("Invalid environment variable name: {}", name)));
+    }
+    Ok(name.clone())
+}
+
+fn env_var(vm: &mut VM, name: &str) -> Option<Value> {
+    std::env::var_os(name).map(|val| Value::string(&vm.globals, val.to_string_lossy().into_owned()))
+}
+
+fn env_pairs(vm: &mut VM) -> Vec<(Value, Value)> {
+    std::env::vars_os()
+        .map(|(key, val)| {
+            let key = Value::string(&vm.globals, key.to_string_lossy().into_owned());
+            let val = Value::string(&vm.globals, val.to_string_lossy().into_owned());
+            (key, val)
+        })
+        .collect()
+}
+
+// Singleton methods
+
+fn get(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let name = expect_name(vm, args[0])?;
+    Ok(env_var(vm, &name).unwrap_or_default())
+}
+
+/// ENV[name] = val
+/// Setting nil removes the variable.
+fn set(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 2)?;
+    let name = expect_name(vm, args[0])?;
+    if args[1].is_nil() {
+        std::env::remove_var(&name);
+    } else {
+        let val = vm.expect_string(&args[1], "Environment variable value")?;
+        std::env::set_var(&name, val);
+    }
+    Ok(args[1])
+}
+
+/// ENV.fetch(name [, default]) {|name| ... }
+fn fetch(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_range(args.len(), 1, 2)?;
+    let name = expect_name(vm, args[0])?;
+    if let Some(val) = env_var(vm, &name) {
+        return Ok(val);
+    }
+    if let Some(method) = args.block {
+        vm.eval_block(method, &Args::new1(args[0]))
+    } else if args.len() == 2 {
+        Ok(args[1])
+    } else {
+        Err(vm.error_argument(format!("Key not found: {:?}", name)))
+    }
+}
+
+fn has_key(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let name = expect_name(vm, args[0])?;
+    Ok(Value::bool(std::env::var_os(name).is_some()))
+}
+
+/// ENV.delete(name)
+/// Returns the deleted value, or nil if the variable does not exist.
+fn delete(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let name = expect_name(vm, args[0])?;
+    let val = env_var(vm, &name);
+    std::env::remove_var(&name);
+    match val {
+        Some(val) => Ok(val),
+        None => match args.block {
+            Some(method) => vm.eval_block(method, &Args::new1(args[0])),
+            None => Ok(Value::nil()),
+        },
+    }
+}
+
+fn each(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let method = match args.block {
+        Some(method) => method,
+        None => {
+            let id = vm.globals.get_ident_id("each");
+            return Ok(Value::enumerator(&vm.globals, id, self_val, args.clone()));
+        }
+    };
+    let mut arg = Args::new2(Value::nil(), Value::nil());
+    for (k, v) in env_pairs(vm) {
+        arg[0] = k;
+        arg[1] = v;
+        vm.eval_block(method, &arg)?;
+    }
+    Ok(self_val)
+}
+
+fn keys(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let keys = env_pairs(vm).into_iter().map(|(k, _)| k).collect();
+    Ok(Value::array_from(&vm.globals, keys))
+}
+
+fn values(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let values = env_pairs(vm).into_iter().map(|(_, v)| v).collect();
+    Ok(Value::array_from(&vm.globals, values))
+}
+
+fn to_h(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let mut map = HashMap::new();
+    for (k, v) in env_pairs(vm) {
+        map.insert(HashKey(k), v);
+    }
+    Ok(Value::hash_from(&vm.globals, map))
+}
+
+fn to_s(vm: &mut VM, _: Value, _args: &Args) -> VMResult {
+    Ok(Value::string(&vm.globals, "ENV".to_string()))
+}
+
+fn inspect(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    let hash = to_h(vm, Value::nil(), args)?;
+    let s = vm.val_inspect(hash);
+    Ok(Value::string(&vm.globals, s))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::*;
+
+    #[test]
+    fn env() {
+        let program = r#"
+            ENV["RURUBY_ENV_TEST"] = "foo"
+            assert("foo", ENV["RURUBY_ENV_TEST"])
+            assert(true, ENV.key?("RURUBY_ENV_TEST"))
+            assert("foo", ENV.fetch("RURUBY_ENV_TEST"))
+            assert("foo", ENV.to_h["RURUBY_ENV_TEST"])
+            assert(true, ENV.keys.include?("RURUBY_ENV_TEST"))
+            assert("foo\n", `echo $RURUBY_ENV_TEST`)
+            found = nil
+            ENV.each { |k, v| found = v if k == "RURUBY_ENV_TEST" }
+            assert("foo", found)
+            assert("foo", ENV.delete("RURUBY_ENV_TEST"))
+            assert(nil, ENV["RURUBY_ENV_TEST"])
+            assert(false, ENV.key?("RURUBY_ENV_TEST"))
+            assert("default", ENV.fetch("RURUBY_ENV_TEST", "default"))
+            assert("RURUBY_ENV_TEST!", ENV.fetch("RURUBY_ENV_TEST") { |k| k + "!" })
+            assert_error { ENV.fetch("RURUBY_ENV_TEST") }
+            assert(nil, ENV.delete("RURUBY_ENV_TEST"))
+            assert("ENV", ENV.to_s)
+        "#;
+        assert_script(program);
+    }
+}
@@ -5,3 +5,9 @@ pub fn init_error(globals: &mut Globals) -> Value {
     let class = ClassRef::from(id, globals.builtins.object);
     Value::class(globals, class)
 }
+
+pub fn init_argument_error(globals: &mut Globals) -> Value {
+    let id = globals.get_ident_id("ArgumentError");
+    let class = ClassRef::from(id, globals.builtins.object);
+    Value::class(globals, class)
+}
@@ -43,8 +43,19 @@ pub fn init_fiber(globals: &mut Globals) -> Value {
 
 fn new(vm: &mut VM, _: Value, args: &Args) -> VMResult {
     vm.check_args_num(args.len(), 0)?;
-    let method = vm.expect_block(args.block)?;
-    let mut context = vm.create_block_context(method)?;
+    let block = vm.expect_block(args.block)?;
+    let mut context = match block {
+        Block::Block(method) => vm.create_block_context(method)?,
+        Block::Proc(procobj) => {
+            let pref = procobj.as_proc().unwrap();
+            ContextRef::from(
+                pref.context.self_value,
+                None,
+                pref.context.iseq_ref,
+                pref.context.outer,
+            )
+        }
+    };
     context.is_fiber = true;
     let (tx0, rx0) = std::sync::mpsc::sync_channel(0);
     let (tx1, rx1) = std::sync::mpsc::sync_channel(0);
@@ -1,22 +1,132 @@
-use std::fs::File;
-use std::io::Read;
+use crate::builtin::dir;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::os::unix::io::AsRawFd;
 use std::path::*;
 //#[macro_use]
 use crate::*;
 
+#[derive(Debug)]
+pub struct FileInfo {
+    path: PathBuf,
+    reader: Option<BufReader<File>>,
+}
+
+pub type FileRef = Ref<FileInfo>;
+
+impl FileRef {
+    pub fn from(path: PathBuf, file: File) -> Self {
+        FileRef::new(FileInfo {
+            path,
+            reader: Some(BufReader::new(file)),
+        })
+    }
+}
+
+impl FileInfo {
+    /// Returns the underlying reader. The caller must ensure that the file is not closed.
+    fn reader(&mut self) -> &mut BufReader<File> {
+        self.reader.as_mut().unwrap()
+    }
+
+    pub fn close(&mut self) {
+        self.reader = None;
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.reader.is_none()
+    }
+}
+
 pub fn init_file(globals: &mut Globals) -> Value {
     let id = globals.get_ident_id("File");
     let class = ClassRef::from(id, globals.builtins.object);
-    let obj = Value::class(globals, class);
+    let mut obj = Value::class(globals, class);
     globals.add_builtin_class_method(obj, "join", join);
     globals.add_builtin_class_method(obj, "basename", basename);
     globals.add_builtin_class_method(obj, "extname", extname);
+    globals.add_builtin_class_method(obj, "dirname", dirname);
+    globals.add_builtin_class_method(obj, "expand_path", expand_path);
+    globals.add_builtin_class_method(obj, "absolute_path", absolute_path);
     globals.add_builtin_class_method(obj, "binread", binread);
     globals.add_builtin_class_method(obj, "read", read);
     globals.add_builtin_class_method(obj, "write", write);
+    globals.add_builtin_class_method(obj, "new", new);
+    globals.add_builtin_class_method(obj, "open", open);
+    globals.add_builtin_class_method(obj, "exist?", exist);
+    globals.add_builtin_class_method(obj, "file?", file);
+    globals.add_builtin_class_method(obj, "directory?", directory);
+    globals.add_builtin_class_method(obj, "size", size);
+    globals.add_builtin_class_method(obj, "mtime", mtime);
+    globals.add_builtin_class_method(obj, "stat", stat);
+    globals.add_builtin_class_method(obj, "delete", delete);
+    globals.add_builtin_class_method(obj, "unlink", delete);
+    globals.add_builtin_class_method(obj, "rename", rename);
+
+    globals.add_builtin_instance_method(class, "path", file_path);
+    globals.add_builtin_instance_method(class, "inspect", file_inspect);
+    globals.add_builtin_instance_method(class, "close", file_close);
+    globals.add_builtin_instance_method(class, "closed?", file_closed);
+    globals.add_builtin_instance_method(class, "each_line", file_each_line);
+    globals.add_builtin_instance_method(class, "each", file_each_line);
+    globals.add_builtin_instance_method(class, "gets", file_gets);
+    globals.add_builtin_instance_method(class, "read", file_read);
+    globals.add_builtin_instance_method(class, "seek", file_seek);
+    globals.add_builtin_instance_method(class, "pos", file_pos);
+    globals.add_builtin_instance_method(class, "tell", file_pos);
+    globals.add_builtin_instance_method(class, "pos=", file_set_pos);
+    globals.add_builtin_instance_method(class, "rewind", file_rewind);
+    globals.add_builtin_instance_method(class, "eof?", file_eof);
+    globals.add_builtin_instance_method(class, "write", file_write);
+    globals.add_builtin_instance_method(class, "<<", file_shl);
+    globals.add_builtin_instance_method(class, "puts", file_puts);
+    globals.add_builtin_instance_method(class, "truncate", file_truncate);
+    globals.add_builtin_instance_method(class, "flock", file_flock);
+    globals.add_builtin_instance_method(class, "size", file_size);
+
+    for (name, val) in &[
+        ("LOCK_SH", libc::LOCK_SH),
+        ("LOCK_EX", libc::LOCK_EX),
+        ("LOCK_NB", libc::LOCK_NB),
+        ("LOCK_UN", libc::LOCK_UN),
+        ("SEEK_SET", libc::SEEK_SET),
+        ("SEEK_CUR", libc::SEEK_CUR),
+        ("SEEK_END", libc::SEEK_END),
+        ("FNM_SYSCASE", 0),
+        ("FNM_NOESCAPE", dir::FNM_NOESCAPE),
+        ("FNM_PATHNAME", dir::FNM_PATHNAME),
+        ("FNM_DOTMATCH", dir::FNM_DOTMATCH),
+        ("FNM_CASEFOLD", dir::FNM_CASEFOLD),
+        ("FNM_EXTGLOB", dir::FNM_EXTGLOB),
+    ] {
+        let id = globals.get_ident_id(*name);
+        obj.set_var(id, Value::fixnum(*val as i64));
+    }
+
+    let id = globals.get_ident_id("Stat");
+    let stat_class = ClassRef::from(id, globals.builtins.object);
+    let stat_obj = Value::class(globals, stat_class);
+    globals.add_builtin_class_method(stat_obj, "new", stat_new);
+    globals.add_builtin_instance_method(stat_class, "size", stat_size);
+    globals.add_builtin_instance_method(stat_class, "mode", stat_mode);
+    globals.add_builtin_instance_method(stat_class, "mtime", stat_mtime);
+    globals.add_builtin_instance_method(stat_class, "file?", stat_file);
+    globals.add_builtin_instance_method(stat_class, "directory?", stat_directory);
+    obj.set_var(id, stat_obj);
     obj
 }
 
+/// Open the source file at `path` as DATA, positioned at `offset`.
+/// Returns None if the file can not be opened, e.g. for -e scripts.
+pub fn open_data(globals: &mut Globals, path: &PathBuf, offset: u64) -> Option<Value> {
+    let mut file = File::open(path).ok()?;
+    file.seek(SeekFrom::Start(offset)).ok()?;
+    let id = globals.get_ident_id("File");
+    let class = globals.builtins.object.get_var(id)?;
+    Some(Value::file(class, FileRef::from(path.clone(), file)))
+}
+
 // Utils
 
 fn string_to_path(vm: &mut VM, string: Value) -> Result<PathBuf, RubyError> {
@@ -24,6 +134,122 @@ fn string_to_path(vm: &mut VM, string: Value) -> Result<PathBuf, RubyError> {
     Ok(PathBuf::from(file))
 }
 
+fn io_error(vm: &VM, err: std::io::Error) -> RubyError {
+    vm.error_internal(format!("IO error. {:?}", err))
+}
+
+pub fn bytes_to_value(globals: &Globals, bytes: Vec<u8>) -> Value {
+    match String::from_utf8(bytes) {
+        Ok(s) => Value::string(globals, s),
+        Err(err) => Value::bytes(globals, err.into_bytes()),
+    }
+}
+
+/// Open the file at `path`. `perm` is the permission bits for a newly created file.
+fn open_file(
+    vm: &mut VM,
+    path: &PathBuf,
+    mode: &str,
+    perm: Option<u32>,
+) -> Result<File, RubyError> {
+    let mut options = OpenOptions::new();
+    if let Some(perm) = perm {
+        options.mode(perm);
+    }
+    // "b" (binary) and "t" (text) have no meaning here.
+    let mode: String = mode.chars().filter(|c| *c != 'b' && *c != 't').collect();
+    match mode.as_str() {
+        "r" => options.read(true),
+        "r+" => options.read(true).write(true),
+        "w" => options.write(true).create(true).truncate(true),
+        "w+" => options.read(true).write(true).create(true).truncate(true),
+        "a" => options.append(true).create(true),
+        "a+" => options.read(true).append(true).create(true),
+        _ => return Err(vm.error_argument(format!("Invalid access mode {}", mode))),
+    };
+    match options.open(path) {
+        Ok(file) => Ok(file),
+        Err(err) => Err(vm.error_internal(format!("Can not open file. {:?}\n{:?}", path, err))),
+    }
+}
+
+/// Normalize `path` lexically, resolving "." and ".." components.
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut res = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                res.pop();
+            }
+            c => res.push(c.as_os_str()),
+        }
+    }
+    res
+}
+
+/// Make `path` absolute, relative to `dir` or the current directory.
+fn make_absolute(vm: &mut VM, path: PathBuf, dir: Option<Value>) -> Result<PathBuf, RubyError> {
+    if path.is_absolute() {
+        return Ok(normalize_path(&path));
+    }
+    let base = match dir {
+        Some(dir) if !dir.is_nil() => {
+            let dir = string_to_path(vm, dir)?;
+            make_absolute(vm, dir, None)?
+        }
+        _ => match std::env::current_dir() {
+            Ok(dir) => dir,
+            Err(err) => return Err(io_error(vm, err)),
+        },
+    };
+    Ok(normalize_path(&base.join(path)))
+}
+
+fn metadata(vm: &mut VM, path: Value) -> Result<std::fs::Metadata, RubyError> {
+    let path = string_to_path(vm, path)?;
+    match std::fs::metadata(&path) {
+        Ok(metadata) => Ok(metadata),
+        Err(err) => Err(vm.error_internal(format!("Can not stat file. {:?}\n{:?}", path, err))),
+    }
+}
+
+fn metadata_mtime(globals: &Globals, metadata: &std::fs::Metadata) -> Value {
+    let duration = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+        .unwrap_or_default();
+    let time = TimeInfo::new(duration.as_secs() as i64, duration.subsec_nanos(), false);
+    Value::time(globals, time)
+}
+
+fn expect_file(vm: &VM, val: Value) -> Result<FileRef, RubyError> {
+    match val.as_file() {
+        Some(fref) if fref.is_closed() => Err(vm.error_internal("Closed stream.")),
+        Some(fref) => Ok(fref),
+        None => Err(vm.error_type("Receiver must be File.")),
+    }
+}
+
+fn read_line(vm: &mut VM, fref: &mut FileRef) -> Result<Option<Value>, RubyError> {
+    let mut buf = vec![];
+    match fref.reader().read_until(b'\n', &mut buf) {
+        Ok(0) => Ok(None),
+        Ok(_) => Ok(Some(bytes_to_value(&vm.globals, buf))),
+        Err(err) => Err(io_error(vm, err)),
+    }
+}
+
+fn write_str(vm: &mut VM, fref: &mut FileRef, s: &str) -> Result<(), RubyError> {
+    let reader = fref.reader();
+    // Discard the read buffer so that the write position matches the logical position.
+    let res = match reader.seek(SeekFrom::Current(0)) {
+        Ok(_) => reader.get_mut().write_all(s.as_bytes()),
+        Err(err) => Err(err),
+    };
+    res.map_err(|err| io_error(vm, err))
+}
 // Class methods
 
 fn join(vm: &mut VM, _: Value, args: &Args) -> VMResult {
@@ -129,6 +355,404 @@ fn write(vm: &mut VM, _: Value, args: &Args) -> VMResult {
     Ok(Value::fixnum(contents.len() as i64))
 }
 
+fn dirname(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let filename = string_to_path(vm, args[0])?;
+    let dirname = match filename.parent() {
+        Some(parent) if parent.as_os_str().is_empty() => ".".to_string(),
+        Some(parent) => parent.to_string_lossy().into_owned(),
+        None if filename.has_root() => "/".to_string(),
+        None => ".".to_string(),
+    };
+    Ok(Value::string(&vm.globals, dirname))
+}
+
+/// File.expand_path(path, dir = nil)
+fn expand_path(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_range(args.len(), 1, 2)?;
+    let path = vm.expect_string(&args[0], "1st arg")?.to_string();
+    let path = if path == "~" || path.starts_with("~/") {
+        match std::env::var("HOME") {
+            Ok(home) => {
+                PathBuf::from(home).join(path.trim_start_matches('~').trim_start_matches('/'))
+            }
+            Err(_) => return Err(vm.error_argument("Couldn't find login name -- expanding '~'")),
+        }
+    } else {
+        PathBuf::from(path)
+    };
+    let dir = if args.len() == 2 { Some(args[1]) } else { None };
+    let path = make_absolute(vm, path, dir)?;
+    Ok(Value::string(
+        &vm.globals,
+        path.to_string_lossy().into_owned(),
+    ))
+}
+
+/// File.absolute_path(path, dir = nil)
+fn absolute_path(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_range(args.len(), 1, 2)?;
+    let path = string_to_path(vm, args[0])?;
+    let dir = if args.len() == 2 { Some(args[1]) } else { None };
+    let path = make_absolute(vm, path, dir)?;
+    Ok(Value::string(
+        &vm.globals,
+        path.to_string_lossy().into_owned(),
+    ))
+}
+
+/// File.new(path, mode = "r", perm = 0666)
+fn new(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_range(args.len(), 1, 3)?;
+    let path = string_to_path(vm, args[0])?;
+    let mode = if args.len() > 1 {
+        vm.expect_string(&args[1], "Mode")?.to_string()
+    } else {
+        "r".to_string()
+    };
+    let perm = match args.get(2) {
+        Some(perm) => Some(perm.expect_integer(vm, "Permission")? as u32),
+        None => None,
+    };
+    let file = open_file(vm, &path, &mode, perm)?;
+    Ok(Value::file(self_val, FileRef::from(path, file)))
+}
+
+/// File.open(path, mode = "r", perm = 0666) { |file| ... }
+/// With a block, the file is closed when the block terminates and the value of the block is returned.
+fn open(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    let file = new(vm, self_val, args)?;
+    let method = match args.block {
+        Some(method) => method,
+        None => return Ok(file),
+    };
+    let res = vm.eval_block(method, &Args::new1(file));
+    file.as_file().unwrap().close();
+    res
+}
+
+fn exist(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let path = string_to_path(vm, args[0])?;
+    Ok(Value::bool(path.exists()))
+}
+
+fn file(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let path = string_to_path(vm, args[0])?;
+    Ok(Value::bool(path.is_file()))
+}
+
+fn directory(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let path = string_to_path(vm, args[0])?;
+    Ok(Value::bool(path.is_dir()))
+}
+
+fn size(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let metadata = metadata(vm, args[0])?;
+    Ok(Value::fixnum(metadata.len() as i64))
+}
+
+fn mtime(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let metadata = metadata(vm, args[0])?;
+    Ok(metadata_mtime(&vm.globals, &metadata))
+}
+
+fn stat(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    let id = vm.globals.get_ident_id("Stat");
+    let stat_class = self_val.get_var(id).unwrap();
+    stat_new(vm, stat_class, args)
+}
+
+/// File.delete(*paths)
+fn delete(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    for arg in args.iter() {
+        let path = string_to_path(vm, *arg)?;
+        match std::fs::remove_file(&path) {
+            Ok(()) => {}
+            Err(err) => {
+                return Err(
+                    vm.error_internal(format!("Can not delete file. {:?}\n{:?}", &path, err))
+                )
+            }
+        }
+    }
+    Ok(Value::fixnum(args.len() as i64))
+}
+
+fn rename(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 2)?;
+    let from = string_to_path(vm, args[0])?;
+    let to = string_to_path(vm, args[1])?;
+    match std::fs::rename(&from, &to) {
+        Ok(()) => Ok(Value::fixnum(0)),
+        Err(err) => Err(vm.error_internal(format!(
+            "Can not rename file. {:?} {:?}\n{:?}",
+            &from, &to, err
+        ))),
+    }
+}
+
+// Instance methods
+
+fn file_path(vm: &mut VM, self_val: Value, _args: &Args) -> VMResult {
+    let fref = self_val.as_file().unwrap();
+    Ok(Value::string(
+        &vm.globals,
+        fref.path.to_string_lossy().into_owned(),
+    ))
+}
+
+fn file_inspect(vm: &mut VM, self_val: Value, _args: &Args) -> VMResult {
+    let fref = self_val.as_file().unwrap();
+    let s = if fref.is_closed() {
+        format!("#<File:{} (closed)>", fref.path.to_string_lossy())
+    } else {
+        format!("#<File:{}>", fref.path.to_string_lossy())
+    };
+    Ok(Value::string(&vm.globals, s))
+}
+
+fn file_close(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    self_val.as_file().unwrap().close();
+    Ok(Value::nil())
+}
+
+fn file_closed(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    Ok(Value::bool(self_val.as_file().unwrap().is_closed()))
+}
+
+fn file_each_line(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let method = match args.block {
+        Some(method) => method,
+        None => {
+            let id = vm.globals.get_ident_id("each_line");
+            let val = Value::enumerator(&vm.globals, id, self_val, args.clone());
+            return Ok(val);
+        }
+    };
+    if method.is_none() {
+        let mut fref = expect_file(vm, self_val)?;
+        let mut ary = vec![];
+        while let Some(line) = read_line(vm, &mut fref)? {
+            ary.push(line);
+        }
+        return Ok(Value::array_from(&vm.globals, ary));
+    }
+    loop {
+        // The block may close the file, so check it on every iteration.
+        let mut fref = expect_file(vm, self_val)?;
+        match read_line(vm, &mut fref)? {
+            Some(line) => vm.eval_block(method, &Args::new1(line))?,
+            None => break,
+        };
+    }
+    Ok(self_val)
+}
+
+fn file_gets(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let mut fref = expect_file(vm, self_val)?;
+    Ok(read_line(vm, &mut fref)?.unwrap_or_default())
+}
+
+/// File#read(length = nil)
+fn file_read(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_range(args.len(), 0, 1)?;
+    let mut fref = expect_file(vm, self_val)?;
+    let mut buf = vec![];
+    if args.len() == 0 || args[0].is_nil() {
+        if let Err(err) = fref.reader().read_to_end(&mut buf) {
+            return Err(io_error(vm, err));
+        };
+        return Ok(bytes_to_value(&vm.globals, buf));
+    }
+    let length = vm.expect_integer(args[0], "Length")?;
+    if length < 0 {
+        return Err(vm.error_argument(format!("Negative length {} given.", length)));
+    }
+    match fref.reader().take(length as u64).read_to_end(&mut buf) {
+        Ok(0) if length > 0 => Ok(Value::nil()),
+        Ok(_) => Ok(bytes_to_value(&vm.globals, buf)),
+        Err(err) => Err(io_error(vm, err)),
+    }
+}
+
+/// File#seek(offset, whence = File::SEEK_SET)
+fn file_seek(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_range(args.len(), 1, 2)?;
+    let mut fref = expect_file(vm, self_val)?;
+    let offset = vm.expect_integer(args[0], "Offset")?;
+    let whence = if args.len() == 2 {
+        vm.expect_integer(args[1], "Whence")? as i32
+    } else {
+        libc::SEEK_SET
+    };
+    let pos = match whence {
+        libc::SEEK_SET if offset >= 0 => SeekFrom::Start(offset as u64),
+        libc::SEEK_CUR => SeekFrom::Current(offset),
+        libc::SEEK_END => SeekFrom::End(offset),
+        _ => return Err(vm.error_argument("Invalid offset or whence.")),
+    };
+    match fref.reader().seek(pos) {
+        Ok(_) => Ok(Value::fixnum(0)),
+        Err(err) => Err(io_error(vm, err)),
+    }
+}
+
+fn file_pos(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let mut fref = expect_file(vm, self_val)?;
+    match fref.reader().seek(SeekFrom::Current(0)) {
+        Ok(pos) => Ok(Value::fixnum(pos as i64)),
+        Err(err) => Err(io_error(vm, err)),
+    }
+}
+
+fn file_set_pos(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    file_seek(vm, self_val, args)?;
+    Ok(args[0])
+}
+
+fn file_rewind(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    file_seek(vm, self_val, &Args::new1(Value::fixnum(0)))
+}
+
+fn file_eof(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let mut fref = expect_file(vm, self_val)?;
+    match fref.reader().fill_buf() {
+        Ok(buf) => Ok(Value::bool(buf.is_empty())),
+        Err(err) => Err(io_error(vm, err)),
+    }
+}
+
+/// File#write(*objects) -> Integer
+fn file_write(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    let mut fref = expect_file(vm, self_val)?;
+    let mut len = 0;
+    for arg in args.iter() {
+        let s = vm.val_to_s(*arg);
+        write_str(vm, &mut fref, &s)?;
+        len += s.len();
+    }
+    Ok(Value::fixnum(len as i64))
+}
+
+fn file_shl(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    file_write(vm, self_val, args)?;
+    Ok(self_val)
+}
+
+fn file_puts(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    let mut fref = expect_file(vm, self_val)?;
+    if args.len() == 0 {
+        write_str(vm, &mut fref, "\n")?;
+    }
+    for arg in args.iter() {
+        let mut s = vm.val_to_s(*arg);
+        if !s.ends_with('\n') {
+            s.push('\n');
+        }
+        write_str(vm, &mut fref, &s)?;
+    }
+    Ok(Value::nil())
+}
+
+fn file_truncate(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let mut fref = expect_file(vm, self_val)?;
+    let len = vm.expect_integer(args[0], "Length")?;
+    if len < 0 {
+        return Err(vm.error_argument(format!("Negative length {} given.", len)));
+    }
+    match fref.reader().get_ref().set_len(len as u64) {
+        Ok(()) => Ok(Value::fixnum(0)),
+        Err(err) => Err(io_error(vm, err)),
+    }
+}
+
+/// File#flock(operation)
+/// Returns false if File::LOCK_NB is specified and the lock could not be acquired.
+fn file_flock(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let mut fref = expect_file(vm, self_val)?;
+    let operation = vm.expect_integer(args[0], "Operation")? as i32;
+    let fd = fref.reader().get_ref().as_raw_fd();
+    if unsafe { libc::flock(fd, operation) } == 0 {
+        return Ok(Value::fixnum(0));
+    }
+    let err = std::io::Error::last_os_error();
+    if operation & libc::LOCK_NB != 0 && err.raw_os_error() == Some(libc::EWOULDBLOCK) {
+        Ok(Value::false_val())
+    } else {
+        Err(io_error(vm, err))
+    }
+}
+
+fn file_size(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let mut fref = expect_file(vm, self_val)?;
+    match fref.reader().get_ref().metadata() {
+        Ok(metadata) => Ok(Value::fixnum(metadata.len() as i64)),
+        Err(err) => Err(io_error(vm, err)),
+    }
+}
+
+// File::Stat
+
+fn stat_new(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let metadata = metadata(vm, args[0])?;
+    let mut stat = Value::ordinary_object(self_val);
+    let vars = [
+        ("size", Value::fixnum(metadata.len() as i64)),
+        ("mode", Value::fixnum(metadata.permissions().mode() as i64)),
+        ("mtime", metadata_mtime(&vm.globals, &metadata)),
+        ("file?", Value::bool(metadata.is_file())),
+        ("directory?", Value::bool(metadata.is_dir())),
+    ];
+    for (name, val) in vars.iter() {
+        let id = vm.globals.get_ident_id(*name);
+        stat.set_var(id, *val);
+    }
+    Ok(stat)
+}
+
+fn stat_var(vm: &mut VM, self_val: Value, name: &str) -> VMResult {
+    let id = vm.globals.get_ident_id(name);
+    Ok(self_val.get_var(id).unwrap_or_default())
+}
+
+fn stat_size(vm: &mut VM, self_val: Value, _args: &Args) -> VMResult {
+    stat_var(vm, self_val, "size")
+}
+
+fn stat_mode(vm: &mut VM, self_val: Value, _args: &Args) -> VMResult {
+    stat_var(vm, self_val, "mode")
+}
+
+fn stat_mtime(vm: &mut VM, self_val: Value, _args: &Args) -> VMResult {
+    stat_var(vm, self_val, "mtime")
+}
+
+fn stat_file(vm: &mut VM, self_val: Value, _args: &Args) -> VMResult {
+    stat_var(vm, self_val, "file?")
+}
+
+fn stat_directory(vm: &mut VM, self_val: Value, _args: &Args) -> VMResult {
+    stat_var(vm, self_val, "directory?")
+}
+
 #[cfg(test)]
 mod tests {
     use crate::test::*;
@@ -145,4 +769,45 @@ mod tests {
         "#;
         assert_script(program);
     }
+
+    #[test]
+    fn file_open() {
+        let program = r#"
+            File.open("file_open.txt", "w") do |f|
+              f.write("foo\n")
+              f << "bar\n" << "baz\n"
+            end
+            assert(true, File.exist?("file_open.txt"))
+            assert(true, File.file?("file_open.txt"))
+            assert(false, File.directory?("file_open.txt"))
+            assert(12, File.size("file_open.txt"))
+            assert(12, File.stat("file_open.txt").size)
+            lines = []
+            File.open("file_open.txt") { |f| f.each_line { |line| lines << line } }
+            assert(["foo\n", "bar\n", "baz\n"], lines)
+            f = File.open("file_open.txt", "r+")
+            assert("foo\n", f.gets)
+            assert(4, f.pos)
+            assert("ba", f.read(2))
+            f.seek(0)
+            assert("foo\nbar\nbaz\n", f.read)
+            assert(nil, f.gets)
+            assert(0, f.flock(File::LOCK_EX))
+            f.truncate(4)
+            f.close
+            assert(true, f.closed?)
+            assert("foo\n", File.read("file_open.txt"))
+            File.rename("file_open.txt", "file_open2.txt")
+            assert(false, File.exist?("file_open.txt"))
+            assert(1, File.delete("file_open2.txt"))
+            File.new("file_open3.txt", "w", 0600).close
+            assert(0600, File.stat("file_open3.txt").mode & 0777)
+            File.delete("file_open3.txt")
+            assert("/usr", File.dirname("/usr/file.txt"))
+            assert(".", File.dirname("file.txt"))
+            assert("/usr/bin", File.expand_path("../bin", "/usr/lib"))
+            assert("/usr/lib/a", File.absolute_path("./a", "/usr/lib"))
+        "#;
+        assert_script(program);
+    }
 }
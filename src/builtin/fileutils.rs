@@ -0,0 +1,144 @@
+use std::os::unix::ffi::OsStrExt;
+use std::path::*;
+//#[macro_use]
+use crate::*;
+
+pub fn init_fileutils(globals: &mut Globals) -> Value {
+    let id = globals.get_ident_id("FileUtils");
+    let class = ClassRef::from(id, globals.builtins.object);
+    let obj = Value::module(globals, class);
+    globals.add_builtin_class_method(obj, "mkdir_p", mkdir_p);
+    globals.add_builtin_class_method(obj, "makedirs", mkdir_p);
+    globals.add_builtin_class_method(obj, "rm_rf", rm_rf);
+    globals.add_builtin_class_method(obj, "rm_r", rm_rf);
+    globals.add_builtin_class_method(obj, "cp", cp);
+    globals.add_builtin_class_method(obj, "mv", mv);
+    globals.add_builtin_class_method(obj, "touch", touch);
+    obj
+}
+
+// Utils
+
+/// Convert a String or an Array of Strings to paths.
+fn to_paths(vm: &mut VM, val: Value) -> Result<Vec<PathBuf>, RubyError> {
+    let list = match val.as_array() {
+        Some(aref) => aref.elements.clone(),
+        None => vec![val],
+    };
+    let mut paths = vec![];
+    for path in list {
+        let path = vm.expect_string(&path, "Path")?;
+        paths.push(PathBuf::from(path));
+    }
+    Ok(paths)
+}
+
+/// Returns the destination path. If `dest` is a directory, `src` is placed in it.
+fn dest_path(src: &Path, dest: PathBuf) -> PathBuf {
+    match src.file_name() {
+        Some(name) if dest.is_dir() => dest.join(name),
+        _ => dest,
+    }
+}
+
+/// Get the destination path of cp and mv.
+fn to_dest(vm: &mut VM, val: Value) -> Result<PathBuf, RubyError> {
+    match to_paths(vm, val)?.into_iter().next() {
+        Some(dest) => Ok(dest),
+        None => Err(vm.error_argument("No destination given.")),
+    }
+}
+
+fn paths_to_array(vm: &mut VM, paths: Vec<PathBuf>) -> Value {
+    let ary = paths
+        .iter()
+        .map(|path| Value::string(&vm.globals, path.to_string_lossy().into_owned()))
+        .collect();
+    Value::array_from(&vm.globals, ary)
+}
+
+// Class methods
+
+/// FileUtils.mkdir_p(list)
+fn mkdir_p(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let paths = to_paths(vm, args[0])?;
+    for path in &paths {
+        if let Err(err) = std::fs::create_dir_all(path) {
+            return Err(
+                vm.error_internal(format!("Can not create directory. {:?}\n{:?}", path, err))
+            );
+        }
+    }
+    Ok(paths_to_array(vm, paths))
+}
+
+/// FileUtils.rm_rf(list)
+/// Errors are ignored.
+fn rm_rf(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let paths = to_paths(vm, args[0])?;
+    for path in &paths {
+        let is_real_dir = std::fs::symlink_metadata(path).map_or(false, |m| m.is_dir());
+        let _ = if is_real_dir {
+            std::fs::remove_dir_all(path)
+        } else {
+            std::fs::remove_file(path)
+        };
+    }
+    Ok(paths_to_array(vm, paths))
+}
+
+/// FileUtils.cp(src, dest)
+fn cp(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 2)?;
+    let dest = to_dest(vm, args[1])?;
+    for src in to_paths(vm, args[0])? {
+        let dest = dest_path(&src, dest.clone());
+        if let Err(err) = std::fs::copy(&src, &dest) {
+            return Err(vm.error_internal(format!(
+                "Can not copy file. {:?} {:?}\n{:?}",
+                src, dest, err
+            )));
+        }
+    }
+    Ok(Value::nil())
+}
+
+/// FileUtils.mv(src, dest)
+fn mv(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 2)?;
+    let dest = to_dest(vm, args[1])?;
+    for src in to_paths(vm, args[0])? {
+        let dest = dest_path(&src, dest.clone());
+        if let Err(err) = std::fs::rename(&src, &dest) {
+            return Err(vm.error_internal(format!(
+                "Can not move file. {:?} {:?}\n{:?}",
+                src, dest, err
+            )));
+        }
+    }
+    Ok(Value::fixnum(0))
+}
+
+/// FileUtils.touch(list)
+/// Update modification times, or create empty files if they do not exist.
+fn touch(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let paths = to_paths(vm, args[0])?;
+    for path in &paths {
+        let res = if path.exists() {
+            let cpath = std::ffi::CString::new(path.as_os_str().as_bytes()).unwrap();
+            match unsafe { libc::utimes(cpath.as_ptr(), std::ptr::null()) } {
+                0 => Ok(()),
+                _ => Err(std::io::Error::last_os_error()),
+            }
+        } else {
+            std::fs::File::create(path).map(|_| ())
+        };
+        if let Err(err) = res {
+            return Err(vm.error_internal(format!("Can not touch file. {:?}\n{:?}", path, err)));
+        }
+    }
+    Ok(paths_to_array(vm, paths))
+}
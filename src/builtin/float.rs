@@ -1,11 +1,67 @@
+use crate::builtin::numeric::{coerce, coerce_bin_op};
 use crate::*;
 
 pub fn init(globals: &mut Globals) -> Value {
     let id = globals.get_ident_id("Float");
-    let class = ClassRef::from(id, globals.builtins.object);
+    let class = ClassRef::from(id, globals.builtins.numeric);
     globals.add_builtin_instance_method(class, "<=>", cmp);
+    globals.add_builtin_instance_method(class, "+", add);
+    globals.add_builtin_instance_method(class, "-", sub);
+    globals.add_builtin_instance_method(class, "*", mul);
+    globals.add_builtin_instance_method(class, "/", div);
+    globals.add_builtin_instance_method(class, "%", rem);
+    globals.add_builtin_instance_method(class, "modulo", rem);
+    globals.add_builtin_instance_method(class, "**", pow);
+    globals.add_builtin_instance_method(class, "divmod", divmod);
     globals.add_builtin_instance_method(class, "floor", floor);
-    Value::class(globals, class)
+    globals.add_builtin_instance_method(class, "ceil", ceil);
+    globals.add_builtin_instance_method(class, "round", round);
+    globals.add_builtin_instance_method(class, "truncate", truncate);
+    globals.add_builtin_instance_method(class, "to_i", to_i);
+    globals.add_builtin_instance_method(class, "to_int", to_i);
+    globals.add_builtin_instance_method(class, "to_f", to_f);
+    globals.add_builtin_instance_method(class, "to_s", to_s);
+    globals.add_builtin_instance_method(class, "inspect", to_s);
+    globals.add_builtin_instance_method(class, "to_r", to_r);
+    globals.add_builtin_instance_method(class, "abs", abs);
+    globals.add_builtin_instance_method(class, "magnitude", abs);
+    globals.add_builtin_instance_method(class, "nan?", nan);
+    globals.add_builtin_instance_method(class, "infinite?", infinite);
+    globals.add_builtin_instance_method(class, "finite?", finite);
+    let mut obj = Value::class(globals, class);
+    let constants = [
+        ("INFINITY", std::f64::INFINITY),
+        ("NAN", std::f64::NAN),
+        ("EPSILON", std::f64::EPSILON),
+        ("MAX", std::f64::MAX),
+        ("MIN", std::f64::MIN_POSITIVE),
+    ];
+    for (name, f) in constants.iter() {
+        let id = globals.get_ident_id(*name);
+        obj.set_var(id, Value::flonum(*f));
+    }
+    obj
+}
+
+/// Convert f64 into a String in the same format as Float#to_s.
+pub fn float_to_s(f: f64) -> String {
+    if f.is_nan() {
+        "NaN".to_string()
+    } else if f.is_infinite() {
+        if f > 0.0 { "Infinity" } else { "-Infinity" }.to_string()
+    } else if f != 0.0 && (f.abs() < 1e-4 || f.abs() >= 1e16) {
+        // Exponent form, e.g. 1.0e+100 and 1.5e-09.
+        let s = format!("{:e}", f);
+        let (mantissa, exp) = s.split_at(s.find('e').unwrap());
+        let exp: i32 = exp[1..].parse().unwrap();
+        let dot = if mantissa.contains('.') { "" } else { ".0" };
+        let sign = if exp < 0 { '-' } else { '+' };
+        format!("{}{}e{}{:02}", mantissa, dot, sign, exp.abs())
+    } else if f.fract() == 0.0 {
+        format!("{:.1}", f)
+    } else {
+        f.to_string()
+    }
 }
 
 // Class methods
@@ -19,7 +75,12 @@ fn cmp(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
     let res = match args[0].unpack() {
         RV::Integer(rhs) => lhs.partial_cmp(&(rhs as f64)),
         RV::Float(rhs) => lhs.partial_cmp(&rhs),
-        _ => return Ok(Value::nil()),
+        _ => {
+            return match coerce(vm, self_val, args[0])? {
+                Some((lhs, rhs)) => vm.eval_cmp(rhs, lhs),
+                None => Ok(Value::nil()),
+            }
+        }
     };
     match res {
         Some(ord) => Ok(Value::fixnum(ord as i64)),
@@ -27,10 +88,182 @@ fn cmp(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
     }
 }
 
+macro_rules! define_op {
+    ($fname:ident, $op:tt, $name:expr) => {
+        fn $fname(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+            vm.check_args_num(args.len(), 1)?;
+            let lhs = self_val.as_flonum().unwrap();
+            match args[0].unpack() {
+                RV::Integer(rhs) => Ok(Value::flonum(lhs $op rhs as f64)),
+                RV::Float(rhs) => Ok(Value::flonum(lhs $op rhs)),
+                _ => coerce_bin_op(vm, self_val, args[0], $name),
+            }
+        }
+    };
+}
+
+define_op!(add, +, "+");
+define_op!(sub, -, "-");
+define_op!(mul, *, "*");
+define_op!(div, /, "/");
+
+fn rem_floor(lhs: f64, rhs: f64) -> f64 {
+    lhs - rhs * (lhs / rhs).floor()
+}
+
+fn rem(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let lhs = self_val.as_flonum().unwrap();
+    match args[0].unpack() {
+        RV::Integer(rhs) => Ok(Value::flonum(rem_floor(lhs, rhs as f64))),
+        RV::Float(rhs) => Ok(Value::flonum(rem_floor(lhs, rhs))),
+        _ => coerce_bin_op(vm, self_val, args[0], "%"),
+    }
+}
+
+fn pow(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let lhs = self_val.as_flonum().unwrap();
+    match args[0].unpack() {
+        RV::Integer(rhs) => Ok(Value::flonum(lhs.powf(rhs as f64))),
+        RV::Float(rhs) => Ok(Value::flonum(lhs.powf(rhs))),
+        _ => coerce_bin_op(vm, self_val, args[0], "**"),
+    }
+}
+
+/// Float#divmod(other)
+/// Returns [(self / other).floor, self % other].
+fn divmod(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let lhs = self_val.as_flonum().unwrap();
+    let rhs = match args[0].unpack() {
+        RV::Integer(rhs) => rhs as f64,
+        RV::Float(rhs) => rhs,
+        _ => return coerce_bin_op(vm, self_val, args[0], "divmod"),
+    };
+    if rhs == 0.0 {
+        return Err(vm.error_zero_division());
+    }
+    let div = f64_to_integer(vm, (lhs / rhs).floor())?;
+    let rem = Value::flonum(rem_floor(lhs, rhs));
+    Ok(Value::array_from(&vm.globals, vec![div, rem]))
+}
+
+fn f64_to_integer(vm: &mut VM, f: f64) -> VMResult {
+    if f.is_nan() {
+        Err(vm.error_argument("NaN can not be converted to Integer."))
+    } else if f.is_infinite() {
+        Err(vm.error_argument(format!(
+            "{} can not be converted to Integer.",
+            float_to_s(f)
+        )))
+    } else if f.abs() >= 9.223_372_036_854_776e18 {
+        Err(vm.error_unimplemented("Bignum is not supported."))
+    } else {
+        Ok(Value::fixnum(f as i64))
+    }
+}
+
+/// Round `self` to `ndigits` decimal digits by `func`.
+/// Returns an Integer if `ndigits` is not positive, otherwise a Float.
+fn round_by(vm: &mut VM, self_val: Value, args: &Args, func: fn(f64) -> f64) -> VMResult {
+    vm.check_args_range(args.len(), 0, 1)?;
+    let num = self_val.as_flonum().unwrap();
+    let ndigits = if args.len() == 1 {
+        vm.expect_integer(args[0], "ndigits")?
+    } else {
+        0
+    };
+    if ndigits > 0 {
+        if ndigits >= 16 || !num.is_finite() {
+            return Ok(self_val);
+        }
+        let unit = 10f64.powi(ndigits as i32);
+        Ok(Value::flonum(func(num * unit) / unit))
+    } else {
+        let unit = 10f64.powi(-ndigits as i32);
+        f64_to_integer(vm, func(num / unit) * unit)
+    }
+}
+
+/// Float#floor(ndigits = 0)
 fn floor(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    round_by(vm, self_val, args, f64::floor)
+}
+
+/// Float#ceil(ndigits = 0)
+fn ceil(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    round_by(vm, self_val, args, f64::ceil)
+}
+
+/// Float#round(ndigits = 0)
+/// Rounds half away from zero.
+fn round(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    round_by(vm, self_val, args, f64::round)
+}
+
+/// Float#truncate(ndigits = 0)
+fn truncate(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    round_by(vm, self_val, args, f64::trunc)
+}
+
+fn to_i(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
     vm.check_args_num(args.len(), 0)?;
-    let lhs = self_val.as_flonum().unwrap();
-    Ok(Value::fixnum(lhs.floor() as i64))
+    let num = self_val.as_flonum().unwrap();
+    f64_to_integer(vm, num.trunc())
+}
+
+fn to_f(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    Ok(self_val)
+}
+
+fn to_s(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let num = self_val.as_flonum().unwrap();
+    Ok(Value::string(&vm.globals, float_to_s(num)))
+}
+
+fn to_r(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let num = self_val.as_flonum().unwrap();
+    match RationalInfo::from_f64(num) {
+        Some(info) => Ok(Value::rational(&vm.globals, info)),
+        None => Err(vm.error_argument(format!(
+            "{} can not be converted to Rational.",
+            float_to_s(num)
+        ))),
+    }
+}
+
+fn abs(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let num = self_val.as_flonum().unwrap();
+    Ok(Value::flonum(num.abs()))
+}
+
+fn nan(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let num = self_val.as_flonum().unwrap();
+    Ok(Value::bool(num.is_nan()))
+}
+
+/// Float#infinite?
+/// Returns 1 for +Infinity, -1 for -Infinity, and nil otherwise.
+fn infinite(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let num = self_val.as_flonum().unwrap();
+    if num.is_infinite() {
+        Ok(Value::fixnum(if num > 0.0 { 1 } else { -1 }))
+    } else {
+        Ok(Value::nil())
+    }
+}
+
+fn finite(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let num = self_val.as_flonum().unwrap();
+    Ok(Value::bool(num.is_finite()))
 }
 
 #[cfg(test)]
@@ -49,4 +282,45 @@ mod tests {
     ";
         assert_script(program);
     }
+
+    #[test]
+    fn float_methods() {
+        let program = r#"
+        assert(1.23, 1.234.round(2))
+        assert(2, 1.5.round)
+        assert(-2, (-1.5).round)
+        assert(1200, 1234.5.round(-2))
+        assert(1.3, 1.21.ceil(1))
+        assert(2, 1.2.ceil)
+        assert(1.2, 1.29.floor(1))
+        assert(-1, (-1.7).truncate)
+        assert(-1, (-1.7).to_i)
+        assert(1.5, (-1.5).abs)
+        assert(true, Float::NAN.nan?)
+        assert(false, 1.0.nan?)
+        assert(1, Float::INFINITY.infinite?)
+        assert(-1, (-Float::INFINITY).infinite?)
+        assert(nil, 1.0.infinite?)
+        assert(false, Float::INFINITY.finite?)
+        assert(true, Float::EPSILON < 0.001)
+        assert("Infinity", Float::INFINITY.to_s)
+        assert("NaN", Float::NAN.to_s)
+        assert("3.0", 3.0.to_s)
+        assert("1.0e+100", 1e100.to_s)
+        assert("1.0e-09", 1e-9.to_s)
+        assert("-1.5e+16", (-1.5e16).to_s)
+        assert("1.0e-05", 0.00001.to_s)
+        assert("0.0001", 0.0001.to_s)
+        assert("1.0e+16", 10000000000000000.0.to_s)
+        assert("1000000000000000.0", 1000000000000000.0.to_s)
+        assert([3, 0.5], 3.5.divmod(1))
+        assert(1.5, 7.5 % 2)
+        assert(0.5, -7.5 % 2)
+        assert(Rational(3, 2), 1.5.to_r)
+        assert(true, 2.0.is_a?(Numeric))
+        assert_error { Float::NAN.to_i }
+        assert_error { Float::INFINITY.round }
+    "#;
+        assert_script(program);
+    }
 }
@@ -30,9 +30,125 @@ pub fn init_hash(globals: &mut Globals) -> Value {
     globals.add_builtin_instance_method(class, "compare_by_identity", compare_by_identity);
     globals.add_builtin_instance_method(class, "sort", sort);
     globals.add_builtin_instance_method(class, "invert", invert);
-    Value::class(globals, class)
+    globals.add_builtin_instance_method(class, "[]", get_elem);
+    globals.add_builtin_instance_method(class, "[]=", store);
+    globals.add_builtin_instance_method(class, "store", store);
+    globals.add_builtin_instance_method(class, "default", default);
+    globals.add_builtin_instance_method(class, "default=", set_default);
+    globals.add_builtin_instance_method(class, "default_proc", default_proc);
+    globals.add_builtin_instance_method(class, "key", key);
+    globals.add_builtin_instance_method(class, "dig", dig);
+    globals.add_builtin_instance_method(class, "to_a", to_a);
+    globals.add_builtin_instance_method(class, "to_h", to_h);
+    globals.add_builtin_instance_method(class, "map", map);
+    globals.add_builtin_instance_method(class, "collect", map);
+    globals.add_builtin_instance_method(class, "filter_map", filter_map);
+    globals.add_builtin_instance_method(class, "each_with_object", each_with_object);
+    globals.add_builtin_instance_method(class, "each_with_index", each_with_index);
+    globals.add_builtin_instance_method(class, "group_by", group_by);
+    globals.add_builtin_instance_method(class, "transform_values", transform_values);
+    globals.add_builtin_instance_method(class, "transform_keys", transform_keys);
+    globals.add_builtin_instance_method(class, "reject", reject);
+    globals.add_builtin_instance_method(class, "min_by", min_by);
+    globals.add_builtin_instance_method(class, "max_by", max_by);
+    globals.add_builtin_instance_method(class, "sort_by", sort_by);
+    globals.add_builtin_instance_method(class, "sum", sum);
+    globals.add_builtin_instance_method(class, "any?", any);
+    globals.add_builtin_instance_method(class, "all?", all);
+    globals.add_builtin_instance_method(class, "count", count);
+    globals.add_builtin_instance_method(class, "find", find);
+    globals.add_builtin_instance_method(class, "detect", find);
+    globals.add_builtin_instance_method(class, "update", update);
+    globals.add_builtin_instance_method(class, "merge!", update);
+    let obj = Value::class(globals, class);
+    globals.add_builtin_class_method(obj, "new", hash_new);
+    globals.add_builtin_class_method(obj, "[]", hash_elem);
+    obj
 }
 
+// Utils
+
+fn pair(vm: &mut VM, k: Value, v: Value) -> Value {
+    Value::array_from(&vm.globals, vec![k, v])
+}
+
+/// Evaluate `block` with each key and value, collecting the results with the pairs.
+fn eval_pairs(
+    vm: &mut VM,
+    hash: HashRef,
+    block: Block,
+) -> Result<Vec<(Value, Value, Value)>, RubyError> {
+    let mut res = vec![];
+    let mut arg = Args::new2(Value::nil(), Value::nil());
+    for (k, v) in hash.iter() {
+        arg[0] = k;
+        arg[1] = v;
+        let val = vm.eval_block(block, &arg)?;
+        res.push((k, v, val));
+    }
+    Ok(res)
+}
+
+// Class methods
+
+/// Hash.new(default = nil)
+/// Hash.new {|hash, key| ... }
+fn hash_new(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_range(args.len(), 0, 1)?;
+    let mut hash = HashRef::from(HashMap::new());
+    match args.block {
+        Some(method) => {
+            if args.len() == 1 {
+                return Err(vm.error_argument("Wrong number of arguments. (given 1, expected 0)"));
+            }
+            hash.default_proc = Some(vm.create_proc(method)?);
+        }
+        None => {
+            if args.len() == 1 {
+                hash.default = args[0];
+            }
+        }
+    }
+    Ok(Value::hash(&vm.globals, hash))
+}
+
+/// Hash[hash]
+/// Hash[[key, value], ...]
+/// Hash[key, value, ...]
+fn hash_elem(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    if args.len() == 1 {
+        if let Some(hash) = args[0].as_hash() {
+            let mut map = HashMap::new();
+            for (k, v) in hash.iter() {
+                map.insert(HashKey(k), v);
+            }
+            return Ok(Value::hash_from(&vm.globals, map));
+        }
+        if let Some(aref) = args[0].as_array() {
+            let mut hash = HashRef::from(HashMap::new());
+            for elem in aref.elements.clone() {
+                match elem.as_array() {
+                    Some(pair) if pair.elements.len() == 2 => {
+                        hash.store(vm, pair.elements[0], pair.elements[1])?;
+                    }
+                    _ => return Err(vm.error_argument("Wrong element type (expected array).")),
+                }
+            }
+            return Ok(Value::hash(&vm.globals, hash));
+        }
+    }
+    if args.len() % 2 != 0 {
+        return Err(vm.error_argument("Odd number of arguments for Hash."));
+    }
+    let mut hash = HashRef::from(HashMap::new());
+    for i in 0..args.len() / 2 {
+        hash.store(vm, args[i * 2], args[i * 2 + 1])?;
+    }
+    Ok(Value::hash(&vm.globals, hash))
+}
+
+// Instance methods
+
 fn inspect(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
     vm.check_args_num(args.len(), 0)?;
     let hash = self_val.as_hash().unwrap();
@@ -56,9 +172,9 @@ fn clone(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
 fn compact(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
     vm.check_args_num(args.len(), 0)?;
     let hash = vm.expect_hash(self_val, "Receiver")?.dup();
-    match hash.inner_mut() {
-        HashInfo::Map(map) => map.retain(|_, &mut v| v != Value::nil()),
-        HashInfo::IdentMap(map) => map.retain(|_, &mut v| v != Value::nil()),
+    match &mut hash.inner_mut().map {
+        HashTable::Map(map) => map.retain(|_, &mut v| v != Value::nil()),
+        HashTable::IdentMap(map) => map.retain(|_, &mut v| v != Value::nil()),
     }
     Ok(Value::hash(&vm.globals, hash))
 }
@@ -66,7 +182,7 @@ fn compact(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
 fn delete(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
     vm.check_args_num(args.len(), 1)?;
     let mut hash = self_val.as_hash().unwrap();
-    let res = match hash.remove(args[0]) {
+    let res = match hash.delete(vm, args[0])? {
         Some(v) => v,
         None => Value::nil(),
     };
@@ -98,8 +214,8 @@ fn select(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
 
 fn has_key(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
     vm.check_args_num(args.len(), 1)?;
-    let hash = self_val.as_hash().unwrap();
-    let res = hash.contains_key(args[0]);
+    let mut hash = self_val.as_hash().unwrap();
+    let res = hash.has_key(vm, args[0])?;
     Ok(Value::bool(res))
 }
 
@@ -176,7 +292,7 @@ fn merge(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
     for arg in args.iter() {
         let other = vm.expect_hash(*arg, "First arg")?;
         for (k, v) in other.iter() {
-            new.insert(k, v);
+            new.store(vm, k, v)?;
         }
     }
 
@@ -187,9 +303,9 @@ fn fetch(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
     vm.check_args_range(args.len(), 1, 2)?;
     let key = args[0];
 
-    let hash = self_val.as_hash().unwrap();
-    let val = match hash.get(&key) {
-        Some(val) => *val,
+    let mut hash = self_val.as_hash().unwrap();
+    let val = match hash.lookup(vm, key)? {
+        Some(val) => val,
         None => {
             match args.block {
                 // TODO: If arg[1] exists, Should warn "block supersedes default value argument".
@@ -212,13 +328,13 @@ fn fetch(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
 fn compare_by_identity(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
     vm.check_args_num(args.len(), 0)?;
     let hash = self_val.as_hash().unwrap();
-    let inner = hash.inner_mut();
+    let inner = &mut hash.inner_mut().map;
     match inner {
-        HashInfo::Map(map) => {
+        HashTable::Map(map) => {
             let new_map = map.into_iter().map(|(k, v)| (IdentKey(k.0), *v)).collect();
-            *inner = HashInfo::IdentMap(new_map);
+            *inner = HashTable::IdentMap(new_map);
         }
-        HashInfo::IdentMap(_) => {}
+        HashTable::IdentMap(_) => {}
     };
     Ok(self_val)
 }
@@ -239,11 +355,389 @@ fn sort(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
 fn invert(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
     vm.check_args_num(args.len(), 0)?;
     let hash = self_val.as_hash().unwrap();
-    let mut new_hash = HashMap::new();
+    let mut new_hash = HashRef::from(HashMap::new());
+    for (k, v) in hash.iter() {
+        new_hash.store(vm, v, k)?;
+    }
+    Ok(Value::hash(&vm.globals, new_hash))
+}
+
+fn get_elem(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let hash = self_val.as_hash().unwrap();
+    hash.get_elem(vm, self_val, args[0])
+}
+
+fn store(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 2)?;
+    let mut hash = self_val.as_hash().unwrap();
+    hash.store(vm, args[0], args[1])?;
+    Ok(args[1])
+}
+
+/// Hash#default(key = nil)
+/// With `key`, the result of the default proc for `key` is returned.
+fn default(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_range(args.len(), 0, 1)?;
+    let hash = self_val.as_hash().unwrap();
+    match hash.default_proc {
+        Some(procobj) if args.len() == 1 => vm.eval_proc(procobj, &Args::new2(self_val, args[0])),
+        Some(_) => Ok(Value::nil()),
+        None => Ok(hash.default),
+    }
+}
+
+fn set_default(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let mut hash = self_val.as_hash().unwrap();
+    hash.default = args[0];
+    hash.default_proc = None;
+    Ok(args[0])
+}
+
+fn default_proc(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let hash = self_val.as_hash().unwrap();
+    Ok(hash.default_proc.unwrap_or_default())
+}
+
+/// Hash#key(val)
+/// Returns the key of the first entry whose value is `val`.
+fn key(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let hash = self_val.as_hash().unwrap();
+    for (k, v) in hash.iter() {
+        if vm.eval_eq(v, args[0])? {
+            return Ok(k);
+        }
+    }
+    Ok(Value::nil())
+}
+
+/// Hash#dig(key, ...)
+fn dig(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_min(args.len(), 1)?;
+    let hash = self_val.as_hash().unwrap();
+    let val = hash.get_elem(vm, self_val, args[0])?;
+    if args.len() == 1 || val.is_nil() {
+        return Ok(val);
+    }
+    let mut rest = Args::new(0);
+    for arg in args.iter().skip(1) {
+        rest.push(*arg);
+    }
+    let id = vm.globals.get_ident_id("dig");
+    let method = vm.get_method(val, id)?;
+    vm.eval_send(method, val, &rest)
+}
+
+fn to_a(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let hash = self_val.as_hash().unwrap();
+    let ary = hash.iter().map(|(k, v)| pair(vm, k, v)).collect();
+    Ok(Value::array_from(&vm.globals, ary))
+}
+
+/// Hash#to_h {|key, value| [new_key, new_value] }
+fn to_h(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let hash = self_val.as_hash().unwrap();
+    let block = match args.block {
+        Some(block) => block,
+        None => return Ok(self_val),
+    };
+    let mut new_hash = HashRef::from(HashMap::new());
+    for (_, _, val) in eval_pairs(vm, hash, block)? {
+        match val.as_array() {
+            Some(aref) if aref.elements.len() == 2 => {
+                new_hash.store(vm, aref.elements[0], aref.elements[1])?;
+            }
+            _ => return Err(vm.error_type("Wrong element type (expected array of 2 elements).")),
+        }
+    }
+    Ok(Value::hash(&vm.globals, new_hash))
+}
+
+fn map(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let hash = self_val.as_hash().unwrap();
+    let block = vm.expect_block(args.block)?;
+    let ary = eval_pairs(vm, hash, block)?
+        .into_iter()
+        .map(|(_, _, val)| val)
+        .collect();
+    Ok(Value::array_from(&vm.globals, ary))
+}
+
+fn filter_map(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let hash = self_val.as_hash().unwrap();
+    let block = vm.expect_block(args.block)?;
+    let ary = eval_pairs(vm, hash, block)?
+        .into_iter()
+        .filter(|(_, _, val)| vm.val_to_bool(*val))
+        .map(|(_, _, val)| val)
+        .collect();
+    Ok(Value::array_from(&vm.globals, ary))
+}
+
+/// Hash#each_with_object(obj) {|[key, value], obj| ... }
+fn each_with_object(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let hash = self_val.as_hash().unwrap();
+    let block = vm.expect_block(args.block)?;
+    let mut arg = Args::new2(Value::nil(), args[0]);
+    for (k, v) in hash.iter() {
+        arg[0] = pair(vm, k, v);
+        vm.eval_block(block, &arg)?;
+    }
+    Ok(args[0])
+}
+
+/// Hash#each_with_index {|[key, value], index| ... }
+fn each_with_index(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let hash = self_val.as_hash().unwrap();
+    let block = vm.expect_block(args.block)?;
+    let mut arg = Args::new2(Value::nil(), Value::nil());
+    for (i, (k, v)) in hash.iter().enumerate() {
+        arg[0] = pair(vm, k, v);
+        arg[1] = Value::fixnum(i as i64);
+        vm.eval_block(block, &arg)?;
+    }
+    Ok(self_val)
+}
+
+fn group_by(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let hash = self_val.as_hash().unwrap();
+    let block = vm.expect_block(args.block)?;
+    let mut res = HashRef::from(HashMap::new());
+    for (k, v, val) in eval_pairs(vm, hash, block)? {
+        let elem = pair(vm, k, v);
+        match res.lookup(vm, val)? {
+            Some(group) => group.as_array().unwrap().elements.push(elem),
+            None => {
+                let group = Value::array_from(&vm.globals, vec![elem]);
+                res.store(vm, val, group)?;
+            }
+        }
+    }
+    Ok(Value::hash(&vm.globals, res))
+}
+
+fn transform_values(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let hash = self_val.as_hash().unwrap();
+    let block = vm.expect_block(args.block)?;
+    let mut map = HashMap::new();
+    for (k, v) in hash.iter() {
+        let val = vm.eval_block(block, &Args::new1(v))?;
+        map.insert(HashKey(k), val);
+    }
+    Ok(Value::hash_from(&vm.globals, map))
+}
+
+fn transform_keys(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let hash = self_val.as_hash().unwrap();
+    let block = vm.expect_block(args.block)?;
+    let mut new_hash = HashRef::from(HashMap::new());
+    for (k, v) in hash.iter() {
+        let key = vm.eval_block(block, &Args::new1(k))?;
+        new_hash.store(vm, key, v)?;
+    }
+    Ok(Value::hash(&vm.globals, new_hash))
+}
+
+fn reject(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let hash = self_val.as_hash().unwrap();
+    let block = vm.expect_block(args.block)?;
+    let mut map = HashMap::new();
+    for (k, v, val) in eval_pairs(vm, hash, block)? {
+        if !vm.val_to_bool(val) {
+            map.insert(HashKey(k), v);
+        }
+    }
+    Ok(Value::hash_from(&vm.globals, map))
+}
+
+/// Find the pair whose block result is the minimum (or maximum if `max` is true).
+fn min_max_by(vm: &mut VM, self_val: Value, args: &Args, max: bool) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let hash = self_val.as_hash().unwrap();
+    let block = vm.expect_block(args.block)?;
+    let mut res: Option<(Value, Value, Value)> = None;
+    for (k, v, val) in eval_pairs(vm, hash, block)? {
+        let replace = match res {
+            None => true,
+            Some((_, _, current)) => {
+                let ord = vm.eval_cmp(current, val)?;
+                match ord.as_fixnum() {
+                    Some(ord) => {
+                        if max {
+                            ord > 0
+                        } else {
+                            ord < 0
+                        }
+                    }
+                    None => {
+                        let lhs = vm.globals.get_class_name(val);
+                        let rhs = vm.globals.get_class_name(current);
+                        return Err(vm.error_argument(format!(
+                            "Comparison of {} with {} failed.",
+                            lhs, rhs
+                        )));
+                    }
+                }
+            }
+        };
+        if replace {
+            res = Some((k, v, val));
+        }
+    }
+    match res {
+        Some((k, v, _)) => Ok(pair(vm, k, v)),
+        None => Ok(Value::nil()),
+    }
+}
+
+fn min_by(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    min_max_by(vm, self_val, args, false)
+}
+
+fn max_by(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    min_max_by(vm, self_val, args, true)
+}
+
+fn sort_by(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let hash = self_val.as_hash().unwrap();
+    let block = vm.expect_block(args.block)?;
+    let mut res = eval_pairs(vm, hash, block)?;
+    let mut err = None;
+    res.sort_by(|(_, _, a), (_, _, b)| match vm.eval_cmp(*b, *a) {
+        Ok(ord) if !ord.is_nil() => ord.to_ordering(),
+        Ok(_) => {
+            err.get_or_insert_with(|| vm.error_argument("Comparison failed."));
+            std::cmp::Ordering::Equal
+        }
+        Err(e) => {
+            err.get_or_insert(e);
+            std::cmp::Ordering::Equal
+        }
+    });
+    if let Some(err) = err {
+        return Err(err);
+    }
+    let ary = res.into_iter().map(|(k, v, _)| pair(vm, k, v)).collect();
+    Ok(Value::array_from(&vm.globals, ary))
+}
+
+/// Hash#sum(init = 0) {|key, value| ... }
+fn sum(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_range(args.len(), 0, 1)?;
+    let hash = self_val.as_hash().unwrap();
+    let mut sum = if args.len() == 1 {
+        args[0]
+    } else {
+        Value::fixnum(0)
+    };
+    let vals: Vec<Value> = match args.block {
+        Some(block) => eval_pairs(vm, hash, block)?
+            .into_iter()
+            .map(|(_, _, val)| val)
+            .collect(),
+        None => hash.iter().map(|(k, v)| pair(vm, k, v)).collect(),
+    };
+    for val in vals {
+        sum = vm.eval_add_values(sum, val)?;
+    }
+    Ok(sum)
+}
+
+fn any(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let hash = self_val.as_hash().unwrap();
+    let block = match args.block {
+        Some(block) => block,
+        None => return Ok(Value::bool(hash.len() != 0)),
+    };
+    let mut arg = Args::new2(Value::nil(), Value::nil());
+    for (k, v) in hash.iter() {
+        arg[0] = k;
+        arg[1] = v;
+        let val = vm.eval_block(block, &arg)?;
+        if vm.val_to_bool(val) {
+            return Ok(Value::true_val());
+        }
+    }
+    Ok(Value::false_val())
+}
+
+fn all(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let hash = self_val.as_hash().unwrap();
+    let block = match args.block {
+        Some(block) => block,
+        None => return Ok(Value::true_val()),
+    };
+    let mut arg = Args::new2(Value::nil(), Value::nil());
+    for (k, v) in hash.iter() {
+        arg[0] = k;
+        arg[1] = v;
+        let val = vm.eval_block(block, &arg)?;
+        if !vm.val_to_bool(val) {
+            return Ok(Value::false_val());
+        }
+    }
+    Ok(Value::true_val())
+}
+
+fn count(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let hash = self_val.as_hash().unwrap();
+    let count = match args.block {
+        Some(block) => eval_pairs(vm, hash, block)?
+            .into_iter()
+            .filter(|(_, _, val)| vm.val_to_bool(*val))
+            .count(),
+        None => hash.len(),
+    };
+    Ok(Value::fixnum(count as i64))
+}
+
+fn find(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let hash = self_val.as_hash().unwrap();
+    let block = vm.expect_block(args.block)?;
+    let mut arg = Args::new2(Value::nil(), Value::nil());
     for (k, v) in hash.iter() {
-        new_hash.insert(HashKey(v), k);
+        arg[0] = k;
+        arg[1] = v;
+        let val = vm.eval_block(block, &arg)?;
+        if vm.val_to_bool(val) {
+            return Ok(pair(vm, k, v));
+        }
+    }
+    Ok(Value::nil())
+}
+
+/// Hash#update(other, ...) {|key, self_val, other_val| ... }
+fn update(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    let mut hash = self_val.as_hash().unwrap();
+    for arg in args.iter() {
+        let other = vm.expect_hash(*arg, "Argument")?;
+        for (k, v) in other.iter() {
+            let v = match (args.block, hash.lookup(vm, k)?) {
+                (Some(block), Some(old)) => vm.eval_block(block, &Args::new3(None, k, old, v))?,
+                _ => v,
+            };
+            hash.store(vm, k, v)?;
+        }
     }
-    Ok(Value::hash_from(&vm.globals, new_hash))
+    Ok(self_val)
 }
 
 #[cfg(test)]
@@ -401,9 +895,98 @@ mod test {
                 "#{key} not exist"                  #  warning: block supersedes default value argument
             }        
             assert("two not exist", res)
-            #h.default = "default"
+            h.default = "default"
+            assert("default", h[:two])
             assert_error { h.fetch(:two) }          # エラー key not found (KeyError)
         "##;
         assert_script(program);
     }
+
+    #[test]
+    fn hash_default() {
+        let program = r#"
+            h = Hash.new(0)
+            assert(0, h[:a])
+            h[:a] += 3
+            assert({a: 3}, h)
+            assert(0, h.default)
+            h = Hash.new { |hash, key| hash[key] = key * 2 }
+            assert(8, h[4])
+            assert({4 => 8}, h)
+            assert(10, h.dig(5))
+            assert(12, h.default(6))
+            h.default = 7
+            assert(nil, h.default_proc)
+            assert(7, h[100])
+            assert(nil, {}[:a])
+            assert(100, h.fetch(1, 100))
+            h = Hash.new { |hash, key| hash[key] = [] }
+            h[:x] << 1
+            h[:x] << 2
+            assert({x: [1, 2]}, h)
+        "#;
+        assert_script(program);
+    }
+
+    #[test]
+    fn hash_elem() {
+        let program = r#"
+            assert({a: 1, b: 2}, Hash[:a, 1, :b, 2])
+            assert({a: 1, b: 2}, Hash[[[:a, 1], [:b, 2]]])
+            assert({a: 1}, Hash[{a: 1}])
+            h = {a: {b: {c: 3}}}
+            assert(3, h.dig(:a, :b, :c))
+            assert(nil, h.dig(:x, :b))
+            h.store(:d, 4)
+            assert(4, h[:d])
+            assert(:d, h.key(4))
+            assert(nil, h.key(5))
+        "#;
+        assert_script(program);
+    }
+
+    #[test]
+    fn hash_enumerable() {
+        let program = r#"
+            h = {"a" => 1, "b" => 2, "c" => 3}
+            assert([["a", 1], ["b", 2], ["c", 3]], h.to_a.sort)
+            assert([2, 4, 6], h.map { |k, v| v * 2 }.sort)
+            assert([20, 30], h.filter_map { |k, v| v * 10 if v > 1 }.sort)
+            assert([1, 2, 3], h.each_with_object([]) { |pair, ary| ary << pair[1] }.sort)
+            x = []
+            h.each_with_index { |(k, v), i| x << [k, v, i] }
+            assert([0, 1, 2], x.map { |k, v, i| i })
+            assert([["a", 1], ["b", 2], ["c", 3]], x.map { |k, v, i| [k, v] }.sort)
+            assert({true => [["b", 2]], false => [["a", 1], ["c", 3]]}, h.group_by { |k, v| v.even? }.transform_values { |v| v.sort })
+            assert({"a" => 2, "b" => 4, "c" => 6}, h.transform_values { |v| v * 2 })
+            assert({"a!" => 1, "b!" => 2, "c!" => 3}, h.transform_keys { |k| k + "!" })
+            assert({"a" => 1, "c" => 3}, h.reject { |k, v| v == 2 })
+            assert(["a", 1], h.min_by { |k, v| v })
+            assert(["c", 3], h.max_by { |k, v| v })
+            assert([["c", 3], ["b", 2], ["a", 1]], h.sort_by { |k, v| -v })
+            assert(6, h.sum { |k, v| v })
+            assert(16, h.sum(10) { |k, v| v })
+            assert(true, h.any? { |k, v| v > 2 })
+            assert(false, h.all? { |k, v| v > 2 })
+            assert(false, {}.any?)
+            assert(3, h.count)
+            assert(2, h.count { |k, v| v != 2 })
+            assert(["b", 2], h.find { |k, v| v == 2 })
+            assert(nil, h.find { |k, v| v == 5 })
+            assert({1 => "a", 2 => "b", 3 => "c"}, h.to_h { |k, v| [v, k] })
+        "#;
+        assert_script(program);
+    }
+
+    #[test]
+    fn hash_update() {
+        let program = r#"
+            h = {a: 1, b: 2}
+            h.update({b: 3, c: 4})
+            assert({a: 1, b: 3, c: 4}, h)
+            h.merge!({a: 10, d: 5}) { |key, old, new| old + new }
+            assert({a: 11, b: 3, c: 4, d: 5}, h)
+        "#;
+        assert_script(program);
+    }
 }
@@ -1,8 +1,12 @@
+use crate::builtin::numeric::{coerce, coerce_bin_op, coerce_cmp, to_f64};
+use crate::builtin::rational;
 use crate::*;
+use divrem::*;
+use std::convert::TryFrom;
 
 pub fn init(globals: &mut Globals) -> Value {
     let id = globals.get_ident_id("Integer");
-    let class = ClassRef::from(id, globals.builtins.object);
+    let class = ClassRef::from(id, globals.builtins.numeric);
     globals.add_builtin_instance_method(class, "==", eq);
     globals.add_builtin_instance_method(class, "!=", neq);
     globals.add_builtin_instance_method(class, ">=", ge);
@@ -10,13 +14,45 @@ pub fn init(globals: &mut Globals) -> Value {
     globals.add_builtin_instance_method(class, "<=", le);
     globals.add_builtin_instance_method(class, "<", lt);
     globals.add_builtin_instance_method(class, "<=>", cmp);
+    globals.add_builtin_instance_method(class, "+", add);
+    globals.add_builtin_instance_method(class, "-", sub);
+    globals.add_builtin_instance_method(class, "*", mul);
+    globals.add_builtin_instance_method(class, "/", div);
+    globals.add_builtin_instance_method(class, "div", div);
+    globals.add_builtin_instance_method(class, "%", rem);
+    globals.add_builtin_instance_method(class, "modulo", rem);
+    globals.add_builtin_instance_method(class, "**", pow_);
 
     globals.add_builtin_instance_method(class, "times", times);
     globals.add_builtin_instance_method(class, "step", step);
     globals.add_builtin_instance_method(class, "chr", chr);
     globals.add_builtin_instance_method(class, "to_f", tof);
     globals.add_builtin_instance_method(class, "floor", floor);
+    globals.add_builtin_instance_method(class, "ceil", ceil);
+    globals.add_builtin_instance_method(class, "round", round);
+    globals.add_builtin_instance_method(class, "truncate", truncate);
     globals.add_builtin_instance_method(class, "even?", even);
+    globals.add_builtin_instance_method(class, "odd?", odd);
+    globals.add_builtin_instance_method(class, "abs", abs);
+    globals.add_builtin_instance_method(class, "magnitude", abs);
+    globals.add_builtin_instance_method(class, "divmod", divmod);
+    globals.add_builtin_instance_method(class, "fdiv", fdiv);
+    globals.add_builtin_instance_method(class, "gcd", gcd);
+    globals.add_builtin_instance_method(class, "lcm", lcm);
+    globals.add_builtin_instance_method(class, "pow", pow);
+    globals.add_builtin_instance_method(class, "digits", digits);
+    globals.add_builtin_instance_method(class, "bit_length", bit_length);
+    globals.add_builtin_instance_method(class, "to_s", to_s);
+    globals.add_builtin_instance_method(class, "inspect", to_s);
+    globals.add_builtin_instance_method(class, "to_i", to_i);
+    globals.add_builtin_instance_method(class, "to_int", to_i);
+    globals.add_builtin_instance_method(class, "to_r", to_r);
+    globals.add_builtin_instance_method(class, "upto", upto);
+    globals.add_builtin_instance_method(class, "downto", downto);
+    globals.add_builtin_instance_method(class, "succ", succ);
+    globals.add_builtin_instance_method(class, "next", succ);
+    globals.add_builtin_instance_method(class, "pred", pred);
+    globals.add_builtin_instance_method(class, "integer?", integer);
     Value::class(globals, class)
 }
 
@@ -30,7 +66,7 @@ fn eq(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
     match args[0].unpack() {
         RV::Integer(rhs) => Ok(Value::bool(lhs == rhs)),
         RV::Float(rhs) => Ok(Value::bool(lhs as f64 == rhs)),
-        _ => Ok(Value::bool(false)),
+        _ => Ok(Value::bool(self_val.equal(args[0]))),
     }
 }
 
@@ -40,41 +76,36 @@ fn neq(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
     match args[0].unpack() {
         RV::Integer(rhs) => Ok(Value::bool(lhs != rhs)),
         RV::Float(rhs) => Ok(Value::bool(lhs as f64 != rhs)),
-        _ => Ok(Value::bool(true)),
+        _ => Ok(Value::bool(!self_val.equal(args[0]))),
     }
 }
 
 macro_rules! define_cmp {
-    ($vm:ident, $self_val:ident, $args:ident, $op:ident) => {
+    ($vm:ident, $self_val:ident, $args:ident, $op:ident, $name:expr) => {
         $vm.check_args_num($args.len(), 1)?;
         let lhs = $vm.expect_integer($self_val, "Receiver")?;
         match $args[0].unpack() {
             RV::Integer(rhs) => return Ok(Value::bool(lhs.$op(&rhs))),
             RV::Float(rhs) => return Ok(Value::bool((lhs as f64).$op(&rhs))),
-            _ => {
-                return Err($vm.error_argument(format!(
-                    "Comparison of Integer with {} failed.",
-                    $vm.globals.get_class_name($args[0])
-                )))
-            }
+            _ => return coerce_cmp($vm, $self_val, $args[0], $name),
         }
     };
 }
 
 fn ge(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
-    define_cmp!(vm, self_val, args, ge);
+    define_cmp!(vm, self_val, args, ge, ">=");
 }
 
 fn gt(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
-    define_cmp!(vm, self_val, args, gt);
+    define_cmp!(vm, self_val, args, gt, ">");
 }
 
 fn le(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
-    define_cmp!(vm, self_val, args, le);
+    define_cmp!(vm, self_val, args, le, "<=");
 }
 
 fn lt(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
-    define_cmp!(vm, self_val, args, lt);
+    define_cmp!(vm, self_val, args, lt, "<");
 }
 
 fn cmp(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
@@ -84,7 +115,12 @@ fn cmp(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
     let res = match args[0].unpack() {
         RV::Integer(rhs) => lhs.partial_cmp(&rhs),
         RV::Float(rhs) => (lhs as f64).partial_cmp(&rhs),
-        _ => return Ok(Value::nil()),
+        _ => {
+            return match coerce(vm, self_val, args[0])? {
+                Some((lhs, rhs)) => vm.eval_cmp(rhs, lhs),
+                None => Ok(Value::nil()),
+            }
+        }
     };
     match res {
         Some(ord) => Ok(Value::fixnum(ord as i64)),
@@ -92,6 +128,73 @@ fn cmp(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
     }
 }
 
+macro_rules! define_op {
+    ($fname:ident, $op:tt, $name:expr) => {
+        fn $fname(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+            vm.check_args_num(args.len(), 1)?;
+            let lhs = self_val.as_fixnum().unwrap();
+            match args[0].unpack() {
+                RV::Integer(rhs) => Ok(Value::fixnum(lhs $op rhs)),
+                RV::Float(rhs) => Ok(Value::flonum(lhs as f64 $op rhs)),
+                _ => coerce_bin_op(vm, self_val, args[0], $name),
+            }
+        }
+    };
+}
+
+define_op!(add, +, "+");
+define_op!(sub, -, "-");
+define_op!(mul, *, "*");
+
+fn div(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let lhs = self_val.as_fixnum().unwrap();
+    match args[0].unpack() {
+        RV::Integer(0) => Err(vm.error_zero_division()),
+        RV::Integer(rhs) => Ok(Value::fixnum(lhs.div_floor(rhs))),
+        RV::Float(rhs) => Ok(Value::flonum(lhs as f64 / rhs)),
+        _ => coerce_bin_op(vm, self_val, args[0], "/"),
+    }
+}
+
+fn rem(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let lhs = self_val.as_fixnum().unwrap();
+    match args[0].unpack() {
+        RV::Integer(0) => Err(vm.error_zero_division()),
+        RV::Integer(rhs) => Ok(Value::fixnum(lhs.rem_floor(rhs))),
+        RV::Float(rhs) => {
+            let lhs = lhs as f64;
+            Ok(Value::flonum(lhs - rhs * (lhs / rhs).floor()))
+        }
+        _ => coerce_bin_op(vm, self_val, args[0], "%"),
+    }
+}
+
+/// Integer#**(other)
+/// A negative Integer exponent gives a Rational.
+fn pow_(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let lhs = self_val.as_fixnum().unwrap();
+    match args[0].unpack() {
+        RV::Integer(rhs) if rhs >= 0 => match u32::try_from(rhs) {
+            Ok(rhs) => Ok(Value::fixnum(lhs.pow(rhs))),
+            Err(_) => Ok(Value::flonum((lhs as f64).powf(rhs as f64))),
+        },
+        RV::Integer(rhs) => {
+            if lhs == 0 {
+                return Err(vm.error_zero_division());
+            }
+            match RationalInfo::new(lhs, 1).pow(rhs) {
+                Some(info) => Ok(Value::rational(&vm.globals, info)),
+                None => Ok(Value::flonum((lhs as f64).powf(rhs as f64))),
+            }
+        }
+        RV::Float(rhs) => Ok(Value::flonum((lhs as f64).powf(rhs))),
+        _ => coerce_bin_op(vm, self_val, args[0], "**"),
+    }
+}
+
 fn times(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
     vm.check_args_num(args.len(), 0)?;
     let method = match args.block {
@@ -136,7 +239,7 @@ fn step(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
         1
     };
 
-    if method == MethodRef::from(0) {
+    if method.is_none() {
         let mut ary = vec![];
         let mut i = start;
         loop {
@@ -173,10 +276,64 @@ fn chr(vm: &mut VM, self_val: Value, _: &Args) -> VMResult {
     Ok(Value::bytes(&vm.globals, vec![num as u8]))
 }
 
+/// Get 10 ** -`ndigits` for rounding methods, or None if `ndigits` is not negative.
+fn round_unit(vm: &mut VM, args: &Args) -> Result<Option<i64>, RubyError> {
+    vm.check_args_range(args.len(), 0, 1)?;
+    if args.len() == 0 {
+        return Ok(None);
+    }
+    let ndigits = vm.expect_integer(args[0], "ndigits")?;
+    if ndigits >= 0 {
+        Ok(None)
+    } else if ndigits < -18 {
+        Ok(Some(0))
+    } else {
+        Ok(Some(10i64.pow(-ndigits as u32)))
+    }
+}
+
+/// Integer#floor(ndigits = 0)
 fn floor(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
-    vm.check_args_num(args.len(), 0)?;
-    self_val.as_fixnum().unwrap();
-    Ok(self_val)
+    let num = self_val.as_fixnum().unwrap();
+    match round_unit(vm, args)? {
+        None => Ok(self_val),
+        Some(0) => Ok(Value::fixnum(0)),
+        Some(unit) => Ok(Value::fixnum(num.div_floor(unit) * unit)),
+    }
+}
+
+/// Integer#ceil(ndigits = 0)
+fn ceil(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    let num = self_val.as_fixnum().unwrap();
+    match round_unit(vm, args)? {
+        None => Ok(self_val),
+        Some(0) => Ok(Value::fixnum(0)),
+        Some(unit) => Ok(Value::fixnum(-(-num).div_floor(unit) * unit)),
+    }
+}
+
+/// Integer#round(ndigits = 0)
+/// Rounds half away from zero.
+fn round(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    let num = self_val.as_fixnum().unwrap();
+    match round_unit(vm, args)? {
+        None => Ok(self_val),
+        Some(0) => Ok(Value::fixnum(0)),
+        Some(unit) => {
+            let res = (num.abs() + unit / 2) / unit * unit;
+            Ok(Value::fixnum(if num < 0 { -res } else { res }))
+        }
+    }
+}
+
+/// Integer#truncate(ndigits = 0)
+fn truncate(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    let num = self_val.as_fixnum().unwrap();
+    match round_unit(vm, args)? {
+        None => Ok(self_val),
+        Some(0) => Ok(Value::fixnum(0)),
+        Some(unit) => Ok(Value::fixnum(num / unit * unit)),
+    }
 }
 
 fn tof(_vm: &mut VM, self_val: Value, _: &Args) -> VMResult {
@@ -189,6 +346,245 @@ fn even(_vm: &mut VM, self_val: Value, _: &Args) -> VMResult {
     Ok(Value::bool(num % 2 == 0))
 }
 
+fn odd(_vm: &mut VM, self_val: Value, _: &Args) -> VMResult {
+    let num = self_val.as_fixnum().unwrap();
+    Ok(Value::bool(num % 2 != 0))
+}
+
+fn abs(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let num = self_val.as_fixnum().unwrap();
+    Ok(Value::fixnum(num.abs()))
+}
+
+/// Integer#divmod(other)
+/// Returns [(self / other).floor, self % other].
+fn divmod(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let lhs = self_val.as_fixnum().unwrap();
+    let (div, rem) = match args[0].unpack() {
+        RV::Integer(0) => return Err(vm.error_zero_division()),
+        RV::Integer(rhs) => (
+            Value::fixnum(lhs.div_floor(rhs)),
+            Value::fixnum(lhs.rem_floor(rhs)),
+        ),
+        RV::Float(rhs) => {
+            let lhs = lhs as f64;
+            let div = (lhs / rhs).floor();
+            (Value::flonum(div), Value::flonum(lhs - rhs * div))
+        }
+        _ => return coerce_bin_op(vm, self_val, args[0], "divmod"),
+    };
+    Ok(Value::array_from(&vm.globals, vec![div, rem]))
+}
+
+fn fdiv(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let lhs = self_val.as_fixnum().unwrap() as f64;
+    match to_f64(args[0]) {
+        Some(rhs) => Ok(Value::flonum(lhs / rhs)),
+        None => coerce_bin_op(vm, self_val, args[0], "fdiv"),
+    }
+}
+
+fn gcd(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let lhs = self_val.as_fixnum().unwrap();
+    let rhs = vm.expect_integer(args[0], "Argument")?;
+    let res = rational::gcd(lhs as i128, rhs as i128);
+    Ok(Value::fixnum(res as i64))
+}
+
+fn lcm(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let lhs = self_val.as_fixnum().unwrap();
+    let rhs = vm.expect_integer(args[0], "Argument")?;
+    if lhs == 0 || rhs == 0 {
+        return Ok(Value::fixnum(0));
+    }
+    let (lhs, rhs) = (lhs as i128, rhs as i128);
+    let res = (lhs * rhs).abs() / rational::gcd(lhs, rhs);
+    Ok(Value::fixnum(res as i64))
+}
+
+/// Integer#pow(other)
+/// Integer#pow(other, modulo)
+fn pow(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_range(args.len(), 1, 2)?;
+    if args.len() == 1 {
+        return pow_(vm, self_val, args);
+    }
+    let base = self_val.as_fixnum().unwrap() as i128;
+    let exp = vm.expect_integer(args[0], "Exponent")?;
+    let modulo = vm.expect_integer(args[1], "Modulo")? as i128;
+    if exp < 0 {
+        return Err(vm.error_argument(
+            "Integer#pow() 2nd argument not allowed unless a 1st argument is non-negative.",
+        ));
+    }
+    if modulo == 0 {
+        return Err(vm.error_zero_division());
+    }
+    let m = modulo.abs();
+    let mut res = 1 % m;
+    let mut base = base.rem_euclid(m);
+    let mut exp = exp;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            res = res * base % m;
+        }
+        base = base * base % m;
+        exp >>= 1;
+    }
+    // The result has the same sign as `modulo`.
+    if modulo < 0 && res != 0 {
+        res += modulo;
+    }
+    Ok(Value::fixnum(res as i64))
+}
+
+/// Integer#digits(base = 10)
+/// Returns the digits from the least significant one.
+fn digits(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_range(args.len(), 0, 1)?;
+    let mut num = self_val.as_fixnum().unwrap();
+    let base = if args.len() == 1 {
+        vm.expect_integer(args[0], "Base")?
+    } else {
+        10
+    };
+    if num < 0 {
+        return Err(vm.error_argument("Out of domain."));
+    }
+    if base < 2 {
+        return Err(vm.error_argument(format!("Invalid radix {}.", base)));
+    }
+    let mut ary = vec![Value::fixnum(num % base)];
+    num /= base;
+    while num > 0 {
+        ary.push(Value::fixnum(num % base));
+        num /= base;
+    }
+    Ok(Value::array_from(&vm.globals, ary))
+}
+
+fn bit_length(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let num = self_val.as_fixnum().unwrap();
+    let num = if num < 0 { !num } else { num };
+    Ok(Value::fixnum(64 - num.leading_zeros() as i64))
+}
+
+/// Integer#to_s(base = 10)
+fn to_s(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_range(args.len(), 0, 1)?;
+    let num = self_val.as_fixnum().unwrap();
+    let base = if args.len() == 1 {
+        vm.expect_integer(args[0], "Base")?
+    } else {
+        10
+    };
+    if base < 2 || base > 36 {
+        return Err(vm.error_argument(format!("Invalid radix {}.", base)));
+    }
+    let mut n = (num as i128).abs();
+    let mut s = vec![];
+    loop {
+        s.push(std::char::from_digit((n % base as i128) as u32, base as u32).unwrap());
+        n /= base as i128;
+        if n == 0 {
+            break;
+        }
+    }
+    if num < 0 {
+        s.push('-');
+    }
+    let s: String = s.iter().rev().collect();
+    Ok(Value::string(&vm.globals, s))
+}
+
+fn to_i(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    Ok(self_val)
+}
+
+fn to_r(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let num = self_val.as_fixnum().unwrap();
+    Ok(Value::rational(&vm.globals, RationalInfo::new(num, 1)))
+}
+
+/// Integer#upto(limit) {|i| ... }
+fn upto(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let method = match args.block {
+        Some(method) => method,
+        None => {
+            let id = vm.globals.get_ident_id("upto");
+            let val = Value::enumerator(&vm.globals, id, self_val, args.clone());
+            return Ok(val);
+        }
+    };
+    let start = self_val.as_fixnum().unwrap();
+    let limit = vm.expect_integer(args[0], "Limit")?;
+    let mut arg = Args::new1(Value::nil());
+    let mut res = vec![];
+    for i in start..=limit {
+        arg[0] = Value::fixnum(i);
+        let val = vm.eval_block(method, &arg)?;
+        if method.is_none() {
+            res.push(val);
+        }
+    }
+    if method.is_none() {
+        return Ok(Value::array_from(&vm.globals, res));
+    }
+    Ok(self_val)
+}
+
+/// Integer#downto(limit) {|i| ... }
+fn downto(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let method = match args.block {
+        Some(method) => method,
+        None => {
+            let id = vm.globals.get_ident_id("downto");
+            let val = Value::enumerator(&vm.globals, id, self_val, args.clone());
+            return Ok(val);
+        }
+    };
+    let start = self_val.as_fixnum().unwrap();
+    let limit = vm.expect_integer(args[0], "Limit")?;
+    let mut arg = Args::new1(Value::nil());
+    let mut res = vec![];
+    for i in (limit..=start).rev() {
+        arg[0] = Value::fixnum(i);
+        let val = vm.eval_block(method, &arg)?;
+        if method.is_none() {
+            res.push(val);
+        }
+    }
+    if method.is_none() {
+        return Ok(Value::array_from(&vm.globals, res));
+    }
+    Ok(self_val)
+}
+
+fn succ(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    Ok(Value::fixnum(self_val.as_fixnum().unwrap() + 1))
+}
+
+fn pred(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    Ok(Value::fixnum(self_val.as_fixnum().unwrap() - 1))
+}
+
+fn integer(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    Ok(Value::true_val())
+}
+
 #[cfg(test)]
 mod tests {
     use crate::test::*;
@@ -263,4 +659,61 @@ mod tests {
         "#;
         assert_script(program);
     }
+
+    #[test]
+    fn integer_arith() {
+        let program = r#"
+        assert(-4, -7 / 2)
+        assert(1, -7 % 2)
+        assert(3.5, 7 / 2.0)
+        assert(Rational(1, 8), 2 ** -3)
+        assert([-4, 1], -7.divmod(2))
+        assert(3.5, 7.fdiv(2))
+        assert_error { 1 / 0 }
+        assert_error { 1 % 0 }
+        assert(3, 3.send(:"+", 0))
+        assert(6.5, 3.send(:"+", 3.5))
+        "#;
+        assert_script(program);
+    }
+
+    #[test]
+    fn integer_methods() {
+        let program = r#"
+        assert(5, -5.abs)
+        assert(1200, 1234.round(-2))
+        assert(-1300, -1250.round(-2))
+        assert(1300, 1234.ceil(-2))
+        assert(1200, 1234.floor(-2))
+        assert(-1200, -1234.truncate(-2))
+        assert(12, 12.round)
+        assert(6, 12.gcd(18))
+        assert(36, 12.lcm(18))
+        assert(4, 3.pow(4, 7))
+        assert(-3, 3.pow(4, -7))
+        assert(81, 3.pow(4))
+        assert([4, 3, 2, 1], 1234.digits)
+        assert([0, 1, 1], 6.digits(2))
+        assert(3, 5.bit_length)
+        assert(0, 0.bit_length)
+        assert(8, 255.bit_length)
+        assert("ff", 255.to_s(16))
+        assert("-101", -5.to_s(2))
+        assert("255", 255.to_s)
+        a = []
+        3.upto(5) {|x| a << x }
+        5.downto(3) {|x| a << x }
+        assert([3, 4, 5, 5, 4, 3], a)
+        assert([6, 8, 10], 3.upto(5).map {|x| x * 2 })
+        assert([5, 4, 3], 5.downto(3).to_a)
+        assert(6, 5.succ)
+        assert(4, 5.pred)
+        assert(true, 5.integer?)
+        assert(false, 5.0.integer?)
+        assert(true, 0.zero?)
+        assert(true, 5.positive?)
+        assert(true, 5.odd?)
+        "#;
+        assert_script(program);
+    }
 }
@@ -0,0 +1,215 @@
+use crate::builtin::file::bytes_to_value;
+use crate::*;
+use std::io::Write;
+
+pub fn init_io(globals: &mut Globals) -> Value {
+    let id = globals.get_ident_id("IO");
+    let class = ClassRef::from(id, globals.builtins.object);
+    let io = Value::class(globals, class);
+    globals.add_builtin_instance_method(class, "write", write);
+    globals.add_builtin_instance_method(class, "print", print);
+    globals.add_builtin_instance_method(class, "puts", puts);
+    globals.add_builtin_instance_method(class, "<<", shl);
+    globals.add_builtin_instance_method(class, "flush", flush);
+    globals.add_builtin_instance_method(class, "sync", sync);
+    globals.add_builtin_instance_method(class, "sync=", set_sync);
+    globals.add_builtin_instance_method(class, "fileno", fileno);
+    globals.add_builtin_instance_method(class, "inspect", inspect);
+    for (name, gvar, fd) in &[("STDOUT", "$stdout", 1), ("STDERR", "$stderr", 2)] {
+        let mut obj = Value::ordinary_object(io);
+        let id = globals.get_ident_id("fileno");
+        obj.set_var(id, Value::fixnum(*fd));
+        let id = globals.get_ident_id(*name);
+        globals.builtins.object.set_var(id, obj);
+        let id = globals.get_ident_id(*gvar);
+        globals.global_var.insert(id, obj);
+    }
+    io
+}
+
+// Utils
+
+/// Get the file descriptor of a built-in IO object.
+fn get_fileno(vm: &mut VM, val: Value) -> Option<i64> {
+    let id = vm.globals.get_ident_id("fileno");
+    val.as_rvalue()?.get_var(id)?.as_fixnum()
+}
+
+fn expect_fileno(vm: &mut VM, val: Value) -> Result<i64, RubyError> {
+    match get_fileno(vm, val) {
+        Some(fd) => Ok(fd),
+        None => {
+            let inspect = vm.val_inspect(val);
+            Err(vm.error_type(format!("Expected IO, but {}.", inspect)))
+        }
+    }
+}
+
+fn write_fd(vm: &mut VM, fd: i64, bytes: &[u8]) -> Result<(), RubyError> {
+    let res = if fd == 2 {
+        std::io::stderr().write_all(bytes)
+    } else {
+        std::io::stdout().write_all(bytes)
+    };
+    res.map_err(|err| vm.error_internal(format!("Can not write. {:?}", err)))
+}
+
+/// Write `bytes` to `out`.
+/// If `out` is not a built-in IO object, its `write` method is called.
+pub fn write_to(vm: &mut VM, out: Value, bytes: Vec<u8>) -> Result<(), RubyError> {
+    match get_fileno(vm, out) {
+        Some(fd) => write_fd(vm, fd, &bytes),
+        None => {
+            let method = vm.globals.get_ident_id("write");
+            let method = vm.get_method(out, method)?;
+            let arg = bytes_to_value(&vm.globals, bytes);
+            vm.eval_send(method, out, &Args::new1(arg))?;
+            Ok(())
+        }
+    }
+}
+
+fn push_to_s(vm: &mut VM, buf: &mut Vec<u8>, val: Value) {
+    match val.as_bytes() {
+        Some(bytes) => buf.extend_from_slice(bytes),
+        None => buf.extend_from_slice(vm.val_to_s(val).as_bytes()),
+    }
+}
+
+/// Build the output of `puts`.
+pub fn puts_bytes(vm: &mut VM, args: &Args) -> Vec<u8> {
+    fn flatten(vm: &mut VM, buf: &mut Vec<u8>, val: Value) {
+        match val.as_array() {
+            None => {
+                push_to_s(vm, buf, val);
+                if buf.last() != Some(&b'\n') {
+                    buf.push(b'\n');
+                }
+            }
+            Some(aref) => {
+                for val in &aref.elements {
+                    flatten(vm, buf, val.clone());
+                }
+            }
+        }
+    }
+    let mut buf = vec![];
+    if args.len() == 0 {
+        buf.push(b'\n');
+    }
+    for arg in args.iter() {
+        flatten(vm, &mut buf, *arg);
+    }
+    buf
+}
+
+/// Build the output of `print`, using $, and $\ as separators.
+pub fn print_bytes(vm: &mut VM, args: &Args) -> Vec<u8> {
+    let mut buf = vec![];
+    let id = vm.globals.get_ident_id("$,");
+    let separator = vm.get_global_var(id);
+    for (i, arg) in args.iter().enumerate() {
+        if i != 0 && !separator.is_nil() {
+            push_to_s(vm, &mut buf, separator);
+        }
+        push_to_s(vm, &mut buf, *arg);
+    }
+    let id = vm.globals.get_ident_id("$\\");
+    let terminator = vm.get_global_var(id);
+    if !terminator.is_nil() {
+        push_to_s(vm, &mut buf, terminator);
+    }
+    buf
+}
+
+// Instance methods
+
+fn write(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    let fd = expect_fileno(vm, self_val)?;
+    let mut buf = vec![];
+    for arg in args.iter() {
+        push_to_s(vm, &mut buf, *arg);
+    }
+    write_fd(vm, fd, &buf)?;
+    Ok(Value::fixnum(buf.len() as i64))
+}
+
+fn print(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    let fd = expect_fileno(vm, self_val)?;
+    let buf = print_bytes(vm, args);
+    write_fd(vm, fd, &buf)?;
+    Ok(Value::nil())
+}
+
+fn puts(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    let fd = expect_fileno(vm, self_val)?;
+    let buf = puts_bytes(vm, args);
+    write_fd(vm, fd, &buf)?;
+    Ok(Value::nil())
+}
+
+fn shl(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let fd = expect_fileno(vm, self_val)?;
+    let mut buf = vec![];
+    push_to_s(vm, &mut buf, args[0]);
+    write_fd(vm, fd, &buf)?;
+    Ok(self_val)
+}
+
+fn flush(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let fd = expect_fileno(vm, self_val)?;
+    let res = if fd == 2 {
+        std::io::stderr().flush()
+    } else {
+        std::io::stdout().flush()
+    };
+    res.map_err(|err| vm.error_internal(format!("Can not flush. {:?}", err)))?;
+    Ok(self_val)
+}
+
+fn sync(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let fd = expect_fileno(vm, self_val)?;
+    Ok(Value::bool(fd == 2))
+}
+
+/// Output is always flushed at exit, so the mode is accepted and ignored.
+fn set_sync(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    expect_fileno(vm, self_val)?;
+    Ok(args[0])
+}
+
+fn fileno(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let fd = expect_fileno(vm, self_val)?;
+    Ok(Value::fixnum(fd))
+}
+
+fn inspect(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let fd = expect_fileno(vm, self_val)?;
+    let name = if fd == 2 { "<STDERR>" } else { "<STDOUT>" };
+    Ok(Value::string(&vm.globals, format!("#<IO:{}>", name)))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::*;
+
+    #[test]
+    fn io() {
+        let program = r##"
+        assert 1, STDOUT.fileno
+        assert 2, STDERR.fileno
+        assert STDOUT, $stdout
+        assert STDERR, $stderr
+        assert 4, $stdout.write("a", "bc", 1)
+        assert STDOUT, $stdout << "x" << "\n"
+        assert "#<IO:<STDOUT>>", STDOUT.inspect
+        "##;
+        assert_script(program);
+    }
+}
@@ -1,6 +1,6 @@
-use crate::loader::*;
+use crate::builtin::file::bytes_to_value;
+use crate::builtin::{complex, io, module, process, random, rational};
 use crate::*;
-use rand;
 use std::path::PathBuf;
 
 pub fn init(globals: &mut Globals) -> Value {
@@ -13,41 +13,81 @@ pub fn init(globals: &mut Globals) -> Value {
     globals.add_builtin_instance_method(kernel_class, "assert_error", assert_error);
     globals.add_builtin_instance_method(kernel_class, "require", require);
     globals.add_builtin_instance_method(kernel_class, "require_relative", require_relative);
+    globals.add_builtin_instance_method(kernel_class, "load", load);
+    globals.add_builtin_instance_method(kernel_class, "autoload", autoload);
+    globals.add_builtin_instance_method(kernel_class, "autoload?", autoload_);
     globals.add_builtin_instance_method(kernel_class, "block_given?", block_given);
     globals.add_builtin_instance_method(kernel_class, "method", method);
     globals.add_builtin_instance_method(kernel_class, "is_a?", isa);
     globals.add_builtin_instance_method(kernel_class, "Integer", integer);
     globals.add_builtin_instance_method(kernel_class, "__dir__", dir);
     globals.add_builtin_instance_method(kernel_class, "__FILE__", file_);
+    globals.add_builtin_instance_method(kernel_class, "__method__", method_name);
     globals.add_builtin_instance_method(kernel_class, "raise", raise);
-    globals.add_builtin_instance_method(kernel_class, "rand", rand);
+    globals.add_builtin_instance_method(kernel_class, "rand", random::kernel_rand);
+    globals.add_builtin_instance_method(kernel_class, "srand", random::srand);
     globals.add_builtin_instance_method(kernel_class, "loop", loop_);
     globals.add_builtin_instance_method(kernel_class, "exit", exit);
+    globals.add_builtin_instance_method(kernel_class, "exit!", exit_);
+    globals.add_builtin_instance_method(kernel_class, "gets", gets);
+    globals.add_builtin_instance_method(kernel_class, "warn", warn);
+    globals.add_builtin_instance_method(kernel_class, "trace_var", trace_var);
+    globals.add_builtin_instance_method(kernel_class, "untrace_var", untrace_var);
+    globals.add_builtin_instance_method(kernel_class, "at_exit", at_exit);
+    globals.add_builtin_instance_method(kernel_class, "proc", proc);
+    globals.add_builtin_instance_method(kernel_class, "lambda", lambda);
+    globals.add_builtin_instance_method(kernel_class, "system", process::system);
+    globals.add_builtin_instance_method(kernel_class, "`", process::backquote);
+    globals.add_builtin_instance_method(kernel_class, "exec", process::exec);
+    globals.add_builtin_instance_method(kernel_class, "spawn", process::spawn);
+    globals.add_builtin_instance_method(kernel_class, "Rational", rational::rational);
+    globals.add_builtin_instance_method(kernel_class, "Complex", complex::complex);
     let kernel = Value::class(globals, kernel_class);
+    // $LOAD_PATH starts with the directories in RUBYLIB.
+    let load_path = match std::env::var("RUBYLIB") {
+        Ok(dirs) => dirs
+            .split(':')
+            .filter(|dir| !dir.is_empty())
+            .map(|dir| Value::string(globals, dir.to_string()))
+            .collect(),
+        Err(_) => vec![],
+    };
+    let id = globals.get_ident_id("$LOAD_PATH");
+    let load_path = Value::array_from(globals, load_path);
+    globals.global_var.insert(id, load_path);
+    let id = globals.get_ident_id("$LOADED_FEATURES");
+    let features = Value::array_from(globals, vec![]);
+    globals.global_var.insert(id, features);
+    for name in &["$VERBOSE", "$DEBUG"] {
+        let id = globals.get_ident_id(*name);
+        globals.global_var.insert(id, Value::false_val());
+    }
+    let id = globals.get_ident_id("$/");
+    let separator = Value::string(globals, "\n".to_string());
+    globals.global_var.insert(id, separator);
     return kernel;
 
+    fn stdout(vm: &mut VM) -> Value {
+        let id = vm.globals.get_ident_id("$stdout");
+        vm.get_global_var(id)
+    }
+
     /// Built-in function "puts".
     fn puts(vm: &mut VM, _: Value, args: &Args) -> VMResult {
-        fn flatten(vm: &mut VM, val: Value) {
-            match val.as_array() {
-                None => println!("{}", vm.val_to_s(val)),
-                Some(aref) => {
-                    for val in &aref.elements {
-                        flatten(vm, val.clone());
-                    }
-                }
-            }
-        }
-        for arg in args.iter() {
-            flatten(vm, *arg);
-        }
+        let buf = io::puts_bytes(vm, args);
+        let out = stdout(vm);
+        io::write_to(vm, out, buf)?;
         Ok(Value::nil())
     }
 
     fn p(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+        let mut buf = String::new();
         for arg in args.iter() {
-            println!("{}", vm.val_inspect(*arg));
+            buf += &vm.val_inspect(*arg);
+            buf.push('\n');
         }
+        let out = stdout(vm);
+        io::write_to(vm, out, buf.into_bytes())?;
         if args.len() == 1 {
             Ok(args[0])
         } else {
@@ -56,16 +96,16 @@ pub fn init(globals: &mut Globals) -> Value {
     }
 
     /// Built-in function "print".
+    /// Prints $_ if no argument is given.
     fn print(vm: &mut VM, _: Value, args: &Args) -> VMResult {
-        for arg in args.iter() {
-            match arg.as_bytes() {
-                Some(bytes) => {
-                    use std::io::{self, Write};
-                    io::stdout().write(bytes).unwrap();
-                }
-                None => print!("{}", vm.val_to_s(*arg)),
-            }
-        }
+        let buf = if args.len() == 0 {
+            let last_line = vm.get_last_line();
+            io::print_bytes(vm, &Args::new1(last_line))
+        } else {
+            io::print_bytes(vm, args)
+        };
+        let out = stdout(vm);
+        io::write_to(vm, out, buf)?;
         Ok(Value::nil())
     }
 
@@ -102,61 +142,69 @@ pub fn init(globals: &mut Globals) -> Value {
             Some(string) => string,
             None => return Err(vm.error_argument("file name must be a string.")),
         };
-        let mut path = std::env::current_dir().unwrap();
-        path.push(file_name);
-        require_main(vm, path)?;
-        Ok(Value::bool(true))
+        let loaded = vm.require_feature(file_name)?;
+        Ok(Value::bool(loaded))
     }
 
+    /// Kernel#require_relative
+    /// Requires the file relative to the directory of the current file.
     fn require_relative(vm: &mut VM, _: Value, args: &Args) -> VMResult {
         vm.check_args_num(args.len(), 1)?;
         let context = vm.context();
-        let mut path = std::path::PathBuf::from(context.iseq_ref.source_info.path.clone());
+        let mut path = PathBuf::from(context.iseq_ref.source_info.path.clone());
 
         let file_name = match args[0].as_string() {
-            Some(string) => PathBuf::from(string),
+            Some(string) => string,
             None => return Err(vm.error_argument("file name must be a string.")),
         };
         path.pop();
-        for p in file_name.iter() {
-            if p == ".." {
-                path.pop();
-            } else {
-                path.push(p);
-            }
+        if path.as_os_str().is_empty() {
+            path = std::env::current_dir().unwrap();
         }
-        path.set_extension("rb");
-        require_main(vm, path)?;
-        Ok(Value::bool(true))
-    }
-
-    fn require_main(vm: &mut VM, path: PathBuf) -> Result<(), RubyError> {
-        let file_name = path.to_string_lossy().to_string();
-        let (absolute_path, program) = match load_file(file_name.clone()) {
-            Ok((path, program)) => (path, program),
-            Err(err) => {
-                match err {
-                    LoadError::NotFound(msg) => {
-                        eprintln!("No such file or directory --- {} (LoadError)", &file_name);
-                        eprintln!("{}", msg);
-                    }
-                    LoadError::CouldntOpen(msg) => {
-                        eprintln!("Cannot open file. '{}'", &file_name);
-                        eprintln!("{}", msg);
-                    }
-                }
-                return Err(vm.error_internal("LoadError"));
+        if file_name.ends_with(".rb") {
+            path.push(file_name);
+        } else {
+            path.push(format!("{}.rb", file_name));
+        }
+        if !path.is_file() {
+            let path = path.to_string_lossy();
+            return Err(vm.error_load(format!("cannot load such file -- {}", path)));
+        }
+        let loaded = vm.require_path(path)?;
+        Ok(Value::bool(loaded))
+    }
+
+    /// Kernel#load
+    /// load(file, wrap = false)
+    /// If `wrap` is true, the file is executed under an anonymous module.
+    fn load(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+        vm.check_args_range(args.len(), 1, 2)?;
+        let file_name = match args[0].as_string() {
+            Some(string) => string.to_string(),
+            None => return Err(vm.error_argument("file name must be a string.")),
+        };
+        let wrap = match args.get(1) {
+            Some(val) if val.as_module().is_some() => Some(*val),
+            Some(val) if vm.val_to_bool(*val) => {
+                let classref = ClassRef::from(None, None);
+                Some(Value::module(&vm.globals, classref))
             }
+            _ => None,
         };
-        #[cfg(feature = "verbose")]
-        #[cfg_attr(tarpaulin, skip)]
-        eprintln!("reading:{}", absolute_path.to_string_lossy());
-        vm.root_path.push(path);
-        vm.class_push(vm.globals.builtins.object);
-        vm.run(absolute_path, &program, None)?;
-        vm.class_pop();
-        vm.root_path.pop().unwrap();
-        Ok(())
+        vm.load_feature(&file_name, wrap)?;
+        Ok(Value::true_val())
+    }
+
+    /// Kernel#autoload
+    /// Registers `file` to be required when the constant is referred first in the top level.
+    fn autoload(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+        let class = vm.globals.builtins.object;
+        module::autoload(vm, class, args)
+    }
+
+    fn autoload_(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+        let class = vm.globals.builtins.object;
+        module::autoload_(vm, class, args)
     }
 
     /// Built-in function "block_given?".
@@ -171,22 +219,15 @@ pub fn init(globals: &mut Globals) -> Value {
             None => return Err(vm.error_type("An argument must be a Symbol.")),
         };
         let method = vm.get_method(self_val, name)?;
-        let val = Value::method(&vm.globals, name, self_val, method);
+        let class = self_val.get_class_object_for_method(&vm.globals);
+        let owner = vm.get_method_owner(class, name).unwrap_or(class);
+        let val = Value::method(&vm.globals, name, self_val, method, owner);
         Ok(val)
     }
 
     fn isa(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
         vm.check_args_num(args.len(), 1)?;
-        let mut recv_class = self_val.get_class_object(&vm.globals);
-        loop {
-            if recv_class.id() == args[0].id() {
-                return Ok(Value::true_val());
-            }
-            recv_class = recv_class.as_class().superclass;
-            if recv_class.is_nil() {
-                return Ok(Value::false_val());
-            }
-        }
+        Ok(Value::bool(vm.is_kind_of(self_val, args[0])))
     }
 
     fn integer(vm: &mut VM, _: Value, args: &Args) -> VMResult {
@@ -250,6 +291,22 @@ pub fn init(globals: &mut Globals) -> Value {
         ))
     }
 
+    /// Built-in function "__method__".
+    /// Returns the name of the current method (or nil at the top level) as a Symbol.
+    fn method_name(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+        vm.check_args_num(args.len(), 0)?;
+        let mut context = vm.context();
+        loop {
+            if let ISeqKind::Method(id) = context.kind {
+                return Ok(Value::symbol(id));
+            }
+            match context.outer {
+                Some(outer) => context = outer,
+                None => return Ok(Value::nil()),
+            }
+        }
+    }
+
     fn raise(vm: &mut VM, _: Value, args: &Args) -> VMResult {
         vm.check_args_range(args.len(), 0, 2)?;
         for arg in args.iter() {
@@ -258,11 +315,6 @@ pub fn init(globals: &mut Globals) -> Value {
         Err(vm.error_unimplemented("error"))
     }
 
-    fn rand(_vm: &mut VM, _: Value, _args: &Args) -> VMResult {
-        let num = rand::random();
-        Ok(Value::flonum(num))
-    }
-
     fn loop_(vm: &mut VM, _: Value, args: &Args) -> VMResult {
         let method = vm.expect_block(args.block)?;
         let arg = Args::new0();
@@ -278,8 +330,142 @@ pub fn init(globals: &mut Globals) -> Value {
         } else {
             args[0].expect_integer(vm, "Expect Integer.")?
         };
+        vm.exec_at_exit();
+        std::process::exit(code as i32);
+    }
+
+    /// Exit immediately without running at_exit handlers.
+    fn exit_(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+        vm.check_args_range(args.len(), 0, 1)?;
+        let code = match args.get(0) {
+            None => 1,
+            Some(val) if *val == Value::true_val() => 0,
+            Some(val) if *val == Value::false_val() => 1,
+            Some(val) => val.expect_integer(vm, "Expect Integer.")?,
+        };
         std::process::exit(code as i32);
     }
+
+    /// Read a line terminated by $/ from the standard input and set it to $_.
+    fn gets(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+        use std::io::BufRead;
+        vm.check_args_num(args.len(), 0)?;
+        let id = vm.globals.get_ident_id("$/");
+        let separator = match vm.get_global_var(id).as_string() {
+            Some(s) => s.to_string(),
+            None => "".to_string(),
+        };
+        let stdin = std::io::stdin();
+        let mut stdin = stdin.lock();
+        let mut line = vec![];
+        let res = match separator.as_bytes() {
+            // nil reads the whole input.
+            [] => std::io::Read::read_to_end(&mut stdin, &mut line),
+            [.., last] => loop {
+                match stdin.read_until(*last, &mut line) {
+                    Ok(0) => break Ok(0),
+                    Ok(_) if line.ends_with(separator.as_bytes()) => break Ok(line.len()),
+                    Ok(_) => {}
+                    Err(err) => break Err(err),
+                }
+            },
+        };
+        let val = match res {
+            Ok(_) if line.is_empty() => Value::nil(),
+            Ok(_) => bytes_to_value(&vm.globals, line),
+            Err(err) => return Err(vm.error_internal(format!("Can not read stdin. {:?}", err))),
+        };
+        vm.set_last_line(val);
+        Ok(val)
+    }
+
+    /// Print messages to $stderr unless $VERBOSE is nil.
+    fn warn(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+        let id = vm.globals.get_ident_id("$VERBOSE");
+        if vm.get_global_var(id).is_nil() || args.len() == 0 {
+            return Ok(Value::nil());
+        }
+        let buf = io::puts_bytes(vm, args);
+        let id = vm.globals.get_ident_id("$stderr");
+        let out = vm.get_global_var(id);
+        io::write_to(vm, out, buf)?;
+        Ok(Value::nil())
+    }
+
+    /// Kernel#trace_var
+    /// Registers a proc (or a block) called with the new value whenever the global variable is assigned.
+    fn trace_var(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+        vm.check_args_range(args.len(), 1, 2)?;
+        let id = expect_global_var_name(vm, args[0])?;
+        let procobj = if args.len() == 2 {
+            if args[1].as_proc().is_none() {
+                return Err(vm.error_argument("Command must be a Proc."));
+            }
+            args[1]
+        } else {
+            let block = vm.expect_block(args.block)?;
+            vm.create_proc(block)?
+        };
+        vm.globals.global_var.add_trace(id, procobj);
+        Ok(Value::nil())
+    }
+
+    /// Kernel#untrace_var
+    /// Removes the given proc (or all procs) registered for the global variable, and returns them.
+    fn untrace_var(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+        vm.check_args_range(args.len(), 1, 2)?;
+        let id = expect_global_var_name(vm, args[0])?;
+        let procs = vm.globals.global_var.remove_trace(id, args.get(1).cloned());
+        Ok(Value::array_from(&vm.globals, procs))
+    }
+
+    fn expect_global_var_name(vm: &mut VM, val: Value) -> Result<IdentId, RubyError> {
+        let name = match val.as_symbol() {
+            Some(id) => vm.globals.get_ident_name(id).to_string(),
+            None => match val.as_string() {
+                Some(s) => s.to_string(),
+                None => {
+                    let inspect = vm.val_inspect(val);
+                    return Err(vm.error_type(format!("{} is not a symbol nor a string.", inspect)));
+                }
+            },
+        };
+        if !name.starts_with('$') {
+            return Err(vm.error_name(format!(
+                "'{}' is not allowed as a global variable name.",
+                name
+            )));
+        }
+        Ok(vm.globals.get_ident_id(name))
+    }
+
+    fn at_exit(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+        vm.check_args_num(args.len(), 0)?;
+        let method = vm.expect_block(args.block)?;
+        let procobj = vm.create_proc(method)?;
+        vm.globals.at_exit.push(procobj);
+        Ok(procobj)
+    }
+
+    fn proc(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+        vm.check_args_num(args.len(), 0)?;
+        let method = vm.expect_block(args.block)?;
+        vm.create_proc(method)
+    }
+
+    /// Kernel#lambda
+    /// A Proc object given as a block is returned as is.
+    fn lambda(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+        vm.check_args_num(args.len(), 0)?;
+        match vm.expect_block(args.block)? {
+            Block::Block(method) => {
+                let procobj = vm.create_proc(Block::Block(method))?;
+                procobj.as_proc().unwrap().is_lambda = true;
+                Ok(procobj)
+            }
+            Block::Proc(procobj) => Ok(procobj),
+        }
+    }
 }
 
 #[cfg(test)]
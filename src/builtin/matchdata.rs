@@ -0,0 +1,316 @@
+use crate::*;
+use fancy_regex::Captures;
+
+/// The result of a regexp match.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchInfo {
+    /// The target string.
+    string: String,
+    /// Byte ranges of the whole match and each group.
+    groups: Vec<Option<(usize, usize)>>,
+    /// Names of the groups (None for the whole match and unnamed groups).
+    names: Vec<Option<String>>,
+}
+
+pub type MatchDataRef = Ref<MatchInfo>;
+
+impl MatchInfo {
+    pub fn new(re: &Regexp, captures: &Captures, given: &str) -> Self {
+        let groups = (0..captures.len())
+            .map(|i| captures.get(i).map(|m| (m.start(), m.end())))
+            .collect();
+        let names = re
+            .capture_names()
+            .map(|name| name.map(|s| s.to_string()))
+            .collect();
+        MatchInfo {
+            string: given.to_string(),
+            groups,
+            names,
+        }
+    }
+
+    /// The matched string of the `i`th group (0 for the whole match).
+    pub fn group(&self, i: usize) -> Option<&str> {
+        match self.groups.get(i) {
+            Some(Some((start, end))) => Some(&self.string[*start..*end]),
+            _ => None,
+        }
+    }
+
+    /// The index of the group named `name`.
+    pub fn group_index(&self, name: &str) -> Option<usize> {
+        self.names
+            .iter()
+            .rposition(|n| n.as_ref().map(|s| s.as_str()) == Some(name))
+    }
+
+    /// The string before the match ($`).
+    pub fn pre_match(&self) -> &str {
+        &self.string[..self.groups[0].unwrap().0]
+    }
+
+    /// The string after the match ($').
+    pub fn post_match(&self) -> &str {
+        &self.string[self.groups[0].unwrap().1..]
+    }
+
+    /// The last matched group ($+).
+    pub fn last_group(&self) -> Option<&str> {
+        (1..self.groups.len())
+            .rev()
+            .find_map(|i| self.group(i))
+            .or_else(|| {
+                if self.groups.len() == 1 {
+                    self.group(0)
+                } else {
+                    None
+                }
+            })
+    }
+
+    pub fn len(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// The char offset of the start or the end of the `i`th group.
+    fn offset(&self, i: usize, end: bool) -> Option<usize> {
+        match self.groups.get(i) {
+            Some(Some((s, e))) => {
+                let pos = if end { *e } else { *s };
+                Some(self.string[..pos].chars().count())
+            }
+            _ => None,
+        }
+    }
+
+    pub fn inspect(&self) -> String {
+        let mut s = format!("#<MatchData {:?}", self.group(0).unwrap_or(""));
+        for i in 1..self.groups.len() {
+            let name = match &self.names.get(i) {
+                Some(Some(name)) => name.clone(),
+                _ => i.to_string(),
+            };
+            match self.group(i) {
+                Some(g) => s += &format!(" {}:{:?}", name, g),
+                None => s += &format!(" {}:nil", name),
+            }
+        }
+        s + ">"
+    }
+}
+
+pub fn init_matchdata(globals: &mut Globals) -> Value {
+    let id = globals.get_ident_id("MatchData");
+    let class = ClassRef::from(id, globals.builtins.object);
+    let obj = Value::class(globals, class);
+    globals.add_builtin_instance_method(class, "[]", index);
+    globals.add_builtin_instance_method(class, "to_a", to_a);
+    globals.add_builtin_instance_method(class, "captures", captures);
+    globals.add_builtin_instance_method(class, "named_captures", named_captures);
+    globals.add_builtin_instance_method(class, "names", names);
+    globals.add_builtin_instance_method(class, "pre_match", pre_match);
+    globals.add_builtin_instance_method(class, "post_match", post_match);
+    globals.add_builtin_instance_method(class, "begin", begin);
+    globals.add_builtin_instance_method(class, "end", end);
+    globals.add_builtin_instance_method(class, "size", size);
+    globals.add_builtin_instance_method(class, "length", size);
+    globals.add_builtin_instance_method(class, "string", string);
+    globals.add_builtin_instance_method(class, "to_s", to_s);
+    globals.add_builtin_instance_method(class, "inspect", inspect);
+    obj
+}
+
+// Utils
+
+fn expect_matchdata(vm: &mut VM, val: Value) -> Result<MatchDataRef, RubyError> {
+    match val.as_matchdata() {
+        Some(mref) => Ok(mref),
+        None => {
+            let inspect = vm.val_inspect(val);
+            Err(vm.error_type(format!("Expected MatchData, but {}.", inspect)))
+        }
+    }
+}
+
+fn str_or_nil(vm: &VM, s: Option<&str>) -> Value {
+    match s {
+        Some(s) => Value::string(&vm.globals, s.to_string()),
+        None => Value::nil(),
+    }
+}
+
+/// Get the index of the group specified by an Integer, a String or a Symbol.
+fn group_index(vm: &mut VM, mref: MatchDataRef, arg: Value) -> Result<Option<usize>, RubyError> {
+    if let Some(i) = arg.as_fixnum() {
+        let len = mref.len() as i64;
+        let i = if i < 0 { i + len } else { i };
+        return Ok(if 0 <= i && i < len {
+            Some(i as usize)
+        } else {
+            None
+        });
+    }
+    let name = match arg.as_symbol() {
+        Some(id) => vm.globals.get_ident_name(id).to_string(),
+        None => match arg.as_string() {
+            Some(s) => s.to_string(),
+            None => {
+                let inspect = vm.val_inspect(arg);
+                return Err(vm.error_type(format!(
+                    "No implicit conversion of {} into Integer.",
+                    inspect
+                )));
+            }
+        },
+    };
+    match mref.group_index(&name) {
+        Some(i) => Ok(Some(i)),
+        None => Err(vm.error_index(format!("Undefined group name reference: {}", name))),
+    }
+}
+
+// Instance methods
+
+fn index(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let mref = expect_matchdata(vm, self_val)?;
+    let s = group_index(vm, mref, args[0])?.and_then(|i| mref.group(i));
+    Ok(str_or_nil(vm, s))
+}
+
+fn to_a(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let mref = expect_matchdata(vm, self_val)?;
+    let ary = (0..mref.len())
+        .map(|i| str_or_nil(vm, mref.group(i)))
+        .collect();
+    Ok(Value::array_from(&vm.globals, ary))
+}
+
+fn captures(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let mref = expect_matchdata(vm, self_val)?;
+    let ary = (1..mref.len())
+        .map(|i| str_or_nil(vm, mref.group(i)))
+        .collect();
+    Ok(Value::array_from(&vm.globals, ary))
+}
+
+fn named_captures(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let mref = expect_matchdata(vm, self_val)?;
+    let mut map = std::collections::HashMap::new();
+    for (i, name) in mref.names.iter().enumerate() {
+        if let Some(name) = name {
+            let key = Value::string(&vm.globals, name.clone());
+            map.insert(HashKey(key), str_or_nil(vm, mref.group(i)));
+        }
+    }
+    Ok(Value::hash_from(&vm.globals, map))
+}
+
+fn names(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let mref = expect_matchdata(vm, self_val)?;
+    let ary = mref
+        .names
+        .iter()
+        .filter_map(|name| name.as_ref())
+        .map(|name| Value::string(&vm.globals, name.clone()))
+        .collect();
+    Ok(Value::array_from(&vm.globals, ary))
+}
+
+fn pre_match(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let mref = expect_matchdata(vm, self_val)?;
+    Ok(Value::string(&vm.globals, mref.pre_match().to_string()))
+}
+
+fn post_match(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let mref = expect_matchdata(vm, self_val)?;
+    Ok(Value::string(&vm.globals, mref.post_match().to_string()))
+}
+
+fn offset(vm: &mut VM, self_val: Value, args: &Args, end: bool) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let mref = expect_matchdata(vm, self_val)?;
+    let i = match group_index(vm, mref, args[0])? {
+        Some(i) => i,
+        None => {
+            let inspect = vm.val_inspect(args[0]);
+            return Err(vm.error_index(format!("Index {} out of matches.", inspect)));
+        }
+    };
+    match mref.offset(i, end) {
+        Some(pos) => Ok(Value::fixnum(pos as i64)),
+        None => Ok(Value::nil()),
+    }
+}
+
+fn begin(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    offset(vm, self_val, args, false)
+}
+
+fn end(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    offset(vm, self_val, args, true)
+}
+
+fn size(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let mref = expect_matchdata(vm, self_val)?;
+    Ok(Value::fixnum(mref.len() as i64))
+}
+
+fn string(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let mref = expect_matchdata(vm, self_val)?;
+    Ok(Value::string(&vm.globals, mref.string.clone()))
+}
+
+fn to_s(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let mref = expect_matchdata(vm, self_val)?;
+    Ok(str_or_nil(vm, mref.group(0)))
+}
+
+fn inspect(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let mref = expect_matchdata(vm, self_val)?;
+    Ok(Value::string(&vm.globals, mref.inspect()))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::*;
+
+    #[test]
+    fn matchdata() {
+        let program = r##"
+        m = /(?<num>\d+)-(?<word>[a-z]+)/.match("ab 12-xyz!")
+        assert("12-xyz", m[0])
+        assert("12", m[1])
+        assert("12", m[:num])
+        assert("xyz", m[:word])
+        assert("xyz", m["word"])
+        assert("xyz", m[-1])
+        assert(nil, m[3])
+        assert(["12-xyz", "12", "xyz"], m.to_a)
+        assert(["12", "xyz"], m.captures)
+        assert({"num" => "12", "word" => "xyz"}, m.named_captures)
+        assert(["num", "word"], m.names)
+        assert("ab ", m.pre_match)
+        assert("!", m.post_match)
+        assert(3, m.begin(0))
+        assert(9, m.end(0))
+        assert(3, m.size)
+        assert("ab 12-xyz!", m.string)
+        assert("12-xyz", m.to_s)
+        assert("#<MatchData \"12-xyz\" num:\"12\" word:\"xyz\">", m.inspect)
+        assert(nil, /z/.match("abc"))
+        "##;
+        assert_script(program);
+    }
+}
@@ -1,62 +1,225 @@
+use crate::builtin::numeric::to_f64;
 use crate::*;
 
 pub fn init_math(globals: &mut Globals) -> Value {
     let id = globals.get_ident_id("Math");
     let class = ClassRef::from(id, globals.builtins.object);
-    let obj = Value::class(globals, class);
+    let mut obj = Value::class(globals, class);
     globals.add_builtin_class_method(obj, "sqrt", sqrt);
+    globals.add_builtin_class_method(obj, "cbrt", cbrt);
     globals.add_builtin_class_method(obj, "cos", cos);
     globals.add_builtin_class_method(obj, "sin", sin);
+    globals.add_builtin_class_method(obj, "tan", tan);
+    globals.add_builtin_class_method(obj, "acos", acos);
+    globals.add_builtin_class_method(obj, "asin", asin);
+    globals.add_builtin_class_method(obj, "atan", atan);
+    globals.add_builtin_class_method(obj, "atan2", atan2);
+    globals.add_builtin_class_method(obj, "cosh", cosh);
+    globals.add_builtin_class_method(obj, "sinh", sinh);
+    globals.add_builtin_class_method(obj, "tanh", tanh);
+    globals.add_builtin_class_method(obj, "acosh", acosh);
+    globals.add_builtin_class_method(obj, "asinh", asinh);
+    globals.add_builtin_class_method(obj, "atanh", atanh);
+    globals.add_builtin_class_method(obj, "exp", exp);
+    globals.add_builtin_class_method(obj, "log", log);
+    globals.add_builtin_class_method(obj, "log2", log2);
+    globals.add_builtin_class_method(obj, "log10", log10);
+    globals.add_builtin_class_method(obj, "hypot", hypot);
+    globals.add_builtin_class_method(obj, "erf", erf);
+    globals.add_builtin_class_method(obj, "erfc", erfc);
+    globals.add_builtin_class_method(obj, "gamma", gamma);
+    globals.add_builtin_class_method(obj, "lgamma", lgamma);
+    globals.add_builtin_class_method(obj, "ldexp", ldexp);
+    globals.add_builtin_class_method(obj, "frexp", frexp);
+    let id = globals.get_ident_id("PI");
+    obj.set_var(id, Value::flonum(std::f64::consts::PI));
+    let id = globals.get_ident_id("E");
+    obj.set_var(id, Value::flonum(std::f64::consts::E));
+    let id = globals.get_ident_id("ArgumentError");
+    let argument_error = globals.builtins.object.get_var(id).unwrap();
+    let id = globals.get_ident_id("DomainError");
+    let class = ClassRef::from(id, argument_error);
+    obj.set_var(id, Value::class(globals, class));
     obj
 }
 
-// Class methods
+mod libm {
+    use std::os::raw::c_int;
+    extern "C" {
+        pub fn erf(x: f64) -> f64;
+        pub fn erfc(x: f64) -> f64;
+        pub fn tgamma(x: f64) -> f64;
+        pub fn lgamma_r(x: f64, sign: *mut c_int) -> f64;
+        pub fn frexp(x: f64, exp: *mut c_int) -> f64;
+        pub fn ldexp(x: f64, exp: c_int) -> f64;
+    }
+}
 
-// Instance methods
+// Utils
 
-fn sqrt(vm: &mut VM, _: Value, args: &Args) -> VMResult {
-    let arg = args[0];
-    let num = if arg.is_packed_num() {
-        if arg.is_packed_fixnum() {
-            arg.as_packed_fixnum() as f64
-        } else {
-            arg.as_packed_flonum()
+/// Convert Integer, Float or Rational into f64.
+fn to_float(vm: &mut VM, val: Value) -> Result<f64, RubyError> {
+    match to_f64(val) {
+        Some(f) => Ok(f),
+        None => {
+            let name = if val.is_nil() {
+                "nil".to_string()
+            } else {
+                vm.globals.get_class_name(val)
+            };
+            Err(vm.error_type(format!("Can't convert {} into Float.", name)))
         }
-    } else {
-        return Err(vm.error_type("Must be a number."));
-    };
-    let res = Value::flonum(num.sqrt());
-    Ok(res)
+    }
 }
 
-fn cos(vm: &mut VM, _: Value, args: &Args) -> VMResult {
-    let arg = args[0];
-    let num = if arg.is_packed_num() {
-        if arg.is_packed_fixnum() {
-            arg.as_packed_fixnum() as f64
-        } else {
-            arg.as_packed_flonum()
+/// Get the single argument of a math function as f64.
+fn arg_to_float(vm: &mut VM, args: &Args) -> Result<f64, RubyError> {
+    vm.check_args_num(args.len(), 1)?;
+    to_float(vm, args[0])
+}
+
+fn error_domain(vm: &mut VM, name: &str) -> RubyError {
+    vm.error_domain(format!(
+        "Numerical argument is out of domain - \"{}\"",
+        name
+    ))
+}
+
+macro_rules! define_math_fn {
+    ($fname:ident, $func:expr) => {
+        fn $fname(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+            let num = arg_to_float(vm, args)?;
+            Ok(Value::flonum($func(num)))
+        }
+    };
+    ($fname:ident, $func:expr, $domain:expr) => {
+        fn $fname(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+            let num = arg_to_float(vm, args)?;
+            if !$domain(num) {
+                return Err(error_domain(vm, stringify!($fname)));
+            }
+            Ok(Value::flonum($func(num)))
         }
-    } else {
-        return Err(vm.error_type("Must be a number."));
     };
-    let res = Value::flonum(num.cos());
-    Ok(res)
 }
 
-fn sin(vm: &mut VM, _: Value, args: &Args) -> VMResult {
-    let arg = args[0];
-    let num = if arg.is_packed_num() {
-        if arg.is_packed_fixnum() {
-            arg.as_packed_fixnum() as f64
+/// Returns true if `num` is in the domain, treating NaN as in the domain.
+fn in_range(num: f64, min: f64, max: f64) -> bool {
+    num.is_nan() || (min <= num && num <= max)
+}
+
+// Class methods
+
+define_math_fn!(sqrt, f64::sqrt, |x| in_range(x, 0.0, std::f64::INFINITY));
+define_math_fn!(cbrt, f64::cbrt);
+define_math_fn!(cos, f64::cos);
+define_math_fn!(sin, f64::sin);
+define_math_fn!(tan, f64::tan);
+define_math_fn!(acos, f64::acos, |x| in_range(x, -1.0, 1.0));
+define_math_fn!(asin, f64::asin, |x| in_range(x, -1.0, 1.0));
+define_math_fn!(atan, f64::atan);
+define_math_fn!(cosh, f64::cosh);
+define_math_fn!(sinh, f64::sinh);
+define_math_fn!(tanh, f64::tanh);
+define_math_fn!(acosh, f64::acosh, |x| in_range(x, 1.0, std::f64::INFINITY));
+define_math_fn!(asinh, f64::asinh);
+define_math_fn!(atanh, f64::atanh, |x| in_range(x, -1.0, 1.0));
+define_math_fn!(exp, f64::exp);
+define_math_fn!(log2, f64::log2, |x| in_range(x, 0.0, std::f64::INFINITY));
+define_math_fn!(log10, f64::log10, |x| in_range(x, 0.0, std::f64::INFINITY));
+define_math_fn!(erf, |x| unsafe { libm::erf(x) });
+define_math_fn!(erfc, |x| unsafe { libm::erfc(x) });
+
+/// Math.log(x, base = E)
+fn log(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_range(args.len(), 1, 2)?;
+    let num = to_float(vm, args[0])?;
+    if !in_range(num, 0.0, std::f64::INFINITY) {
+        return Err(error_domain(vm, "log"));
+    }
+    if args.len() == 1 {
+        return Ok(Value::flonum(num.ln()));
+    }
+    let base = to_float(vm, args[1])?;
+    if !in_range(base, 0.0, std::f64::INFINITY) {
+        return Err(error_domain(vm, "log"));
+    }
+    Ok(Value::flonum(num.ln() / base.ln()))
+}
+
+fn atan2(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 2)?;
+    let y = to_float(vm, args[0])?;
+    let x = to_float(vm, args[1])?;
+    Ok(Value::flonum(y.atan2(x)))
+}
+
+fn hypot(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 2)?;
+    let x = to_float(vm, args[0])?;
+    let y = to_float(vm, args[1])?;
+    Ok(Value::flonum(x.hypot(y)))
+}
+
+/// Math.gamma(x)
+/// Negative integers and -Infinity are out of the domain.
+fn gamma(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    let num = arg_to_float(vm, args)?;
+    if num == std::f64::NEG_INFINITY || (num < 0.0 && num.fract() == 0.0) {
+        return Err(error_domain(vm, "gamma"));
+    }
+    if num == 0.0 {
+        // Keep the sign of zero.
+        let inf = if num.is_sign_negative() {
+            std::f64::NEG_INFINITY
         } else {
-            arg.as_packed_flonum()
-        }
+            std::f64::INFINITY
+        };
+        return Ok(Value::flonum(inf));
+    }
+    Ok(Value::flonum(unsafe { libm::tgamma(num) }))
+}
+
+/// Math.lgamma(x)
+/// Returns [log(|gamma(x)|), sign of gamma(x)].
+fn lgamma(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    let num = arg_to_float(vm, args)?;
+    if num == std::f64::NEG_INFINITY {
+        return Err(error_domain(vm, "lgamma"));
+    }
+    let (res, sign) = if num == 0.0 {
+        let sign = if num.is_sign_negative() { -1 } else { 1 };
+        (std::f64::INFINITY, sign)
     } else {
-        return Err(vm.error_type("Must be a number."));
+        let mut sign = 0;
+        let res = unsafe { libm::lgamma_r(num, &mut sign) };
+        (res, if sign < 0 { -1 } else { 1 })
     };
-    let res = Value::flonum(num.sin());
-    Ok(res)
+    let ary = vec![Value::flonum(res), Value::fixnum(sign)];
+    Ok(Value::array_from(&vm.globals, ary))
+}
+
+/// Math.ldexp(fraction, exponent)
+/// Returns fraction * 2 ** exponent.
+fn ldexp(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 2)?;
+    let frac = to_float(vm, args[0])?;
+    let exp = match args[1].as_flonum() {
+        Some(f) => f as i64,
+        None => vm.expect_integer(args[1], "Exponent")?,
+    };
+    let exp = exp.max(std::i32::MIN as i64).min(std::i32::MAX as i64);
+    Ok(Value::flonum(unsafe { libm::ldexp(frac, exp as i32) }))
+}
+
+/// Math.frexp(x)
+/// Returns [fraction, exponent] where x == fraction * 2 ** exponent.
+fn frexp(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    let num = arg_to_float(vm, args)?;
+    let mut exp = 0;
+    let frac = unsafe { libm::frexp(num, &mut exp) };
+    let ary = vec![Value::flonum(frac), Value::fixnum(exp as i64)];
+    Ok(Value::array_from(&vm.globals, ary))
 }
 
 #[cfg(test)]
@@ -77,4 +240,59 @@ mod test {
         "#;
         assert_script(program);
     }
+
+    #[test]
+    fn math_functions() {
+        let program = r#"
+        assert(3.141592653589793, Math::PI)
+        assert(2.718281828459045, Math::E)
+        assert(1.0, Math.tan(Math::PI / 4).round(10))
+        assert(0.7853981633974483, Math.atan(1))
+        assert(-2.356194490192345, Math.atan2(-1, -1))
+        assert(0.5, Math.sin(Math.asin(0.5)).round(10))
+        assert(0.0, Math.acos(1))
+        assert(0.0, Math.sinh(0))
+        assert(1.0, Math.cosh(0))
+        assert(0.0, Math.tanh(0))
+        assert(0.5, Math.tanh(Math.atanh(0.5)).round(10))
+        assert(0.0, Math.acosh(1))
+        assert(0.0, Math.asinh(0))
+        assert(1.0, Math.exp(0))
+        assert(1.0, Math.log(Math::E))
+        assert(3.0, Math.log(8, 2))
+        assert(3.0, Math.log2(8))
+        assert(3.0, Math.log10(1000))
+        assert(-Float::INFINITY, Math.log(0))
+        assert(3.0, Math.cbrt(27))
+        assert(5.0, Math.hypot(3, 4))
+        assert(0.0, Math.erf(0))
+        assert(1.0, Math.erfc(0))
+        assert(0.8427007929497149, Math.erf(1))
+        assert(120.0, Math.gamma(6))
+        assert(Float::INFINITY, Math.gamma(0))
+        assert([0.0, 1], Math.lgamma(1))
+        assert(-1, Math.lgamma(-0.5)[1])
+        assert(1, Math.lgamma(-1.5)[1])
+        assert(12.0, Math.ldexp(0.75, 4))
+        assert([0.75, 4], Math.frexp(12))
+        assert(0.5, Math.sqrt(Rational(1, 4)))
+        assert(true, Math.sqrt(Float::NAN).nan?)
+        assert(true, Math::DomainError.is_a?(Class))
+        assert(ArgumentError, Math::DomainError.superclass)
+        assert_error { Math.sqrt(-1) }
+        assert_error { Math.log(-1) }
+        assert_error { Math.log(8, -2) }
+        assert_error { Math.log2(-1) }
+        assert_error { Math.log10(-1) }
+        assert_error { Math.acos(2) }
+        assert_error { Math.asin(-2) }
+        assert_error { Math.acosh(0.5) }
+        assert_error { Math.atanh(2) }
+        assert_error { Math.gamma(-1) }
+        assert_error { Math.lgamma(-Float::INFINITY) }
+        assert_error { Math.sqrt("4") }
+        assert_error { Math.sin(nil) }
+        "#;
+        assert_script(program);
+    }
 }
@@ -1,17 +1,209 @@
 use crate::*;
+use std::path::PathBuf;
 
 pub fn init_method(globals: &mut Globals) -> Value {
     let proc_id = globals.get_ident_id("Method");
     let class = ClassRef::from(proc_id, globals.builtins.object);
     globals.add_builtin_instance_method(class, "call", method_call);
+    globals.add_builtin_instance_method(class, "to_proc", to_proc);
+    globals.add_builtin_instance_method(class, "arity", arity);
+    globals.add_builtin_instance_method(class, "name", name);
+    globals.add_builtin_instance_method(class, "owner", owner);
+    globals.add_builtin_instance_method(class, "receiver", receiver);
+    globals.add_builtin_instance_method(class, "unbind", unbind);
     Value::class(globals, class)
 }
 
+pub fn init_unbound_method(globals: &mut Globals) -> Value {
+    let proc_id = globals.get_ident_id("UnboundMethod");
+    let class = ClassRef::from(proc_id, globals.builtins.object);
+    globals.add_builtin_instance_method(class, "bind", bind);
+    globals.add_builtin_instance_method(class, "bind_call", bind_call);
+    globals.add_builtin_instance_method(class, "arity", arity);
+    globals.add_builtin_instance_method(class, "name", name);
+    globals.add_builtin_instance_method(class, "owner", owner);
+    Value::class(globals, class)
+}
+
+fn expect_method(vm: &VM, self_val: Value) -> Result<MethodObjRef, RubyError> {
+    match self_val.as_method() {
+        Some(method) => Ok(method),
+        None => Err(vm.error_unimplemented("Expected Method object.")),
+    }
+}
+
 pub fn method_call(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
-    let method = match self_val.as_method() {
-        Some(method) => method,
-        None => return Err(vm.error_unimplemented("Expected Method object.")),
-    };
+    let method = expect_method(vm, self_val)?;
     let res = vm.eval_send(method.method, method.receiver, args)?;
     Ok(res)
 }
+
+/// Method#to_proc
+/// Returns a Proc which calls the method with given arguments and block.
+fn to_proc(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let program = "Proc.new { |*args, &block| call(*args, &block) }";
+    let method = vm.parse_program(PathBuf::from("(to_proc)"), program)?;
+    let procobj = vm.eval_send(method, self_val, &Args::new0())?;
+    procobj.as_proc().unwrap().is_lambda = true;
+    Ok(procobj)
+}
+
+fn arity(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let method = expect_method(vm, self_val)?;
+    let arity = match vm.globals.get_method_info(method.method) {
+        MethodInfo::RubyFunc { iseq } => iseq.params.arity(),
+        MethodInfo::AttrReader { .. } => 0,
+        MethodInfo::AttrWriter { .. } => 1,
+        MethodInfo::BuiltinFunc { .. } | MethodInfo::BuiltinClosure { .. } => -1,
+    };
+    Ok(Value::fixnum(arity))
+}
+
+fn name(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let method = expect_method(vm, self_val)?;
+    Ok(Value::symbol(method.name))
+}
+
+fn owner(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let method = expect_method(vm, self_val)?;
+    Ok(method.owner)
+}
+
+fn receiver(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let method = expect_method(vm, self_val)?;
+    Ok(method.receiver)
+}
+
+fn unbind(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let method = expect_method(vm, self_val)?;
+    let val = Value::unbound_method(&vm.globals, method.name, method.method, method.owner);
+    Ok(val)
+}
+
+/// UnboundMethod#bind(obj)
+/// `obj` must be kind of the owner of the method.
+fn bind(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let method = expect_method(vm, self_val)?;
+    let receiver = args[0];
+    if !vm.is_kind_of(receiver, method.owner) {
+        let owner = vm.val_inspect(method.owner);
+        return Err(vm.error_type(format!("bind argument must be an instance of {}.", owner)));
+    }
+    let val = Value::method(
+        &vm.globals,
+        method.name,
+        receiver,
+        method.method,
+        method.owner,
+    );
+    Ok(val)
+}
+
+fn bind_call(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    if args.len() == 0 {
+        return Err(vm.error_argument("Wrong number of arguments. (given 0, expected 1+)"));
+    }
+    let method = bind(vm, self_val, &Args::new1(args[0]))?;
+    let mut call_args = Args::new(args.len() - 1);
+    for i in 1..args.len() {
+        call_args[i - 1] = args[i];
+    }
+    call_args.block = args.block;
+    method_call(vm, method, &call_args)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::*;
+
+    #[test]
+    fn method_to_proc() {
+        let program = r#"
+        def double(x)
+          x * 2
+        end
+        assert([2, 4, 6], [1, 2, 3].map(&method(:double)))
+        pr = method(:double).to_proc
+        assert(Proc, pr.class)
+        assert(10, pr.call(5))
+        def with_block
+          yield 3
+        end
+        assert(9, method(:with_block).to_proc.call { |x| x * x })
+        "#;
+        assert_script(program);
+    }
+
+    #[test]
+    fn method_arity_owner() {
+        let program = r#"
+        class Foo
+          attr_accessor :a
+          def m0; end
+          def m2(a, b); end
+          def m_opt(a, b = 1); end
+          def m_rest(*a); end
+          def m_post(a, *b, c); end
+        end
+        foo = Foo.new
+        assert(0, foo.method(:m0).arity)
+        assert(2, foo.method(:m2).arity)
+        assert(-2, foo.method(:m_opt).arity)
+        assert(-1, foo.method(:m_rest).arity)
+        assert(-3, foo.method(:m_post).arity)
+        assert(0, foo.method(:a).arity)
+        assert(1, foo.method("a=".to_sym).arity)
+        assert(:m2, foo.method(:m2).name)
+        assert(foo, foo.method(:m2).receiver)
+        assert(Foo, foo.method(:m2).owner)
+        module Bar
+          def bar; 7; end
+        end
+        class Baz < Foo
+          include Bar
+        end
+        assert(Bar, Baz.new.method(:bar).owner)
+        assert(Foo, Baz.new.method(:m0).owner)
+        "#;
+        assert_script(program);
+    }
+
+    #[test]
+    fn unbound_method() {
+        let program = r#"
+        class Foo
+          def initialize(x)
+            @x = x
+          end
+          def get(y)
+            @x + y
+          end
+        end
+        class Bar < Foo
+          def initialize(x)
+            @x = x
+          end
+        end
+        um = Foo.new(1).method(:get).unbind
+        assert(UnboundMethod, um.class)
+        assert(:get, um.name)
+        assert(Foo, um.owner)
+        assert(1, um.arity)
+        assert(12, um.bind(Foo.new(10)).call(2))
+        assert(23, um.bind(Bar.new(20)).call(3))
+        assert(104, um.bind_call(Foo.new(100), 4))
+        assert_error { um.bind(3) }
+        um2 = Foo.instance_method(:get)
+        assert(UnboundMethod, um2.class)
+        assert(7, um2.bind(Foo.new(5)).call(2))
+        "#;
+        assert_script(program);
+    }
+}
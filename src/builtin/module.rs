@@ -4,6 +4,7 @@ pub fn init(globals: &mut Globals) {
     let class = globals.module_class;
     globals.add_builtin_instance_method(class, "constants", constants);
     globals.add_builtin_instance_method(class, "instance_methods", instance_methods);
+    globals.add_builtin_instance_method(class, "instance_method", instance_method);
     globals.add_builtin_instance_method(class, "attr_accessor", attr_accessor);
     globals.add_builtin_instance_method(class, "attr", attr_reader);
     globals.add_builtin_instance_method(class, "attr_reader", attr_reader);
@@ -11,9 +12,12 @@ pub fn init(globals: &mut Globals) {
     globals.add_builtin_instance_method(class, "module_function", module_function);
     globals.add_builtin_instance_method(class, "singleton_class?", singleton_class);
     globals.add_builtin_instance_method(class, "const_get", const_get);
+    globals.add_builtin_instance_method(class, "autoload", module_autoload);
+    globals.add_builtin_instance_method(class, "autoload?", module_autoload_);
     globals.add_builtin_instance_method(class, "include", include);
     globals.add_builtin_instance_method(class, "included_modules", included_modules);
     globals.add_builtin_instance_method(class, "ancestors", ancestors);
+    globals.add_builtin_instance_method(class, "===", teq);
 }
 
 fn constants(vm: &mut VM, self_val: Value, _: &Args) -> VMResult {
@@ -56,10 +60,70 @@ fn const_get(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
         Some(symbol) => symbol,
         None => return Err(vm.error_type("1st arg must be Symbol.")),
     };
-    let val = vm.get_super_const(self_val, name)?;
+    let val = vm.get_super_const_autoload(self_val, name)?;
     Ok(val)
 }
 
+fn module_autoload(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    autoload(vm, self_val, args)
+}
+
+fn module_autoload_(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    autoload_(vm, self_val, args)
+}
+
+/// Register `file` to be required when the constant in `class` is referred first.
+/// Does nothing if the constant is already defined.
+pub fn autoload(vm: &mut VM, class: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 2)?;
+    let mut cref = vm.expect_module(class)?;
+    let name = match args[0].as_symbol() {
+        Some(id) => id,
+        None => match args[0].as_string() {
+            Some(name) => {
+                let name = name.to_string();
+                vm.globals.get_ident_id(name)
+            }
+            None => return Err(vm.error_type("1st arg must be Symbol or String.")),
+        },
+    };
+    let file_name = match args[1].as_string() {
+        Some(s) if !s.is_empty() => s.to_string(),
+        Some(_) => return Err(vm.error_argument("Empty file name.")),
+        None => return Err(vm.error_type("2nd arg must be String.")),
+    };
+    if class.get_var(name).is_none() {
+        cref.autoload.insert(name, file_name);
+    }
+    Ok(Value::nil())
+}
+
+/// Returns the file name registered by autoload for the constant, or nil.
+pub fn autoload_(vm: &mut VM, class: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let cref = vm.expect_module(class)?;
+    let name = match args[0].as_symbol() {
+        Some(id) => id,
+        None => return Err(vm.error_type("1st arg must be Symbol.")),
+    };
+    match cref.autoload.get(&name) {
+        Some(file_name) => Ok(Value::string(&vm.globals, file_name.clone())),
+        None => Ok(Value::nil()),
+    }
+}
+
+fn instance_method(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    vm.expect_module(self_val)?;
+    let name = match args[0].as_symbol() {
+        Some(id) => id,
+        None => return Err(vm.error_type("An argument must be a Symbol.")),
+    };
+    let method = vm.get_instance_method(self_val, name)?;
+    let owner = vm.get_method_owner(self_val, name).unwrap_or(self_val);
+    Ok(Value::unbound_method(&vm.globals, name, method, owner))
+}
+
 fn instance_methods(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
     let mut class = vm.expect_module(self_val)?;
     vm.check_args_range(args.len(), 0, 1)?;
@@ -229,6 +293,11 @@ fn ancestors(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
     Ok(Value::array_from(&vm.globals, ary))
 }
 
+fn teq(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    Ok(Value::bool(vm.is_kind_of(args[0], self_val)))
+}
+
 #[cfg(test)]
 mod test {
     use crate::test::*;
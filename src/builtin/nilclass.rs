@@ -0,0 +1,97 @@
+use crate::*;
+use std::collections::HashMap;
+
+pub fn init_nilclass(globals: &mut Globals) -> Value {
+    let id = globals.get_ident_id("NilClass");
+    let class = ClassRef::from(id, globals.builtins.object);
+    let obj = Value::class(globals, class);
+    globals.add_builtin_instance_method(class, "to_s", to_s);
+    globals.add_builtin_instance_method(class, "to_a", to_a);
+    globals.add_builtin_instance_method(class, "to_h", to_h);
+    globals.add_builtin_instance_method(class, "to_i", to_i);
+    globals.add_builtin_instance_method(class, "to_f", to_f);
+    globals.add_builtin_instance_method(class, "inspect", inspect);
+    globals.add_builtin_instance_method(class, "nil?", nil);
+    globals.add_builtin_instance_method(class, "&", and);
+    globals.add_builtin_instance_method(class, "|", or);
+    globals.add_builtin_instance_method(class, "^", or);
+    obj
+}
+
+fn to_s(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    Ok(Value::string(&vm.globals, "".to_string()))
+}
+
+fn to_a(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    Ok(Value::array_from(&vm.globals, vec![]))
+}
+
+fn to_h(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    Ok(Value::hash_from(&vm.globals, HashMap::new()))
+}
+
+fn to_i(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    Ok(Value::fixnum(0))
+}
+
+fn to_f(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    Ok(Value::flonum(0.0))
+}
+
+fn inspect(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    Ok(Value::string(&vm.globals, "nil".to_string()))
+}
+
+fn nil(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    Ok(Value::true_val())
+}
+
+fn and(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    Ok(Value::false_val())
+}
+
+fn or(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    Ok(Value::bool(vm.val_to_bool(args[0])))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::*;
+
+    #[test]
+    fn nilclass() {
+        let program = r#"
+        assert(NilClass, nil.class)
+        assert("", nil.to_s)
+        assert("nil", nil.inspect)
+        assert([], nil.to_a)
+        assert({}, nil.to_h)
+        assert(0, nil.to_i)
+        assert(0.0, nil.to_f)
+        assert(true, nil.nil?)
+        assert(false, 1.nil?)
+        assert(false, nil & true)
+        assert(true, nil | 1)
+        assert(false, nil | false)
+        assert(true, nil ^ true)
+        assert(true, nil.is_a?(NilClass))
+        assert(true, NilClass === nil)
+        class NilClass
+          def empty?
+            true
+          end
+        end
+        assert(true, nil.empty?)
+        "#;
+        assert_script(program);
+    }
+}
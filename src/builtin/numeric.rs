@@ -0,0 +1,225 @@
+use crate::*;
+
+/// Numeric is the superclass of Integer, Float, Rational and Complex.
+pub fn init_numeric(globals: &mut Globals) -> Value {
+    let id = globals.get_ident_id("Numeric");
+    let class = ClassRef::from(id, globals.builtins.object);
+    globals.add_builtin_instance_method(class, "coerce", coerce_);
+    globals.add_builtin_instance_method(class, "integer?", integer);
+    globals.add_builtin_instance_method(class, "real?", real);
+    globals.add_builtin_instance_method(class, "zero?", zero);
+    globals.add_builtin_instance_method(class, "nonzero?", nonzero);
+    globals.add_builtin_instance_method(class, "positive?", positive);
+    globals.add_builtin_instance_method(class, "negative?", negative);
+    globals.add_builtin_instance_method(class, "clamp", clamp);
+    globals.add_builtin_instance_method(class, "<", lt);
+    globals.add_builtin_instance_method(class, "<=", le);
+    globals.add_builtin_instance_method(class, ">", gt);
+    globals.add_builtin_instance_method(class, ">=", ge);
+    globals.add_builtin_instance_method(class, "to_c", to_c);
+    Value::class(globals, class)
+}
+
+// Utils
+
+/// Convert Integer, Float or Rational into f64.
+pub fn to_f64(val: Value) -> Option<f64> {
+    match val.unpack() {
+        RV::Integer(i) => Some(i as f64),
+        RV::Float(f) => Some(f),
+        _ => val.as_rational().map(|r| r.to_f()),
+    }
+}
+
+fn send(vm: &mut VM, receiver: Value, method: &str, arg: Value) -> VMResult {
+    let id = vm.globals.get_ident_id(method);
+    let method = vm.get_method(receiver, id)?;
+    vm.eval_send(method, receiver, &Args::new1(arg))
+}
+
+/// Call `rhs.coerce(lhs)` and return the converted pair.
+/// Returns None if `rhs` does not have `coerce`.
+pub fn coerce(vm: &mut VM, lhs: Value, rhs: Value) -> Result<Option<(Value, Value)>, RubyError> {
+    let id = vm.globals.get_ident_id("coerce");
+    let method = match vm.get_method(rhs, id) {
+        Ok(method) => method,
+        Err(_) => return Ok(None),
+    };
+    let res = vm.eval_send(method, rhs, &Args::new1(lhs))?;
+    match res.as_array() {
+        Some(aref) if aref.elements.len() == 2 => Ok(Some((aref.elements[0], aref.elements[1]))),
+        _ => Err(vm.error_type("coerce must return [x, y].")),
+    }
+}
+
+/// Evaluate the binary operator `method` for `lhs` and `rhs` through the coerce protocol.
+pub fn coerce_bin_op(vm: &mut VM, lhs: Value, rhs: Value, method: &str) -> VMResult {
+    match coerce(vm, lhs, rhs)? {
+        Some((lhs, rhs)) => send(vm, lhs, method, rhs),
+        None => {
+            let lhs = vm.globals.get_class_name(lhs);
+            let rhs = vm.globals.get_class_name(rhs);
+            Err(vm.error_type(format!("{} can't be coerced into {}.", rhs, lhs)))
+        }
+    }
+}
+
+/// Evaluate the comparison operator `method` for `lhs` and `rhs` through the coerce protocol.
+pub fn coerce_cmp(vm: &mut VM, lhs: Value, rhs: Value, method: &str) -> VMResult {
+    match coerce(vm, lhs, rhs)? {
+        Some((lhs, rhs)) => send(vm, lhs, method, rhs),
+        None => Err(error_cmp(vm, lhs, rhs)),
+    }
+}
+
+fn error_cmp(vm: &mut VM, lhs: Value, rhs: Value) -> RubyError {
+    let lhs = vm.globals.get_class_name(lhs);
+    let rhs = vm.globals.get_class_name(rhs);
+    vm.error_argument(format!("Comparison of {} with {} failed.", lhs, rhs))
+}
+
+/// Evaluate `lhs <=> rhs` and return the result as i64.
+fn cmp(vm: &mut VM, lhs: Value, rhs: Value) -> Result<i64, RubyError> {
+    match vm.eval_cmp(rhs, lhs)?.as_fixnum() {
+        Some(ord) => Ok(ord),
+        None => Err(error_cmp(vm, lhs, rhs)),
+    }
+}
+
+// Instance methods
+
+/// Numeric#coerce(other)
+/// Returns [other, self] if both are the same class, otherwise both are converted into Float.
+fn coerce_(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let other = args[0];
+    if vm.globals.get_class_name(self_val) == vm.globals.get_class_name(other) {
+        return Ok(Value::array_from(&vm.globals, vec![other, self_val]));
+    }
+    match (to_f64(other), to_f64(self_val)) {
+        (Some(lhs), Some(rhs)) => Ok(Value::array_from(
+            &vm.globals,
+            vec![Value::flonum(lhs), Value::flonum(rhs)],
+        )),
+        _ => {
+            let lhs = vm.globals.get_class_name(other);
+            Err(vm.error_type(format!("{} can't be coerced into Float.", lhs)))
+        }
+    }
+}
+
+fn integer(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    Ok(Value::false_val())
+}
+
+fn real(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    Ok(Value::true_val())
+}
+
+fn zero(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let res = vm.eval_eq(self_val, Value::fixnum(0))?;
+    Ok(Value::bool(res))
+}
+
+fn nonzero(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    if vm.eval_eq(self_val, Value::fixnum(0))? {
+        Ok(Value::nil())
+    } else {
+        Ok(self_val)
+    }
+}
+
+fn positive(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let ord = cmp(vm, self_val, Value::fixnum(0))?;
+    Ok(Value::bool(ord > 0))
+}
+
+fn negative(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let ord = cmp(vm, self_val, Value::fixnum(0))?;
+    Ok(Value::bool(ord < 0))
+}
+
+/// Numeric#clamp(min, max)
+/// Numeric#clamp(range)
+fn clamp(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_range(args.len(), 1, 2)?;
+    let (min, max) = if args.len() == 2 {
+        (args[0], args[1])
+    } else {
+        match args[0].as_range() {
+            Some(range) if !range.exclude => (range.start, range.end),
+            Some(_) => return Err(vm.error_argument("Cannot clamp with an exclusive range.")),
+            None => return Err(vm.error_type("Wrong argument type (expected Range).")),
+        }
+    };
+    if cmp(vm, min, max)? > 0 {
+        return Err(vm.error_argument("min argument must be smaller than max argument."));
+    }
+    if cmp(vm, self_val, min)? < 0 {
+        Ok(min)
+    } else if cmp(vm, self_val, max)? > 0 {
+        Ok(max)
+    } else {
+        Ok(self_val)
+    }
+}
+
+fn lt(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    Ok(Value::bool(cmp(vm, self_val, args[0])? < 0))
+}
+
+fn le(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    Ok(Value::bool(cmp(vm, self_val, args[0])? <= 0))
+}
+
+fn gt(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    Ok(Value::bool(cmp(vm, self_val, args[0])? > 0))
+}
+
+fn ge(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    Ok(Value::bool(cmp(vm, self_val, args[0])? >= 0))
+}
+
+fn to_c(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let info = ComplexInfo::new(self_val, Value::fixnum(0));
+    Ok(Value::complex(&vm.globals, info))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::*;
+
+    #[test]
+    fn numeric() {
+        let program = r#"
+            assert(true, 3.is_a?(Numeric))
+            assert(true, 3.5.is_a?(Numeric))
+            assert(true, Rational(1, 2).is_a?(Numeric))
+            assert(true, 0.zero?)
+            assert(false, 0.5.zero?)
+            assert(true, 3.positive?)
+            assert(true, (-0.5).negative?)
+            assert(false, Rational(1, 3).negative?)
+            assert(5, 7.clamp(1, 5))
+            assert(1, -7.clamp(1, 5))
+            assert(3.5, 3.5.clamp(1, 5))
+            assert(5, 10.clamp(1..5))
+            assert([2.0, 1.0], 1.coerce(2.0))
+            assert([2, 1], 1.coerce(2))
+            assert_error { 1 + "1" }
+            assert_error { 1.send(:"<", "1") }
+        "#;
+        assert_script(program);
+    }
+}
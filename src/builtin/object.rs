@@ -10,6 +10,10 @@ pub fn init(globals: &mut Globals) {
     globals.add_builtin_instance_method(object, "clone", dup);
     globals.add_builtin_instance_method(object, "dup", dup);
     globals.add_builtin_instance_method(object, "eql?", eql);
+    globals.add_builtin_instance_method(object, "==", eq);
+    globals.add_builtin_instance_method(object, "!=", ne);
+    globals.add_builtin_instance_method(object, "===", teq);
+    globals.add_builtin_instance_method(object, "hash", hash);
     globals.add_builtin_instance_method(object, "to_i", toi);
     globals.add_builtin_instance_method(object, "instance_variable_set", instance_variable_set);
     globals.add_builtin_instance_method(object, "instance_variable_get", instance_variable_get);
@@ -17,6 +21,7 @@ pub fn init(globals: &mut Globals) {
     globals.add_builtin_instance_method(object, "freeze", freeze);
     globals.add_builtin_instance_method(object, "super", super_);
     globals.add_builtin_instance_method(object, "equal?", equal);
+    globals.add_builtin_instance_method(object, "nil?", nil);
     globals.add_builtin_instance_method(object, "send", send);
     globals.add_builtin_instance_method(object, "eval", eval);
 }
@@ -72,6 +77,39 @@ fn eql(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
     Ok(Value::bool(self_val == args[0]))
 }
 
+fn eq(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    // Hash keys may have `eql?` and `hash` defined in Ruby.
+    if self_val.as_hash().is_some() {
+        return Ok(Value::bool(vm.eval_eq(self_val, args[0])?));
+    }
+    Ok(Value::bool(self_val.equal(args[0])))
+}
+
+fn ne(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    Ok(Value::bool(!vm.eval_eq(self_val, args[0])?))
+}
+
+fn teq(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    Ok(Value::bool(vm.eval_eq(self_val, args[0])?))
+}
+
+fn hash(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+    vm.check_args_num(args.len(), 0)?;
+    let mut hasher = DefaultHasher::new();
+    self_val.hash(&mut hasher);
+    Ok(Value::fixnum(hasher.finish() as i64 >> 2))
+}
+
+fn nil(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    Ok(Value::false_val())
+}
+
 fn toi(vm: &mut VM, self_val: Value, _: &Args) -> VMResult {
     //vm.check_args_num(args.len(), 1, 1)?;
     let self_ = self_val;
@@ -225,7 +263,7 @@ fn eval(vm: &mut VM, _: Value, args: &Args) -> VMResult {
 
     let method = vm.parse_program_eval(std::path::PathBuf::from(env_name), program)?;
     let args = Args::new0();
-    let res = vm.eval_block(method, &args)?;
+    let res = vm.eval_block(Block::Block(method), &args)?;
     Ok(res)
 }
 
@@ -0,0 +1,71 @@
+use crate::builtin::file::bytes_to_value;
+use crate::builtin::process::{command_from_args, raw_status, set_last_status};
+use crate::*;
+use std::io::Write;
+use std::process::{Output, Stdio};
+
+pub fn init_open3(globals: &mut Globals) -> Value {
+    let id = globals.get_ident_id("Open3");
+    let class = ClassRef::from(id, globals.builtins.object);
+    let obj = Value::module(globals, class);
+    globals.add_builtin_class_method(obj, "capture3", capture3);
+    globals.add_builtin_class_method(obj, "capture2", capture2);
+    obj
+}
+
+// Utils
+
+/// Run the command given by `args` and wait for it, capturing stdout (and stderr if `stderr` is true).
+/// The `stdin_data:` keyword argument is written to the standard input of the command.
+fn capture(vm: &mut VM, args: &Args, stderr: bool) -> Result<(Output, Value), RubyError> {
+    vm.check_kwargs(args, &["stdin_data"])?;
+    let stdin_data = match args.get_kwarg(&mut vm.globals, "stdin_data") {
+        Some(val) => vm.expect_string(&val, "stdin_data")?.clone().into_bytes(),
+        None => vec![],
+    };
+    let mut command = command_from_args(vm, args)?;
+    command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(if stderr {
+            Stdio::piped()
+        } else {
+            Stdio::inherit()
+        });
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(err) => return Err(vm.error_internal(format!("Can not run command. {:?}", err))),
+    };
+    let pid = child.id();
+    // Write stdin in another thread so that a command filling its output pipes never blocks us.
+    let mut stdin = child.stdin.take().unwrap();
+    let writer = std::thread::spawn(move || {
+        let _ = stdin.write_all(&stdin_data);
+    });
+    let output = match child.wait_with_output() {
+        Ok(output) => output,
+        Err(err) => return Err(vm.error_internal(format!("Can not run command. {:?}", err))),
+    };
+    let _ = writer.join();
+    let status = set_last_status(vm, pid, raw_status(output.status));
+    Ok((output, status))
+}
+
+// Module functions
+
+/// Open3.capture3([env,] command, *args, stdin_data: "")
+/// Returns [stdout, stderr, status].
+fn capture3(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    let (output, status) = capture(vm, args, true)?;
+    let stdout = bytes_to_value(&vm.globals, output.stdout);
+    let stderr = bytes_to_value(&vm.globals, output.stderr);
+    Ok(Value::array_from(&vm.globals, vec![stdout, stderr, status]))
+}
+
+/// Open3.capture2([env,] command, *args, stdin_data: "")
+/// Returns [stdout, status].
+fn capture2(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    let (output, status) = capture(vm, args, false)?;
+    let stdout = bytes_to_value(&vm.globals, output.stdout);
+    Ok(Value::array_from(&vm.globals, vec![stdout, status]))
+}
@@ -1,19 +1,489 @@
+use crate::builtin::file::bytes_to_value;
 use crate::*;
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::process::{Command, ExitStatus, Stdio};
 
 pub fn init_process(globals: &mut Globals) -> Value {
     let id = globals.get_ident_id("Process");
     let class = ClassRef::from(id, globals.builtins.object);
     let mut obj = Value::class(globals, class);
     globals.add_builtin_class_method(obj, "clock_gettime", clock_gettime);
-    let id = globals.get_ident_id("CLOCK_MONOTONIC");
-    obj.set_var(id, Value::fixnum(0));
+    globals.add_builtin_class_method(obj, "pid", pid);
+    globals.add_builtin_class_method(obj, "spawn", spawn);
+    globals.add_builtin_class_method(obj, "wait", wait);
+    globals.add_builtin_class_method(obj, "waitpid", wait);
+    globals.add_builtin_class_method(obj, "wait2", wait2);
+    globals.add_builtin_class_method(obj, "kill", kill);
+    globals.add_builtin_class_method(obj, "last_status", last_status);
+    for (name, val) in &[
+        ("CLOCK_REALTIME", libc::CLOCK_REALTIME),
+        ("CLOCK_MONOTONIC", libc::CLOCK_MONOTONIC),
+        ("CLOCK_PROCESS_CPUTIME_ID", libc::CLOCK_PROCESS_CPUTIME_ID),
+        ("CLOCK_THREAD_CPUTIME_ID", libc::CLOCK_THREAD_CPUTIME_ID),
+    ] {
+        let id = globals.get_ident_id(*name);
+        obj.set_var(id, Value::fixnum(*val as i64));
+    }
+
+    let id = globals.get_ident_id("Status");
+    let status_class = ClassRef::from(id, globals.builtins.object);
+    let status_obj = Value::class(globals, status_class);
+    globals.add_builtin_instance_method(status_class, "pid", status_pid);
+    globals.add_builtin_instance_method(status_class, "to_i", status_to_i);
+    globals.add_builtin_instance_method(status_class, "exitstatus", status_exitstatus);
+    globals.add_builtin_instance_method(status_class, "success?", status_success);
+    globals.add_builtin_instance_method(status_class, "exited?", status_exited);
+    globals.add_builtin_instance_method(status_class, "signaled?", status_signaled);
+    globals.add_builtin_instance_method(status_class, "termsig", status_termsig);
+    globals.add_builtin_instance_method(status_class, "to_s", status_to_s);
+    globals.add_builtin_instance_method(status_class, "inspect", status_inspect);
+    obj.set_var(id, status_obj);
+
+    let id = globals.get_ident_id("$$");
+    let pid = Value::fixnum(std::process::id() as i64);
+    globals.global_var.insert(id, pid);
+    obj
+}
+
+const SIGNALS: &[(&str, i32)] = &[
+    ("HUP", libc::SIGHUP),
+    ("INT", libc::SIGINT),
+    ("QUIT", libc::SIGQUIT),
+    ("ILL", libc::SIGILL),
+    ("TRAP", libc::SIGTRAP),
+    ("ABRT", libc::SIGABRT),
+    ("BUS", libc::SIGBUS),
+    ("FPE", libc::SIGFPE),
+    ("KILL", libc::SIGKILL),
+    ("USR1", libc::SIGUSR1),
+    ("SEGV", libc::SIGSEGV),
+    ("USR2", libc::SIGUSR2),
+    ("PIPE", libc::SIGPIPE),
+    ("ALRM", libc::SIGALRM),
+    ("TERM", libc::SIGTERM),
+    ("CHLD", libc::SIGCHLD),
+    ("CONT", libc::SIGCONT),
+    ("STOP", libc::SIGSTOP),
+    ("TSTP", libc::SIGTSTP),
+    ("TTIN", libc::SIGTTIN),
+    ("TTOU", libc::SIGTTOU),
+];
+
+// Utils
+
+/// Build a Command from `args`: ([env,] command) or ([env,] program, *args).
+/// A single command string is run by the shell if needed.
+pub fn command_from_args(vm: &mut VM, args: &[Value]) -> Result<Command, RubyError> {
+    let (env, args) = match args.first().and_then(|val| val.as_hash()) {
+        Some(href) => (href.iter().collect(), &args[1..]),
+        None => (vec![], args),
+    };
+    if args.is_empty() {
+        return Err(vm.error_argument("Wrong number of arguments. (given 0, expected 1+)"));
+    }
+    let mut strings = vec![];
+    for arg in args {
+        strings.push(vm.expect_string(arg, "Command")?.clone());
+    }
+    let mut command = if strings.len() == 1 && needs_shell(&strings[0]) {
+        let mut command = Command::new("/bin/sh");
+        command.arg("-c").arg(&strings[0]);
+        command
+    } else if strings.len() == 1 {
+        let mut words = strings[0].split_whitespace();
+        let mut command = Command::new(words.next().unwrap_or(""));
+        command.args(words);
+        command
+    } else {
+        let mut command = Command::new(&strings[0]);
+        command.args(&strings[1..]);
+        command
+    };
+    for (key, val) in env {
+        let key = vm.expect_string(&key, "Environment variable name")?.clone();
+        if val.is_nil() {
+            command.env_remove(key);
+        } else {
+            let val = vm.expect_string(&val, "Environment variable value")?;
+            command.env(key, val);
+        }
+    }
+    Ok(command)
+}
+
+/// Like CRuby, a command string is run without the shell
+/// unless it contains meta characters or starts with a shell builtin.
+fn needs_shell(command: &str) -> bool {
+    const BUILTINS: &[&str] = &[
+        "exit", "cd", "export", "set", "unset", "eval", "exec", ".", ":", "source", "ulimit",
+        "umask", "alias", "trap", "wait", "readonly", "shift",
+    ];
+    if command.contains(|c| "*?{}[]<>()~&|\\$;'`\"\n#=%".contains(c)) {
+        return true;
+    }
+    match command.split_whitespace().next() {
+        Some(word) => BUILTINS.contains(&word),
+        None => true,
+    }
+}
+
+/// Convert ExitStatus to the raw status value of waitpid(2).
+pub fn raw_status(status: ExitStatus) -> i32 {
+    match status.code() {
+        Some(code) => (code & 0xff) << 8,
+        None => status.signal().unwrap_or(0),
+    }
+}
+
+/// Generate Process::Status object and set it to `$?`.
+pub fn set_last_status(vm: &mut VM, pid: u32, status: i32) -> Value {
+    let process_id = vm.globals.get_ident_id("Process");
+    let status_id = vm.globals.get_ident_id("Status");
+    let class = vm
+        .globals
+        .builtins
+        .object
+        .get_var(process_id)
+        .and_then(|process| process.get_var(status_id))
+        .unwrap();
+    let mut obj = Value::ordinary_object(class);
+    let id = vm.globals.get_ident_id("pid");
+    obj.set_var(id, Value::fixnum(pid as i64));
+    let id = vm.globals.get_ident_id("status");
+    obj.set_var(id, Value::fixnum(status as i64));
+    let id = vm.globals.get_ident_id("$?");
+    vm.set_global_var(id, obj);
     obj
 }
 
+fn signal_name(sig: i32) -> Option<&'static str> {
+    SIGNALS
+        .iter()
+        .find(|(_, num)| *num == sig)
+        .map(|(name, _)| *name)
+}
+
+fn expect_signal(vm: &mut VM, val: Value) -> Result<i32, RubyError> {
+    if let Some(num) = val.as_fixnum() {
+        return Ok(num as i32);
+    }
+    let name = match val.as_symbol() {
+        Some(id) => vm.globals.get_ident_name(id).to_string(),
+        None => vm.expect_string(&val, "Signal")?.clone(),
+    };
+    let name = name.trim_start_matches("SIG");
+    match SIGNALS.iter().find(|(sig_name, _)| *sig_name == name) {
+        Some((_, num)) => Ok(*num),
+        None => Err(vm.error_argument(format!("Unsupported signal: SIG{}", name))),
+    }
+}
+
+fn waitpid(vm: &mut VM, pid: i32) -> Result<(u32, i32), RubyError> {
+    let mut status = 0;
+    let res = unsafe { libc::waitpid(pid, &mut status, 0) };
+    if res < 0 {
+        let err = std::io::Error::last_os_error();
+        return Err(vm.error_internal(format!("Can not wait process. {:?}", err)));
+    }
+    set_last_status(vm, res as u32, status);
+    Ok((res as u32, status))
+}
+
+// Kernel methods
+
+/// Kernel#system([env,] command, *args)
+/// Returns true if the command succeeded, false if it failed, and nil if it could not be run.
+pub fn system(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    let mut command = command_from_args(vm, args)?;
+    let res = command.spawn().and_then(|mut child| {
+        let pid = child.id();
+        child.wait().map(|status| (pid, status))
+    });
+    match res {
+        Ok((pid, status)) => {
+            set_last_status(vm, pid, raw_status(status));
+            Ok(Value::bool(status.success()))
+        }
+        Err(_) => {
+            set_last_status(vm, 0, 127 << 8);
+            Ok(Value::nil())
+        }
+    }
+}
+
+/// Kernel#`(command)
+/// Run `command` by the shell and returns its standard output.
+pub fn backquote(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let mut command = command_from_args(vm, args)?;
+    let res = command.stdout(Stdio::piped()).spawn().and_then(|child| {
+        let pid = child.id();
+        child.wait_with_output().map(|output| (pid, output))
+    });
+    let (pid, output) = match res {
+        Ok(res) => res,
+        Err(err) => return Err(vm.error_internal(format!("Can not run command. {:?}", err))),
+    };
+    set_last_status(vm, pid, raw_status(output.status));
+    Ok(bytes_to_value(&vm.globals, output.stdout))
+}
+
+/// Kernel#exec([env,] command, *args)
+/// Replace the current process. Returns only on failure.
+pub fn exec(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    let mut command = command_from_args(vm, args)?;
+    let err = command.exec();
+    Err(vm.error_internal(format!("Can not exec command. {:?}", err)))
+}
+
 // Class methods
 
+fn pid(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    Ok(Value::fixnum(std::process::id() as i64))
+}
+
+/// Process.spawn([env,] command, *args)
+/// Start the command without waiting and returns its pid.
+pub fn spawn(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    let mut command = command_from_args(vm, args)?;
+    match command.spawn() {
+        Ok(child) => Ok(Value::fixnum(child.id() as i64)),
+        Err(err) => Err(vm.error_internal(format!("Can not spawn command. {:?}", err))),
+    }
+}
+
+/// Process.wait(pid = -1)
+fn wait(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_range(args.len(), 0, 1)?;
+    let pid = if args.len() == 0 {
+        -1
+    } else {
+        vm.expect_integer(args[0], "Pid")? as i32
+    };
+    let (pid, _) = waitpid(vm, pid)?;
+    Ok(Value::fixnum(pid as i64))
+}
+
+/// Process.wait2(pid = -1)
+fn wait2(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    let pid = wait(vm, self_val, args)?;
+    let id = vm.globals.get_ident_id("$?");
+    let status = vm.get_global_var(id);
+    Ok(Value::array_from(&vm.globals, vec![pid, status]))
+}
+
+/// Process.kill(signal, *pids)
+fn kill(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_min(args.len(), 2)?;
+    let sig = expect_signal(vm, args[0])?;
+    for pid in args.iter().skip(1) {
+        let pid = vm.expect_integer(*pid, "Pid")?;
+        if unsafe { libc::kill(pid as libc::pid_t, sig) } != 0 {
+            let err = std::io::Error::last_os_error();
+            return Err(vm.error_internal(format!("Can not kill process. {:?}", err)));
+        }
+    }
+    Ok(Value::fixnum(args.len() as i64 - 1))
+}
+
+fn last_status(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let id = vm.globals.get_ident_id("$?");
+    Ok(vm.get_global_var(id))
+}
+
+// Class methods
+
+/// Process.clock_gettime(clock_id, unit = :float_second)
 fn clock_gettime(vm: &mut VM, _: Value, args: &Args) -> VMResult {
-    vm.check_args_num(args.len(), 1)?;
-    let duration = vm.globals.instant.elapsed();
-    Ok(Value::flonum(duration.as_secs_f64()))
+    vm.check_args_range(args.len(), 1, 2)?;
+    let clock_id = vm.expect_integer(args[0], "Clock id")?;
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    if unsafe { libc::clock_gettime(clock_id as libc::clockid_t, &mut ts) } != 0 {
+        return Err(vm.error_argument(format!("Invalid clock id. {}", clock_id)));
+    }
+    let nanos = ts.tv_sec as i64 * 1_000_000_000 + ts.tv_nsec as i64;
+    let unit = if args.len() == 2 {
+        match args[1].as_symbol() {
+            Some(id) => vm.globals.get_ident_name(id).to_string(),
+            None => return Err(vm.error_argument("Unit must be Symbol.")),
+        }
+    } else {
+        "float_second".to_string()
+    };
+    let val = match unit.as_str() {
+        "float_second" => Value::flonum(nanos as f64 / 1_000_000_000.0),
+        "float_millisecond" => Value::flonum(nanos as f64 / 1_000_000.0),
+        "float_microsecond" => Value::flonum(nanos as f64 / 1_000.0),
+        "second" => Value::fixnum(nanos / 1_000_000_000),
+        "millisecond" => Value::fixnum(nanos / 1_000_000),
+        "microsecond" => Value::fixnum(nanos / 1_000),
+        "nanosecond" => Value::fixnum(nanos),
+        _ => return Err(vm.error_argument(format!("Unexpected unit: {}", unit))),
+    };
+    Ok(val)
+}
+
+// Process::Status
+
+// The wait status macros are `unsafe fn` in older versions of libc.
+
+#[allow(unused_unsafe)]
+fn wifexited(status: i32) -> bool {
+    unsafe { libc::WIFEXITED(status) }
+}
+
+#[allow(unused_unsafe)]
+fn wexitstatus(status: i32) -> i32 {
+    unsafe { libc::WEXITSTATUS(status) }
+}
+
+#[allow(unused_unsafe)]
+fn wifsignaled(status: i32) -> bool {
+    unsafe { libc::WIFSIGNALED(status) }
+}
+
+#[allow(unused_unsafe)]
+fn wtermsig(status: i32) -> i32 {
+    unsafe { libc::WTERMSIG(status) }
+}
+
+fn status_var(vm: &mut VM, self_val: Value, name: &str) -> i32 {
+    let id = vm.globals.get_ident_id(name);
+    self_val
+        .get_var(id)
+        .and_then(|val| val.as_fixnum())
+        .unwrap_or(0) as i32
+}
+
+fn status_pid(vm: &mut VM, self_val: Value, _args: &Args) -> VMResult {
+    Ok(Value::fixnum(status_var(vm, self_val, "pid") as i64))
+}
+
+fn status_to_i(vm: &mut VM, self_val: Value, _args: &Args) -> VMResult {
+    Ok(Value::fixnum(status_var(vm, self_val, "status") as i64))
+}
+
+fn status_exitstatus(vm: &mut VM, self_val: Value, _args: &Args) -> VMResult {
+    let status = status_var(vm, self_val, "status");
+    if wifexited(status) {
+        Ok(Value::fixnum(wexitstatus(status) as i64))
+    } else {
+        Ok(Value::nil())
+    }
+}
+
+fn status_success(vm: &mut VM, self_val: Value, _args: &Args) -> VMResult {
+    let status = status_var(vm, self_val, "status");
+    if wifexited(status) {
+        Ok(Value::bool(wexitstatus(status) == 0))
+    } else {
+        Ok(Value::nil())
+    }
+}
+
+fn status_exited(vm: &mut VM, self_val: Value, _args: &Args) -> VMResult {
+    let status = status_var(vm, self_val, "status");
+    Ok(Value::bool(wifexited(status)))
+}
+
+fn status_signaled(vm: &mut VM, self_val: Value, _args: &Args) -> VMResult {
+    let status = status_var(vm, self_val, "status");
+    Ok(Value::bool(wifsignaled(status)))
+}
+
+fn status_termsig(vm: &mut VM, self_val: Value, _args: &Args) -> VMResult {
+    let status = status_var(vm, self_val, "status");
+    if wifsignaled(status) {
+        Ok(Value::fixnum(wtermsig(status) as i64))
+    } else {
+        Ok(Value::nil())
+    }
+}
+
+fn status_string(vm: &mut VM, self_val: Value) -> String {
+    let pid = status_var(vm, self_val, "pid");
+    let status = status_var(vm, self_val, "status");
+    if wifsignaled(status) {
+        let sig = wtermsig(status);
+        match signal_name(sig) {
+            Some(name) => format!("pid {} SIG{} (signal {})", pid, name, sig),
+            None => format!("pid {} signal {}", pid, sig),
+        }
+    } else {
+        format!("pid {} exit {}", pid, wexitstatus(status))
+    }
+}
+
+fn status_to_s(vm: &mut VM, self_val: Value, _args: &Args) -> VMResult {
+    let s = status_string(vm, self_val);
+    Ok(Value::string(&vm.globals, s))
+}
+
+fn status_inspect(vm: &mut VM, self_val: Value, _args: &Args) -> VMResult {
+    let s = format!("#<Process::Status: {}>", status_string(vm, self_val));
+    Ok(Value::string(&vm.globals, s))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::*;
+
+    #[test]
+    fn process_command() {
+        let program = r#"
+            assert("hello\n", `echo hello`)
+            assert(true, $?.success?)
+            assert(0, $?.exitstatus)
+            name = "world"
+            assert("hello world\n", %x(echo hello #{name}))
+            assert("3\n", `echo #{1 + 2}`)
+            assert(true, system("true"))
+            assert(false, system("exit 3"))
+            assert(3, $?.exitstatus)
+            assert(false, $?.success?)
+            assert(true, system("test", "a", "=", "a"))
+            assert(nil, system("no-such-command-for-test"))
+            assert(true, system({"FOO" => "bar"}, "test \"$FOO\" = bar"))
+            assert(Process.pid, $$)
+        "#;
+        assert_script(program);
+    }
+
+    #[test]
+    fn process_spawn() {
+        let program = r#"
+            pid = Process.spawn("exit 2")
+            assert(pid, Process.wait(pid))
+            assert(pid, $?.pid)
+            assert(2, $?.exitstatus)
+            assert(Process::Status, Process.last_status.class)
+            pid = spawn("sleep", "10")
+            assert(1, Process.kill(:TERM, pid))
+            pid, status = Process.wait2(pid)
+            assert(true, status.signaled?)
+            assert(15, status.termsig)
+            assert(nil, status.exitstatus)
+            assert("pid #{pid} SIGTERM (signal 15)", status.to_s)
+        "#;
+        assert_script(program);
+    }
+
+    #[test]
+    fn open3() {
+        let program = r#"
+            out, err, status = Open3.capture3("echo out; echo err >&2; exit 1")
+            assert("out\n", out)
+            assert("err\n", err)
+            assert(1, status.exitstatus)
+            out, status = Open3.capture2("tr", "a-z", "A-Z", stdin_data: "abc")
+            assert("ABC", out)
+            assert(true, status.success?)
+        "#;
+        assert_script(program);
+    }
 }
@@ -1,13 +1,21 @@
 use crate::*;
+use std::path::PathBuf;
 
 #[derive(Debug, Clone)]
 pub struct ProcInfo {
     pub context: ContextRef,
+    pub is_lambda: bool,
+    /// The method name for a Proc created by Symbol#to_proc.
+    pub symbol: Option<IdentId>,
 }
 
 impl ProcInfo {
     pub fn new(context: ContextRef) -> Self {
-        ProcInfo { context }
+        ProcInfo {
+            context,
+            is_lambda: false,
+            symbol: None,
+        }
     }
 }
 
@@ -24,10 +32,37 @@ pub fn init_proc(globals: &mut Globals) -> Value {
     let class = ClassRef::from(proc_id, globals.builtins.object);
     let obj = Value::class(globals, class);
     globals.add_builtin_instance_method(class, "call", proc_call);
+    globals.add_builtin_instance_method(class, "[]", proc_call);
+    globals.add_builtin_instance_method(class, "yield", proc_call);
+    globals.add_builtin_instance_method(class, "===", proc_call);
+    globals.add_builtin_instance_method(class, "to_proc", to_proc);
+    globals.add_builtin_instance_method(class, "lambda?", lambda);
+    globals.add_builtin_instance_method(class, "arity", arity);
+    globals.add_builtin_instance_method(class, "parameters", parameters);
+    globals.add_builtin_instance_method(class, "curry", curry);
+    globals.add_builtin_instance_method(class, ">>", compose_right);
+    globals.add_builtin_instance_method(class, "<<", compose_left);
     globals.add_builtin_class_method(obj, "new", proc_new);
     obj
 }
 
+fn expect_proc(vm: &VM, self_val: Value) -> Result<ProcRef, RubyError> {
+    match self_val.as_proc() {
+        Some(pref) => Ok(pref),
+        None => Err(vm.error_unimplemented("Expected Proc object.")),
+    }
+}
+
+/// Evaluate `program` with `self_val` as self, and return the resulting Proc object
+/// which inherits lambda-ness from `self_val`.
+fn eval_proc_program(vm: &mut VM, self_val: Value, program: &str, args: &Args) -> VMResult {
+    let method = vm.parse_program(PathBuf::from("(proc)"), program)?;
+    let maker = vm.eval_send(method, self_val, &Args::new0())?;
+    let procobj = vm.eval_proc(maker, args)?;
+    procobj.as_proc().unwrap().is_lambda = expect_proc(vm, self_val)?.is_lambda;
+    Ok(procobj)
+}
+
 // Class methods
 
 fn proc_new(vm: &mut VM, _: Value, args: &Args) -> VMResult {
@@ -39,17 +74,230 @@ fn proc_new(vm: &mut VM, _: Value, args: &Args) -> VMResult {
 // Instance methods
 
 fn proc_call(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
-    let pref = match self_val.as_proc() {
-        Some(pref) => pref,
-        None => return Err(vm.error_unimplemented("Expected Proc object.")),
+    vm.eval_proc(self_val, args)
+}
+
+fn to_proc(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    Ok(self_val)
+}
+
+fn lambda(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let pref = expect_proc(vm, self_val)?;
+    Ok(Value::bool(pref.is_lambda))
+}
+
+fn arity(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let pref = expect_proc(vm, self_val)?;
+    Ok(Value::fixnum(pref.context.iseq_ref.params.arity()))
+}
+
+/// Proc#parameters
+/// Mandatory parameters of a proc which is not a lambda are reported as optional.
+fn parameters(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let pref = expect_proc(vm, self_val)?;
+    let params = &pref.context.iseq_ref.params;
+    let req = if pref.is_lambda { "req" } else { "opt" };
+    let mut kinds = vec![];
+    kinds.extend(std::iter::repeat(req).take(params.req_params));
+    kinds.extend(std::iter::repeat("opt").take(params.opt_params));
+    if params.rest_param {
+        kinds.push("rest");
+    }
+    kinds.extend(std::iter::repeat(req).take(params.post_params));
+    let kw_start = kinds.len();
+    for id in &params.param_ident[kw_start..kw_start + params.keyword_params.len()] {
+        if params.req_keyword_params.contains(id) {
+            kinds.push("keyreq");
+        } else {
+            kinds.push("key");
+        }
+    }
+    if params.kwrest_param.is_some() {
+        kinds.push("keyrest");
+    }
+    if params.block_param {
+        kinds.push("block");
+    }
+    let mut res = vec![];
+    for (kind, id) in kinds.iter().zip(params.param_ident.iter()) {
+        let kind = Value::symbol(vm.globals.get_ident_id(*kind));
+        let param = Value::array_from(&vm.globals, vec![kind, Value::symbol(*id)]);
+        res.push(param);
+    }
+    if params.no_keyword_param {
+        let nokey = Value::symbol(vm.globals.get_ident_id("nokey"));
+        let param = Value::array_from(&vm.globals, vec![nokey]);
+        res.insert(res.len() - if params.block_param { 1 } else { 0 }, param);
+    }
+    Ok(Value::array_from(&vm.globals, res))
+}
+
+/// Proc#curry(arity = nil)
+/// Returns a curried proc which calls `self` when the given arguments are sufficient.
+fn curry(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_range(args.len(), 0, 1)?;
+    let pref = expect_proc(vm, self_val)?;
+    let params = &pref.context.iseq_ref.params;
+    let req = (params.req_params + params.post_params) as i64;
+    let arity = if args.len() == 0 {
+        req
+    } else {
+        let arity = vm.expect_integer(args[0], "Arity")?;
+        let variadic = params.opt_params > 0 || params.rest_param;
+        if pref.is_lambda && (arity < req || !variadic && arity != req) {
+            return Err(vm.error_argument(format!(
+                "Wrong number of arguments. (given {}, expected {})",
+                arity, req
+            )));
+        }
+        arity
     };
-    let context = Context::from_args(
-        vm,
-        self_val,
-        pref.context.iseq_ref,
-        args,
-        pref.context.outer,
-    )?;
-    let res = vm.run_context(ContextRef::from_local(&context))?;
-    Ok(res)
+    let maker = if pref.is_lambda { "lambda" } else { "Proc.new" };
+    let program = format!(
+        r#"
+        Proc.new do
+          curry = nil
+          curry = lambda do |given|
+            {} do |*args|
+              all = given + args
+              if all.size >= {}
+                call(*all)
+              else
+                curry.call(all)
+              end
+            end
+          end
+          curry.call([])
+        end"#,
+        maker, arity
+    );
+    eval_proc_program(vm, self_val, &program, &Args::new0())
+}
+
+fn compose(vm: &mut VM, self_val: Value, args: &Args, program: &str) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let id = vm.globals.get_ident_id("call");
+    if vm.get_method(args[0], id).is_err() {
+        return Err(vm.error_type("Callable object is expected."));
+    }
+    eval_proc_program(vm, self_val, program, args)
+}
+
+/// Proc#>>(g)
+/// Returns a proc which calls `g` with the result of `self`.
+fn compose_right(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    let program = "Proc.new { |g| Proc.new { |*args, &block| g.call(call(*args, &block)) } }";
+    compose(vm, self_val, args, program)
+}
+
+/// Proc#<<(g)
+/// Returns a proc which calls `self` with the result of `g`.
+fn compose_left(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    let program = "Proc.new { |g| Proc.new { |*args, &block| call(g.call(*args, &block)) } }";
+    compose(vm, self_val, args, program)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::*;
+
+    #[test]
+    fn lambda() {
+        let program = r#"
+        l = lambda { |x, y| x + y }
+        assert(true, l.lambda?)
+        assert(3, l.call(1, 2))
+        assert_error { l.call(1) }
+        assert_error { l.call(1, 2, 3) }
+        pr = proc { |x, y| [x, y] }
+        assert(false, pr.lambda?)
+        assert([1, nil], pr.call(1))
+        assert([1, 2], pr.call(1, 2, 3))
+        assert([1, 2], pr.call([1, 2]))
+        sq = ->(x) { x * x }
+        assert(true, sq.lambda?)
+        assert(16, sq.call(4))
+        assert(25, sq[5])
+        assert(36, sq.(6))
+        assert(49, sq.yield(7))
+        assert(7, -> { 7 }.call)
+        assert(9, ->(x, y = 2, *z) { x + y + z.size }.call(4, 3, 1, 1))
+        add = -> x, y do x + y end
+        assert(5, add.call(2, 3))
+        assert([2, 4], [1, 2].map(&->(x) { x * 2 }))
+        "#;
+        assert_script(program);
+    }
+
+    #[test]
+    fn lambda_return() {
+        let program = r#"
+        def foo
+          l = lambda { return 1 }
+          l.call
+          2
+        end
+        assert(2, foo)
+        def bar
+          pr = Proc.new { return 1 }
+          pr.call
+          2
+        end
+        assert(1, bar)
+        def baz
+          l = -> { [1, 2, 3].each { |x| return x * 10 if x == 2 }; 0 }
+          l.call + 5
+        end
+        assert(25, baz)
+        l = lambda { |x| return x * 3 }
+        assert(9, l.call(3))
+        "#;
+        assert_script(program);
+    }
+
+    #[test]
+    fn proc_arity_parameters() {
+        let program = r#"
+        assert(0, proc {}.arity)
+        assert(2, proc { |x, y| }.arity)
+        assert(-2, proc { |x, *y| }.arity)
+        assert(-1, lambda { |*x| }.arity)
+        assert(-3, ->(a, *b, c) {}.arity)
+        assert([[:opt, :x], [:opt, :y]], proc { |x, y| }.parameters)
+        assert([[:req, :x], [:opt, :y], [:rest, :z], [:block, :b]], ->(x, y = 1, *z, &b) {}.parameters)
+        "#;
+        assert_script(program);
+    }
+
+    #[test]
+    fn proc_curry_compose() {
+        let program = r#"
+        add3 = ->(a, b, c) { a + b + c }
+        c = add3.curry
+        assert(true, c.lambda?)
+        assert(6, c[1][2][3])
+        assert(6, c.(1, 2).(3))
+        assert(6, c.(1).(2, 3))
+        sum = proc { |*a| a.size }
+        assert(3, sum.curry(3)[1][2][3])
+        assert_error { add3.curry(2) }
+        f = ->(x) { x + 1 }
+        g = ->(x) { x * 2 }
+        assert(8, (f >> g).call(3))
+        assert(7, (f << g).call(3))
+        assert(true, (f >> g).lambda?)
+        class Tripler
+          def call(x)
+            x * 3
+          end
+        end
+        assert(12, (f >> Tripler.new).call(3))
+        assert_error { f >> 1 }
+        "#;
+        assert_script(program);
+    }
 }
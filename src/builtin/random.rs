@@ -0,0 +1,478 @@
+use crate::builtin::numeric::to_f64;
+use crate::*;
+
+const N: usize = 624;
+const M: usize = 397;
+const MATRIX_A: u32 = 0x9908_b0df;
+const UPPER_MASK: u32 = 0x8000_0000;
+const LOWER_MASK: u32 = 0x7fff_ffff;
+
+/// Mersenne Twister (MT19937) pseudo random number generator.
+/// The sequence is compatible with CRuby for the same seed.
+#[derive(Clone)]
+pub struct RandomInfo {
+    state: [u32; N],
+    index: usize,
+    seed: i64,
+}
+
+impl std::fmt::Debug for RandomInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RandomInfo {{ seed: {} }}", self.seed)
+    }
+}
+
+impl PartialEq for RandomInfo {
+    fn eq(&self, other: &Self) -> bool {
+        self.seed == other.seed && self.index == other.index && self.state[..] == other.state[..]
+    }
+}
+
+pub type RandomRef = Ref<RandomInfo>;
+
+impl RandomInfo {
+    pub fn new(seed: i64) -> Self {
+        let mut info = RandomInfo {
+            state: [0; N],
+            index: N,
+            seed,
+        };
+        // The absolute value of the seed is split into 32-bit words from the least significant one.
+        let abs = seed.wrapping_abs() as u64;
+        let (lo, hi) = (abs as u32, (abs >> 32) as u32);
+        if hi == 0 {
+            info.init_genrand(lo);
+        } else {
+            info.init_by_array(&[lo, hi]);
+        }
+        info
+    }
+
+    /// Generate a seed from the system random source.
+    pub fn new_seed() -> i64 {
+        (::rand::random::<u64>() >> 1) as i64
+    }
+
+    pub fn seed(&self) -> i64 {
+        self.seed
+    }
+
+    fn init_genrand(&mut self, s: u32) {
+        self.state[0] = s;
+        for i in 1..N {
+            let prev = self.state[i - 1];
+            self.state[i] = 1_812_433_253u32
+                .wrapping_mul(prev ^ (prev >> 30))
+                .wrapping_add(i as u32);
+        }
+        self.index = N;
+    }
+
+    fn init_by_array(&mut self, key: &[u32]) {
+        self.init_genrand(19_650_218);
+        let mut i = 1;
+        let mut j = 0;
+        for _ in 0..std::cmp::max(N, key.len()) {
+            let prev = self.state[i - 1];
+            self.state[i] = (self.state[i] ^ (prev ^ (prev >> 30)).wrapping_mul(1_664_525))
+                .wrapping_add(key[j])
+                .wrapping_add(j as u32);
+            i += 1;
+            j += 1;
+            if i >= N {
+                self.state[0] = self.state[N - 1];
+                i = 1;
+            }
+            if j >= key.len() {
+                j = 0;
+            }
+        }
+        for _ in 0..N - 1 {
+            let prev = self.state[i - 1];
+            self.state[i] = (self.state[i] ^ (prev ^ (prev >> 30)).wrapping_mul(1_566_083_941))
+                .wrapping_sub(i as u32);
+            i += 1;
+            if i >= N {
+                self.state[0] = self.state[N - 1];
+                i = 1;
+            }
+        }
+        self.state[0] = 0x8000_0000;
+        self.index = N;
+    }
+
+    fn next_state(&mut self) {
+        for i in 0..N {
+            let y = (self.state[i] & UPPER_MASK) | (self.state[(i + 1) % N] & LOWER_MASK);
+            let mag = if y & 1 == 0 { 0 } else { MATRIX_A };
+            self.state[i] = self.state[(i + M) % N] ^ (y >> 1) ^ mag;
+        }
+        self.index = 0;
+    }
+
+    /// Generate a random number in [0, 0xffffffff].
+    pub fn genrand_u32(&mut self) -> u32 {
+        if self.index >= N {
+            self.next_state();
+        }
+        let mut y = self.state[self.index];
+        self.index += 1;
+        y ^= y >> 11;
+        y ^= (y << 7) & 0x9d2c_5680;
+        y ^= (y << 15) & 0xefc6_0000;
+        y ^ (y >> 18)
+    }
+
+    /// Generate a random number in [0, 1) with 53-bit resolution.
+    pub fn genrand_real(&mut self) -> f64 {
+        let a = (self.genrand_u32() >> 5) as f64;
+        let b = (self.genrand_u32() >> 6) as f64;
+        (a * 67_108_864.0 + b) * (1.0 / 9_007_199_254_740_992.0)
+    }
+
+    /// Generate a random integer in [0, limit].
+    pub fn limited(&mut self, limit: u64) -> u64 {
+        if limit == 0 {
+            return 0;
+        }
+        let mask = std::u64::MAX >> limit.leading_zeros();
+        loop {
+            let mut val = 0u64;
+            let mut retry = false;
+            for i in (0..2).rev() {
+                if (mask >> (i * 32)) & 0xffff_ffff != 0 {
+                    val |= (self.genrand_u32() as u64) << (i * 32);
+                    val &= mask;
+                    if limit < val {
+                        retry = true;
+                        break;
+                    }
+                }
+            }
+            if !retry {
+                return val;
+            }
+        }
+    }
+
+    /// Generate `len` random bytes.
+    pub fn bytes(&mut self, len: usize) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(len);
+        while bytes.len() < len {
+            let x = self.genrand_u32();
+            let n = std::cmp::min(4, len - bytes.len());
+            bytes.extend_from_slice(&x.to_le_bytes()[..n]);
+        }
+        bytes
+    }
+}
+
+pub fn init_random(globals: &mut Globals) -> Value {
+    let id = globals.get_ident_id("Random");
+    let class = ClassRef::from(id, globals.builtins.object);
+    let mut obj = Value::class(globals, class);
+    globals.add_builtin_instance_method(class, "rand", rand);
+    globals.add_builtin_instance_method(class, "bytes", bytes);
+    globals.add_builtin_instance_method(class, "seed", seed);
+    globals.add_builtin_class_method(obj, "new", random_new);
+    globals.add_builtin_class_method(obj, "new_seed", new_seed);
+    globals.add_builtin_class_method(obj, "rand", random_rand);
+    globals.add_builtin_class_method(obj, "bytes", random_bytes);
+    globals.add_builtin_class_method(obj, "srand", srand);
+    globals.add_builtin_class_method(obj, "seed", random_seed);
+    let info = RandomRef::new(RandomInfo::new(RandomInfo::new_seed()));
+    let default = Value::random(obj, info);
+    let id = globals.get_ident_id("DEFAULT");
+    obj.set_var(id, default);
+    obj
+}
+
+// Utils
+
+/// Get the generator used by Kernel#rand and Kernel#srand.
+fn default_random(vm: &mut VM) -> Result<RandomRef, RubyError> {
+    let id = vm.globals.get_ident_id("DEFAULT");
+    match vm.globals.builtins.random.get_var(id) {
+        Some(val) => expect_random(vm, val),
+        None => Err(vm.error_internal("Random::DEFAULT is not defined.")),
+    }
+}
+
+fn expect_random(vm: &mut VM, val: Value) -> Result<RandomRef, RubyError> {
+    match val.as_random() {
+        Some(rref) => Ok(rref),
+        None => {
+            let inspect = vm.val_inspect(val);
+            Err(vm.error_type(format!("Expected Random, but {}.", inspect)))
+        }
+    }
+}
+
+fn error_invalid(vm: &mut VM, val: Value) -> RubyError {
+    let inspect = vm.val_inspect(val);
+    vm.error_argument(format!("Invalid argument - {}", inspect))
+}
+
+/// Generate a random value for `max` by `rref`.
+/// Returns None if `max` is not valid.
+/// `max` may be nil (Float in [0, 1)), a positive Integer, a positive Float, or a Range.
+fn rand_value(vm: &mut VM, mut rref: RandomRef, max: Value) -> Result<Option<Value>, RubyError> {
+    if max.is_nil() {
+        return Ok(Some(Value::flonum(rref.genrand_real())));
+    }
+    match max.unpack() {
+        RV::Integer(max) if max > 0 => {
+            return Ok(Some(Value::fixnum(rref.limited(max as u64 - 1) as i64)))
+        }
+        RV::Float(max) if max > 0.0 && max.is_finite() => {
+            return Ok(Some(Value::flonum(rref.genrand_real() * max)))
+        }
+        RV::Integer(_) | RV::Float(_) => return Ok(None),
+        _ => {}
+    }
+    let range = match max.as_range() {
+        Some(range) => range,
+        None => {
+            let inspect = vm.val_inspect(max);
+            return Err(vm.error_type(format!(
+                "No implicit conversion of {} into Integer.",
+                inspect
+            )));
+        }
+    };
+    match (range.start.unpack(), range.end.unpack()) {
+        (RV::Integer(start), RV::Integer(end)) => {
+            let end = if range.exclude { end - 1 } else { end };
+            if end < start {
+                return Ok(None);
+            }
+            let val = rref.limited((end - start) as u64) as i64;
+            Ok(Some(Value::fixnum(start + val)))
+        }
+        _ => {
+            let (start, end) = match (to_f64(range.start), to_f64(range.end)) {
+                (Some(start), Some(end)) => (start, end),
+                _ => return Err(error_invalid(vm, max)),
+            };
+            if end < start || (range.exclude && end == start) {
+                return Ok(None);
+            }
+            Ok(Some(Value::flonum(
+                rref.genrand_real() * (end - start) + start,
+            )))
+        }
+    }
+}
+
+/// Generate a random integer in [0, max) by `rng`.
+/// `rng` is the `random:` keyword argument of Array#shuffle and Array#sample.
+/// If `rng` is not a Random, `rng.rand(max)` is used.
+pub fn random_upto(vm: &mut VM, rng: Option<Value>, max: usize) -> Result<usize, RubyError> {
+    if max == 0 {
+        return Ok(0);
+    }
+    let rng = match rng {
+        Some(rng) => rng,
+        None => {
+            let mut rref = default_random(vm)?;
+            return Ok(rref.limited(max as u64 - 1) as usize);
+        }
+    };
+    if let Some(mut rref) = rng.as_random() {
+        return Ok(rref.limited(max as u64 - 1) as usize);
+    }
+    let id = vm.globals.get_ident_id("rand");
+    let method = vm.get_method(rng, id)?;
+    let val = vm.eval_send(method, rng, &Args::new1(Value::fixnum(max as i64)))?;
+    match val.as_fixnum() {
+        Some(i) if 0 <= i && (i as usize) < max => Ok(i as usize),
+        Some(i) => Err(vm.error_argument(format!("Random number too big {}.", i))),
+        None => Err(vm.error_type("Random number must be an Integer.")),
+    }
+}
+
+/// Get the `random:` keyword argument.
+pub fn random_kw_arg(vm: &mut VM, args: &Args) -> Result<Option<Value>, RubyError> {
+    vm.check_kwargs(args, &["random"])?;
+    Ok(args.get_kwarg(&mut vm.globals, "random"))
+}
+
+// Class methods
+
+/// Random.new(seed = Random.new_seed)
+fn random_new(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_range(args.len(), 0, 1)?;
+    let seed = if args.len() == 0 {
+        RandomInfo::new_seed()
+    } else {
+        vm.expect_integer(args[0], "Seed")?
+    };
+    let info = RandomRef::new(RandomInfo::new(seed));
+    Ok(Value::random(self_val, info))
+}
+
+fn new_seed(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    Ok(Value::fixnum(RandomInfo::new_seed()))
+}
+
+fn random_rand(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_range(args.len(), 0, 1)?;
+    let rref = default_random(vm)?;
+    let max = if args.len() == 0 {
+        Value::nil()
+    } else {
+        args[0]
+    };
+    match rand_value(vm, rref, max)? {
+        Some(val) => Ok(val),
+        None => Err(error_invalid(vm, max)),
+    }
+}
+
+fn random_bytes(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let mut rref = default_random(vm)?;
+    let len = vm.expect_integer(args[0], "Length")?;
+    if len < 0 {
+        return Err(vm.error_argument("Negative string size (or size too big)."));
+    }
+    Ok(Value::bytes(&vm.globals, rref.bytes(len as usize)))
+}
+
+fn random_seed(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let rref = default_random(vm)?;
+    Ok(Value::fixnum(rref.seed()))
+}
+
+/// Kernel#srand(seed = Random.new_seed)
+/// Reseed Random::DEFAULT and return the previous seed.
+pub fn srand(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_range(args.len(), 0, 1)?;
+    let seed = if args.len() == 0 {
+        RandomInfo::new_seed()
+    } else {
+        vm.expect_integer(args[0], "Seed")?
+    };
+    let mut rref = default_random(vm)?;
+    let prev = rref.seed();
+    *rref = RandomInfo::new(seed);
+    Ok(Value::fixnum(prev))
+}
+
+/// Kernel#rand(max = 0)
+/// Unlike Random#rand, a Float `max` is truncated into Integer and a negative `max` means `max.abs`.
+pub fn kernel_rand(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_range(args.len(), 0, 1)?;
+    let rref = default_random(vm)?;
+    if args.len() == 0 {
+        return Ok(rand_value(vm, rref, Value::nil())?.unwrap());
+    }
+    let max = match args[0].unpack() {
+        RV::Integer(i) => Value::fixnum(i.abs()),
+        RV::Float(f) => Value::fixnum(f.trunc().abs() as i64),
+        _ if args[0].is_nil() => Value::nil(),
+        _ if args[0].as_range().is_some() => {
+            return Ok(rand_value(vm, rref, args[0])?.unwrap_or(Value::nil()));
+        }
+        _ => args[0],
+    };
+    match max.as_fixnum() {
+        Some(0) => rand_value(vm, rref, Value::nil()).map(|v| v.unwrap()),
+        _ => match rand_value(vm, rref, max)? {
+            Some(val) => Ok(val),
+            None => Err(error_invalid(vm, max)),
+        },
+    }
+}
+
+// Instance methods
+
+/// Random#rand(max = nil)
+fn rand(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_range(args.len(), 0, 1)?;
+    let rref = expect_random(vm, self_val)?;
+    let max = if args.len() == 0 {
+        Value::nil()
+    } else {
+        args[0]
+    };
+    match rand_value(vm, rref, max)? {
+        Some(val) => Ok(val),
+        None => Err(error_invalid(vm, max)),
+    }
+}
+
+fn bytes(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let mut rref = expect_random(vm, self_val)?;
+    let len = vm.expect_integer(args[0], "Length")?;
+    if len < 0 {
+        return Err(vm.error_argument("Negative string size (or size too big)."));
+    }
+    Ok(Value::bytes(&vm.globals, rref.bytes(len as usize)))
+}
+
+fn seed(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let rref = expect_random(vm, self_val)?;
+    Ok(Value::fixnum(rref.seed()))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::*;
+
+    #[test]
+    fn random() {
+        let program = r#"
+        r = Random.new(1234)
+        assert(1234, r.seed)
+        assert(0.1915194503788923, r.rand)
+        r = Random.new(42)
+        assert(0.3745401188473625, r.rand)
+        r = Random.new(42)
+        assert([51, 92, 14, 71, 60], (0..4).map { r.rand(100) })
+        r1 = Random.new(2020)
+        r2 = Random.new(2020)
+        assert(r1.rand(1000), r2.rand(1000))
+        assert(r1.rand(1.5), r2.rand(1.5))
+        assert(r1.rand(10..20), r2.rand(10..20))
+        assert(r1.bytes(5), r2.bytes(5))
+        assert(5, r1.bytes(5).bytes.size)
+        x = r1.rand(10...20)
+        assert(true, 10 <= x && x < 20)
+        x = r1.rand(1.0..2.0)
+        assert(true, x.is_a?(Float) && 1.0 <= x && x <= 2.0)
+        assert(true, Random::DEFAULT.is_a?(Random))
+        assert_error { r1.rand(0) }
+        assert_error { r1.rand(-1) }
+        assert_error { r1.rand(5..1) }
+        "#;
+        assert_script(program);
+    }
+
+    #[test]
+    fn kernel_rand() {
+        let program = r#"
+        srand(42)
+        assert(42, srand(42))
+        a = [rand, rand(10), rand(1..6)]
+        srand(42)
+        assert(a, [rand, rand(10), rand(1..6)])
+        srand(42)
+        assert(0.3745401188473625, Random.rand)
+        srand(42)
+        assert(51, rand(100))
+        srand(42)
+        assert(51, rand(-100))
+        srand(42)
+        assert(51, rand(100.5))
+        assert(nil, rand(5..1))
+        x = rand(0)
+        assert(true, 0.0 <= x && x < 1.0)
+        "#;
+        assert_script(program);
+    }
+}
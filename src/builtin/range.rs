@@ -1,3 +1,5 @@
+use crate::builtin::numeric::to_f64;
+use crate::builtin::string::str_succ;
 use crate::*;
 
 #[derive(Debug, Clone, PartialEq, Hash)]
@@ -24,8 +26,11 @@ impl RangeInfo {
     }
 
     pub fn inspect(&self, vm: &mut VM) -> String {
-        let start = vm.val_inspect(self.start);
-        let end = vm.val_inspect(self.end);
+        let (start, end) = match (self.start.is_nil(), self.end.is_nil()) {
+            (true, false) => ("".to_string(), vm.val_inspect(self.end)),
+            (false, true) => (vm.val_inspect(self.start), "".to_string()),
+            _ => (vm.val_inspect(self.start), vm.val_inspect(self.end)),
+        };
         let sym = if self.exclude { "..." } else { ".." };
         format!("{}{}{}", start, sym, end)
     }
@@ -38,18 +43,48 @@ pub fn init_range(globals: &mut Globals) -> Value {
     globals.add_builtin_instance_method(class, "to_s", to_s);
     globals.add_builtin_instance_method(class, "inspect", inspect);
     globals.add_builtin_instance_method(class, "map", map);
+    globals.add_builtin_instance_method(class, "collect", map);
     globals.add_builtin_instance_method(class, "flat_map", flat_map);
     globals.add_builtin_instance_method(class, "each", each);
+    globals.add_builtin_instance_method(class, "reverse_each", reverse_each);
+    globals.add_builtin_instance_method(class, "each_slice", each_slice);
+    globals.add_builtin_instance_method(class, "step", step);
+    globals.add_builtin_instance_method(class, "%", step);
     globals.add_builtin_instance_method(class, "all?", all);
     globals.add_builtin_instance_method(class, "begin", begin);
-    globals.add_builtin_instance_method(class, "first", firat);
+    globals.add_builtin_instance_method(class, "first", first);
     globals.add_builtin_instance_method(class, "end", end);
     globals.add_builtin_instance_method(class, "last", last);
+    globals.add_builtin_instance_method(class, "min", min);
+    globals.add_builtin_instance_method(class, "max", max);
+    globals.add_builtin_instance_method(class, "sum", sum);
+    globals.add_builtin_instance_method(class, "size", size);
+    globals.add_builtin_instance_method(class, "count", count);
+    globals.add_builtin_instance_method(class, "exclude_end?", exclude_end);
     globals.add_builtin_instance_method(class, "to_a", to_a);
+    globals.add_builtin_instance_method(class, "entries", to_a);
+    globals.add_builtin_instance_method(class, "include?", include);
+    globals.add_builtin_instance_method(class, "member?", include);
+    globals.add_builtin_instance_method(class, "cover?", cover);
+    globals.add_builtin_instance_method(class, "===", teq);
     globals.add_builtin_class_method(obj, "new", range_new);
     obj
 }
 
+/// Create a new Range object.
+/// Both ends must be comparable with `<=>` unless one of them is nil.
+pub fn create_range(vm: &mut VM, start: Value, end: Value, exclude: bool) -> VMResult {
+    let checked =
+        (start.is_packed_fixnum() && end.is_packed_fixnum()) || start.is_nil() || end.is_nil();
+    if !checked {
+        match vm.eval_cmp(end, start) {
+            Ok(ord) if !ord.is_nil() => {}
+            _ => return Err(vm.error_argument("Bad value for range.")),
+        }
+    }
+    Ok(Value::range(&vm.globals, start, end, exclude))
+}
+
 fn range_new(vm: &mut VM, _: Value, args: &Args) -> VMResult {
     let len = args.len();
     vm.check_args_range(len, 2, 3)?;
@@ -59,7 +94,185 @@ fn range_new(vm: &mut VM, _: Value, args: &Args) -> VMResult {
     } else {
         vm.val_to_bool(args[2])
     };
-    Ok(Value::range(&vm.globals, start, end, exclude_end))
+    create_range(vm, start, end, exclude_end)
+}
+
+fn enumerator(vm: &mut VM, method: &str, self_val: Value, args: &Args) -> VMResult {
+    let id = vm.globals.get_ident_id(method);
+    Ok(Value::enumerator(&vm.globals, id, self_val, args.clone()))
+}
+
+/// Returns `lhs <=> rhs` as i64.
+fn compare(vm: &mut VM, lhs: Value, rhs: Value) -> Result<i64, RubyError> {
+    let ord = vm.eval_cmp(rhs, lhs)?;
+    match ord.as_fixnum() {
+        Some(ord) => Ok(ord),
+        None => {
+            let lhs = vm.globals.get_class_name(lhs);
+            let rhs = vm.globals.get_class_name(rhs);
+            Err(vm.error_argument(format!("Comparison of {} with {} failed.", lhs, rhs)))
+        }
+    }
+}
+
+/// Compare `lhs` and `rhs` by calling the block.
+fn compare_by_block(vm: &mut VM, method: Block, lhs: Value, rhs: Value) -> Result<i64, RubyError> {
+    let ord = vm.eval_block(method, &Args::new2(lhs, rhs))?;
+    match ord.as_fixnum() {
+        Some(ord) => Ok(ord),
+        None => {
+            let lhs = vm.globals.get_class_name(lhs);
+            let rhs = vm.globals.get_class_name(rhs);
+            Err(vm.error_argument(format!("Comparison of {} with {} failed.", lhs, rhs)))
+        }
+    }
+}
+
+fn is_empty(vm: &mut VM, range: &RangeInfo) -> Result<bool, RubyError> {
+    if range.start.is_nil() || range.end.is_nil() {
+        return Ok(false);
+    }
+    let ord = compare(vm, range.start, range.end)?;
+    Ok(ord > 0 || ord == 0 && range.exclude)
+}
+
+/// The last Integer of the range which starts with an Integer.
+fn integer_end(vm: &mut VM, range: &RangeInfo) -> Result<i64, RubyError> {
+    match range.end.unpack() {
+        RV::Integer(i) => Ok(if range.exclude { i - 1 } else { i }),
+        RV::Float(f) if range.exclude && f.floor() == f => Ok(f as i64 - 1),
+        RV::Float(f) => Ok(f.floor() as i64),
+        _ => {
+            let class = vm.globals.get_class_name(range.end);
+            Err(vm.error_type(format!("Can't iterate to {}.", class)))
+        }
+    }
+}
+
+/// Call `f` for each element of `range` in order, until `f` returns false.
+/// Strings are iterated by String#succ in the same way as String#upto.
+fn range_each<F>(vm: &mut VM, range: &RangeInfo, mut f: F) -> Result<(), RubyError>
+where
+    F: FnMut(&mut VM, Value) -> Result<bool, RubyError>,
+{
+    if let Some(start) = range.start.as_fixnum() {
+        let end = if range.end.is_nil() {
+            None
+        } else {
+            Some(integer_end(vm, range)?)
+        };
+        let mut i = start;
+        while end.map_or(true, |end| i <= end) {
+            if !f(vm, Value::fixnum(i))? {
+                break;
+            }
+            i += 1;
+        }
+        return Ok(());
+    }
+    if let Some(start) = range.start.as_string() {
+        let mut current = start.clone();
+        if range.end.is_nil() {
+            loop {
+                let val = Value::string(&vm.globals, current.clone());
+                if !f(vm, val)? {
+                    return Ok(());
+                }
+                current = str_succ(&current);
+            }
+        }
+        let end = vm.expect_string(&range.end, "End")?.clone();
+        if current > end || range.exclude && current == end {
+            return Ok(());
+        }
+        let after_end = str_succ(&end);
+        while current != after_end {
+            let next = if range.exclude || current != end {
+                Some(str_succ(&current))
+            } else {
+                None
+            };
+            let val = Value::string(&vm.globals, current);
+            if !f(vm, val)? {
+                break;
+            }
+            current = match next {
+                Some(next) => next,
+                None => break,
+            };
+            if range.exclude && current == end || current.len() > end.len() || current.is_empty() {
+                break;
+            }
+        }
+        return Ok(());
+    }
+    let class = vm.globals.get_class_name(range.start);
+    Err(vm.error_type(format!("Can't iterate from {}.", class)))
+}
+
+fn collect(vm: &mut VM, range: &RangeInfo) -> Result<Vec<Value>, RubyError> {
+    if range.end.is_nil() {
+        return Err(vm.error_range("Cannot convert endless range to an array."));
+    }
+    let mut v = vec![];
+    range_each(vm, range, |_, val| {
+        v.push(val);
+        Ok(true)
+    })?;
+    Ok(v)
+}
+
+/// The number of elements which are yielded by Float step, in the same way as CRuby.
+fn float_step_size(start: f64, end: f64, unit: f64, exclude: bool) -> f64 {
+    let n = (end - start) / unit;
+    let err = (start.abs() + end.abs() + (end - start).abs()) / unit.abs() * std::f64::EPSILON;
+    if unit.is_infinite() {
+        return if unit > 0.0 && start <= end || unit < 0.0 && start >= end {
+            1.0
+        } else {
+            0.0
+        };
+    }
+    let err = if err > 0.5 { 0.5 } else { err };
+    if exclude {
+        if n <= 0.0 {
+            return 0.0;
+        }
+        let mut n = if n < 1.0 { 0.0 } else { (n - err).floor() };
+        let d = (n + 1.0) * unit + start;
+        if start < end && d < end || start > end && d > end {
+            n += 1.0;
+        }
+        n + 1.0
+    } else {
+        if n < 0.0 {
+            return 0.0;
+        }
+        (n + err).floor() + 1.0
+    }
+}
+
+/// Yield `val` to the block.
+/// Results are collected into `res` when the block is the dummy one given by Enumerator.
+fn yield_value(
+    vm: &mut VM,
+    method: Block,
+    val: Value,
+    res: &mut Vec<Value>,
+) -> Result<(), RubyError> {
+    let val = vm.eval_block(method, &Args::new1(val))?;
+    if method.is_none() {
+        res.push(val);
+    }
+    Ok(())
+}
+
+fn each_result(vm: &mut VM, method: Block, self_val: Value, res: Vec<Value>) -> VMResult {
+    if method.is_none() {
+        Ok(Value::array_from(&vm.globals, res))
+    } else {
+        Ok(self_val)
+    }
 }
 
 fn to_s(vm: &mut VM, self_val: Value, _: &Args) -> VMResult {
@@ -84,11 +297,18 @@ fn end(_vm: &mut VM, self_val: Value, _: &Args) -> VMResult {
     Ok(range.end)
 }
 
-fn firat(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+fn exclude_end(_vm: &mut VM, self_val: Value, _: &Args) -> VMResult {
+    let range = self_val.as_range().unwrap();
+    Ok(Value::bool(range.exclude))
+}
+
+fn first(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_range(args.len(), 0, 1)?;
     let range = self_val.as_range().unwrap();
-    let start = range.start.as_fixnum().unwrap();
-    let mut end = range.end.as_fixnum().unwrap() - if range.exclude { 1 } else { 0 };
     if args.len() == 0 {
+        if range.start.is_nil() {
+            return Err(vm.error_range("Cannot get the first element of beginless range."));
+        }
         return Ok(range.start);
     };
     let arg = args[0].expect_integer(&vm, "Argument")?;
@@ -96,19 +316,21 @@ fn firat(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
         return Err(vm.error_argument("Negative array size"));
     };
     let mut v = vec![];
-    if start + arg - 1 < end {
-        end = start + arg - 1;
-    };
-    for i in start..=end {
-        v.push(Value::fixnum(i));
+    if arg > 0 {
+        range_each(vm, range, |_, val| {
+            v.push(val);
+            Ok(v.len() < arg as usize)
+        })?;
     }
     Ok(Value::array_from(&vm.globals, v))
 }
 
 fn last(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_range(args.len(), 0, 1)?;
     let range = self_val.as_range().unwrap();
-    let mut start = range.start.as_fixnum().unwrap();
-    let end = range.end.as_fixnum().unwrap() - if range.exclude { 1 } else { 0 };
+    if range.end.is_nil() {
+        return Err(vm.error_range("Cannot get the last element of endless range."));
+    }
     if args.len() == 0 {
         return Ok(range.end);
     };
@@ -116,28 +338,270 @@ fn last(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
     if arg < 0 {
         return Err(vm.error_argument("Negative array size"));
     };
-    let mut v = vec![];
-    if end - arg + 1 > start {
-        start = end - arg + 1;
+    let v = match (range.start.as_fixnum(), range.end.as_fixnum()) {
+        (Some(mut start), Some(end)) => {
+            let end = end - if range.exclude { 1 } else { 0 };
+            if end - arg + 1 > start {
+                start = end - arg + 1;
+            };
+            (start..=end).map(Value::fixnum).collect()
+        }
+        _ => {
+            let mut v = collect(vm, range)?;
+            let len = v.len();
+            v.split_off(len - std::cmp::min(len, arg as usize))
+        }
     };
-    for i in start..=end {
-        v.push(Value::fixnum(i));
-    }
     Ok(Value::array_from(&vm.globals, v))
 }
 
+fn min(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let range = self_val.as_range().unwrap();
+    if let Some(method) = args.block {
+        if range.end.is_nil() {
+            return Err(vm.error_range(
+                "Cannot get the minimum of endless range with custom comparison method.",
+            ));
+        }
+        let mut res: Option<Value> = None;
+        for val in collect(vm, range)? {
+            res = match res {
+                Some(min) if compare_by_block(vm, method, val, min)? >= 0 => Some(min),
+                _ => Some(val),
+            };
+        }
+        return Ok(res.unwrap_or_default());
+    }
+    if range.start.is_nil() {
+        return Err(vm.error_range("Cannot get the minimum of beginless range."));
+    }
+    if is_empty(vm, range)? {
+        return Ok(Value::nil());
+    }
+    Ok(range.start)
+}
+
+fn max(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let range = self_val.as_range().unwrap();
+    if range.end.is_nil() {
+        return Err(vm.error_range("Cannot get the maximum of endless range."));
+    }
+    if let Some(method) = args.block {
+        let mut res: Option<Value> = None;
+        for val in collect(vm, range)? {
+            res = match res {
+                Some(max) if compare_by_block(vm, method, val, max)? <= 0 => Some(max),
+                _ => Some(val),
+            };
+        }
+        return Ok(res.unwrap_or_default());
+    }
+    if is_empty(vm, range)? {
+        return Ok(Value::nil());
+    }
+    if range.exclude {
+        let end = match range.end.as_fixnum() {
+            Some(end) => end,
+            None => return Err(vm.error_type("Cannot exclude non Integer end value.")),
+        };
+        if !range.start.is_packed_fixnum() {
+            return Err(vm.error_type("Cannot exclude end value with non Integer begin value."));
+        }
+        return Ok(Value::fixnum(end - 1));
+    }
+    Ok(range.end)
+}
+
+fn sum(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_range(args.len(), 0, 1)?;
+    let range = self_val.as_range().unwrap();
+    let init = if args.len() == 0 {
+        Value::fixnum(0)
+    } else {
+        args[0]
+    };
+    match (args.block, range.start.as_fixnum(), range.end.as_fixnum()) {
+        (None, Some(start), Some(end)) => {
+            // (start + end) * n / 2
+            let end = end - if range.exclude { 1 } else { 0 };
+            if end < start {
+                return Ok(init);
+            }
+            let n = (end - start + 1) as i128;
+            let sum = (start as i128 + end as i128) * n / 2;
+            if sum >= std::i64::MIN as i128 && sum <= std::i64::MAX as i128 {
+                return vm.eval_add_values(init, Value::fixnum(sum as i64));
+            }
+        }
+        _ => {}
+    };
+    let mut acc = init;
+    let mut arg = Args::new1(Value::nil());
+    range_each(vm, range, |vm, val| {
+        let val = match args.block {
+            Some(method) => {
+                arg[0] = val;
+                vm.eval_block(method, &arg)?
+            }
+            None => val,
+        };
+        acc = vm.eval_add_values(acc, val)?;
+        Ok(true)
+    })?;
+    Ok(acc)
+}
+
+fn size(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let range = self_val.as_range().unwrap();
+    let start = to_f64(range.start);
+    let end = to_f64(range.end);
+    let size = match (range.start.as_fixnum(), range.end.as_fixnum()) {
+        (Some(start), Some(end)) => {
+            let n = end - start + if range.exclude { 0 } else { 1 };
+            Value::fixnum(std::cmp::max(n, 0))
+        }
+        _ => match (start, end) {
+            (Some(start), Some(end)) => {
+                let n = float_step_size(start, end, 1.0, range.exclude);
+                if n.is_infinite() {
+                    Value::flonum(n)
+                } else {
+                    Value::fixnum(n as i64)
+                }
+            }
+            (Some(_), None) if range.end.is_nil() => Value::flonum(std::f64::INFINITY),
+            (None, Some(_)) if range.start.is_nil() => Value::flonum(std::f64::INFINITY),
+            _ => Value::nil(),
+        },
+    };
+    Ok(size)
+}
+
+fn count(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_range(args.len(), 0, 1)?;
+    let range = self_val.as_range().unwrap();
+    if args.len() == 0 && args.block.is_none() {
+        if range.start.is_packed_fixnum() && range.end.is_packed_fixnum() {
+            return size(vm, self_val, args);
+        }
+        if range.start.is_nil() || range.end.is_nil() {
+            return Ok(Value::flonum(std::f64::INFINITY));
+        }
+    }
+    let mut count = 0;
+    let mut arg = Args::new1(Value::nil());
+    range_each(vm, range, |vm, val| {
+        let matched = if args.len() == 1 {
+            vm.eval_eq(val, args[0])?
+        } else if let Some(method) = args.block {
+            arg[0] = val;
+            let res = vm.eval_block(method, &arg)?;
+            vm.val_to_bool(res)
+        } else {
+            true
+        };
+        if matched {
+            count += 1;
+        }
+        Ok(true)
+    })?;
+    Ok(Value::fixnum(count))
+}
+
+fn step(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_range(args.len(), 0, 1)?;
+    let range = self_val.as_range().unwrap();
+    let unit = if args.len() == 0 {
+        Value::fixnum(1)
+    } else {
+        args[0]
+    };
+    match to_f64(unit) {
+        Some(f) if f == 0.0 => return Err(vm.error_argument("Step can't be 0.")),
+        Some(f) if f < 0.0 => return Err(vm.error_argument("Step can't be negative.")),
+        Some(_) => {}
+        None => {
+            let class = vm.globals.get_class_name(unit);
+            return Err(vm.error_type(format!("No implicit conversion of {} into Integer.", class)));
+        }
+    };
+    let method = match args.block {
+        Some(method) => method,
+        None => return enumerator(vm, "step", self_val, args),
+    };
+    let mut res = vec![];
+    if range.start.is_packed_flonum() || range.end.is_packed_flonum() || unit.is_packed_flonum() {
+        let start = match to_f64(range.start) {
+            Some(f) => f,
+            None => {
+                let class = vm.globals.get_class_name(range.start);
+                return Err(vm.error_type(format!("Can't iterate from {}.", class)));
+            }
+        };
+        let end = match to_f64(range.end) {
+            Some(f) => f,
+            None => std::f64::INFINITY,
+        };
+        let unit = to_f64(unit).unwrap();
+        let n = float_step_size(start, end, unit, range.exclude);
+        let mut i = 0.0;
+        while i < n {
+            let d = i * unit + start;
+            yield_value(
+                vm,
+                method,
+                Value::flonum(if end < d { end } else { d }),
+                &mut res,
+            )?;
+            i += 1.0;
+        }
+        return each_result(vm, method, self_val, res);
+    }
+    let unit = vm.expect_integer(unit, "Step")?;
+    match (range.start.as_fixnum(), range.end.is_nil()) {
+        (Some(start), true) => {
+            let mut i = start;
+            loop {
+                yield_value(vm, method, Value::fixnum(i), &mut res)?;
+                i += unit;
+            }
+        }
+        (Some(start), false) => {
+            let end = integer_end(vm, range)?;
+            let mut i = start;
+            while i <= end {
+                yield_value(vm, method, Value::fixnum(i), &mut res)?;
+                i += unit;
+            }
+        }
+        _ => {
+            let mut n = 0;
+            range_each(vm, range, |vm, val| {
+                if n % unit == 0 {
+                    yield_value(vm, method, val, &mut res)?;
+                }
+                n += 1;
+                Ok(true)
+            })?;
+        }
+    }
+    each_result(vm, method, self_val, res)
+}
+
 fn map(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
     let range = self_val.as_range().unwrap();
     let method = vm.expect_block(args.block)?;
     let mut res = vec![];
-    let start = range.start.expect_integer(&vm, "Start")?;
-    let end = range.end.expect_integer(&vm, "End")? + if range.exclude { 0 } else { 1 };
     let mut arg = Args::new1(Value::nil());
-    for i in start..end {
-        arg[0] = Value::fixnum(i);
+    range_each(vm, range, |vm, val| {
+        arg[0] = val;
         let val = vm.eval_block(method, &arg)?;
         res.push(val);
-    }
+        Ok(true)
+    })?;
     let res = Value::array_from(&vm.globals, res);
     Ok(res)
 }
@@ -146,11 +610,9 @@ fn flat_map(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
     let range = self_val.as_range().unwrap();
     let method = vm.expect_block(args.block)?;
     let mut res = vec![];
-    let start = range.start.expect_integer(&vm, "Start")?;
-    let end = range.end.expect_integer(&vm, "End")? + if range.exclude { 0 } else { 1 };
     let mut arg = Args::new1(Value::nil());
-    for i in start..end {
-        arg[0] = Value::fixnum(i);
+    range_each(vm, range, |vm, val| {
+        arg[0] = val;
         let val = vm.eval_block(method, &arg)?;
         match val.as_array() {
             Some(aref) => {
@@ -159,51 +621,306 @@ fn flat_map(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
             }
             None => res.push(val),
         };
-    }
+        Ok(true)
+    })?;
     let res = Value::array_from(&vm.globals, res);
     Ok(res)
 }
 
 fn each(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
     let range = self_val.as_range().unwrap();
-    let method = vm.expect_block(args.block)?;
-    let start = range.start.expect_integer(&vm, "Start")?;
-    let end = range.end.expect_integer(&vm, "End")? + if range.exclude { 0 } else { 1 };
-    for i in start..end {
-        let arg = Args::new1(Value::fixnum(i));
-        vm.eval_block(method, &arg)?;
+    let method = match args.block {
+        Some(method) => method,
+        None => return enumerator(vm, "each", self_val, args),
+    };
+    let mut res = vec![];
+    range_each(vm, range, |vm, val| {
+        yield_value(vm, method, val, &mut res)?;
+        Ok(true)
+    })?;
+    each_result(vm, method, self_val, res)
+}
+
+fn reverse_each(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let range = self_val.as_range().unwrap();
+    let method = match args.block {
+        Some(method) => method,
+        None => return enumerator(vm, "reverse_each", self_val, args),
+    };
+    if range.end.is_nil() {
+        let class = vm.globals.get_class_name(range.end);
+        return Err(vm.error_type(format!("Can't iterate from {}.", class)));
     }
-    Ok(self_val)
+    let mut res = vec![];
+    match range.start.as_fixnum() {
+        Some(start) => {
+            let mut i = integer_end(vm, range)?;
+            while i >= start {
+                yield_value(vm, method, Value::fixnum(i), &mut res)?;
+                i -= 1;
+            }
+        }
+        None => {
+            for val in collect(vm, range)?.into_iter().rev() {
+                yield_value(vm, method, val, &mut res)?;
+            }
+        }
+    }
+    each_result(vm, method, self_val, res)
 }
 
-fn all(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+fn each_slice(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
     let range = self_val.as_range().unwrap();
-    let method = vm.expect_block(args.block)?;
-    let start = range.start.expect_integer(&vm, "Start")?;
-    let end = range.end.expect_integer(&vm, "End")? + if range.exclude { 0 } else { 1 };
-    for i in start..end {
-        let arg = Args::new1(Value::fixnum(i));
-        let res = vm.eval_block(method, &arg)?;
-        if !vm.val_to_bool(res) {
-            return Ok(Value::false_val());
+    let n = args[0].expect_integer(&vm, "Slice size")?;
+    if n <= 0 {
+        return Err(vm.error_argument("Invalid slice size."));
+    }
+    let method = match args.block {
+        Some(method) => method,
+        None => return enumerator(vm, "each_slice", self_val, args),
+    };
+    let mut res = vec![];
+    let mut slice = vec![];
+    range_each(vm, range, |vm, val| {
+        slice.push(val);
+        if slice.len() == n as usize {
+            let ary = Value::array_from(&vm.globals, std::mem::replace(&mut slice, vec![]));
+            yield_value(vm, method, ary, &mut res)?;
         }
+        Ok(true)
+    })?;
+    if !slice.is_empty() {
+        let ary = Value::array_from(&vm.globals, slice);
+        yield_value(vm, method, ary, &mut res)?;
     }
-    Ok(Value::true_val())
+    each_result(vm, method, Value::nil(), res)
+}
+
+fn all(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    let range = self_val.as_range().unwrap();
+    let method = vm.expect_block(args.block)?;
+    let mut res = true;
+    let mut arg = Args::new1(Value::nil());
+    range_each(vm, range, |vm, val| {
+        arg[0] = val;
+        let val = vm.eval_block(method, &arg)?;
+        res = vm.val_to_bool(val);
+        Ok(res)
+    })?;
+    Ok(Value::bool(res))
 }
 
 fn to_a(vm: &mut VM, self_val: Value, _: &Args) -> VMResult {
     let range = self_val.as_range().unwrap();
-    let start = range.start.expect_integer(&vm, "Range.start")?;
-    let end = range.end.expect_integer(&vm, "Range.end")?;
-    let mut v = vec![];
-    if range.exclude {
-        for i in start..end {
-            v.push(Value::fixnum(i));
-        }
-    } else {
-        for i in start..=end {
-            v.push(Value::fixnum(i));
+    let v = collect(vm, range)?;
+    Ok(Value::array_from(&vm.globals, v))
+}
+
+fn include(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let range = self_val.as_range().unwrap();
+    let val = args[0];
+    let res = match (range.start.as_string(), range.end.as_string()) {
+        (Some(_), Some(_)) => {
+            if val.as_string().is_none() {
+                return Ok(Value::false_val());
+            }
+            let mut res = false;
+            range_each(vm, range, |vm, elem| {
+                res = vm.eval_eq(elem, val)?;
+                Ok(!res)
+            })?;
+            res
         }
+        _ => vm.range_include(range, val)?,
+    };
+    Ok(Value::bool(res))
+}
+
+fn cover(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let range = self_val.as_range().unwrap();
+    let other = match args[0].as_range() {
+        Some(other) => other,
+        None => return Ok(Value::bool(vm.range_include(range, args[0])?)),
+    };
+    if !range.end.is_nil() && other.end.is_nil() || !range.start.is_nil() && other.start.is_nil() {
+        return Ok(Value::false_val());
+    }
+    if is_empty(vm, other)? {
+        return Ok(Value::false_val());
+    }
+    if !other.start.is_nil() && !vm.range_include(range, other.start)? {
+        return Ok(Value::false_val());
+    }
+    if range.end.is_nil() {
+        return Ok(Value::true_val());
+    }
+    let ord = compare(vm, range.end, other.end)?;
+    let res = match (range.exclude, other.exclude) {
+        (r, o) if r == o => ord >= 0,
+        (true, false) => ord > 0,
+        _ if ord >= 0 => true,
+        _ => match (other.start.as_fixnum(), other.end.as_fixnum()) {
+            // the maximum value of `other` is end - 1.
+            (Some(_), Some(end)) => compare(vm, range.end, Value::fixnum(end - 1))? >= 0,
+            _ => false,
+        },
+    };
+    Ok(Value::bool(res))
+}
+
+fn teq(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let range = self_val.as_range().unwrap();
+    Ok(Value::bool(vm.range_include(&range, args[0])?))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::*;
+
+    #[test]
+    fn range_iteration() {
+        let program = r#"
+        assert([1, 2, 3, 4, 5], (1..5).to_a)
+        assert([1, 2, 3, 4], (1...5).to_a)
+        assert([], (5..1).to_a)
+        assert([1, 2, 3], (1..3.5).to_a)
+        assert(["a", "b", "c", "d", "e"], ("a".."e").to_a)
+        assert(["a", "b", "c", "d"], ("a"..."e").to_a)
+        assert(["az", "ba", "bb", "bc"], ("az".."bc").to_a)
+        assert([], ("y".."ab").to_a)
+        assert(["08", "09", "10"], ("08".."10").to_a)
+        a = []
+        (1..).each { |x| break if x > 3; a << x }
+        assert([1, 2, 3], a)
+        a = []
+        ("a"..).each { |x| break if x == "c"; a << x }
+        assert(["a", "b"], a)
+        assert([5, 4, 3, 2, 1], (1..5).reverse_each.to_a)
+        a = []
+        ("a".."c").reverse_each { |x| a << x }
+        assert(["c", "b", "a"], a)
+        a = []
+        (1..7).each_slice(3) { |x| a << x }
+        assert([[1, 2, 3], [4, 5, 6], [7]], a)
+        assert([1, 2, 3], (1..).first(3))
+        assert(["a", "b"], ("a".."z").first(2))
+        assert([8, 9, 10], (1..10).last(3))
+        assert([2, 4, 6], (1..3).map { |x| x * 2 })
+        assert_error { (1.0..2.0).each {} }
+        assert_error { (1..).to_a }
+        "#;
+        assert_script(program);
+    }
+
+    #[test]
+    fn range_step() {
+        let program = r#"
+        assert([1, 4, 7, 10], (1..10).step(3).to_a)
+        assert([1, 4, 7], (1...10).step(3).to_a)
+        assert([0, 5, 10], ((0..10) % 5).to_a)
+        assert([1.0, 1.5, 2.0], (1.0..2.0).step(0.5).to_a)
+        assert([1.0, 1.5], (1.0...2.0).step(0.5).to_a)
+        assert([0.0, 0.1, 0.2, 0.30000000000000004, 0.4, 0.5], (0.0..0.5).step(0.1).to_a)
+        assert(["a", "c", "e"], ("a".."e").step(2).to_a)
+        a = []
+        (1..).step(2) { |x| break if x > 7; a << x }
+        assert([1, 3, 5, 7], a)
+        assert_error { (1..10).step(0) }
+        assert_error { (1..10).step(-1) }
+        "#;
+        assert_script(program);
+    }
+
+    #[test]
+    fn range_query() {
+        let program = r#"
+        assert(true, (1..10).include?(5))
+        assert(false, (1...10).include?(10))
+        assert(true, (1..10).member?(2.5))
+        assert(true, ("a".."z").include?("m"))
+        assert(false, ("a".."z").include?("mm"))
+        assert(true, ("a".."z").cover?("mm"))
+        assert(true, (1..10).cover?(2..5))
+        assert(false, (1..10).cover?(2..11))
+        assert(true, (1..10).cover?(2...11))
+        assert(false, (1...10).cover?(2..10))
+        assert(true, (1..10) === 3)
+        assert(true, (1..) === 100)
+        assert(false, (1..) === 0)
+        assert(true, (..5) === -100)
+        assert(false, (...5) === 5)
+        assert(true, (1.5..2.5).cover?(2))
+        assert(10, (1..10).size)
+        assert(9, (1...10).size)
+        assert(0, (10..1).size)
+        assert(3, (1.0..3.5).size)
+        assert(Float::INFINITY, (1..).size)
+        assert(nil, ("a".."z").size)
+        assert(10, (1..10).count)
+        assert(5, (1..10).count { |x| x.even? })
+        assert(1, (1..10).count(3))
+        assert(26, ("a".."z").count)
+        assert(true, (1...3).exclude_end?)
+        "#;
+        assert_script(program);
+    }
+
+    #[test]
+    fn range_sum_min_max() {
+        let program = r#"
+        assert(5050, (1..100).sum)
+        assert(4950, (1...100).sum)
+        assert(0, (10..1).sum)
+        assert(65, (1..10).sum(10))
+        assert(55.0, (1..10).sum(0.0))
+        assert(110, (1..10).sum { |x| x * 2 })
+        assert(500000000500000000, (1..1000000000).sum)
+        assert(1, (1..10).min)
+        assert(10, (1..10).max)
+        assert(9, (1...10).max)
+        assert(2.5, (1..2.5).max)
+        assert(nil, (5..1).min)
+        assert(nil, (5..1).max)
+        assert(10, (1..10).min { |a, b| b <=> a })
+        assert(1, (1..).min)
+        assert(5, (..5).max)
+        assert("a", ("a".."c").min)
+        assert_error { (1..).max }
+        assert_error { (..5).min }
+        assert_error { (1...2.5).max }
+        "#;
+        assert_script(program);
+    }
+
+    #[test]
+    fn range_endless_beginless() {
+        let program = r#"
+        r = (1..)
+        assert(1, r.begin)
+        assert(nil, r.end)
+        assert("1..", r.inspect)
+        assert("..5", (..5).inspect)
+        assert("1...", (1...).inspect)
+        assert("nil..nil", (nil..nil).inspect)
+        x = [1, 2, (3..), 4]
+        assert(3, x[2].begin)
+        r = case 7
+            when (..0) then "negative"
+            when (1...5) then "small"
+            when (5..) then "large"
+            end
+        assert("large", r)
+        assert(Range.new(1, nil), (1..))
+        assert_error { Range.new(1, "a") }
+        assert_error { Range.new(Object.new, Object.new) }
+        assert(3.0..4.0, Range.new(3.0, 4.0))
+        "#;
+        assert_script(program);
     }
-    Ok(Value::array_from(&vm.globals, v))
 }
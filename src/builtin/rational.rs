@@ -0,0 +1,419 @@
+use crate::builtin::numeric::{coerce, coerce_bin_op};
+use crate::*;
+use std::convert::TryFrom;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RationalInfo {
+    pub num: i64,
+    pub den: i64,
+}
+
+pub fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        let r = a % b;
+        a = b;
+        b = r;
+    }
+    a
+}
+
+impl RationalInfo {
+    /// Create a reduced rational number. `den` must not be 0.
+    pub fn new(num: i64, den: i64) -> Self {
+        RationalInfo::from_i128(num as i128, den as i128).unwrap()
+    }
+
+    /// Create a reduced rational number.
+    /// Returns None if `den` is 0 or the reduced value does not fit in i64.
+    pub fn from_i128(num: i128, den: i128) -> Option<Self> {
+        if den == 0 {
+            return None;
+        }
+        let g = gcd(num, den);
+        let (num, den) = if den < 0 {
+            (-num / g, -den / g)
+        } else {
+            (num / g, den / g)
+        };
+        Some(RationalInfo {
+            num: i64::try_from(num).ok()?,
+            den: i64::try_from(den).ok()?,
+        })
+    }
+
+    /// Convert a finite f64 into the exactly equal rational number.
+    pub fn from_f64(f: f64) -> Option<Self> {
+        if !f.is_finite() {
+            return None;
+        }
+        let mut num = f;
+        let mut den: i128 = 1;
+        while num.fract() != 0.0 {
+            num *= 2.0;
+            den *= 2;
+            if den > std::i64::MAX as i128 {
+                return None;
+            }
+        }
+        if num.abs() > std::i64::MAX as f64 {
+            return None;
+        }
+        RationalInfo::from_i128(num as i128, den)
+    }
+
+    pub fn to_f(&self) -> f64 {
+        self.num as f64 / self.den as f64
+    }
+
+    pub fn to_s(&self) -> String {
+        format!("{}/{}", self.num, self.den)
+    }
+
+    pub fn inspect(&self) -> String {
+        format!("({}/{})", self.num, self.den)
+    }
+
+    pub fn add(&self, other: &Self) -> Option<Self> {
+        let (a, b, c, d) = self.widen(other);
+        RationalInfo::from_i128(a * d + c * b, b * d)
+    }
+
+    pub fn sub(&self, other: &Self) -> Option<Self> {
+        let (a, b, c, d) = self.widen(other);
+        RationalInfo::from_i128(a * d - c * b, b * d)
+    }
+
+    pub fn mul(&self, other: &Self) -> Option<Self> {
+        let (a, b, c, d) = self.widen(other);
+        RationalInfo::from_i128(a * c, b * d)
+    }
+
+    /// Returns None if `other` is 0 or the result overflows.
+    pub fn div(&self, other: &Self) -> Option<Self> {
+        let (a, b, c, d) = self.widen(other);
+        RationalInfo::from_i128(a * d, b * c)
+    }
+
+    pub fn pow(&self, exp: i64) -> Option<Self> {
+        let (mut num, mut den) = (self.num as i128, self.den as i128);
+        if exp < 0 {
+            std::mem::swap(&mut num, &mut den);
+        }
+        let exp = u32::try_from(exp.abs()).ok()?;
+        RationalInfo::from_i128(num.checked_pow(exp)?, den.checked_pow(exp)?)
+    }
+
+    pub fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        let (a, b, c, d) = self.widen(other);
+        (a * d).cmp(&(c * b))
+    }
+
+    pub fn floor(&self) -> i64 {
+        (self.num as i128).div_euclid(self.den as i128) as i64
+    }
+
+    pub fn ceil(&self) -> i64 {
+        -(-(self.num as i128)).div_euclid(self.den as i128) as i64
+    }
+
+    pub fn truncate(&self) -> i64 {
+        self.num / self.den
+    }
+
+    /// Round half away from zero.
+    pub fn round(&self) -> i64 {
+        let (num, den) = (self.num as i128, self.den as i128);
+        let res = (num.abs() * 2 + den).div_euclid(den * 2);
+        (if num < 0 { -res } else { res }) as i64
+    }
+
+    fn widen(&self, other: &Self) -> (i128, i128, i128, i128) {
+        (
+            self.num as i128,
+            self.den as i128,
+            other.num as i128,
+            other.den as i128,
+        )
+    }
+}
+
+pub fn init_rational(globals: &mut Globals) -> Value {
+    let id = globals.get_ident_id("Rational");
+    let class = ClassRef::from(id, globals.builtins.numeric);
+    globals.add_builtin_instance_method(class, "numerator", numerator);
+    globals.add_builtin_instance_method(class, "denominator", denominator);
+    globals.add_builtin_instance_method(class, "+", add);
+    globals.add_builtin_instance_method(class, "-", sub);
+    globals.add_builtin_instance_method(class, "*", mul);
+    globals.add_builtin_instance_method(class, "/", div);
+    globals.add_builtin_instance_method(class, "quo", div);
+    globals.add_builtin_instance_method(class, "**", pow);
+    globals.add_builtin_instance_method(class, "<=>", cmp);
+    globals.add_builtin_instance_method(class, "==", eq);
+    globals.add_builtin_instance_method(class, "coerce", coerce_);
+    globals.add_builtin_instance_method(class, "abs", abs);
+    globals.add_builtin_instance_method(class, "to_f", to_f);
+    globals.add_builtin_instance_method(class, "to_i", truncate);
+    globals.add_builtin_instance_method(class, "truncate", truncate);
+    globals.add_builtin_instance_method(class, "to_r", to_r);
+    globals.add_builtin_instance_method(class, "floor", floor);
+    globals.add_builtin_instance_method(class, "ceil", ceil);
+    globals.add_builtin_instance_method(class, "round", round);
+    globals.add_builtin_instance_method(class, "to_s", to_s);
+    globals.add_builtin_instance_method(class, "inspect", inspect);
+    Value::class(globals, class)
+}
+
+// Utils
+
+/// Convert Integer or Rational into RationalInfo.
+pub fn to_rational(val: Value) -> Option<RationalInfo> {
+    match val.as_fixnum() {
+        Some(i) => Some(RationalInfo { num: i, den: 1 }),
+        None => val.as_rational(),
+    }
+}
+
+/// Wrap the result of an operation on RationalInfo, raising an error on overflow.
+fn rational_result(vm: &mut VM, info: Option<RationalInfo>) -> VMResult {
+    match info {
+        Some(info) => Ok(Value::rational(&vm.globals, info)),
+        None => Err(vm.error_unimplemented("Rational overflow. (Bignum is not supported)")),
+    }
+}
+
+/// Kernel#Rational(num, den = 1)
+pub fn rational(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_range(args.len(), 1, 2)?;
+    let mut rats = vec![];
+    for arg in args.iter() {
+        let rat = match arg.as_flonum() {
+            Some(f) => RationalInfo::from_f64(f),
+            None => to_rational(*arg),
+        };
+        match rat {
+            Some(rat) => rats.push(rat),
+            None => {
+                let inspect = vm.val_inspect(*arg);
+                return Err(vm.error_type(format!("Can't convert {} into Rational.", inspect)));
+            }
+        }
+    }
+    if args.len() == 1 {
+        return Ok(Value::rational(&vm.globals, rats[0]));
+    }
+    if rats[1].num == 0 {
+        return Err(vm.error_zero_division());
+    }
+    let res = rats[0].div(&rats[1]);
+    rational_result(vm, res)
+}
+
+// Instance methods
+
+fn numerator(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    Ok(Value::fixnum(self_val.as_rational().unwrap().num))
+}
+
+fn denominator(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    Ok(Value::fixnum(self_val.as_rational().unwrap().den))
+}
+
+macro_rules! define_op {
+    ($fname:ident, $op:ident, $fop:tt, $name:expr) => {
+        fn $fname(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+            vm.check_args_num(args.len(), 1)?;
+            let lhs = self_val.as_rational().unwrap();
+            if let Some(rhs) = to_rational(args[0]) {
+                let res = lhs.$op(&rhs);
+                rational_result(vm, res)
+            } else if let Some(rhs) = args[0].as_flonum() {
+                Ok(Value::flonum(lhs.to_f() $fop rhs))
+            } else {
+                coerce_bin_op(vm, self_val, args[0], $name)
+            }
+        }
+    };
+}
+
+define_op!(add, add, +, "+");
+define_op!(sub, sub, -, "-");
+define_op!(mul, mul, *, "*");
+
+fn div(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let lhs = self_val.as_rational().unwrap();
+    if let Some(rhs) = to_rational(args[0]) {
+        if rhs.num == 0 {
+            return Err(vm.error_zero_division());
+        }
+        let res = lhs.div(&rhs);
+        rational_result(vm, res)
+    } else if let Some(rhs) = args[0].as_flonum() {
+        Ok(Value::flonum(lhs.to_f() / rhs))
+    } else {
+        coerce_bin_op(vm, self_val, args[0], "/")
+    }
+}
+
+fn pow(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let lhs = self_val.as_rational().unwrap();
+    match to_rational(args[0]) {
+        Some(rhs) if rhs.den == 1 => {
+            if lhs.num == 0 && rhs.num < 0 {
+                return Err(vm.error_zero_division());
+            }
+            let res = lhs.pow(rhs.num);
+            rational_result(vm, res)
+        }
+        Some(rhs) => Ok(Value::flonum(lhs.to_f().powf(rhs.to_f()))),
+        None => match args[0].as_flonum() {
+            Some(rhs) => Ok(Value::flonum(lhs.to_f().powf(rhs))),
+            None => coerce_bin_op(vm, self_val, args[0], "**"),
+        },
+    }
+}
+
+fn cmp(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let lhs = self_val.as_rational().unwrap();
+    if let Some(rhs) = to_rational(args[0]) {
+        return Ok(Value::fixnum(lhs.cmp(&rhs) as i64));
+    }
+    if let Some(rhs) = args[0].as_flonum() {
+        return match lhs.to_f().partial_cmp(&rhs) {
+            Some(ord) => Ok(Value::fixnum(ord as i64)),
+            None => Ok(Value::nil()),
+        };
+    }
+    match coerce(vm, self_val, args[0])? {
+        Some((lhs, rhs)) => vm.eval_cmp(rhs, lhs),
+        None => Ok(Value::nil()),
+    }
+}
+
+fn eq(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    Ok(Value::bool(self_val.equal(args[0])))
+}
+
+/// Rational#coerce(other)
+fn coerce_(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let other = args[0];
+    let pair = if let Some(rat) = to_rational(other) {
+        vec![Value::rational(&vm.globals, rat), self_val]
+    } else if other.as_flonum().is_some() {
+        let lhs = self_val.as_rational().unwrap();
+        vec![other, Value::flonum(lhs.to_f())]
+    } else if other.as_complex().is_some() {
+        let lhs = ComplexInfo::new(self_val, Value::fixnum(0));
+        vec![other, Value::complex(&vm.globals, lhs)]
+    } else {
+        let name = vm.globals.get_class_name(other);
+        return Err(vm.error_type(format!("{} can't be coerced into Rational.", name)));
+    };
+    Ok(Value::array_from(&vm.globals, pair))
+}
+
+fn abs(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let info = self_val.as_rational().unwrap();
+    if info.num >= 0 {
+        return Ok(self_val);
+    }
+    let res = RationalInfo::from_i128(-(info.num as i128), info.den as i128);
+    rational_result(vm, res)
+}
+
+fn to_f(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    Ok(Value::flonum(self_val.as_rational().unwrap().to_f()))
+}
+
+fn truncate(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    Ok(Value::fixnum(self_val.as_rational().unwrap().truncate()))
+}
+
+fn to_r(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    Ok(self_val)
+}
+
+fn floor(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    Ok(Value::fixnum(self_val.as_rational().unwrap().floor()))
+}
+
+fn ceil(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    Ok(Value::fixnum(self_val.as_rational().unwrap().ceil()))
+}
+
+fn round(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    Ok(Value::fixnum(self_val.as_rational().unwrap().round()))
+}
+
+fn to_s(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let s = self_val.as_rational().unwrap().to_s();
+    Ok(Value::string(&vm.globals, s))
+}
+
+fn inspect(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let s = self_val.as_rational().unwrap().inspect();
+    Ok(Value::string(&vm.globals, s))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::*;
+
+    #[test]
+    fn rational() {
+        let program = r#"
+            a = Rational(3, 6)
+            assert(1, a.numerator)
+            assert(2, a.denominator)
+            assert("1/2", a.to_s)
+            assert("(1/2)", a.inspect)
+            assert(Rational(5, 6), a + Rational(1, 3))
+            assert(Rational(1, 6), a - Rational(1, 3))
+            assert(Rational(1, 6), a * Rational(1, 3))
+            assert(Rational(3, 2), a / Rational(1, 3))
+            assert(Rational(3, 2), a + 1)
+            assert(Rational(3, 2), 1 + a)
+            assert(Rational(-1, 2), 0 - a)
+            assert(Rational(2, 1), 1 / a)
+            assert(1.0, a + 0.5)
+            assert(1.0, 0.5 + a)
+            assert(Rational(1, 8), a ** 3)
+            assert(Rational(4, 1), a ** -2)
+            assert(0.5, a.to_f)
+            assert(true, a == 0.5)
+            assert(true, Rational(4, 2) == 2)
+            assert(true, a < 1)
+            assert(true, 1 > a)
+            assert(-1, a <=> 1)
+            assert(1, 1 <=> a)
+            assert(Rational(1, 2), Rational(0.5))
+            assert(Rational(-7, 2), Rational(-7, 2).abs * -1)
+            assert(-4, Rational(-7, 2).floor)
+            assert(-3, Rational(-7, 2).ceil)
+            assert(-4, Rational(-7, 2).round)
+            assert(-3, Rational(-7, 2).truncate)
+            assert(Rational(1, 2), 1r / 2)
+            assert(Rational(3, 2), 1.5r)
+            assert_error { Rational(1, 0) }
+            assert_error { a / 0 }
+        "#;
+        assert_script(program);
+    }
+}
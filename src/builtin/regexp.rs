@@ -54,6 +54,8 @@ pub fn init_regexp(globals: &mut Globals) -> Value {
     globals.add_builtin_class_method(regexp, "compile", regexp_new);
     globals.add_builtin_class_method(regexp, "escape", regexp_escape);
     globals.add_builtin_class_method(regexp, "quote", regexp_escape);
+    globals.add_builtin_instance_method(classref, "match", regexp_match);
+    globals.add_builtin_instance_method(classref, "=~", regexp_rmatch);
     regexp
 }
 
@@ -76,43 +78,41 @@ fn regexp_escape(vm: &mut VM, _: Value, args: &Args) -> VMResult {
 
 // Instance methods
 
-// Utility methods
-
-impl Regexp {
-    fn get_captures(vm: &mut VM, captures: &Captures, given: &str) {
-        let id1 = vm.globals.get_ident_id("$&");
-        let id2 = vm.globals.get_ident_id("$'");
-        match captures.get(0) {
-            Some(m) => {
-                let val = Value::string(&vm.globals, given[m.start()..m.end()].to_string());
-                vm.set_global_var(id1, val);
-                let val = Value::string(&vm.globals, given[m.end()..].to_string());
-                vm.set_global_var(id2, val);
-            }
-            None => {
-                vm.set_global_var(id1, Value::nil());
-                vm.set_global_var(id2, Value::nil());
-            }
-        };
-
-        for i in 1..captures.len() {
-            match captures.get(i) {
-                Some(m) => Regexp::set_special_global(vm, i, given, m.start(), m.end()),
-                None => Regexp::set_special_global_nil(vm, i),
-            };
-        }
+fn regexp_match(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let re = self_val.as_regexp().unwrap();
+    if args[0].is_nil() {
+        vm.set_last_match(Value::nil());
+        return Ok(Value::nil());
     }
+    expect_string!(given, vm, args[0]);
+    Regexp::find_one(vm, &re.regexp, given)?;
+    Ok(vm.get_last_match())
+}
 
-    fn set_special_global(vm: &mut VM, i: usize, given: &str, start: usize, end: usize) {
-        let id = vm.globals.get_ident_id(format!("${}", i));
-        let val = Value::string(&vm.globals, given[start..end].to_string());
-        //eprintln!("${}: {}", i, given[start..end].to_string());
-        vm.set_global_var(id, val);
+fn regexp_rmatch(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let re = self_val.as_regexp().unwrap();
+    if args[0].is_nil() {
+        vm.set_last_match(Value::nil());
+        return Ok(Value::nil());
     }
+    expect_string!(given, vm, args[0]);
+    let res = match Regexp::find_one(vm, &re.regexp, given)? {
+        Some(mat) => Value::fixnum(given[..mat.start()].chars().count() as i64),
+        None => Value::nil(),
+    };
+    Ok(res)
+}
+
+// Utility methods
 
-    fn set_special_global_nil(vm: &mut VM, i: usize) {
-        let id = vm.globals.get_ident_id(format!("${}", i));
-        vm.set_global_var(id, Value::nil());
+impl Regexp {
+    /// Stores the result of a successful match into `$~`.
+    fn get_captures(vm: &mut VM, re: &Regexp, captures: &Captures, given: &str) {
+        let info = MatchInfo::new(re, captures, given);
+        let val = Value::matchdata(&vm.globals, MatchDataRef::new(info));
+        vm.set_last_match(val);
     }
 
     /// Replaces the leftmost-first match with `replace`.
@@ -133,7 +133,7 @@ impl Regexp {
                 Ok(Some(captures)) => {
                     let mut res = given.to_string();
                     let m = captures.get(0).unwrap();
-                    Regexp::get_captures(vm, &captures, given);
+                    Regexp::get_captures(vm, re, &captures, given);
                     let mut rep = "".to_string();
                     let mut escape = false;
                     for ch in replace.chars() {
@@ -178,19 +178,19 @@ impl Regexp {
         vm: &mut VM,
         re_val: Value,
         given: &str,
-        block: MethodRef,
+        block: Block,
     ) -> Result<(String, bool), RubyError> {
         fn replace_(
             vm: &mut VM,
             re: &Regexp,
             given: &str,
-            block: MethodRef,
+            block: Block,
         ) -> Result<(String, bool), RubyError> {
             let (start, end, matched_str) = match re.captures_from_pos(given, 0) {
                 Ok(None) => return Ok((given.to_string(), false)),
                 Ok(Some(captures)) => {
                     let m = captures.get(0).unwrap();
-                    Regexp::get_captures(vm, &captures, given);
+                    Regexp::get_captures(vm, re, &captures, given);
                     (m.start(), m.end(), m.as_str())
                 }
                 Err(err) => return Err(vm.error_internal(format!("Capture failed. {:?}", err))),
@@ -246,7 +246,7 @@ impl Regexp {
                         };
                         range.push((m.start(), m.end()));
                         //eprintln!("{} {} [{:?}]", m.start(), m.end(), m.as_str());
-                        Regexp::get_captures(vm, &captures, given);
+                        Regexp::get_captures(vm, re, &captures, given);
                     }
                     Err(err) => return Err(vm.error_internal(format!("Capture failed. {:?}", err))),
                 };
@@ -273,13 +273,13 @@ impl Regexp {
         vm: &mut VM,
         re_val: Value,
         given: &str,
-        block: MethodRef,
+        block: Block,
     ) -> Result<(String, bool), RubyError> {
         fn replace_(
             vm: &mut VM,
             re: &Regexp,
             given: &str,
-            block: MethodRef,
+            block: Block,
         ) -> Result<(String, bool), RubyError> {
             let mut range = vec![];
             let mut i = 0;
@@ -289,7 +289,7 @@ impl Regexp {
                     Ok(Some(captures)) => {
                         let m = captures.get(0).unwrap();
                         i = m.end();
-                        Regexp::get_captures(vm, &captures, given);
+                        Regexp::get_captures(vm, re, &captures, given);
                         (m.start(), m.end(), m.as_str())
                     }
                     Err(err) => return Err(vm.error_internal(format!("Capture failed. {:?}", err))),
@@ -323,9 +323,12 @@ impl Regexp {
         given: &'a str,
     ) -> Result<Option<Match<'a>>, RubyError> {
         match re.captures(given) {
-            Ok(None) => Ok(None),
+            Ok(None) => {
+                vm.set_last_match(Value::nil());
+                Ok(None)
+            }
             Ok(Some(captures)) => {
-                Regexp::get_captures(vm, &captures, given);
+                Regexp::get_captures(vm, re, &captures, given);
                 Ok(captures.get(0))
             }
             Err(err) => Err(vm.error_internal(format!("Capture failed. {:?}", err))),
@@ -369,8 +372,8 @@ impl Regexp {
             };
         }
         match last_captures {
-            Some(c) => Regexp::get_captures(vm, &c, given),
-            None => {}
+            Some(c) => Regexp::get_captures(vm, re, &c, given),
+            None => vm.set_last_match(Value::nil()),
         }
         Ok(ary)
     }
@@ -392,4 +395,33 @@ mod test {
     "#;
         assert_script(program);
     }
+
+    #[test]
+    fn regexp_last_match() {
+        let program = r#"
+    assert 1, "foo bar" =~ /(o+) (b)/
+    assert "oo b", $~[0]
+    assert "oo b", $&
+    assert "f", $`
+    assert "ar", $'
+    assert "b", $+
+    assert ["oo", "b", nil], [$1, $2, $3]
+    def foo
+      assert nil, $~
+      "xyz" =~ /y/
+      [1].each { assert "y", $& }
+      $&
+    end
+    assert "y", foo
+    assert "oo b", $&
+    assert nil, "abc" =~ /z/
+    assert nil, $~
+    assert nil, $1
+    assert "b", "abc".match(/b/)[0]
+    assert "ab", "abc".match("c").pre_match
+    assert 1, /b/ =~ "abc"
+    assert nil, /b/.match(nil)
+    "#;
+        assert_script(program);
+    }
 }
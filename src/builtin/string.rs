@@ -12,10 +12,40 @@ pub enum RString {
 use std::cmp::Ordering;
 use std::str::FromStr;
 impl RString {
+    /// Chars in U+10FF00..=U+10FFFF stand for raw bytes in string literals
+    /// which are not valid as UTF-8 (e.g. "\xE1").
+    pub const RAW_BYTE_BASE: u32 = 0x10_ff00;
+
     pub fn new_string(string: String) -> Self {
         RString::Str(string)
     }
 
+    /// Encode a raw byte as a char which can be held in a String.
+    pub fn raw_byte_to_char(byte: u8) -> char {
+        std::char::from_u32(RString::RAW_BYTE_BASE + byte as u32).unwrap()
+    }
+
+    /// Build RString from a string literal, restoring raw bytes encoded by `raw_byte_to_char`.
+    pub fn from_literal(string: &str) -> Self {
+        if string.chars().all(|c| (c as u32) < RString::RAW_BYTE_BASE) {
+            return RString::Str(string.to_string());
+        }
+        let mut bytes = vec![];
+        for c in string.chars() {
+            let code = c as u32;
+            if code >= RString::RAW_BYTE_BASE {
+                bytes.push((code - RString::RAW_BYTE_BASE) as u8);
+            } else {
+                let mut buf = [0; 4];
+                bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            }
+        }
+        match String::from_utf8(bytes) {
+            Ok(s) => RString::Str(s),
+            Err(err) => RString::Bytes(err.into_bytes()),
+        }
+    }
+
     pub fn new_bytes(bytes: Vec<u8>) -> Self {
         RString::Bytes(bytes)
     }
@@ -142,12 +172,15 @@ pub fn init_string(globals: &mut Globals) -> Value {
     globals.add_builtin_instance_method(class, "gsub!", string_gsub_);
     globals.add_builtin_instance_method(class, "scan", string_scan);
     globals.add_builtin_instance_method(class, "=~", string_rmatch);
+    globals.add_builtin_instance_method(class, "match", string_match);
     globals.add_builtin_instance_method(class, "tr", string_tr);
     globals.add_builtin_instance_method(class, "size", string_size);
     globals.add_builtin_instance_method(class, "bytes", string_bytes);
     globals.add_builtin_instance_method(class, "chars", string_chars);
     globals.add_builtin_instance_method(class, "sum", string_sum);
     globals.add_builtin_instance_method(class, "upcase", string_upcase);
+    globals.add_builtin_instance_method(class, "succ", string_succ);
+    globals.add_builtin_instance_method(class, "next", string_succ);
     globals.add_builtin_instance_method(class, "chomp", string_chomp);
     globals.add_builtin_instance_method(class, "to_i", string_toi);
     globals.add_builtin_instance_method(class, "<", lt);
@@ -415,9 +448,23 @@ fn string_to_sym(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
 }
 
 fn string_split(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
-    vm.check_args_range(args.len(), 1, 2)?;
+    vm.check_args_range(args.len(), 0, 2)?;
     expect_string!(string, vm, self_val);
-    expect_string!(sep, vm, args[0]);
+    // Without a separator, $; is used. If $; is nil, split on whitespace.
+    let sep_val = if args.len() == 0 {
+        let id = vm.globals.get_ident_id("$;");
+        vm.get_global_var(id)
+    } else {
+        args[0]
+    };
+    if sep_val.is_nil() {
+        let vec = string
+            .split_whitespace()
+            .map(|x| Value::string(&vm.globals, x.to_string()))
+            .collect();
+        return Ok(Value::array_from(&vm.globals, vec));
+    }
+    expect_string!(sep, vm, sep_val);
     let lim = if args.len() > 1 {
         args[1].expect_integer(vm, "Second arg must be Integer.")?
     } else {
@@ -518,7 +565,7 @@ fn string_scan(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
         return Err(vm.error_argument("1st arg must be RegExp or String."));
     };
     match args.block {
-        Some(block) if block == MethodRef::from(0) => {
+        Some(block) if block.is_none() => {
             let mut v = vec![];
             for arg in vec {
                 let block_args = Args::new1(arg);
@@ -563,6 +610,20 @@ fn string_rmatch(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
     };
 }
 
+fn string_match(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    expect_string!(given, vm, self_val);
+    let re = if let Some(re) = args[0].as_regexp() {
+        re
+    } else if let Some(s) = args[0].as_string() {
+        vm.create_regexp_from_string(s)?.as_regexp().unwrap()
+    } else {
+        return Err(vm.error_argument("1st arg must be RegExp or String."));
+    };
+    Regexp::find_one(vm, &re.regexp, given)?;
+    Ok(vm.get_last_match())
+}
+
 fn string_tr(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
     vm.check_args_num(args.len(), 2)?;
     expect_string!(rec, vm, self_val);
@@ -574,8 +635,10 @@ fn string_tr(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
 
 fn string_size(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
     vm.check_args_num(args.len(), 0)?;
-    expect_string!(rec, vm, self_val);
-    Ok(Value::fixnum(rec.chars().count() as i64))
+    expect_bytes!(rec, vm, self_val);
+    // An invalid byte sequence is counted as one char.
+    let len = String::from_utf8_lossy(rec).chars().count();
+    Ok(Value::fixnum(len as i64))
 }
 
 fn string_bytes(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
@@ -615,6 +678,49 @@ fn string_upcase(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
     Ok(Value::string(&vm.globals, res))
 }
 
+/// Returns the successor of `s` in the same way as String#succ.
+/// The rightmost alphanumeric is incremented and a carry moves to the next alphanumeric to the left.
+pub fn str_succ(s: &str) -> String {
+    let mut chars: Vec<char> = s.chars().collect();
+    if chars.is_empty() {
+        return String::new();
+    }
+    if !chars.iter().any(|c| c.is_ascii_alphanumeric()) {
+        let last = chars.len() - 1;
+        chars[last] = std::char::from_u32(chars[last] as u32 + 1).unwrap_or(chars[last]);
+        return chars.into_iter().collect();
+    }
+    let mut end = chars.len();
+    loop {
+        let pos = match chars[..end].iter().rposition(|c| c.is_ascii_alphanumeric()) {
+            Some(pos) => pos,
+            None => unreachable!(),
+        };
+        let (next, carry) = match chars[pos] {
+            'z' => ('a', 'a'),
+            'Z' => ('A', 'A'),
+            '9' => ('0', '1'),
+            c => {
+                chars[pos] = (c as u8 + 1) as char;
+                return chars.into_iter().collect();
+            }
+        };
+        chars[pos] = next;
+        if !chars[..pos].iter().any(|c| c.is_ascii_alphanumeric()) {
+            chars.insert(pos, carry);
+            return chars.into_iter().collect();
+        }
+        end = pos;
+    }
+}
+
+fn string_succ(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let self_ = vm.expect_string(&self_val, "Receiver")?;
+    let res = str_succ(self_);
+    Ok(Value::string(&vm.globals, res))
+}
+
 fn string_chomp(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
     vm.check_args_num(args.len(), 0)?;
     let self_ = vm.expect_string(&self_val, "Receiver")?;
@@ -800,6 +906,22 @@ mod test {
         assert_script(program);
     }
 
+    #[test]
+    fn string_succ() {
+        let program = r#"
+        assert "b", "a".succ
+        assert "aa", "z".succ
+        assert "AAa", "Zz".succ
+        assert "b0", "a9".succ
+        assert "2.0", "1.9".succ
+        assert "az", "ay".next
+        assert "1000", "999".succ
+        assert "<<koalb>>", "<<koala>>".succ
+        assert "", "".succ
+        "#;
+        assert_script(program);
+    }
+
     #[test]
     fn string_chomp() {
         let program = r#"
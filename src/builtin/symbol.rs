@@ -0,0 +1,272 @@
+use crate::builtin::string::str_succ;
+use crate::*;
+use std::path::PathBuf;
+
+pub fn init_symbol(globals: &mut Globals) -> Value {
+    let id = globals.get_ident_id("Symbol");
+    let class = ClassRef::from(id, globals.builtins.object);
+    let obj = Value::class(globals, class);
+    globals.add_builtin_instance_method(class, "to_s", to_s);
+    globals.add_builtin_instance_method(class, "id2name", to_s);
+    globals.add_builtin_instance_method(class, "name", to_s);
+    globals.add_builtin_instance_method(class, "to_sym", to_sym);
+    globals.add_builtin_instance_method(class, "inspect", inspect);
+    globals.add_builtin_instance_method(class, "to_proc", to_proc);
+    globals.add_builtin_instance_method(class, "<=>", cmp);
+    globals.add_builtin_instance_method(class, "length", length);
+    globals.add_builtin_instance_method(class, "size", length);
+    globals.add_builtin_instance_method(class, "empty?", empty);
+    globals.add_builtin_instance_method(class, "upcase", upcase);
+    globals.add_builtin_instance_method(class, "downcase", downcase);
+    globals.add_builtin_instance_method(class, "capitalize", capitalize);
+    globals.add_builtin_instance_method(class, "swapcase", swapcase);
+    globals.add_builtin_instance_method(class, "succ", succ);
+    globals.add_builtin_instance_method(class, "next", succ);
+    globals.add_builtin_instance_method(class, "start_with?", start_with);
+    globals.add_builtin_instance_method(class, "end_with?", end_with);
+    globals.add_builtin_instance_method(class, "[]", index);
+    globals.add_builtin_class_method(obj, "all_symbols", all_symbols);
+    obj
+}
+
+fn symbol_name(vm: &VM, self_val: Value) -> String {
+    let id = self_val.as_packed_symbol();
+    vm.globals.get_ident_name(id).to_string()
+}
+
+fn new_symbol(vm: &mut VM, name: String) -> Value {
+    let id = vm.globals.get_ident_id(name);
+    Value::symbol(id)
+}
+
+// Class methods
+
+fn all_symbols(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let symbols = vm
+        .globals
+        .ident_table
+        .get_all_ids()
+        .into_iter()
+        .map(Value::symbol)
+        .collect();
+    Ok(Value::array_from(&vm.globals, symbols))
+}
+
+// Instance methods
+
+fn to_s(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let name = symbol_name(vm, self_val);
+    Ok(Value::string(&vm.globals, name))
+}
+
+fn to_sym(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    Ok(self_val)
+}
+
+fn inspect(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let s = vm.val_inspect(self_val);
+    Ok(Value::string(&vm.globals, s))
+}
+
+/// Symbol#to_proc
+/// Returns a lambda which sends the symbol to its first argument with the rest of arguments.
+/// The method is called natively in VM::eval_proc(), and the Proc is cached for each symbol.
+fn to_proc(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let id = self_val.as_packed_symbol();
+    if let Some(procobj) = vm.globals.symbol_procs.get(&id) {
+        return Ok(*procobj);
+    }
+    // The block is never evaluated, and only gives the parameters of the Proc.
+    let program = "Proc.new { |recv, *args| }";
+    let method = vm.parse_program(PathBuf::from("(to_proc)"), program)?;
+    let main_object = vm.globals.main_object;
+    let procobj = vm.eval_send(method, main_object, &Args::new0())?;
+    let mut pref = procobj.as_proc().unwrap();
+    pref.is_lambda = true;
+    pref.symbol = Some(id);
+    vm.globals.symbol_procs.insert(id, procobj);
+    Ok(procobj)
+}
+
+fn cmp(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    if !args[0].is_packed_symbol() {
+        return Ok(Value::nil());
+    }
+    let lhs = symbol_name(vm, self_val);
+    let rhs = symbol_name(vm, args[0]);
+    let ord = match lhs.cmp(&rhs) {
+        std::cmp::Ordering::Less => -1,
+        std::cmp::Ordering::Equal => 0,
+        std::cmp::Ordering::Greater => 1,
+    };
+    Ok(Value::fixnum(ord))
+}
+
+fn length(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let len = symbol_name(vm, self_val).chars().count();
+    Ok(Value::fixnum(len as i64))
+}
+
+fn empty(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    Ok(Value::bool(symbol_name(vm, self_val).is_empty()))
+}
+
+fn upcase(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let name = symbol_name(vm, self_val).to_uppercase();
+    Ok(new_symbol(vm, name))
+}
+
+fn downcase(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let name = symbol_name(vm, self_val).to_lowercase();
+    Ok(new_symbol(vm, name))
+}
+
+fn capitalize(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let name = symbol_name(vm, self_val);
+    let mut chars = name.chars();
+    let name = match chars.next() {
+        Some(c) => c
+            .to_uppercase()
+            .chain(chars.as_str().to_lowercase().chars())
+            .collect(),
+        None => name,
+    };
+    Ok(new_symbol(vm, name))
+}
+
+fn swapcase(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let name = symbol_name(vm, self_val)
+        .chars()
+        .map(|c| {
+            if c.is_uppercase() {
+                c.to_lowercase().collect::<String>()
+            } else {
+                c.to_uppercase().collect::<String>()
+            }
+        })
+        .collect();
+    Ok(new_symbol(vm, name))
+}
+
+fn succ(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let name = str_succ(&symbol_name(vm, self_val));
+    Ok(new_symbol(vm, name))
+}
+
+fn start_with(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    let name = symbol_name(vm, self_val);
+    for arg in args.iter() {
+        let prefix = vm.expect_string(arg, "Prefix")?;
+        if name.starts_with(prefix.as_str()) {
+            return Ok(Value::true_val());
+        }
+    }
+    Ok(Value::false_val())
+}
+
+fn end_with(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    let name = symbol_name(vm, self_val);
+    for arg in args.iter() {
+        let suffix = vm.expect_string(arg, "Suffix")?;
+        if name.ends_with(suffix.as_str()) {
+            return Ok(Value::true_val());
+        }
+    }
+    Ok(Value::false_val())
+}
+
+/// Symbol#[] is evaluated as String#[] of the symbol name.
+fn index(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    let name = symbol_name(vm, self_val);
+    let string = Value::string(&vm.globals, name);
+    let id = vm.globals.get_ident_id("[]");
+    let method = vm.get_method(string, id)?;
+    vm.eval_send(method, string, args)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::*;
+
+    #[test]
+    fn symbol() {
+        let program = r#"
+        assert(Symbol, :foo.class)
+        assert("foo", :foo.to_s)
+        assert("foo", :foo.id2name)
+        assert(:foo, :foo.to_sym)
+        assert(":foo", :foo.inspect)
+        assert(3, :foo.length)
+        assert(3, :foo.size)
+        assert(false, :foo.empty?)
+        assert(-1, :abc <=> :abd)
+        assert(0, :abc <=> :abc)
+        assert(1, :b <=> :a)
+        assert(nil, :a <=> "a")
+        assert(:FOO, :foo.upcase)
+        assert(:foo, :FOO.downcase)
+        assert(:Foo, :fOO.capitalize)
+        assert(:FoO, :fOo.swapcase)
+        assert(:fop, :foo.succ)
+        assert(true, :foobar.start_with?("foo"))
+        assert(true, :foobar.end_with?("baz", "bar"))
+        assert("o", :foo[1])
+        assert(true, Symbol.all_symbols.include?(:foo))
+        assert(true, :foo.is_a?(Symbol))
+        "#;
+        assert_script(program);
+    }
+
+    #[test]
+    fn symbol_to_proc() {
+        let program = r##"
+        assert(["A", "B"], ["a", "b"].map(&:upcase))
+        assert([1, 2], [[1, 9], [2, 8]].map(&:first))
+        assert([2, 3], [1, 2].map(&:succ))
+        pr = :upcase.to_proc
+        assert("FOO", pr.call("foo"))
+        assert(pr, :upcase.to_proc)
+        class Foo
+          def initialize(name)
+            @name = name
+          end
+          def name
+            @name
+          end
+        end
+        assert(["a", "b"], [Foo.new("a"), Foo.new("b")].map(&:name))
+        assert([1, 2, 3], :push.to_proc.call([1], 2, 3))
+        assert_error { :upcase.to_proc.call }
+        sym = ("#" + "{$injected = true}").to_sym
+        assert_error { sym.to_proc.call(1) }
+        assert(nil, $injected)
+        "##;
+        assert_script(program);
+    }
+
+    #[test]
+    fn symbol_reopen() {
+        let program = r#"
+        class Symbol
+          def twice
+            to_s * 2
+          end
+        end
+        assert("abab", :ab.twice)
+        "#;
+        assert_script(program);
+    }
+}
@@ -0,0 +1,752 @@
+use crate::*;
+use std::cmp::Ordering;
+use std::ffi::CStr;
+
+/// A point in time with nanosecond precision.
+/// Local times are converted with the TZ database of the machine.
+#[derive(Debug, Clone, Copy, PartialEq, Hash)]
+pub struct TimeInfo {
+    /// Seconds since the Unix epoch.
+    pub secs: i64,
+    pub nsec: u32,
+    pub utc: bool,
+}
+
+/// Broken-down time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DateTime {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub min: u32,
+    pub sec: u32,
+    /// Day of week. (Sunday is 0)
+    pub wday: u32,
+    /// Day of year. (1..366)
+    pub yday: u32,
+}
+
+const MONTH_NAMES: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+const DAY_NAMES: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+/// Returns the number of days since 1970-01-01 of the given date in the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let mp = if month > 2 { month - 3 } else { month + 9 } as i64;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// Inverse of `days_from_civil`.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = if z >= 0 { z } else { z - 146096 } / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400;
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
+
+/// Returns UTC offset in seconds and the zone name of the local time zone at `secs`.
+fn localtime(secs: i64) -> (i64, String) {
+    let t = secs as libc::time_t;
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&t, &mut tm) }.is_null() {
+        return (0, "UTC".to_string());
+    }
+    let zone = if tm.tm_zone.is_null() {
+        String::new()
+    } else {
+        unsafe { CStr::from_ptr(tm.tm_zone) }
+            .to_string_lossy()
+            .into_owned()
+    };
+    (tm.tm_gmtoff as i64, zone)
+}
+
+fn weeks_in_year(year: i64) -> u32 {
+    let p = |y: i64| (y + y.div_euclid(4) - y.div_euclid(100) + y.div_euclid(400)).rem_euclid(7);
+    if p(year) == 4 || p(year - 1) == 3 {
+        53
+    } else {
+        52
+    }
+}
+
+impl TimeInfo {
+    pub fn new(secs: i64, nsec: u32, utc: bool) -> Self {
+        TimeInfo { secs, nsec, utc }
+    }
+
+    pub fn now() -> Self {
+        let duration = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default();
+        TimeInfo::new(duration.as_secs() as i64, duration.subsec_nanos(), false)
+    }
+
+    pub fn from_utc(dt: &DateTime, nsec: u32) -> Self {
+        let days = days_from_civil(dt.year, dt.month, dt.day);
+        let secs = days * 86400 + (dt.hour * 3600 + dt.min * 60 + dt.sec) as i64;
+        TimeInfo::new(secs, nsec, true)
+    }
+
+    pub fn from_local(dt: &DateTime, nsec: u32) -> Self {
+        let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+        tm.tm_year = (dt.year - 1900) as i32;
+        tm.tm_mon = dt.month as i32 - 1;
+        tm.tm_mday = dt.day as i32;
+        tm.tm_hour = dt.hour as i32;
+        tm.tm_min = dt.min as i32;
+        tm.tm_sec = dt.sec as i32;
+        tm.tm_isdst = -1;
+        let secs = unsafe { libc::mktime(&mut tm) } as i64;
+        TimeInfo::new(secs, nsec, false)
+    }
+
+    /// UTC offset in seconds.
+    pub fn utc_offset(&self) -> i64 {
+        if self.utc {
+            0
+        } else {
+            localtime(self.secs).0
+        }
+    }
+
+    pub fn zone(&self) -> String {
+        if self.utc {
+            "UTC".to_string()
+        } else {
+            localtime(self.secs).1
+        }
+    }
+
+    pub fn datetime(&self) -> DateTime {
+        let secs = self.secs + self.utc_offset();
+        let days = secs.div_euclid(86400);
+        let rem = secs.rem_euclid(86400) as u32;
+        let (year, month, day) = civil_from_days(days);
+        DateTime {
+            year,
+            month,
+            day,
+            hour: rem / 3600,
+            min: rem % 3600 / 60,
+            sec: rem % 60,
+            wday: (days + 4).rem_euclid(7) as u32,
+            yday: (days - days_from_civil(year, 1, 1) + 1) as u32,
+        }
+    }
+
+    pub fn to_f(&self) -> f64 {
+        self.secs as f64 + self.nsec as f64 / 1_000_000_000.0
+    }
+
+    /// Returns a new TimeInfo which is `nanos` nanoseconds after `self`.
+    pub fn add_nanos(&self, nanos: i128) -> Self {
+        let total = self.secs as i128 * 1_000_000_000 + self.nsec as i128 + nanos;
+        TimeInfo::new(
+            total.div_euclid(1_000_000_000) as i64,
+            total.rem_euclid(1_000_000_000) as u32,
+            self.utc,
+        )
+    }
+
+    pub fn cmp(&self, other: &TimeInfo) -> Ordering {
+        (self.secs, self.nsec).cmp(&(other.secs, other.nsec))
+    }
+
+    pub fn to_s(&self) -> String {
+        if self.utc {
+            self.strftime("%Y-%m-%d %H:%M:%S UTC")
+        } else {
+            self.strftime("%Y-%m-%d %H:%M:%S %z")
+        }
+    }
+
+    pub fn inspect(&self) -> String {
+        if self.nsec == 0 {
+            return self.to_s();
+        }
+        let frac = format!("{:09}", self.nsec);
+        let frac = frac.trim_end_matches('0');
+        if self.utc {
+            self.strftime(&format!("%Y-%m-%d %H:%M:%S.{} UTC", frac))
+        } else {
+            self.strftime(&format!("%Y-%m-%d %H:%M:%S.{} %z", frac))
+        }
+    }
+
+    /// Format `self` according to the directives in `format`.
+    /// Supports the directives and the flags (-_0^#), width and colons of CRuby's Time#strftime.
+    pub fn strftime(&self, format: &str) -> String {
+        let dt = self.datetime();
+        let mut res = String::new();
+        let chars: Vec<char> = format.chars().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            if chars[i] != '%' {
+                res.push(chars[i]);
+                i += 1;
+                continue;
+            }
+            let start = i;
+            i += 1;
+            let mut flags = vec![];
+            while i < chars.len() && "-_0^#".contains(chars[i]) {
+                flags.push(chars[i]);
+                i += 1;
+            }
+            let mut width = None;
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                width = Some(width.unwrap_or(0) * 10 + chars[i].to_digit(10).unwrap() as usize);
+                i += 1;
+            }
+            let mut colons = 0;
+            while i < chars.len() && chars[i] == ':' {
+                colons += 1;
+                i += 1;
+            }
+            if i == chars.len() {
+                res.extend(&chars[start..]);
+                break;
+            }
+            let conv = chars[i];
+            i += 1;
+            match self.format_directive(&dt, conv, &flags, width, colons) {
+                Some(s) => res += &s,
+                None => res.extend(&chars[start..i]),
+            }
+        }
+        res
+    }
+
+    fn format_directive(
+        &self,
+        dt: &DateTime,
+        conv: char,
+        flags: &[char],
+        width: Option<usize>,
+        colons: usize,
+    ) -> Option<String> {
+        let num = |n: i64, default_width: usize, default_pad: char| {
+            let pad = if flags.contains(&'-') {
+                None
+            } else if flags.contains(&'_') {
+                Some(' ')
+            } else if flags.contains(&'0') {
+                Some('0')
+            } else {
+                Some(default_pad)
+            };
+            let width = width.unwrap_or(default_width);
+            match pad {
+                None => n.to_string(),
+                Some('0') if n < 0 => format!("-{:0>1$}", -n, width.saturating_sub(1)),
+                Some('0') => format!("{:0>1$}", n, width),
+                Some(_) => format!("{:>1$}", n, width),
+            }
+        };
+        let text = |s: &str, swapcase_to_lower: bool| {
+            let s = if flags.contains(&'^') {
+                s.to_uppercase()
+            } else if flags.contains(&'#') {
+                if swapcase_to_lower {
+                    s.to_lowercase()
+                } else {
+                    s.to_uppercase()
+                }
+            } else {
+                s.to_string()
+            };
+            match width {
+                Some(width) if !flags.contains(&'-') => {
+                    let pad = if flags.contains(&'0') { '0' } else { ' ' };
+                    let len = s.chars().count();
+                    let mut padded: String = std::iter::repeat(pad)
+                        .take(width.saturating_sub(len))
+                        .collect();
+                    padded += &s;
+                    padded
+                }
+                _ => s,
+            }
+        };
+        let frac = |default_width: usize| {
+            let digits = format!("{:09}", self.nsec);
+            let width = width.unwrap_or(default_width);
+            if width <= 9 {
+                digits[..width].to_string()
+            } else {
+                format!("{:0<1$}", digits, width)
+            }
+        };
+        let hour12 = match dt.hour % 12 {
+            0 => 12,
+            h => h,
+        } as i64;
+        let iso_week = || {
+            let wday = if dt.wday == 0 { 7 } else { dt.wday } as i64;
+            let week = (dt.yday as i64 - wday + 10) / 7;
+            if week < 1 {
+                (dt.year - 1, weeks_in_year(dt.year - 1) as i64)
+            } else if week > weeks_in_year(dt.year) as i64 {
+                (dt.year + 1, 1)
+            } else {
+                (dt.year, week)
+            }
+        };
+        let s = match conv {
+            'Y' => num(dt.year, 4, '0'),
+            'C' => num(dt.year.div_euclid(100), 2, '0'),
+            'y' => num(dt.year.rem_euclid(100), 2, '0'),
+            'm' => num(dt.month as i64, 2, '0'),
+            'B' => text(MONTH_NAMES[dt.month as usize - 1], false),
+            'b' | 'h' => text(&MONTH_NAMES[dt.month as usize - 1][..3], false),
+            'd' => num(dt.day as i64, 2, '0'),
+            'e' => num(dt.day as i64, 2, ' '),
+            'j' => num(dt.yday as i64, 3, '0'),
+            'H' => num(dt.hour as i64, 2, '0'),
+            'k' => num(dt.hour as i64, 2, ' '),
+            'I' => num(hour12, 2, '0'),
+            'l' => num(hour12, 2, ' '),
+            'P' => text(if dt.hour < 12 { "am" } else { "pm" }, false),
+            'p' => text(if dt.hour < 12 { "AM" } else { "PM" }, true),
+            'M' => num(dt.min as i64, 2, '0'),
+            'S' => num(dt.sec as i64, 2, '0'),
+            'L' => frac(3),
+            'N' => frac(9),
+            'z' => {
+                let offset = self.utc_offset();
+                let sign = if offset < 0 { '-' } else { '+' };
+                let offset = offset.abs();
+                let (h, m, s) = (offset / 3600, offset % 3600 / 60, offset % 60);
+                let body = match colons {
+                    0 => format!("{:02}{:02}", h, m),
+                    1 => format!("{:02}:{:02}", h, m),
+                    _ => format!("{:02}:{:02}:{:02}", h, m, s),
+                };
+                let s = format!("{}{}", sign, body);
+                match width {
+                    Some(width) if width > s.len() => {
+                        format!("{}{:0>2$}", sign, body, width - 1)
+                    }
+                    _ => s,
+                }
+            }
+            'Z' => text(&self.zone(), true),
+            'A' => text(DAY_NAMES[dt.wday as usize], false),
+            'a' => text(&DAY_NAMES[dt.wday as usize][..3], false),
+            'u' => num(if dt.wday == 0 { 7 } else { dt.wday } as i64, 1, '0'),
+            'w' => num(dt.wday as i64, 1, '0'),
+            'U' => num(((dt.yday + 6 - dt.wday) / 7) as i64, 2, '0'),
+            'W' => num(((dt.yday + 6 - (dt.wday + 6) % 7) / 7) as i64, 2, '0'),
+            'G' => num(iso_week().0, 4, '0'),
+            'g' => num(iso_week().0.rem_euclid(100), 2, '0'),
+            'V' => num(iso_week().1, 2, '0'),
+            's' => num(self.secs, 1, '0'),
+            'n' => "\n".to_string(),
+            't' => "\t".to_string(),
+            '%' => "%".to_string(),
+            'c' => text(&self.strftime("%a %b %e %H:%M:%S %Y"), false),
+            'D' | 'x' => text(&self.strftime("%m/%d/%y"), false),
+            'F' => text(&self.strftime("%Y-%m-%d"), false),
+            'T' | 'X' => text(&self.strftime("%H:%M:%S"), false),
+            'R' => text(&self.strftime("%H:%M"), false),
+            'r' => text(&self.strftime("%I:%M:%S %p"), false),
+            'v' => text(&self.strftime("%e-%^b-%Y"), false),
+            '+' => text(&self.strftime("%a %b %e %H:%M:%S %Z %Y"), false),
+            _ => return None,
+        };
+        Some(s)
+    }
+}
+
+pub fn init_time(globals: &mut Globals) -> Value {
+    let id = globals.get_ident_id("Time");
+    let class = ClassRef::from(id, globals.builtins.object);
+    let obj = Value::class(globals, class);
+    globals.add_builtin_instance_method(class, "+", add);
+    globals.add_builtin_instance_method(class, "-", sub);
+    globals.add_builtin_instance_method(class, "<=>", cmp);
+    globals.add_builtin_instance_method(class, "year", year);
+    globals.add_builtin_instance_method(class, "month", month);
+    globals.add_builtin_instance_method(class, "mon", month);
+    globals.add_builtin_instance_method(class, "day", day);
+    globals.add_builtin_instance_method(class, "mday", day);
+    globals.add_builtin_instance_method(class, "hour", hour);
+    globals.add_builtin_instance_method(class, "min", min);
+    globals.add_builtin_instance_method(class, "sec", sec);
+    globals.add_builtin_instance_method(class, "usec", usec);
+    globals.add_builtin_instance_method(class, "nsec", nsec);
+    globals.add_builtin_instance_method(class, "wday", wday);
+    globals.add_builtin_instance_method(class, "yday", yday);
+    globals.add_builtin_instance_method(class, "zone", zone);
+    globals.add_builtin_instance_method(class, "utc_offset", utc_offset);
+    globals.add_builtin_instance_method(class, "gmt_offset", utc_offset);
+    globals.add_builtin_instance_method(class, "utc?", is_utc);
+    globals.add_builtin_instance_method(class, "utc", getutc);
+    globals.add_builtin_instance_method(class, "getutc", getutc);
+    globals.add_builtin_instance_method(class, "localtime", getlocal);
+    globals.add_builtin_instance_method(class, "getlocal", getlocal);
+    globals.add_builtin_instance_method(class, "to_i", to_i);
+    globals.add_builtin_instance_method(class, "to_f", to_f);
+    globals.add_builtin_instance_method(class, "to_s", to_s);
+    globals.add_builtin_instance_method(class, "inspect", inspect);
+    globals.add_builtin_instance_method(class, "strftime", strftime);
+    globals.add_builtin_instance_method(class, "iso8601", iso8601);
+    globals.add_builtin_instance_method(class, "xmlschema", iso8601);
+    globals.add_builtin_class_method(obj, "now", now);
+    globals.add_builtin_class_method(obj, "new", time_new);
+    globals.add_builtin_class_method(obj, "at", at);
+    globals.add_builtin_class_method(obj, "mktime", local);
+    globals.add_builtin_class_method(obj, "local", local);
+    globals.add_builtin_class_method(obj, "utc", utc);
+    globals.add_builtin_class_method(obj, "gm", utc);
+    obj
+}
+
+// Utils
+
+fn expect_time(vm: &VM, val: Value) -> Result<TimeInfo, RubyError> {
+    match val.as_time() {
+        Some(time) => Ok(time),
+        None => Err(vm.error_type("Receiver must be Time.")),
+    }
+}
+
+fn expect_num(vm: &mut VM, val: Value, msg: &str) -> Result<f64, RubyError> {
+    match val.as_fixnum() {
+        Some(i) => Ok(i as f64),
+        None => vm.expect_flonum(val, msg),
+    }
+}
+
+/// Convert a number of seconds to nanoseconds.
+fn to_nanos(vm: &mut VM, val: Value) -> Result<i128, RubyError> {
+    match val.as_fixnum() {
+        Some(i) => Ok(i as i128 * 1_000_000_000),
+        None => {
+            let f = vm.expect_flonum(val, "Seconds")?;
+            Ok((f * 1_000_000_000.0).round() as i128)
+        }
+    }
+}
+
+fn month_from_value(vm: &mut VM, val: Value) -> Result<u32, RubyError> {
+    if let Some(s) = val.as_string() {
+        let lower = s.to_lowercase();
+        return match MONTH_NAMES
+            .iter()
+            .position(|name| name[..3].to_lowercase() == lower)
+        {
+            Some(i) => Ok(i as u32 + 1),
+            None => Err(vm.error_argument(format!("Mon out of range. {}", s))),
+        };
+    };
+    let month = vm.expect_integer(val, "Month")?;
+    if month < 1 || month > 12 {
+        return Err(vm.error_argument(format!("Mon out of range. {}", month)));
+    }
+    Ok(month as u32)
+}
+
+/// Parse (year, month = 1, day = 1, hour = 0, min = 0, sec = 0, usec = 0).
+fn datetime_from_args(vm: &mut VM, args: &Args) -> Result<(DateTime, u32), RubyError> {
+    vm.check_args_range(args.len(), 1, 7)?;
+    let year = vm.expect_integer(args[0], "Year")?;
+    let month = if args.len() > 1 {
+        month_from_value(vm, args[1])?
+    } else {
+        1
+    };
+    let mut fields = [1, 0, 0, 0];
+    let ranges = [(1, 31), (0, 24), (0, 59), (0, 60)];
+    for i in 0..4 {
+        if args.len() > i + 2 && !args[i + 2].is_nil() {
+            let val = vm.expect_integer(args[i + 2], "Argument")?;
+            if val < ranges[i].0 || val > ranges[i].1 {
+                return Err(vm.error_argument("Argument out of range."));
+            }
+            fields[i] = val as u32;
+        }
+    }
+    let nsec = if args.len() == 7 {
+        (expect_num(vm, args[6], "Usec")? * 1000.0) as u32
+    } else {
+        0
+    };
+    let dt = DateTime {
+        year,
+        month,
+        day: fields[0],
+        hour: fields[1],
+        min: fields[2],
+        sec: fields[3],
+        wday: 0,
+        yday: 0,
+    };
+    Ok((dt, nsec))
+}
+
+// Class methods
+
+fn now(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    Ok(Value::time(&vm.globals, TimeInfo::now()))
+}
+
+/// Time.new -> now
+/// Time.new(year, month = 1, day = 1, hour = 0, min = 0, sec = 0)
+fn time_new(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    if args.len() == 0 {
+        return now(vm, self_val, args);
+    }
+    local(vm, self_val, args)
+}
+
+/// Time.at(time)
+/// Time.at(seconds, usec = 0)
+fn at(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    vm.check_args_range(args.len(), 1, 2)?;
+    if let Some(time) = args[0].as_time() {
+        return Ok(Value::time(&vm.globals, time));
+    }
+    let mut nanos = to_nanos(vm, args[0])?;
+    if args.len() == 2 {
+        nanos += (expect_num(vm, args[1], "Usec")? * 1000.0) as i128;
+    }
+    let time = TimeInfo::new(0, 0, false).add_nanos(nanos);
+    Ok(Value::time(&vm.globals, time))
+}
+
+fn local(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    let (dt, nsec) = datetime_from_args(vm, args)?;
+    Ok(Value::time(&vm.globals, TimeInfo::from_local(&dt, nsec)))
+}
+
+fn utc(vm: &mut VM, _: Value, args: &Args) -> VMResult {
+    let (dt, nsec) = datetime_from_args(vm, args)?;
+    Ok(Value::time(&vm.globals, TimeInfo::from_utc(&dt, nsec)))
+}
+
+// Instance methods
+
+fn add(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let time = expect_time(vm, self_val)?;
+    if args[0].as_time().is_some() {
+        return Err(vm.error_type("Time + Time ?"));
+    }
+    let nanos = to_nanos(vm, args[0])?;
+    Ok(Value::time(&vm.globals, time.add_nanos(nanos)))
+}
+
+/// Time - Time -> Float
+/// Time - Numeric -> Time
+fn sub(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let time = expect_time(vm, self_val)?;
+    if let Some(other) = args[0].as_time() {
+        let diff = (time.secs - other.secs) as f64
+            + (time.nsec as f64 - other.nsec as f64) / 1_000_000_000.0;
+        return Ok(Value::flonum(diff));
+    }
+    let nanos = to_nanos(vm, args[0])?;
+    Ok(Value::time(&vm.globals, time.add_nanos(-nanos)))
+}
+
+fn cmp(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let time = expect_time(vm, self_val)?;
+    let other = match args[0].as_time() {
+        Some(other) => other,
+        None => return Ok(Value::nil()),
+    };
+    let res = match time.cmp(&other) {
+        Ordering::Less => -1,
+        Ordering::Equal => 0,
+        Ordering::Greater => 1,
+    };
+    Ok(Value::fixnum(res))
+}
+
+macro_rules! define_accessor {
+    ($name:ident, $field:ident) => {
+        fn $name(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+            vm.check_args_num(args.len(), 0)?;
+            let time = expect_time(vm, self_val)?;
+            Ok(Value::fixnum(time.datetime().$field as i64))
+        }
+    };
+}
+
+define_accessor!(year, year);
+define_accessor!(month, month);
+define_accessor!(day, day);
+define_accessor!(hour, hour);
+define_accessor!(min, min);
+define_accessor!(sec, sec);
+define_accessor!(wday, wday);
+define_accessor!(yday, yday);
+
+fn usec(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let time = expect_time(vm, self_val)?;
+    Ok(Value::fixnum(time.nsec as i64 / 1000))
+}
+
+fn nsec(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let time = expect_time(vm, self_val)?;
+    Ok(Value::fixnum(time.nsec as i64))
+}
+
+fn zone(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let time = expect_time(vm, self_val)?;
+    Ok(Value::string(&vm.globals, time.zone()))
+}
+
+fn utc_offset(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let time = expect_time(vm, self_val)?;
+    Ok(Value::fixnum(time.utc_offset()))
+}
+
+fn is_utc(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let time = expect_time(vm, self_val)?;
+    Ok(Value::bool(time.utc))
+}
+
+fn getutc(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let time = expect_time(vm, self_val)?;
+    Ok(Value::time(
+        &vm.globals,
+        TimeInfo::new(time.secs, time.nsec, true),
+    ))
+}
+
+fn getlocal(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let time = expect_time(vm, self_val)?;
+    Ok(Value::time(
+        &vm.globals,
+        TimeInfo::new(time.secs, time.nsec, false),
+    ))
+}
+
+fn to_i(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let time = expect_time(vm, self_val)?;
+    Ok(Value::fixnum(time.secs))
+}
+
+fn to_f(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 0)?;
+    let time = expect_time(vm, self_val)?;
+    Ok(Value::flonum(time.to_f()))
+}
+
+fn to_s(vm: &mut VM, self_val: Value, _: &Args) -> VMResult {
+    let time = expect_time(vm, self_val)?;
+    Ok(Value::string(&vm.globals, time.to_s()))
+}
+
+fn inspect(vm: &mut VM, self_val: Value, _: &Args) -> VMResult {
+    let time = expect_time(vm, self_val)?;
+    Ok(Value::string(&vm.globals, time.inspect()))
+}
+
+fn strftime(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_num(args.len(), 1)?;
+    let time = expect_time(vm, self_val)?;
+    let format = vm.expect_string(&args[0], "Format")?;
+    Ok(Value::string(&vm.globals, time.strftime(format)))
+}
+
+/// Time#iso8601(fraction_digits = 0)
+fn iso8601(vm: &mut VM, self_val: Value, args: &Args) -> VMResult {
+    vm.check_args_range(args.len(), 0, 1)?;
+    let time = expect_time(vm, self_val)?;
+    let digits = if args.len() == 1 {
+        vm.expect_integer(args[0], "Fraction digits")?
+    } else {
+        0
+    };
+    let mut format = "%Y-%m-%dT%H:%M:%S".to_string();
+    if digits > 0 {
+        format += &format!(".%{}N", digits);
+    }
+    format += if time.utc { "Z" } else { "%:z" };
+    Ok(Value::string(&vm.globals, time.strftime(&format)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test::*;
+
+    #[test]
+    fn time() {
+        let program = r#"
+            t = Time.utc(2020, 2, 29, 13, 4, 5)
+            assert(2020, t.year)
+            assert(2, t.month)
+            assert(29, t.day)
+            assert(13, t.hour)
+            assert(4, t.min)
+            assert(5, t.sec)
+            assert(6, t.wday)
+            assert(60, t.yday)
+            assert(1582981445, t.to_i)
+            assert("2020-02-29 13:04:05 UTC", t.to_s)
+            assert("2020-02-29T13:04:05Z", t.iso8601)
+            assert("Sat Feb 29 01:04:05 PM 2020", t.strftime("%a %b %e %I:%M:%S %p %Y"))
+            assert("Saturday, FEBRUARY 29 (060) 2020-W09-6 pm", t.strftime("%A, %^B %-d (%j) %G-W%V-%u %P"))
+            assert("20-02-29 13:04 +0000 +00:00 %", t.strftime("%y-%m-%d %R %z %:z %%"))
+            t2 = t + 1.5
+            assert(1.5, t2 - t)
+            assert(500000, t2.usec)
+            assert("2020-02-29T13:04:06.500Z", t2.iso8601(3))
+            assert(-1, t <=> t2)
+            assert(t, t2 - 1.5)
+            assert(true, Time.at(1582981445).getutc == t)
+            assert(t.to_i, Time.at(t.to_i).to_i)
+            assert(true, Time.now - Time.now <= 0)
+            assert(5, Time.mktime(2000, 1, 2, 3, 4, 5).sec)
+        "#;
+        assert_script(program);
+    }
+}
@@ -0,0 +1,560 @@
+//! High-level API for embedding ruruby in Rust applications.
+//!
+//! ```ignore
+//! let mut ruby = Ruby::new();
+//! let class = ruby.define_class("Greeter", None)?;
+//! ruby.define_method(class, "greet", |vm, _self_val, args| {
+//!     vm.check_args_num(args.len(), 1)?;
+//!     let name: String = vm.convert(args[0])?;
+//!     Ok(vm.to_value(format!("Hello, {}!", name)))
+//! })?;
+//! let greeting: String = ruby.eval_as("Greeter.new.greet(\"world\")")?;
+//! ```
+use crate::*;
+use std::convert::{Infallible, TryFrom};
+use std::path::PathBuf;
+use std::rc::Rc;
+
+/// A Ruby interpreter.
+/// Every `Ruby` owns its own VM and globals, so interpreters do not share
+/// classes, constants or global variables with each other.
+/// Values must not be passed to an interpreter other than the one which created them.
+/// Interpreters keep no thread-local state, so several of them can be used
+/// in the same thread in any order.
+#[derive(Debug)]
+pub struct Ruby {
+    vm: VM,
+}
+
+impl Default for Ruby {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Ruby {
+    pub fn new() -> Self {
+        Ruby { vm: VM::new() }
+    }
+
+    /// The underlying VM, for the APIs not covered by `Ruby`.
+    pub fn vm(&mut self) -> &mut VM {
+        &mut self.vm
+    }
+
+    /// Evaluate `program` at the top level.
+    pub fn eval(&mut self, program: &str) -> VMResult {
+        self.vm.run(PathBuf::from("(eval)"), program, None)
+    }
+
+    /// Evaluate `program` at the top level, and convert the result into `T`.
+    pub fn eval_as<T>(&mut self, program: &str) -> Result<T, RubyError>
+    where
+        T: TryFrom<Value>,
+        T::Error: Into<ConversionError>,
+    {
+        let val = self.eval(program)?;
+        self.vm.convert(val)
+    }
+
+    /// Convert `val` into a Ruby object.
+    pub fn to_value(&self, val: impl IntoValue) -> Value {
+        self.vm.to_value(val)
+    }
+
+    /// Convert the Ruby object `val` into `T`.
+    pub fn convert<T>(&self, val: Value) -> Result<T, RubyError>
+    where
+        T: TryFrom<Value>,
+        T::Error: Into<ConversionError>,
+    {
+        self.vm.convert(val)
+    }
+
+    /// Call the method `method` of `receiver`, and convert the result into `T`.
+    pub fn call<T>(&mut self, receiver: Value, method: &str, args: &[Value]) -> Result<T, RubyError>
+    where
+        T: TryFrom<Value>,
+        T::Error: Into<ConversionError>,
+    {
+        let val = self.vm.call_method(receiver, method, args)?;
+        self.vm.convert(val)
+    }
+
+    /// Call the top-level method `name`, and convert the result into `T`.
+    pub fn call_function<T>(&mut self, name: &str, args: &[Value]) -> Result<T, RubyError>
+    where
+        T: TryFrom<Value>,
+        T::Error: Into<ConversionError>,
+    {
+        let main = self.vm.globals.main_object;
+        self.call(main, name, args)
+    }
+
+    /// Get the top-level constant `name`.
+    pub fn get_const(&mut self, name: &str) -> Option<Value> {
+        let id = self.vm.globals.get_ident_id(name);
+        self.vm.globals.builtins.object.get_var(id)
+    }
+
+    /// Set the top-level constant `name`.
+    pub fn set_const(&mut self, name: &str, val: Value) {
+        let id = self.vm.globals.get_ident_id(name);
+        self.vm.globals.builtins.object.set_var(id, val);
+    }
+
+    pub fn get_global_var(&mut self, name: &str) -> Value {
+        let id = self.vm.globals.get_ident_id(name);
+        self.vm.get_global_var(id)
+    }
+
+    /// Assign `val` to the global variable `name` in the same way as Ruby code does.
+    pub fn set_global_var(&mut self, name: &str, val: Value) -> Result<(), RubyError> {
+        let id = self.vm.globals.get_ident_id(name);
+        self.vm.assign_global_var(id, val)
+    }
+
+    /// Define the top-level class `name`, or reopen it if it already exists.
+    /// If `superclass` is None, the class inherits Object.
+    pub fn define_class(&mut self, name: &str, superclass: Option<Value>) -> VMResult {
+        self.define_module_or_class(name, superclass, false)
+    }
+
+    /// Define the top-level module `name`, or reopen it if it already exists.
+    pub fn define_module(&mut self, name: &str) -> VMResult {
+        self.define_module_or_class(name, None, true)
+    }
+
+    fn define_module_or_class(
+        &mut self,
+        name: &str,
+        superclass: Option<Value>,
+        is_module: bool,
+    ) -> VMResult {
+        let vm = &mut self.vm;
+        let id = vm.globals.get_ident_id(name);
+        if let Some(val) = vm.globals.builtins.object.get_var(id) {
+            if val.is_module().is_some() != is_module {
+                let kind = if is_module { "module" } else { "class" };
+                return Err(vm.error_type(format!("{} is not {}.", name, kind)));
+            };
+            let classref = vm.expect_module(val)?;
+            if let Some(superclass) = superclass {
+                if classref.superclass.id() != superclass.id() {
+                    return Err(vm.error_type(format!("superclass mismatch for class {}.", name)));
+                }
+            }
+            return Ok(val);
+        }
+        let superclass = match superclass {
+            Some(superclass) => {
+                vm.expect_class(superclass, "Superclass")?;
+                superclass
+            }
+            None => vm.globals.builtins.object,
+        };
+        let classref = ClassRef::from(id, superclass);
+        let val = if is_module {
+            Value::module(&vm.globals, classref)
+        } else {
+            Value::class(&vm.globals, classref)
+        };
+        vm.globals.builtins.object.set_var(id, val);
+        Ok(val)
+    }
+
+    /// Define the instance method `name` of `class` implemented by the closure `func`.
+    pub fn define_method<F>(&mut self, class: Value, name: &str, func: F) -> Result<(), RubyError>
+    where
+        F: Fn(&mut VM, Value, &Args) -> VMResult + 'static,
+    {
+        self.vm.expect_module(class)?;
+        let (id, method) = self.vm.add_closure(name, Rc::new(func));
+        self.vm.add_instance_method(class, id, method);
+        Ok(())
+    }
+
+    /// Define the singleton method `name` of `obj` implemented by the closure `func`.
+    /// For a class or a module, this defines a class method.
+    pub fn define_singleton_method<F>(
+        &mut self,
+        obj: Value,
+        name: &str,
+        func: F,
+    ) -> Result<(), RubyError>
+    where
+        F: Fn(&mut VM, Value, &Args) -> VMResult + 'static,
+    {
+        let (id, method) = self.vm.add_closure(name, Rc::new(func));
+        self.vm.add_singleton_method(obj, id, method)
+    }
+
+    /// Define the top-level method `name` implemented by the closure `func`.
+    pub fn define_global_function<F>(&mut self, name: &str, func: F)
+    where
+        F: Fn(&mut VM, Value, &Args) -> VMResult + 'static,
+    {
+        let (id, method) = self.vm.add_closure(name, Rc::new(func));
+        self.vm.add_object_method(id, method);
+    }
+}
+
+impl VM {
+    fn add_closure(&mut self, name: &str, func: BuiltinClosure) -> (IdentId, MethodRef) {
+        let id = self.globals.get_ident_id(name);
+        let info = MethodInfo::BuiltinClosure {
+            name: name.to_string(),
+            func,
+        };
+        (id, self.globals.add_method(info))
+    }
+
+    /// Call the method `method` of `receiver` with `args`.
+    pub fn call_method(&mut self, receiver: Value, method: &str, args: &[Value]) -> VMResult {
+        let id = self.globals.get_ident_id(method);
+        let method = self.get_method(receiver, id)?;
+        let mut arg = Args::new(0);
+        for val in args {
+            arg.push(*val);
+        }
+        self.eval_send(method, receiver, &arg)
+    }
+
+    /// Convert `val` into a Ruby object.
+    pub fn to_value(&self, val: impl IntoValue) -> Value {
+        val.into_value(&self.globals)
+    }
+
+    /// Convert the Ruby object `val` into `T`.
+    /// Returns TypeError if `val` can not be converted.
+    pub fn convert<T>(&self, val: Value) -> Result<T, RubyError>
+    where
+        T: TryFrom<Value>,
+        T::Error: Into<ConversionError>,
+    {
+        T::try_from(val).map_err(|err| {
+            let err = err.into();
+            let class = self.globals.get_class_name(err.found);
+            self.error_type(format!(
+                "No implicit conversion of {} into {}.",
+                class, err.expected
+            ))
+        })
+    }
+}
+
+// Conversions from Ruby objects
+
+/// The error of the conversion from the Ruby object `found` into a Rust type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConversionError {
+    pub expected: &'static str,
+    pub found: Value,
+}
+
+impl ConversionError {
+    fn new(expected: &'static str, found: Value) -> Self {
+        ConversionError { expected, found }
+    }
+}
+
+impl From<Infallible> for ConversionError {
+    fn from(err: Infallible) -> Self {
+        match err {}
+    }
+}
+
+impl TryFrom<Value> for i64 {
+    type Error = ConversionError;
+    fn try_from(val: Value) -> Result<Self, Self::Error> {
+        val.as_fixnum()
+            .ok_or_else(|| ConversionError::new("Integer", val))
+    }
+}
+
+/// Integers are also converted.
+impl TryFrom<Value> for f64 {
+    type Error = ConversionError;
+    fn try_from(val: Value) -> Result<Self, Self::Error> {
+        match (val.as_flonum(), val.as_fixnum()) {
+            (Some(f), _) => Ok(f),
+            (None, Some(i)) => Ok(i as f64),
+            (None, None) => Err(ConversionError::new("Float", val)),
+        }
+    }
+}
+
+/// Only true and false are converted. Use `VM::val_to_bool` for the truthiness.
+impl TryFrom<Value> for bool {
+    type Error = ConversionError;
+    fn try_from(val: Value) -> Result<Self, Self::Error> {
+        if val == Value::true_val() {
+            Ok(true)
+        } else if val == Value::false_val() {
+            Ok(false)
+        } else {
+            Err(ConversionError::new("true or false", val))
+        }
+    }
+}
+
+impl TryFrom<Value> for String {
+    type Error = ConversionError;
+    fn try_from(val: Value) -> Result<Self, Self::Error> {
+        match val.as_string() {
+            Some(s) => Ok(s.to_string()),
+            None => Err(ConversionError::new("String", val)),
+        }
+    }
+}
+
+impl<T> TryFrom<Value> for Vec<T>
+where
+    T: TryFrom<Value>,
+    T::Error: Into<ConversionError>,
+{
+    type Error = ConversionError;
+    fn try_from(val: Value) -> Result<Self, Self::Error> {
+        let aref = match val.as_array() {
+            Some(aref) => aref,
+            None => return Err(ConversionError::new("Array", val)),
+        };
+        aref.elements
+            .iter()
+            .map(|elem| T::try_from(*elem).map_err(|err| err.into()))
+            .collect()
+    }
+}
+
+macro_rules! try_from_option {
+    ($($ty:ty),*) => {$(
+        /// nil is converted into None.
+        impl TryFrom<Value> for Option<$ty> {
+            type Error = ConversionError;
+            fn try_from(val: Value) -> Result<Self, Self::Error> {
+                if val.is_nil() {
+                    Ok(None)
+                } else {
+                    <$ty>::try_from(val).map(Some)
+                }
+            }
+        }
+    )*};
+}
+
+// Option<Value> is converted by `From<T> for Option<T>`, so it can not be generic.
+try_from_option!(i64, f64, bool, String);
+
+// Conversions into Ruby objects
+
+impl From<i64> for Value {
+    fn from(num: i64) -> Self {
+        Value::fixnum(num)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(num: i32) -> Self {
+        Value::fixnum(num as i64)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(num: f64) -> Self {
+        Value::flonum(num)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::bool(b)
+    }
+}
+
+impl From<()> for Value {
+    fn from(_: ()) -> Self {
+        Value::nil()
+    }
+}
+
+/// Conversion into a Ruby object.
+/// Unlike `Into<Value>`, this also covers objects which need `Globals` to be created, e.g. String and Array.
+pub trait IntoValue {
+    fn into_value(self, globals: &Globals) -> Value;
+}
+
+macro_rules! into_value_from {
+    ($($ty:ty),*) => {$(
+        impl IntoValue for $ty {
+            fn into_value(self, _globals: &Globals) -> Value {
+                self.into()
+            }
+        }
+    )*};
+}
+
+into_value_from!(Value, i64, i32, f64, bool, ());
+
+impl IntoValue for String {
+    fn into_value(self, globals: &Globals) -> Value {
+        Value::string(globals, self)
+    }
+}
+
+impl IntoValue for &str {
+    fn into_value(self, globals: &Globals) -> Value {
+        Value::string(globals, self.to_string())
+    }
+}
+
+impl<T: IntoValue> IntoValue for Vec<T> {
+    fn into_value(self, globals: &Globals) -> Value {
+        let ary = self
+            .into_iter()
+            .map(|elem| elem.into_value(globals))
+            .collect();
+        Value::array_from(globals, ary)
+    }
+}
+
+/// None is converted into nil.
+impl<T: IntoValue> IntoValue for Option<T> {
+    fn into_value(self, globals: &Globals) -> Value {
+        match self {
+            Some(val) => val.into_value(globals),
+            None => Value::nil(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+
+    #[test]
+    fn embed_eval_call() {
+        let mut ruby = Ruby::new();
+        ruby.eval("def add(a, b); a + b; end").unwrap();
+        let res: i64 = ruby.call_function("add", &[3.into(), 4.into()]).unwrap();
+        assert_eq!(7, res);
+        let s = ruby.to_value("abc");
+        let res: String = ruby.call(s, "upcase", &[]).unwrap();
+        assert_eq!("ABC", res);
+        let res: Vec<Option<i64>> = ruby.eval_as("[1, nil, 3]").unwrap();
+        assert_eq!(vec![Some(1), None, Some(3)], res);
+        let res: f64 = ruby.eval_as("2").unwrap();
+        assert_eq!(2.0, res);
+        let ary = ruby.to_value(vec!["a", "b"]);
+        let res: String = ruby.call(ary, "join", &[ruby.to_value("-")]).unwrap();
+        assert_eq!("a-b", res);
+    }
+
+    #[test]
+    fn embed_error() {
+        let mut ruby = Ruby::new();
+        assert!(ruby.eval("1 +").is_err());
+        assert!(ruby.eval("undefined_method").is_err());
+        assert!(ruby.eval_as::<i64>("\"1\"").is_err());
+        assert!(ruby.call_function::<Value>("no_such_method", &[]).is_err());
+        // The interpreter is still usable after errors.
+        assert_eq!(3, ruby.eval_as::<i64>("1 + 2").unwrap());
+    }
+
+    #[test]
+    fn embed_define() {
+        let mut ruby = Ruby::new();
+        let counter = Rc::new(Cell::new(0));
+        let class = ruby.define_class("Counter", None).unwrap();
+        let c = counter.clone();
+        ruby.define_method(class, "up", move |vm, _, args| {
+            vm.check_args_num(args.len(), 1)?;
+            let n: i64 = vm.convert(args[0])?;
+            c.set(c.get() + n);
+            Ok(vm.to_value(c.get()))
+        })
+        .unwrap();
+        ruby.define_singleton_method(class, "name_of", |vm, self_val, _| {
+            let name = vm.val_inspect(self_val);
+            Ok(vm.to_value(name))
+        })
+        .unwrap();
+        let module = ruby.define_module("Util").unwrap();
+        ruby.define_singleton_method(module, "twice", |vm, _, args| {
+            let s: String = vm.convert(args[0])?;
+            Ok(vm.to_value(s.repeat(2)))
+        })
+        .unwrap();
+        ruby.define_global_function("rust_sum", |vm, _, args| {
+            let mut sum = 0;
+            for arg in args.iter() {
+                sum += vm.convert::<i64>(*arg)?;
+            }
+            Ok(vm.to_value(sum))
+        });
+        ruby.eval(
+            r#"
+            c = Counter.new
+            c.up(2)
+            assert 5, c.up(3)
+            assert "Counter", Counter.name_of
+            assert "abab", Util.twice("ab")
+            assert 6, rust_sum(1, 2, 3)
+            class Counter
+              def up_twice(n); up(n); up(n); end
+            end
+            c.up_twice(1)
+            "#,
+        )
+        .unwrap();
+        assert_eq!(7, counter.get());
+        assert!(ruby.eval("Counter.new.up(\"x\")").is_err());
+        assert!(ruby.define_module("Counter").is_err());
+        assert_eq!(class, ruby.define_class("Counter", None).unwrap());
+    }
+
+    #[test]
+    fn embed_isolation() {
+        let mut ruby1 = Ruby::new();
+        let mut ruby2 = Ruby::new();
+        ruby1
+            .eval("$x = 1; class Foo; end; def foo; 1; end")
+            .unwrap();
+        ruby2.eval("$x = 2").unwrap();
+        let x = ruby1.get_global_var("$x");
+        assert_eq!(1, ruby1.convert::<i64>(x).unwrap());
+        let x = ruby2.get_global_var("$x");
+        assert_eq!(2, ruby2.convert::<i64>(x).unwrap());
+        assert!(ruby1.get_const("Foo").is_some());
+        assert!(ruby2.get_const("Foo").is_none());
+        assert!(ruby2.eval("foo").is_err());
+        let val = ruby2.to_value(10);
+        ruby2.set_const("TEN", val);
+        assert!(ruby1.eval("TEN").is_err());
+        assert_eq!(10, ruby2.eval_as::<i64>("TEN").unwrap());
+    }
+
+    #[test]
+    fn embed_nested() {
+        let mut ruby1 = Ruby::default();
+        let ruby2 = Rc::new(RefCell::new(Ruby::default()));
+        ruby2
+            .borrow_mut()
+            .eval("class Foo; def hash; 1; end; def eql?(o); true; end; end")
+            .unwrap();
+        let r2 = ruby2.clone();
+        let object = ruby1.get_const("Object").unwrap();
+        ruby1
+            .define_method(object, "size_in_ruby2", move |vm, _, _| {
+                let size: i64 = r2
+                    .borrow_mut()
+                    .eval_as("{Foo.new => 1, Foo.new => 2}.size")?;
+                Ok(vm.to_value(size))
+            })
+            .unwrap();
+        let res: i64 = ruby1.eval_as("{1 => size_in_ruby2}[1]").unwrap();
+        assert_eq!(1, res);
+        assert_eq!(2, ruby1.eval_as::<i64>("{1 => 2}[1]").unwrap());
+    }
+}
@@ -20,7 +20,6 @@ pub enum ParseErrKind {
     UnexpectedEOF,
     UnexpectedToken,
     SyntaxError(String),
-    LoadError(String),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -31,9 +30,14 @@ pub enum RuntimeErrKind {
     NoMethod(String),
     Argument(String),
     Index(String),
+    ZeroDivision(String),
+    Domain(String),
+    Range(String),
     Type(String),
     Regexp(String),
     Fiber(String),
+    NoMatchingPattern(String),
+    Load(String),
 }
 
 impl RubyError {
@@ -71,7 +75,6 @@ impl RubyError {
                 ParseErrKind::UnexpectedEOF => eprintln!("Unexpected EOF"),
                 ParseErrKind::UnexpectedToken => eprintln!("Unexpected token"),
                 ParseErrKind::SyntaxError(n) => eprintln!("SyntaxError: {}", n),
-                ParseErrKind::LoadError(n) => eprintln!("LoadError: {}", n),
             },
             RubyErrorKind::RuntimeErr(e) => match e {
                 RuntimeErrKind::Name(n) => eprintln!("NoNameError ({})", n),
@@ -81,8 +84,15 @@ impl RubyError {
                 RuntimeErrKind::Internal(n) => eprintln!("InternalError ({})", n),
                 RuntimeErrKind::Argument(n) => eprintln!("ArgumentError ({})", n),
                 RuntimeErrKind::Index(n) => eprintln!("IndexError ({})", n),
+                RuntimeErrKind::ZeroDivision(n) => eprintln!("ZeroDivisionError ({})", n),
+                RuntimeErrKind::Domain(n) => eprintln!("Math::DomainError ({})", n),
+                RuntimeErrKind::Range(n) => eprintln!("RangeError ({})", n),
                 RuntimeErrKind::Regexp(n) => eprintln!("RegexpError ({})", n),
                 RuntimeErrKind::Fiber(n) => eprintln!("FiberError ({})", n),
+                RuntimeErrKind::NoMatchingPattern(n) => {
+                    eprintln!("NoMatchingPatternError ({})", n)
+                }
+                RuntimeErrKind::Load(n) => eprintln!("LoadError ({})", n),
             },
             RubyErrorKind::MethodReturn(_) => {
                 eprintln!("LocalJumpError");
@@ -5,7 +5,7 @@ use std::collections::HashMap;
 pub struct Globals {
     // Global info
     pub ident_table: IdentifierTable,
-    pub global_var: ValueTable,
+    pub global_var: GlobalVars,
     method_table: GlobalMethodTable,
     inline_cache: InlineCache,
     method_cache: MethodCache,
@@ -17,6 +17,10 @@ pub struct Globals {
     pub class_class: ClassRef,
     pub module_class: ClassRef,
     pub object_class: ClassRef,
+    /// Procs registered by Kernel#at_exit.
+    pub at_exit: Vec<Value>,
+    /// Procs created by Symbol#to_proc.
+    pub symbol_procs: HashMap<IdentId, Value>,
 
     case_dispatch: CaseDispatchMap,
 }
@@ -25,13 +29,17 @@ pub type GlobalsRef = Ref<Globals>;
 
 #[derive(Debug, Clone)]
 pub struct BuiltinClass {
+    pub numeric: Value,
     pub integer: Value,
     pub float: Value,
+    pub rational: Value,
+    pub complex: Value,
     pub array: Value,
     pub class: Value,
     pub module: Value,
     pub procobj: Value,
     pub method: Value,
+    pub unbound_method: Value,
     pub range: Value,
     pub hash: Value,
     pub regexp: Value,
@@ -39,25 +47,43 @@ pub struct BuiltinClass {
     pub fiber: Value,
     pub object: Value,
     pub enumerator: Value,
+    pub time: Value,
+    pub random: Value,
+    pub matchdata: Value,
+    pub symbol: Value,
+    pub nilclass: Value,
+    pub trueclass: Value,
+    pub falseclass: Value,
 }
 
 impl BuiltinClass {
     fn new(object: Value, module: Value, class: Value) -> Self {
         let nil = Value::nil();
         BuiltinClass {
+            numeric: nil,
             integer: nil,
             float: nil,
+            rational: nil,
+            complex: nil,
             array: nil,
             class,
             module,
             procobj: nil,
             method: nil,
+            unbound_method: nil,
             range: nil,
             hash: nil,
             regexp: nil,
             string: nil,
             fiber: nil,
             enumerator: nil,
+            time: nil,
+            random: nil,
+            matchdata: nil,
+            symbol: nil,
+            nilclass: nil,
+            trueclass: nil,
+            falseclass: nil,
             object,
         }
     }
@@ -83,9 +109,10 @@ impl Globals {
         let builtins = BuiltinClass::new(object, module, class);
 
         let main_object = Value::ordinary_object(object);
+        let global_var = GlobalVars::new(&mut ident_table);
         let mut globals = Globals {
             ident_table,
-            global_var: HashMap::new(),
+            global_var,
             method_table: GlobalMethodTable::new(),
             inline_cache: InlineCache::new(),
             method_cache: MethodCache::new(),
@@ -96,6 +123,8 @@ impl Globals {
             module_class,
             class_class,
             builtins,
+            at_exit: vec![],
+            symbol_procs: HashMap::new(),
             case_dispatch: CaseDispatchMap::new(),
         };
         // Generate singleton class for Object
@@ -106,17 +135,28 @@ impl Globals {
 
         module::init(&mut globals);
         class::init(&mut globals);
+        globals.builtins.numeric = numeric::init_numeric(&mut globals);
         globals.builtins.integer = integer::init(&mut globals);
         globals.builtins.float = float::init(&mut globals);
+        globals.builtins.rational = rational::init_rational(&mut globals);
+        globals.builtins.complex = complex::init_complex(&mut globals);
         globals.builtins.array = array::init_array(&mut globals);
         globals.builtins.procobj = procobj::init_proc(&mut globals);
         globals.builtins.method = method::init_method(&mut globals);
+        globals.builtins.unbound_method = method::init_unbound_method(&mut globals);
         globals.builtins.range = range::init_range(&mut globals);
         globals.builtins.string = string::init_string(&mut globals);
         globals.builtins.hash = hash::init_hash(&mut globals);
         globals.builtins.regexp = regexp::init_regexp(&mut globals);
         globals.builtins.fiber = fiber::init_fiber(&mut globals);
         globals.builtins.enumerator = enumerator::init_enumerator(&mut globals);
+        globals.builtins.time = time::init_time(&mut globals);
+        globals.builtins.random = random::init_random(&mut globals);
+        globals.builtins.matchdata = matchdata::init_matchdata(&mut globals);
+        globals.builtins.symbol = symbol::init_symbol(&mut globals);
+        globals.builtins.nilclass = nilclass::init_nilclass(&mut globals);
+        globals.builtins.trueclass = boolean::init_trueclass(&mut globals);
+        globals.builtins.falseclass = boolean::init_falseclass(&mut globals);
         object::init(&mut globals);
         let kernel = kernel::init(&mut globals);
         object_class.include.push(kernel);
@@ -233,12 +273,18 @@ impl Globals {
                 ObjKind::Class(_) => "Class".to_string(),
                 ObjKind::Module(_) => "Module".to_string(),
                 ObjKind::Proc(_) => "Proc".to_string(),
-                ObjKind::Method(_) => "Method".to_string(),
+                ObjKind::Method(_) => oref.class_name(self).to_string(),
                 ObjKind::Ordinary => oref.class_name(self).to_string(),
                 ObjKind::Integer(_) => "Integer".to_string(),
                 ObjKind::Float(_) => "Float".to_string(),
                 ObjKind::Fiber(_) => "Fiber".to_string(),
                 ObjKind::Enumerator(_) => "Enumerator".to_string(),
+                ObjKind::File(_) => oref.class_name(self).to_string(),
+                ObjKind::Time(_) => "Time".to_string(),
+                ObjKind::Rational(_) => "Rational".to_string(),
+                ObjKind::Complex(_) => "Complex".to_string(),
+                ObjKind::Random(_) => oref.class_name(self).to_string(),
+                ObjKind::MatchData(_) => "MatchData".to_string(),
             },
         }
     }
@@ -3,6 +3,7 @@
 #![feature(cow_is_borrowed)]
 extern crate fancy_regex;
 pub mod builtin;
+pub mod embed;
 pub mod error;
 pub mod globals;
 pub mod loader;
@@ -11,12 +12,19 @@ pub mod test;
 pub mod util;
 pub mod value;
 pub mod vm;
+pub use crate::builtin::complex::ComplexInfo;
 pub use crate::builtin::enumerator::*;
 pub use crate::builtin::fiber::*;
+pub use crate::builtin::file::FileRef;
+pub use crate::builtin::matchdata::{MatchDataRef, MatchInfo};
 pub use crate::builtin::procobj::*;
 pub use crate::builtin::range::*;
+pub use crate::builtin::random::RandomRef;
+pub use crate::builtin::rational::RationalInfo;
 pub use crate::builtin::regexp::*;
 pub use crate::builtin::string::RString;
+pub use crate::builtin::time::TimeInfo;
+pub use crate::embed::{ConversionError, IntoValue, Ruby};
 pub use crate::error::*;
 pub use crate::globals::*;
 pub use crate::parse::parser::{LvarCollector, LvarId, ParseResult, Parser};
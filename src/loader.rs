@@ -1,5 +1,11 @@
+use crate::*;
 use std::fs::*;
 use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Libraries which are built into the interpreter.
+/// `require` of these names succeeds without loading any file.
+const BUILTIN_FEATURES: &[&str] = &["fileutils", "open3"];
 
 pub enum LoadError {
     NotFound(String),
@@ -33,3 +39,163 @@ pub fn load_file(file_name: impl Into<String>) -> Result<(std::path::PathBuf, St
 
     Ok((absolute_path, file_body))
 }
+
+impl VM {
+    /// Kernel#require
+    /// Loads `name` unless it is already in $LOADED_FEATURES.
+    /// Returns false if the file was already loaded, or `name` is a built-in library.
+    pub fn require_feature(&mut self, name: &str) -> Result<bool, RubyError> {
+        if BUILTIN_FEATURES.contains(&name.trim_end_matches(".rb")) {
+            return Ok(false);
+        }
+        match self.search_feature(name) {
+            Some(path) => self.require_path(path),
+            None => Err(self.error_load(format!("cannot load such file -- {}", name))),
+        }
+    }
+
+    /// Search the file for `require`.
+    /// Explicit paths ("/", "./", "../" and "~/") are resolved from the current directory,
+    /// and other names are searched in the directories of $LOAD_PATH.
+    /// ".rb" is added to `name` unless it already has the extension.
+    pub fn search_feature(&mut self, name: &str) -> Option<PathBuf> {
+        let file_name = if name.ends_with(".rb") {
+            name.to_string()
+        } else {
+            format!("{}.rb", name)
+        };
+        match expand_explicit_path(&file_name) {
+            Some(path) => find_file(&path),
+            None => self.search_load_path(&file_name),
+        }
+    }
+
+    /// Search `file_name` in the directories of $LOAD_PATH.
+    fn search_load_path(&mut self, file_name: &str) -> Option<PathBuf> {
+        let id = self.globals.get_ident_id("$LOAD_PATH");
+        let load_path = self.get_global_var(id).as_array()?;
+        for dir in &load_path.elements {
+            let dir = match dir.as_string() {
+                Some(dir) => expand_home(dir),
+                None => continue,
+            };
+            if let Some(path) = find_file(&dir.join(file_name)) {
+                return Some(path);
+            }
+        }
+        None
+    }
+
+    /// Load the file at `path` unless it is already in $LOADED_FEATURES.
+    /// Returns false if the file was already loaded.
+    pub fn require_path(&mut self, path: PathBuf) -> Result<bool, RubyError> {
+        let (absolute_path, program) = self.read_script(&path)?;
+        let id = self.globals.get_ident_id("$LOADED_FEATURES");
+        let mut features = match self.get_global_var(id).as_array() {
+            Some(features) => features,
+            None => return Err(self.error_type("$LOADED_FEATURES must be an Array.")),
+        };
+        let feature = absolute_path.to_string_lossy().to_string();
+        if features
+            .elements
+            .iter()
+            .any(|val| val.as_string() == Some(&feature))
+        {
+            return Ok(false);
+        }
+        // Register before running the file to prevent loading it recursively.
+        let feature = Value::string(&self.globals, feature);
+        features.elements.push(feature);
+        let main_object = self.globals.main_object;
+        let object = self.globals.builtins.object;
+        if let Err(err) = self.exec_script(path, absolute_path, &program, main_object, object) {
+            features.elements.retain(|val| *val != feature);
+            return Err(err);
+        }
+        Ok(true)
+    }
+
+    /// Kernel#load
+    /// Loads `name` every time it is called, without adding ".rb".
+    /// If `wrap` is a module, top-level methods and constants of the file are defined in it.
+    pub fn load_feature(&mut self, name: &str, wrap: Option<Value>) -> Result<(), RubyError> {
+        let path = match expand_explicit_path(name) {
+            Some(path) => find_file(&path),
+            None => find_file(&expand_home(name)).or_else(|| self.search_load_path(name)),
+        };
+        let path = match path {
+            Some(path) => path,
+            None => return Err(self.error_load(format!("cannot load such file -- {}", name))),
+        };
+        let (absolute_path, program) = self.read_script(&path)?;
+        match wrap {
+            Some(module) => {
+                // The file is executed with a new main object extended by `module`.
+                let main_object = Value::ordinary_object(self.globals.builtins.object);
+                let singleton = self.get_singleton_class(main_object)?;
+                singleton.as_module().unwrap().include.push(module);
+                self.exec_script(path, absolute_path, &program, main_object, module)
+            }
+            None => {
+                let main_object = self.globals.main_object;
+                let object = self.globals.builtins.object;
+                self.exec_script(path, absolute_path, &program, main_object, object)
+            }
+        }
+    }
+
+    fn read_script(&self, path: &Path) -> Result<(PathBuf, String), RubyError> {
+        load_file(path.to_string_lossy()).map_err(|err| {
+            let msg = match err {
+                LoadError::NotFound(msg) | LoadError::CouldntOpen(msg) => msg,
+            };
+            let path = path.to_string_lossy();
+            self.error_load(format!("cannot load such file -- {} ({})", path, msg))
+        })
+    }
+
+    fn exec_script(
+        &mut self,
+        path: PathBuf,
+        absolute_path: PathBuf,
+        program: &str,
+        main_object: Value,
+        class: Value,
+    ) -> Result<(), RubyError> {
+        #[cfg(feature = "verbose")]
+        #[cfg_attr(tarpaulin, skip)]
+        eprintln!("reading:{}", absolute_path.to_string_lossy());
+        self.root_path.push(path);
+        self.class_push(class);
+        let res = self.run(absolute_path, program, Some(main_object));
+        self.class_pop();
+        self.root_path.pop().unwrap();
+        res.map(|_| ())
+    }
+}
+
+/// Returns the path from the current directory if `name` is an explicit path.
+fn expand_explicit_path(name: &str) -> Option<PathBuf> {
+    if name.starts_with('/') || name.starts_with("./") || name.starts_with("../") {
+        Some(PathBuf::from(name))
+    } else if name.starts_with("~/") {
+        Some(expand_home(name))
+    } else {
+        None
+    }
+}
+
+fn expand_home(name: &str) -> PathBuf {
+    match (name.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(name),
+    }
+}
+
+fn find_file(path: &Path) -> Option<PathBuf> {
+    if path.is_file() {
+        path.canonicalize().ok()
+    } else {
+        None
+    }
+}
@@ -4,8 +4,9 @@ extern crate clap;
 extern crate ruruby;
 extern crate rustyline;
 
-use clap::{App, AppSettings, Arg};
+use clap::{App, AppSettings, Arg, ArgMatches};
 use ruruby::loader::{load_file, LoadError};
+use std::path::PathBuf;
 use std::thread;
 mod repl;
 use repl::*;
@@ -17,46 +18,154 @@ fn main() {
         .author("monochrome")
         .about("A toy Ruby interpreter")
         .setting(AppSettings::TrailingVarArg)
+        .arg(
+            Arg::from_usage("-e [code]... 'One line of script. Several -e's allowed. Omit [file]'")
+                .number_of_values(1)
+                .use_delimiter(false),
+        )
+        .arg(
+            Arg::from_usage(
+                "-I [directory]... 'Specify $LOAD_PATH directory (may be used more than once)'",
+            )
+            .number_of_values(1)
+            .use_delimiter(false),
+        )
+        .arg(
+            Arg::from_usage("-r [library]... 'Require the library before executing your script'")
+                .number_of_values(1)
+                .use_delimiter(false),
+        )
+        .arg(Arg::from_usage(
+            "-n 'Assume \"while gets; ...; end\" loop around your script'",
+        ))
+        .arg(Arg::from_usage(
+            "-p 'Assume loop like -n but print line also like sed'",
+        ))
+        .arg(Arg::from_usage("-c 'Check syntax only'"))
+        .arg(Arg::from_usage("-w 'Turn warnings on for your script'"))
+        .arg(Arg::from_usage(
+            "-d 'Set debugging flags (set $DEBUG to true)'",
+        ))
         .arg(Arg::from_usage("[file]... 'Input file name'").multiple(true));
     let m = app.get_matches();
-    let args: Vec<&str> = match m.values_of("file") {
+    let mut args: Vec<&str> = match m.values_of("file") {
         Some(val) => val.collect(),
+        None => vec![],
+    };
+    if args.is_empty() && !m.is_present("e") {
+        repl_vm();
+        return;
+    }
+    let mut vm = VMRef::new(VM::new());
+    set_options(&mut vm, &m);
+
+    // With -e, all the arguments are passed to ARGV.
+    let (program_name, path, program) = match m.values_of("e") {
+        Some(codes) => {
+            let code: Vec<&str> = codes.collect();
+            ("-e", PathBuf::from("-e"), code.join("\n"))
+        }
         None => {
-            repl_vm();
-            return;
+            let file_name = args.remove(0);
+            match read_file(file_name) {
+                Some((path, program)) => (file_name, path, program),
+                None => std::process::exit(1),
+            }
         }
     };
-    let mut vm = VMRef::new(VM::new());
+    let program_name = Value::string(&vm.globals, program_name.to_string());
+    let id = vm.globals.get_ident_id("$0");
+    vm.set_global_var(id, program_name);
     let id = vm.globals.get_ident_id("ARGV");
-    let mut res: Vec<Value> = args
+    let argv = args
         .iter()
         .map(|x| Value::string(&vm.globals, x.to_string()))
         .collect();
-    res.remove(0);
-    let argv = Value::array_from(&vm.globals, res);
+    let argv = Value::array_from(&vm.globals, argv);
     vm.globals.builtins.object.set_var(id, argv);
-    exec_file(&mut vm, args[0]);
-    return;
-}
 
-fn exec_file(vm: &mut VMRef, file_name: impl Into<String>) {
-    let file_name = file_name.into();
-    let (absolute_path, program) = match load_file(file_name.clone()) {
-        Ok((path, program)) => (path, program),
-        Err(err) => match err {
-            LoadError::NotFound(msg) => {
-                eprintln!("No such file or directory --- {} (LoadError)", &file_name);
-                eprintln!("{}", msg);
-                return;
-            }
-            LoadError::CouldntOpen(msg) => {
-                eprintln!("Cannot open file. '{}'", &file_name);
-                eprintln!("{}", msg);
-                return;
+    if m.is_present("c") {
+        match vm.parse_program(path, &program) {
+            Ok(_) => println!("Syntax OK"),
+            Err(err) => {
+                err.show_err();
+                err.show_loc(0);
+                std::process::exit(1);
             }
-        },
+        }
+        return;
+    }
+
+    // -n and -p: wrap the script in a loop, keeping line numbers.
+    let program = if m.is_present("p") {
+        format!("while gets; {}\nprint $_\nend", program)
+    } else if m.is_present("n") {
+        format!("while gets; {}\nend", program)
+    } else {
+        program
     };
 
+    if let Some(libs) = m.values_of("r") {
+        for lib in libs {
+            let lib = lib
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('#', "\\#");
+            let script = format!("require \"{}\"", lib);
+            if !exec_program(&mut vm, PathBuf::from("-r"), script) {
+                std::process::exit(1);
+            }
+        }
+    }
+    let res = exec_program(&mut vm, path, program);
+    vm.exec_at_exit();
+    if !res {
+        std::process::exit(1);
+    }
+}
+
+/// Set global variables given by the command line options.
+fn set_options(vm: &mut VMRef, m: &ArgMatches) {
+    if let Some(dirs) = m.values_of("I") {
+        let id = vm.globals.get_ident_id("$LOAD_PATH");
+        let load_path = vm.get_global_var(id);
+        let mut aref = load_path.as_array().unwrap();
+        for dir in dirs.rev() {
+            let dir = Value::string(&vm.globals, dir.to_string());
+            aref.elements.insert(0, dir);
+        }
+    }
+    if m.is_present("w") {
+        let id = vm.globals.get_ident_id("$VERBOSE");
+        vm.set_global_var(id, Value::true_val());
+    }
+    if m.is_present("d") {
+        let id = vm.globals.get_ident_id("$DEBUG");
+        vm.set_global_var(id, Value::true_val());
+    }
+}
+
+fn read_file(file_name: &str) -> Option<(PathBuf, String)> {
+    match load_file(file_name.to_string()) {
+        Ok((path, program)) => Some((path, program)),
+        Err(err) => {
+            match err {
+                LoadError::NotFound(msg) => {
+                    eprintln!("No such file or directory --- {} (LoadError)", &file_name);
+                    eprintln!("{}", msg);
+                }
+                LoadError::CouldntOpen(msg) => {
+                    eprintln!("Cannot open file. '{}'", &file_name);
+                    eprintln!("{}", msg);
+                }
+            };
+            None
+        }
+    }
+}
+
+/// Execute `program`. Returns false if an error occured.
+fn exec_program(vm: &mut VMRef, absolute_path: PathBuf, program: String) -> bool {
     let root_path = absolute_path.clone();
     #[cfg(feature = "verbose")]
     #[cfg_attr(tarpaulin, skip)]
@@ -66,15 +175,16 @@ fn exec_file(vm: &mut VMRef, file_name: impl Into<String>) {
     let res = thread::spawn(move || vm2.run(absolute_path, &program, None))
         .join()
         .unwrap();
+    vm.root_path.pop();
     match res {
-        Ok(_) => {}
+        Ok(_) => true,
         Err(err) => {
             err.show_err();
             for i in 0..err.info.len() {
                 eprint!("{}:", i);
                 err.show_loc(i);
             }
+            false
         }
-    };
-    vm.root_path.pop();
+    }
 }
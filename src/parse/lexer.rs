@@ -1,6 +1,7 @@
 use super::*;
 use crate::error::{ParseErrKind, RubyError};
 use crate::util::*;
+use crate::RString;
 use std::collections::HashMap;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -13,6 +14,10 @@ pub struct Lexer {
     reserved: HashMap<String, Reserved>,
     reserved_rev: HashMap<Reserved, String>,
     quote_state: Vec<QuoteState>,
+    /// Heredocs read so far: (position after `<<`, end of the line, end of the terminator line).
+    heredocs: Vec<(u32, u32, u32)>,
+    /// End position of the variable interpolated by `#@ivar` or `#$gvar`.
+    short_interpolation: Option<u32>,
     pub source_info: SourceInfoRef,
     state_save: Vec<(u32, u32)>, // (token_start_pos, pos)
 }
@@ -21,10 +26,57 @@ pub struct Lexer {
 enum QuoteState {
     DoubleQuote,
     RegEx,
+    Command(char),
+    Heredoc(Heredoc),
     Brace,
     //Expr,
 }
 
+/// The body of an interpolated heredoc.
+#[derive(Debug, Clone, PartialEq)]
+struct Heredoc {
+    /// End of the body (the start of the terminator line).
+    end: u32,
+    /// Position to resume after the body is read (just after `<<ID`).
+    resume: u32,
+    /// Width of the indentation to be removed from each line (for `<<~ID`).
+    dedent: usize,
+}
+
+/// The kind and the delimiters of a percent literal (e.g. `%w(...)`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct PercentLiteral {
+    pub kind: char,
+    open: char,
+    term: char,
+    /// Nesting level of the paired delimiters.
+    depth: usize,
+}
+
+impl PercentLiteral {
+    pub fn is_word_list(&self) -> bool {
+        match self.kind {
+            'w' | 'W' | 'i' | 'I' => true,
+            _ => false,
+        }
+    }
+
+    fn is_interpolated(&self) -> bool {
+        match self.kind {
+            'Q' | 'W' | 'I' | 'r' | 'x' => true,
+            _ => false,
+        }
+    }
+}
+
+/// A part of the body of a percent literal.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PercentPart {
+    Str(String),
+    /// Separator of words in %w, %W, %i and %I.
+    Sep,
+}
+
 #[derive(Debug, Clone)]
 pub struct LexerResult {
     pub tokens: Vec<Token>,
@@ -55,6 +107,7 @@ impl Lexer {
             "BEGIN" => Reserved::BEGIN,
             "END" => Reserved::END,
             "alias" => Reserved::Alias,
+            "and" => Reserved::And,
             "begin" => Reserved::Begin,
             "break" => Reserved::Break,
             "case" => Reserved::Case,
@@ -73,8 +126,12 @@ impl Lexer {
             "module" => Reserved::Module,
             "next" => Reserved::Next,
             "nil" => Reserved::Nil,
-            "return" => Reserved::Return,
+            "not" => Reserved::Not,
+            "or" => Reserved::Or,
+            "redo" => Reserved::Redo,
             "rescue" => Reserved::Rescue,
+            "retry" => Reserved::Retry,
+            "return" => Reserved::Return,
             "self" => Reserved::Self_,
             "then" => Reserved::Then,
             "true" => Reserved::True,
@@ -93,6 +150,8 @@ impl Lexer {
             reserved,
             reserved_rev,
             quote_state: vec![],
+            heredocs: vec![],
+            short_interpolation: None,
             source_info: SourceInfoRef::new(SourceInfo::new(std::path::PathBuf::default())),
             state_save: vec![],
         }
@@ -223,6 +282,10 @@ impl Lexer {
     }
 
     fn fetch_token(&mut self) -> Result<Token, RubyError> {
+        if self.short_interpolation == Some(self.pos) {
+            self.token_start_pos = self.pos;
+            return self.lex_after_interpolation(self.pos);
+        }
         loop {
             self.token_start_pos = self.pos;
             if let Some(tok) = self.skip_whitespace() {
@@ -237,7 +300,16 @@ impl Lexer {
                 Err(_) => return Ok(self.new_eof(self.pos)),
             };
 
-            if ch.is_ascii_alphabetic() || ch == '_' {
+            if ch == '_' && self.is_end_of_program(pos) {
+                // Text after `__END__` is not a part of the program.
+                let data_pos = (pos as usize + 7..self.len)
+                    .find(|p| self.source_info.code[*p] == '\n')
+                    .map_or(self.len, |p| p + 1);
+                self.source_info.data_pos = Some(data_pos);
+                self.len = pos as usize;
+                self.pos = pos;
+                return Ok(self.new_eof(pos));
+            } else if ch.is_ascii_alphabetic() || ch == '_' {
                 return self.lex_identifier(ch, VarKind::Identifier);
             } else if ch.is_numeric() {
                 return self.lex_number_literal(ch);
@@ -311,10 +383,8 @@ impl Lexer {
                     ']' => return Ok(self.new_punct(Punct::RBracket)),
                     '{' => return Ok(self.new_punct(Punct::LBrace)),
                     '}' => match self.quote_state.last() {
-                        Some(QuoteState::DoubleQuote) => return self.lex_interpolate_string(),
-                        Some(QuoteState::RegEx) => return self.lex_interpolate_regexp(),
                         Some(QuoteState::Brace) => return Ok(self.new_punct(Punct::RBrace)),
-                        _ => return Err(self.error_unexpected(pos)),
+                        _ => return self.lex_after_interpolation(pos),
                     },
                     '.' => {
                         if self.consume('.') {
@@ -329,6 +399,7 @@ impl Lexer {
                     }
                     '?' => return Ok(self.new_punct(Punct::Question)),
                     '\\' => return Ok(self.new_punct(Punct::Backslash)),
+                    '`' => return Ok(self.new_punct(Punct::BackQuote)),
                     '=' => {
                         if self.consume('=') {
                             if self.consume('=') {
@@ -383,7 +454,13 @@ impl Lexer {
                     }
                     '&' => {
                         if self.consume('&') {
-                            return Ok(self.new_punct(Punct::LAnd));
+                            if self.consume('=') {
+                                return Ok(self.new_punct(Punct::AssignOp(BinOp::LAnd)));
+                            } else {
+                                return Ok(self.new_punct(Punct::LAnd));
+                            }
+                        } else if self.consume('.') {
+                            return Ok(self.new_punct(Punct::SafeNav));
                         } else if self.consume('=') {
                             return Ok(self.new_punct(Punct::AssignOp(BinOp::BitAnd)));
                         } else {
@@ -437,7 +514,21 @@ impl Lexer {
             None => {
                 match self.get() {
                     Ok(ch) => {
-                        if ch.is_alphanumeric() || ch == '_' || ch == '&' || ch == '\'' {
+                        if var_kind == VarKind::GlobalVar && "~*$?!@/\\;,.=:<>\"&`'+".contains(ch) {
+                            // Special variables: $? (last status), $$ (pid), $~ (last match) etc.
+                            tok.push(ch);
+                            return Ok(self.new_global_var(tok));
+                        } else if var_kind == VarKind::GlobalVar && ch == '-' {
+                            // Option variables: $-w, $-I etc.
+                            tok.push(ch);
+                            match self.peek() {
+                                Ok(ch) if ch.is_ascii_alphanumeric() || ch == '_' => {
+                                    tok.push(self.get()?);
+                                    return Ok(self.new_global_var(tok));
+                                }
+                                _ => return Err(self.error_unexpected(self.pos)),
+                            }
+                        } else if ch.is_alphanumeric() || ch == '_' || ch == '&' || ch == '\'' {
                             tok.push(ch);
                         } else {
                             return Err(self.error_unexpected(self.pos));
@@ -464,18 +555,31 @@ impl Lexer {
             }
         }
         match var_kind {
-            VarKind::InstanceVar => {
-                return Ok(self.new_instance_var(tok));
-            }
-            VarKind::GlobalVar => {
-                return Ok(self.new_global_var(tok));
+            VarKind::InstanceVar | VarKind::GlobalVar => {
+                let start = self.token_start_pos as usize;
+                if start > 0 && self.source_info.code[start - 1] == '#' {
+                    // `#@ivar` or `#$gvar` in a string literal.
+                    self.short_interpolation = Some(self.pos);
+                }
+                if var_kind == VarKind::InstanceVar {
+                    return Ok(self.new_instance_var(tok));
+                } else {
+                    return Ok(self.new_global_var(tok));
+                }
             }
             _ => {}
         }
 
+        // A method name after '.' may be a reserved word with '?' or '!' (e.g. `x.nil?`).
+        let method_name = self.token_start_pos > 0
+            && self.source_info.code[self.token_start_pos as usize - 1] == '.'
+            && match self.peek() {
+                Ok(ch) => ch == '?' || ch == '!',
+                Err(_) => false,
+            };
         match self.reserved.get(&tok) {
-            Some(reserved) => Ok(self.new_reserved(*reserved)),
-            None => {
+            Some(reserved) if !method_name => Ok(self.new_reserved(*reserved)),
+            _ => {
                 if is_const {
                     let (has_suffix, trailing_space) = match self.peek() {
                         Ok(ch) if ch == ':' || ch == '=' || ch == '(' => (true, false),
@@ -501,17 +605,44 @@ impl Lexer {
         }
     }
 
+    /// Returns true if `__END__` at `pos` occupies a whole line.
+    fn is_end_of_program(&self, pos: u32) -> bool {
+        let pos = pos as usize;
+        let code = &self.source_info.code;
+        if pos > 0 && code[pos - 1] != '\n' {
+            return false;
+        }
+        if pos + 7 > self.len || code[pos..pos + 7].iter().collect::<String>() != "__END__" {
+            return false;
+        }
+        match code.get(pos + 7) {
+            None | Some('\n') => true,
+            Some('\r') => code.get(pos + 8) == Some(&'\n'),
+            _ => false,
+        }
+    }
+
     /// Read number literal
     fn lex_number_literal(&mut self, ch: char) -> Result<Token, RubyError> {
         if ch == '0' {
-            if self.consume('x') {
-                return self.lex_hex_number();
-            } else if self.consume('b') {
-                return self.lex_bin_number();
+            if self.consume('x') || self.consume('X') {
+                return self.lex_radix_number(16);
+            } else if self.consume('b') || self.consume('B') {
+                return self.lex_radix_number(2);
+            } else if self.consume('o') || self.consume('O') {
+                return self.lex_radix_number(8);
+            } else if self.consume('d') || self.consume('D') {
+                return self.lex_radix_number(10);
+            } else if let Ok(ch) = self.peek() {
+                // 017, 0_17
+                if ch.is_ascii_digit() || ch == '_' {
+                    return self.lex_radix_number(8);
+                }
             }
         };
         let mut s = ch.to_string();
         let mut decimal_flag = false;
+        let mut exp_flag = false;
         loop {
             if let Some(ch) = self.consume_numeric() {
                 s.push(ch);
@@ -549,68 +680,79 @@ impl Lexer {
                 }
             }
             decimal_flag = true;
+            exp_flag = true;
         }
-        if decimal_flag {
+        let tok = if !exp_flag && self.consume_suffix('r') {
+            // Rational literal: 1r, 1.5r
+            let (int, frac) = match s.find('.') {
+                Some(pos) => (s[..pos].to_string(), s[pos + 1..].to_string()),
+                None => (s.clone(), String::new()),
+            };
+            let num = format!("{}{}", int, frac);
+            let den = 10i64.checked_pow(frac.len() as u32);
+            match (num.parse::<i64>(), den) {
+                (Ok(num), Some(den)) => self.new_rationallit(num, den),
+                _ => return Err(self.error_parse("Too large rational literal.", self.pos)),
+            }
+        } else if decimal_flag {
             match s.parse::<f64>() {
-                Ok(f) => Ok(self.new_floatlit(f)),
-                Err(err) => Err(self.error_parse(&format!("{:?}", err), self.pos)),
+                Ok(f) => self.new_floatlit(f),
+                Err(err) => return Err(self.error_parse(&format!("{:?}", err), self.pos)),
             }
         } else {
             match s.parse::<i64>() {
-                Ok(i) => Ok(self.new_numlit(i)),
-                Err(err) => Err(self.error_parse(&format!("{:?}", err), self.pos)),
+                Ok(i) => self.new_numlit(i),
+                Err(err) => return Err(self.error_parse(&format!("{:?}", err), self.pos)),
             }
+        };
+        if self.consume_suffix('i') {
+            // Imaginary literal: 2i, 2.5i, 1ri
+            Ok(self.new_imaginarylit(tok.kind))
+        } else {
+            Ok(tok)
         }
     }
 
-    fn lex_hex_number(&mut self) -> Result<Token, RubyError> {
+    /// Read integer literal with `radix` after the prefix (0x, 0b, 0o, 0d or 0).
+    fn lex_radix_number(&mut self, radix: u32) -> Result<Token, RubyError> {
         let mut val = match self.get() {
-            Ok(ch @ '0'..='9') => (ch as u64 - '0' as u64),
-            Ok(ch @ 'a'..='f') => (ch as u64 - 'a' as u64 + 10),
-            Ok(ch @ 'A'..='F') => (ch as u64 - 'A' as u64 + 10),
-            Ok(_) => {
-                self.push_back();
-                return Err(self.error_unexpected(self.pos));
-            }
+            Ok(ch) => match ch.to_digit(radix) {
+                Some(d) => d as u64,
+                None if radix == 8 && ch == '_' => 0,
+                None => {
+                    self.push_back();
+                    return Err(self.error_unexpected(self.pos));
+                }
+            },
             Err(_) => return Err(self.error_unexpected(self.pos)),
         };
         loop {
             match self.get() {
-                Ok(ch @ '0'..='9') => val = val * 16 + (ch as u64 - '0' as u64),
-                Ok(ch @ 'a'..='f') => val = val * 16 + (ch as u64 - 'a' as u64 + 10),
-                Ok(ch @ 'A'..='F') => val = val * 16 + (ch as u64 - 'A' as u64 + 10),
                 Ok('_') => {}
-                Ok(_) => {
-                    self.push_back();
-                    break;
-                }
+                Ok(ch) => match ch.to_digit(radix) {
+                    Some(d) => val = val * radix as u64 + d as u64,
+                    None if ch.is_ascii_digit() => {
+                        return Err(self.error_parse("Invalid digit in numeric literal.", self.pos))
+                    }
+                    None => {
+                        self.push_back();
+                        break;
+                    }
+                },
                 Err(_) => break,
             }
         }
         Ok(self.new_numlit(val as i64))
     }
 
-    fn lex_bin_number(&mut self) -> Result<Token, RubyError> {
-        let mut val = match self.get() {
-            Ok(ch @ '0'..='1') => (ch as u64 - '0' as u64),
-            Ok(_) => {
-                self.push_back();
-                return Err(self.error_unexpected(self.pos));
-            }
-            Err(_) => return Err(self.error_unexpected(self.pos)),
+    /// Read character literal (?a, ?\n) after `?`.
+    pub fn lex_char_literal(&mut self) -> Result<Token, RubyError> {
+        let mut s = "".to_string();
+        match self.get()? {
+            '\\' => self.read_escape(&mut s)?,
+            ch => s.push(ch),
         };
-        loop {
-            match self.get() {
-                Ok(ch @ '0'..='1') => val = val * 2 + (ch as u64 - '0' as u64),
-                Ok('_') => {}
-                Ok(_) => {
-                    self.push_back();
-                    break;
-                }
-                Err(_) => break,
-            }
-        }
-        Ok(self.new_numlit(val as i64))
+        Ok(self.new_stringlit(s))
     }
 
     /// Read string literal
@@ -619,9 +761,9 @@ impl Lexer {
         loop {
             match self.get()? {
                 '"' => return Ok(self.new_stringlit(s)),
-                '\\' => s.push(self.read_escaped_char()?),
+                '\\' => self.read_escape(&mut s)?,
                 '#' => {
-                    if self.consume('{') {
+                    if self.consume('{') || self.is_short_interpolation() {
                         return Ok(self.new_open_dq(s));
                     } else {
                         s.push('#');
@@ -637,9 +779,9 @@ impl Lexer {
         loop {
             match self.get()? {
                 '"' => return Ok(self.new_close_dq(s)),
-                '\\' => s.push(self.read_escaped_char()?),
+                '\\' => self.read_escape(&mut s)?,
                 '#' => {
-                    if self.consume('{') {
+                    if self.consume('{') || self.is_short_interpolation() {
                         return Ok(self.new_inter_dq(s));
                     } else {
                         s.push('#');
@@ -677,7 +819,7 @@ impl Lexer {
                     s.push(self.get()?);
                 }
                 '#' => {
-                    if self.consume('{') {
+                    if self.consume('{') || self.is_short_interpolation() {
                         self.quote_state.push(QuoteState::RegEx);
                         return Ok(self.new_open_reg(s));
                     } else {
@@ -704,7 +846,7 @@ impl Lexer {
                     s.push(self.get()?);
                 }
                 '#' => {
-                    if self.consume('{') {
+                    if self.consume('{') || self.is_short_interpolation() {
                         return Ok(self.new_inter_dq(s));
                     } else {
                         s.push('#');
@@ -715,21 +857,340 @@ impl Lexer {
         }
     }
 
-    pub fn lex_percent_notation(&mut self) -> Result<Token, RubyError> {
-        if self.consume('w') {
-            let mut s = "".to_string();
-            if !self.consume('(') {
-                return Err(self.error_unexpected(self.pos));
+    /// Read command literal (`...` or %x(...)) terminated by `term`.
+    pub fn lex_command_literal(&mut self, term: char) -> Result<Token, RubyError> {
+        let mut s = "".to_string();
+        loop {
+            match self.get()? {
+                c if c == term => return Ok(self.new_stringlit(s)),
+                '\\' => self.read_escape(&mut s)?,
+                '#' => {
+                    if self.consume('{') || self.is_short_interpolation() {
+                        self.quote_state.push(QuoteState::Command(term));
+                        return Ok(self.new_open_dq(s));
+                    } else {
+                        s.push('#');
+                    }
+                }
+                c => s.push(c),
             }
-            loop {
-                match self.get()? {
-                    ')' => return Ok(self.new_percent('w', s)),
-                    ch => s.push(ch),
+        }
+    }
+
+    fn lex_interpolate_command(&mut self, term: char) -> Result<Token, RubyError> {
+        let mut s = "".to_string();
+        loop {
+            match self.get()? {
+                c if c == term => return Ok(self.new_close_dq(s)),
+                '\\' => self.read_escape(&mut s)?,
+                '#' => {
+                    if self.consume('{') || self.is_short_interpolation() {
+                        return Ok(self.new_inter_dq(s));
+                    } else {
+                        s.push('#');
+                    }
                 }
+                c => s.push(c),
+            }
+        }
+    }
+
+    /// Returns true if `#` just read is followed by an interpolated variable (`#@ivar`, `#$gvar`).
+    fn is_short_interpolation(&self) -> bool {
+        let code = &self.source_info.code;
+        let pos = self.pos as usize;
+        match code.get(pos) {
+            Some('@') | Some('$') if pos + 1 < self.len => {
+                let c = code[pos + 1];
+                c.is_ascii_alphabetic() || c == '_'
+            }
+            _ => false,
+        }
+    }
+
+    /// Read the rest of the literal after an interpolated expression.
+    fn lex_after_interpolation(&mut self, pos: u32) -> Result<Token, RubyError> {
+        match self.quote_state.last() {
+            Some(QuoteState::DoubleQuote) => self.lex_interpolate_string(),
+            Some(QuoteState::RegEx) => self.lex_interpolate_regexp(),
+            Some(QuoteState::Command(term)) => {
+                let term = *term;
+                self.lex_interpolate_command(term)
+            }
+            Some(QuoteState::Heredoc(heredoc)) => {
+                let heredoc = heredoc.clone();
+                self.lex_heredoc_body(heredoc, false, false)
             }
+            _ => Err(self.error_unexpected(pos)),
+        }
+    }
+
+    /// Read heredoc (<<ID, <<-ID, <<~ID, <<'ID' or <<"ID") following `<<`.
+    /// The body starts at the next line, or after the body of the preceding heredoc on the same line.
+    pub fn lex_heredoc(&mut self) -> Result<Token, RubyError> {
+        let start = self.pos;
+        let mode = if self.consume('~') {
+            '~'
+        } else if self.consume('-') {
+            '-'
         } else {
+            ' '
+        };
+        let quote = match self.peek()? {
+            c @ '\'' | c @ '"' => {
+                self.get()?;
+                Some(c)
+            }
+            _ => None,
+        };
+        let mut id = "".to_string();
+        loop {
+            match quote {
+                Some(q) => match self.get()? {
+                    c if c == q => break,
+                    '\n' => return Err(self.error_unexpected(self.pos - 1)),
+                    c => id.push(c),
+                },
+                None => match self.peek() {
+                    Ok(c) if c.is_alphanumeric() || c == '_' => id.push(self.get()?),
+                    _ => break,
+                },
+            }
+        }
+        if id.is_empty() {
             return Err(self.error_unexpected(self.pos));
         }
+        let resume = self.pos;
+        let code = &self.source_info.code;
+        let line_end = (resume as usize..self.len)
+            .find(|p| code[*p] == '\n')
+            .unwrap_or(self.len) as u32;
+        let body_start = self
+            .heredocs
+            .iter()
+            .filter(|(pos, end, _)| *end == line_end && *pos < start)
+            .map(|(_, _, term_end)| *term_end)
+            .max()
+            .unwrap_or(line_end + 1) as usize;
+        // Look for the terminator line.
+        let mut line = body_start;
+        let (body_end, term_end) = loop {
+            if line >= self.len {
+                return Err(
+                    self.error_parse(&format!("Can not find heredoc terminator {}", id), start)
+                );
+            }
+            let eol = (line..self.len)
+                .find(|p| code[*p] == '\n')
+                .unwrap_or(self.len);
+            let text: String = code[line..eol].iter().collect();
+            let text = text.trim_end_matches('\r');
+            let text = if mode == ' ' { text } else { text.trim_start() };
+            if text == id {
+                break (line, std::cmp::min(eol + 1, self.len));
+            }
+            line = eol + 1;
+        };
+        let dedent = if mode == '~' {
+            code[body_start..body_end]
+                .split(|c| *c == '\n')
+                .filter(|line| line.iter().any(|c| !c.is_ascii_whitespace()))
+                .map(|line| {
+                    let mut width = 0;
+                    for c in line {
+                        match c {
+                            ' ' => width += 1,
+                            '\t' => width = (width / 8 + 1) * 8,
+                            _ => break,
+                        }
+                    }
+                    width
+                })
+                .min()
+                .unwrap_or(0)
+        } else {
+            0
+        };
+        if !self.heredocs.iter().any(|(pos, _, _)| *pos == start) {
+            self.heredocs.push((start, line_end, term_end as u32));
+        }
+        let heredoc = Heredoc {
+            end: body_end as u32,
+            resume,
+            dedent,
+        };
+        self.pos = body_start as u32;
+        self.lex_heredoc_body(heredoc, quote == Some('\''), true)
+    }
+
+    /// Read the body of a heredoc until its end or an interpolation.
+    /// `open` is true when reading from the beginning of the body.
+    fn lex_heredoc_body(
+        &mut self,
+        heredoc: Heredoc,
+        raw: bool,
+        open: bool,
+    ) -> Result<Token, RubyError> {
+        let mut s = "".to_string();
+        while self.pos < heredoc.end {
+            if heredoc.dedent > 0 && self.source_info.code[self.pos as usize - 1] == '\n' {
+                self.skip_indent(heredoc.dedent);
+                if self.pos >= heredoc.end {
+                    break;
+                }
+            }
+            match self.get()? {
+                '\\' if !raw => self.read_escape(&mut s)?,
+                '#' if !raw && (self.consume('{') || self.is_short_interpolation()) => {
+                    return if open {
+                        let tok = self.new_open_dq(s);
+                        self.quote_state.push(QuoteState::Heredoc(heredoc));
+                        Ok(tok)
+                    } else {
+                        Ok(self.new_inter_dq(s))
+                    };
+                }
+                c => s.push(c),
+            }
+        }
+        if open {
+            self.pos = heredoc.resume;
+            Ok(self.new_stringlit(s))
+        } else {
+            let tok = self.new_close_dq(s);
+            self.pos = heredoc.resume;
+            Ok(tok)
+        }
+    }
+
+    /// Skip the indentation of a line in a heredoc up to `width` columns.
+    fn skip_indent(&mut self, width: usize) {
+        let mut col = 0;
+        while col < width {
+            match self.source_info.code.get(self.pos as usize) {
+                Some(' ') => col += 1,
+                Some('\t') => {
+                    let next = (col / 8 + 1) * 8;
+                    if next > width {
+                        return;
+                    }
+                    col = next;
+                }
+                _ => return,
+            }
+            self.pos += 1;
+        }
+    }
+
+    /// Skip the bodies of heredocs which began on the line just terminated.
+    fn skip_heredoc_bodies(&mut self) {
+        let line_end = self.pos - 1;
+        if let Some(term_end) = self
+            .heredocs
+            .iter()
+            .filter(|(_, end, _)| *end == line_end)
+            .map(|(_, _, term_end)| *term_end)
+            .max()
+        {
+            self.pos = term_end;
+        }
+    }
+
+    /// Read the kind and the opening delimiter of a percent literal following `%`.
+    /// The kind is 'Q' for `%(...)`.
+    pub fn lex_percent_literal(&mut self) -> Result<PercentLiteral, RubyError> {
+        let kind = match self.peek()? {
+            c @ 'q'
+            | c @ 'Q'
+            | c @ 'w'
+            | c @ 'W'
+            | c @ 'i'
+            | c @ 'I'
+            | c @ 'r'
+            | c @ 's'
+            | c @ 'x' => {
+                self.get()?;
+                c
+            }
+            _ => 'Q',
+        };
+        let open = self.get()?;
+        let term = match open {
+            '(' => ')',
+            '[' => ']',
+            '{' => '}',
+            '<' => '>',
+            c if c.is_ascii_punctuation() => c,
+            _ => return Err(self.error_unexpected(self.pos - 1)),
+        };
+        Ok(PercentLiteral {
+            kind,
+            open,
+            term,
+            depth: 0,
+        })
+    }
+
+    /// Read the body of a percent literal until the closing delimiter or `#{`.
+    /// Returns the parts read, and true if the body was suspended by an interpolation.
+    pub fn lex_percent_body(
+        &mut self,
+        lit: &mut PercentLiteral,
+    ) -> Result<(Vec<PercentPart>, bool), RubyError> {
+        let mut parts = vec![];
+        let mut s = "".to_string();
+        loop {
+            match self.get()? {
+                c if c == lit.term && lit.depth == 0 => {
+                    if lit.kind == 'r' {
+                        self.check_postfix(&mut s);
+                    }
+                    parts.push(PercentPart::Str(s));
+                    return Ok((parts, false));
+                }
+                c if c == lit.term => {
+                    lit.depth -= 1;
+                    s.push(c);
+                }
+                c if c == lit.open => {
+                    lit.depth += 1;
+                    s.push(c);
+                }
+                c if lit.is_word_list() && c.is_ascii_whitespace() => {
+                    if !s.is_empty() {
+                        parts.push(PercentPart::Str(s));
+                        s = "".to_string();
+                    }
+                    parts.push(PercentPart::Sep);
+                }
+                '\\' => {
+                    let c = self.peek()?;
+                    if lit.kind == 'r' {
+                        s.push('\\');
+                        s.push(self.get()?);
+                    } else if c == lit.term
+                        || c == lit.open
+                        || lit.is_word_list() && c.is_ascii_whitespace()
+                    {
+                        s.push(self.get()?);
+                    } else if lit.is_interpolated() {
+                        self.read_escape(&mut s)?;
+                    } else {
+                        // Only `\\` and escaped delimiters are allowed in non-interpolated literals.
+                        if c == '\\' {
+                            self.get()?;
+                        }
+                        s.push('\\');
+                    }
+                }
+                '#' if lit.is_interpolated() && self.consume('{') => {
+                    self.quote_state.push(QuoteState::Brace);
+                    parts.push(PercentPart::Str(s));
+                    return Ok((parts, true));
+                }
+                c => s.push(c),
+            }
+        }
     }
 
     fn char_to_hex(&self, c: char) -> Result<u32, RubyError> {
@@ -741,30 +1202,140 @@ impl Lexer {
         }
     }
 
+    /// Read an escape sequence following `\\` in an interpolated literal and push it to `s`.
+    fn read_escape(&mut self, s: &mut String) -> Result<(), RubyError> {
+        if self.consume('\n') {
+            // Line continuation.
+            return Ok(());
+        }
+        if self.peek()? == 'u' && self.source_info.code.get(self.pos as usize + 1) == Some(&'{') {
+            // \u{XXXX XXXX ...}
+            self.pos += 2;
+            loop {
+                while self.consume(' ') {}
+                if self.consume('}') {
+                    return Ok(());
+                }
+                let c = self.read_hex_char(1, 6)?;
+                s.push(c);
+            }
+        }
+        if let Some(byte) = self.read_escaped_byte()? {
+            // Consecutive byte escapes (e.g. "\xE3\x81\x82") are decoded together as UTF-8.
+            let mut bytes = vec![byte];
+            while self.source_info.code.get(self.pos as usize) == Some(&'\\') {
+                self.pos += 1;
+                match self.read_escaped_byte()? {
+                    Some(byte) => bytes.push(byte),
+                    None => {
+                        self.pos -= 1;
+                        break;
+                    }
+                }
+            }
+            match String::from_utf8(bytes) {
+                Ok(string) => s.push_str(&string),
+                Err(err) => s.extend(
+                    err.into_bytes()
+                        .iter()
+                        .map(|b| RString::raw_byte_to_char(*b)),
+                ),
+            }
+            return Ok(());
+        }
+        s.push(self.read_escaped_char()?);
+        Ok(())
+    }
+
+    /// Read `min` to `max` hex digits as a code point.
+    fn read_hex_char(&mut self, min: usize, max: usize) -> Result<char, RubyError> {
+        let mut code = 0;
+        for i in 0..max {
+            match self.peek()? {
+                c if c.is_ascii_hexdigit() => {
+                    self.get()?;
+                    code = code * 16 + self.char_to_hex(c)?;
+                }
+                _ if i < min => return Err(self.error_unexpected(self.pos)),
+                _ => break,
+            }
+        }
+        match std::char::from_u32(code) {
+            Some(c) => Ok(c),
+            None => Err(self.error_parse("Invalid Unicode escape.", self.pos - 1)),
+        }
+    }
+
+    /// Read a byte escape (\\xHH, \\nnn or \\M-x) following `\\`.
+    /// Returns None without consuming any char if it is not a byte escape.
+    fn read_escaped_byte(&mut self) -> Result<Option<u8>, RubyError> {
+        let byte = match self.peek()? {
+            'x' => {
+                self.get()?;
+                self.read_hex_char(1, 2)? as u32
+            }
+            c @ '0'..='7' => {
+                // Octal: \nnn
+                self.get()?;
+                let mut code = c as u32 - '0' as u32;
+                for _ in 0..2 {
+                    match self.peek()? {
+                        c @ '0'..='7' => {
+                            self.get()?;
+                            code = code * 8 + (c as u32 - '0' as u32);
+                        }
+                        _ => break,
+                    }
+                }
+                code
+            }
+            'M' if self.source_info.code.get(self.pos as usize + 1) == Some(&'-') => {
+                // Meta: \M-x
+                self.pos += 2;
+                self.read_control_target()? as u32 | 0x80
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(byte as u8))
+    }
+
     fn read_escaped_char(&mut self) -> Result<char, RubyError> {
+        if let Some(byte) = self.read_escaped_byte()? {
+            return Ok(byte as char);
+        }
         let ch = match self.get()? {
             c @ '\'' | c @ '"' | c @ '?' | c @ '\\' => c,
             'a' => '\x07',
             'b' => '\x08',
+            'e' => '\x1b',
             'f' => '\x0c',
             'n' => '\x0a',
             'r' => '\x0d',
+            's' => ' ',
             't' => '\x09',
             'v' => '\x0b',
-            'x' => {
-                let c1 = self.get()?;
-                let c1 = self.char_to_hex(c1)?;
-                let c2 = self.get()?;
-                let c2 = self.char_to_hex(c2)?;
-                match std::char::from_u32(c1 * 16 + c2) {
-                    Some(c) => c,
-                    None => return Err(self.error_unexpected(self.pos)),
-                }
-            }
+            'u' => self.read_hex_char(4, 4)?,
+            'C' if self.consume('-') => self.read_control_char()?,
+            'c' => self.read_control_char()?,
             c => c,
         };
         Ok(ch)
     }
+
+    /// Read the char (possibly escaped) following `\\M-`, `\\C-` or `\\c`.
+    fn read_control_target(&mut self) -> Result<char, RubyError> {
+        match self.get()? {
+            '\\' => self.read_escaped_char(),
+            c => Ok(c),
+        }
+    }
+
+    /// Read a control char: \\C-x or \\cx.
+    fn read_control_char(&mut self) -> Result<char, RubyError> {
+        let c = self.read_control_target()? as u32;
+        let code = if c == '?' as u32 { 0x7f } else { c & 0x9f };
+        Ok(std::char::from_u32(code).unwrap())
+    }
 }
 
 impl Lexer {
@@ -798,6 +1369,30 @@ impl Lexer {
         }
     }
 
+    /// Consume the next char as a suffix of a numeric literal,
+    /// if the char is equal to the given one and is not followed by an identifier char.
+    fn consume_suffix(&mut self, ch: char) -> bool {
+        let pos = self.pos as usize;
+        if pos >= self.len || self.source_info.code[pos] != ch {
+            return false;
+        }
+        let is_ident_char = |ch: Option<&char>| match ch {
+            Some(ch) => ch.is_ascii_alphanumeric() || *ch == '_',
+            None => false,
+        };
+        let mut next = pos + 1;
+        // `r` may be followed by `i` (e.g. 1ri).
+        if ch == 'r' && self.source_info.code.get(next) == Some(&'i') {
+            next += 1;
+        }
+        if is_ident_char(self.source_info.code.get(next)) {
+            false
+        } else {
+            self.pos += 1;
+            true
+        }
+    }
+
     /// Consume the next char, if the char is numeric char.
     /// Return Some(ch) if the token (ch) was consumed.
     fn consume_numeric(&mut self) -> Option<char> {
@@ -844,6 +1439,7 @@ impl Lexer {
         loop {
             if self.consume('\n') {
                 res = Some(self.new_line_term());
+                self.skip_heredoc_bodies();
                 self.token_start_pos = self.pos;
             } else if self.consume_whitespace() {
                 self.token_start_pos = self.pos;
@@ -903,6 +1499,14 @@ impl Lexer {
         Token::new_floatlit(num, self.cur_loc())
     }
 
+    fn new_rationallit(&self, num: i64, den: i64) -> Token {
+        Token::new_rationallit(num, den, self.cur_loc())
+    }
+
+    fn new_imaginarylit(&self, kind: TokenKind) -> Token {
+        Token::new_imaginarylit(kind, self.cur_loc())
+    }
+
     fn new_stringlit(&self, string: impl Into<String>) -> Token {
         Annot::new(TokenKind::StringLit(string.into()), self.cur_loc())
     }
@@ -927,10 +1531,6 @@ impl Lexer {
         Token::new_open_reg(s, self.cur_loc())
     }
 
-    fn new_percent(&self, kind: char, content: String) -> Token {
-        Token::new_percent(kind, content, self.cur_loc())
-    }
-
     fn new_space(&self) -> Token {
         Annot::new(TokenKind::Space, self.cur_loc())
     }
@@ -61,6 +61,12 @@ pub enum NodeKind {
         when_: Vec<CaseBranch>,
         else_: Box<Node>,
     },
+    CaseIn {
+        cond: Box<Node>,
+        in_: Vec<InBranch>,
+        else_: Option<Box<Node>>, // raise NoMatchingPatternError if None
+    },
+    PatternTest(Box<Node>, Box<Pattern>), // expr in pattern
     Begin {
         body: Box<Node>,
         rescue: Vec<(Node, Node)>, // (ex_class_list, ex_param)
@@ -74,6 +80,7 @@ pub enum NodeKind {
     },
     Break(Box<Node>),
     Next(Box<Node>),
+    Redo,
     Return(Box<Node>),
     Yield(SendArgs),
 
@@ -82,6 +89,8 @@ pub enum NodeKind {
     OptionalParam(IdentId, Box<Node>),
     RestParam(IdentId),
     KeywordParam(IdentId, Box<Option<Node>>),
+    KWRestParam(IdentId),
+    NoKWParam,
     BlockParam(IdentId),
 
     MethodDef(IdentId, NodeVec, Box<Node>, LvarCollector), // id, params, body
@@ -98,6 +107,7 @@ pub enum NodeKind {
         method: IdentId,
         send_args: SendArgs,
         completed: bool,
+        safe_nav: bool,
     }, //receiver, method_name, args
 }
 
@@ -105,6 +115,7 @@ pub enum NodeKind {
 pub struct SendArgs {
     pub args: NodeVec,
     pub kw_args: Vec<(IdentId, Node)>,
+    pub kw_splat: NodeVec,
     pub block: Option<Box<Node>>,
 }
 
@@ -113,11 +124,73 @@ impl SendArgs {
         SendArgs {
             args: vec![],
             kw_args: vec![],
+            kw_splat: vec![],
             block: None,
         }
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct InBranch {
+    pub pattern: Pattern,
+    pub guard: Option<Box<Node>>,
+    pub body: Box<Node>,
+}
+
+impl InBranch {
+    pub fn new(pattern: Pattern, guard: Option<Node>, body: Node) -> Self {
+        InBranch {
+            pattern,
+            guard: guard.map(Box::new),
+            body: Box::new(body),
+        }
+    }
+}
+
+/// Patterns for `case/in`, `expr in pattern` and `expr => pattern`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+    /// Value pattern. Matches if `value === obj`.
+    Value(Box<Node>),
+    /// Variable pattern. Always matches and binds obj to the local variable.
+    Bind(IdentId),
+    /// pattern => name
+    Capture(Box<Pattern>, IdentId),
+    /// pattern | pattern | ...
+    Alt(Vec<Pattern>),
+    /// Const(pre, *rest, post) / [pre, *rest, post]
+    /// rest: None for no splat, Some(None) for an anonymous splat.
+    Array {
+        constant: Option<Box<Node>>,
+        pre: Vec<Pattern>,
+        rest: Option<Option<IdentId>>,
+        post: Vec<Pattern>,
+    },
+    /// Const(*pre, mid, *post) / [*pre, mid, *post]
+    Find {
+        constant: Option<Box<Node>>,
+        pre: Option<IdentId>,
+        mid: Vec<Pattern>,
+        post: Option<IdentId>,
+    },
+    /// Const(key: pattern, **rest) / {key: pattern, **rest}
+    Hash {
+        constant: Option<Box<Node>>,
+        pairs: Vec<(IdentId, Pattern)>,
+        rest: HashPatternRest,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HashPatternRest {
+    /// Other keys are allowed.
+    Any,
+    /// **nil: other keys are not allowed.
+    NoKey,
+    /// **name: other keys are collected into a Hash.
+    Bind(IdentId),
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct CaseBranch {
     pub when: Vec<Node>,
@@ -287,6 +360,14 @@ impl Node {
         Node::new(NodeKind::KeywordParam(id, Box::new(default)), loc)
     }
 
+    pub fn new_kwrest_param(id: IdentId, loc: Loc) -> Self {
+        Node::new(NodeKind::KWRestParam(id), loc)
+    }
+
+    pub fn new_nokw_param(loc: Loc) -> Self {
+        Node::new(NodeKind::NoKWParam, loc)
+    }
+
     pub fn new_block_param(id: IdentId, loc: Loc) -> Self {
         Node::new(NodeKind::BlockParam(id), loc)
     }
@@ -320,6 +401,17 @@ impl Node {
         Node::new(NodeKind::MulAssign(lhs, rhs), loc)
     }
 
+    /// Nested lhs of multiple assignment, e.g. `(b, c)` in `a, (b, c) = ...`.
+    /// This is represented as MulAssign without rhs, and assigns the value on the stack.
+    pub fn new_mlhs(lhs: Vec<Node>, loc: Loc) -> Self {
+        Node::new(NodeKind::MulAssign(lhs, vec![]), loc)
+    }
+
+    pub fn new_assign_op(op: BinOp, lhs: Node, rhs: Node) -> Self {
+        let loc = lhs.loc().merge(rhs.loc());
+        Node::new(NodeKind::AssignOp(op, Box::new(lhs), Box::new(rhs)), loc)
+    }
+
     pub fn new_method_decl(
         id: IdentId,
         params: Vec<Node>,
@@ -369,6 +461,7 @@ impl Node {
         method: IdentId,
         mut send_args: SendArgs,
         completed: bool,
+        safe_nav: bool,
         loc: Loc,
     ) -> Self {
         let loc = match (send_args.args.last(), &send_args.block) {
@@ -382,6 +475,7 @@ impl Node {
                 method,
                 send_args,
                 completed,
+                safe_nav,
             },
             loc,
         )
@@ -391,6 +485,7 @@ impl Node {
         let send_args = SendArgs {
             args: vec![],
             kw_args: vec![],
+            kw_splat: vec![],
             block: None,
         };
         Node::new(
@@ -399,6 +494,7 @@ impl Node {
                 method,
                 send_args,
                 completed,
+                safe_nav: false,
             },
             loc,
         )
@@ -439,6 +535,24 @@ impl Node {
         )
     }
 
+    pub fn new_case_in(cond: Node, in_: Vec<InBranch>, else_: Option<Node>, loc: Loc) -> Self {
+        Node::new(
+            NodeKind::CaseIn {
+                cond: Box::new(cond),
+                in_,
+                else_: else_.map(Box::new),
+            },
+            loc,
+        )
+    }
+
+    pub fn new_pattern_test(expr: Node, pattern: Pattern, loc: Loc) -> Self {
+        Node::new(
+            NodeKind::PatternTest(Box::new(expr), Box::new(pattern)),
+            loc,
+        )
+    }
+
     pub fn new_begin(
         body: Node,
         rescue: Vec<(Node, Node)>,
@@ -465,6 +579,10 @@ impl Node {
         Node::new(NodeKind::Next(Box::new(val)), loc)
     }
 
+    pub fn new_redo(loc: Loc) -> Self {
+        Node::new(NodeKind::Redo, loc)
+    }
+
     pub fn new_return(val: Node, loc: Loc) -> Self {
         Node::new(NodeKind::Return(Box::new(val)), loc)
     }
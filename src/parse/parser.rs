@@ -10,6 +10,8 @@ pub struct Parser {
     prev_loc: Loc,
     context_stack: Vec<Context>,
     pub ident_table: IdentifierTable,
+    /// Bodies of BEGIN blocks, which are evaluated before the program.
+    begin_blocks: Vec<Node>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -180,9 +182,17 @@ enum ContextKind {
 struct ArgList {
     args: Vec<Node>,
     kw_args: Vec<(IdentId, Node)>,
+    kw_splat: Vec<Node>,
     block: Option<Box<Node>>,
 }
 
+/// An element of array patterns and find patterns.
+#[derive(Debug, Clone, PartialEq)]
+enum PatternElem {
+    Pattern(Pattern),
+    Splat(Option<IdentId>),
+}
+
 impl Parser {
     pub fn new() -> Self {
         let lexer = Lexer::new();
@@ -191,6 +201,7 @@ impl Parser {
             prev_loc: Loc(0, 0),
             context_stack: vec![],
             ident_table: IdentifierTable::new(),
+            begin_blocks: vec![],
         }
     }
 
@@ -426,6 +437,7 @@ impl Parser {
             TokenKind::Ident(ident, _, _) => ident,
             TokenKind::Const(ident, _, _) => ident,
             TokenKind::InstanceVar(ident) => ident,
+            TokenKind::GlobalVar(ident) => ident,
             TokenKind::StringLit(ident) => ident,
             TokenKind::Reserved(reserved) => {
                 self.lexer.get_string_from_reserved(reserved).to_string()
@@ -498,8 +510,14 @@ impl Parser {
     ) -> Result<(Node, LvarCollector), RubyError> {
         self.lexer.init(path, program);
         self.context_stack.push(Context::new_class(lvar));
-        let node = self.parse_comp_stmt()?;
+        let mut node = self.parse_comp_stmt()?;
         let lvar = self.context_stack.pop().unwrap().lvar;
+        if !self.begin_blocks.is_empty() {
+            let loc = node.loc();
+            let mut nodes = std::mem::replace(&mut self.begin_blocks, vec![]);
+            nodes.push(node);
+            node = Node::new_comp_stmt(nodes, loc);
+        }
         Ok((node, lvar))
     }
 
@@ -583,6 +601,23 @@ impl Parser {
         // EXPR : NOT
         // | KEYWORD-AND
         // | KEYWORD-OR
+        // KEYWORD-AND : EXPR and NOT
+        // KEYWORD-OR : EXPR or NOT
+        let mut node = self.parse_not()?;
+        loop {
+            if self.consume_reserved_no_skip_line_term(Reserved::And)? {
+                let rhs = self.parse_not()?;
+                node = Node::new_binop(BinOp::LAnd, node, rhs);
+            } else if self.consume_reserved_no_skip_line_term(Reserved::Or)? {
+                let rhs = self.parse_not()?;
+                node = Node::new_binop(BinOp::LOr, node, rhs);
+            } else {
+                return Ok(node);
+            }
+        }
+    }
+
+    fn parse_not(&mut self) -> Result<Node, RubyError> {
         // NOT : ARG
         // | UNPARENTHESIZED-METHOD
         // | ! UNPARENTHESIZED-METHOD
@@ -603,6 +638,20 @@ impl Parser {
         // | :: FNAME( ARGS )
         // COMMAND-WITH-DO-BLOCK : FNAME ARGS DO-BLOCK
         // | PRIMARY . FNAME ARGS DO-BLOCK [CHAIN-METHOD]* [ . FNAME ARGS]
+        // KEYWORD-NOT : not NOT
+        if self.consume_reserved(Reserved::Not)? {
+            let loc = self.prev_loc();
+            let node = self.parse_not()?;
+            return Ok(Node::new_unop(UnOp::Not, node, loc));
+        }
+        if self.peek_no_term()?.kind == TokenKind::Punct(Punct::Mul) {
+            // EXPR : MLHS `=' MRHS (MLHS begins with `*')
+            let node = self.parse_mlhs_item()?;
+            if !self.consume_punct_no_term(Punct::Comma)? {
+                return self.parse_mul_assign_rhs(vec![node]);
+            }
+            return Ok(self.parse_mul_assign(node)?);
+        }
         let node = self.parse_arg()?;
         if self.consume_punct_no_term(Punct::Comma)?
         /*&& node.is_lvar()*/
@@ -623,7 +672,7 @@ impl Parser {
                     receiver,
                     mut send_args,
                     completed: false,
-                    ..
+                    safe_nav,
                 },
             loc,
         } = node.clone()
@@ -633,8 +682,20 @@ impl Parser {
             } else {
                 send_args.block = self.parse_block()?
             };
-            let node = Node::new_send(*receiver, method, send_args, true, loc);
+            let node = Node::new_send(*receiver, method, send_args, true, safe_nav, loc);
             Ok(node)
+        } else if self.consume_reserved_no_skip_line_term(Reserved::In)? {
+            // EXPR : ARG in PATTERN
+            let loc = node.loc();
+            let pattern = self.parse_pattern_top()?;
+            Ok(Node::new_pattern_test(node, pattern, loc.merge(self.prev_loc())))
+        } else if self.consume_punct_no_term(Punct::FatArrow)? {
+            // EXPR : ARG => PATTERN
+            let loc = node.loc();
+            let pattern = self.parse_pattern_top()?;
+            let loc = loc.merge(self.prev_loc());
+            let branch = InBranch::new(pattern, None, Node::new_nil(loc));
+            Ok(Node::new_case_in(node, vec![branch], None, loc))
         } else {
             // EXPR : ARG
             Ok(node)
@@ -643,18 +704,32 @@ impl Parser {
 
     fn parse_mul_assign(&mut self, node: Node) -> Result<Node, RubyError> {
         // EXPR : MLHS `=' MRHS
-        let mut mlhs = vec![node];
+        // MLHS : MLHS_ITEM [`,' MLHS_ITEM]* [`,']
+        let mut mlhs = vec![Parser::as_mlhs_item(node)];
         loop {
-            if self.peek_no_term()?.kind == TokenKind::Punct(Punct::Assign) {
-                break;
+            match self.peek_no_term()?.kind {
+                TokenKind::Punct(Punct::Assign) | TokenKind::Punct(Punct::RParen) => {
+                    // A trailing comma works as an anonymous splat: `a, = ary`
+                    let loc = self.prev_loc();
+                    mlhs.push(Node::new_splat(Node::new_nil(loc), loc));
+                    break;
+                }
+                _ => {}
             }
-            let node = self.parse_function()?;
-            mlhs.push(node);
+            mlhs.push(self.parse_mlhs_item()?);
             if !self.consume_punct_no_term(Punct::Comma)? {
                 break;
             }
         }
+        if self.peek_no_term()?.kind == TokenKind::Punct(Punct::RParen) {
+            // Nested MLHS: `(b, c)` in `a, (b, c) = ...`
+            let loc = mlhs[0].loc().merge(self.prev_loc());
+            return Ok(Node::new_mlhs(mlhs, loc));
+        }
+        self.parse_mul_assign_rhs(mlhs)
+    }
 
+    fn parse_mul_assign_rhs(&mut self, mlhs: Vec<Node>) -> Result<Node, RubyError> {
         if !self.consume_punct_no_term(Punct::Assign)? {
             let loc = self.loc();
             return Err(self.error_unexpected(loc, "Expected '='."));
@@ -662,11 +737,56 @@ impl Parser {
 
         let mrhs = self.parse_arg_list(None)?;
         for lhs in &mlhs {
-            self.check_lhs(lhs)?;
+            self.check_mlhs(lhs)?;
         }
         return Ok(Node::new_mul_assign(mlhs, mrhs));
     }
 
+    /// Parse an item of MLHS: `lhs`, `*lhs`, `*` or `(MLHS)`.
+    fn parse_mlhs_item(&mut self) -> Result<Node, RubyError> {
+        if self.consume_punct_no_term(Punct::Mul)? {
+            let loc = self.prev_loc();
+            match self.peek_no_term()?.kind {
+                TokenKind::Punct(Punct::Comma)
+                | TokenKind::Punct(Punct::Assign)
+                | TokenKind::Punct(Punct::RParen) => {
+                    Ok(Node::new_splat(Node::new_nil(loc), loc))
+                }
+                _ => {
+                    let node = self.parse_function()?;
+                    Ok(Node::new_splat(node, loc))
+                }
+            }
+        } else {
+            let node = self.parse_function()?;
+            Ok(Parser::as_mlhs_item(node))
+        }
+    }
+
+    /// Unwrap a parenthesized nested MLHS.
+    fn as_mlhs_item(node: Node) -> Node {
+        match node.kind {
+            NodeKind::CompStmt(mut nodes) if nodes.len() == 1 => match nodes[0].kind {
+                NodeKind::MulAssign(_, ref mrhs) if mrhs.is_empty() => nodes.remove(0),
+                _ => Node::new(NodeKind::CompStmt(nodes), node.loc),
+            },
+            _ => node,
+        }
+    }
+
+    fn check_mlhs(&mut self, lhs: &Node) -> Result<(), RubyError> {
+        match &lhs.kind {
+            NodeKind::Splat(lhs) => self.check_mlhs(lhs),
+            NodeKind::MulAssign(mlhs, _) => {
+                for lhs in mlhs {
+                    self.check_mlhs(lhs)?;
+                }
+                Ok(())
+            }
+            _ => self.check_lhs(lhs),
+        }
+    }
+
     fn parse_arg_list(
         &mut self,
         punct: impl Into<Option<Punct>>,
@@ -708,6 +828,7 @@ impl Parser {
             operation,
             send_args,
             true,
+            false,
             loc,
         ))
     }
@@ -715,22 +836,24 @@ impl Parser {
     fn parse_arglist(&mut self) -> Result<SendArgs, RubyError> {
         let first_arg = self.parse_arg()?;
         if self.is_line_term()? {
-            return Ok(SendArgs{args:vec![first_arg], kw_args:vec![], block:None});
+            return Ok(SendArgs{args:vec![first_arg], kw_args:vec![], kw_splat:vec![], block:None});
         }
 
         if first_arg.is_operation() && self.is_command()? {
             let args =
                 vec![self.parse_command(first_arg.as_method_name().unwrap(), first_arg.loc())?];
-            return Ok(SendArgs{args, kw_args:vec![], block:None});
+            return Ok(SendArgs{args, kw_args:vec![], kw_splat:vec![], block:None});
         }
 
         let mut args = vec![first_arg];
         let mut kw_args = vec![];
+        let mut kw_splat = vec![];
         let mut block = None;
         if self.consume_punct_no_term(Punct::Comma)? {
             let res = self.parse_argument_list(None)?;
             let mut new_args = res.args;
             kw_args = res.kw_args;
+            kw_splat = res.kw_splat;
             block = res.block;
             args.append(&mut new_args);
         }
@@ -741,7 +864,7 @@ impl Parser {
             }
             None => {}
         };
-        Ok(SendArgs{args, kw_args, block})
+        Ok(SendArgs{args, kw_args, kw_splat, block})
     }
 
     fn is_command(&mut self) -> Result<bool, RubyError> {
@@ -753,6 +876,8 @@ impl Parser {
             | TokenKind::Const(_, _, _)
             | TokenKind::NumLit(_)
             | TokenKind::FloatLit(_)
+            | TokenKind::RationalLit(_, _)
+            | TokenKind::ImaginaryLit(_)
             | TokenKind::StringLit(_)
             | TokenKind::OpenString(_) => Ok(true),
             TokenKind::Punct(p) => match p {
@@ -764,6 +889,9 @@ impl Parser {
                 | Punct::Plus
                 | Punct::Minus
                 | Punct::Arrow => Ok(true),
+                Punct::Rem => Ok(self.is_percent_literal(tok.loc)),
+                Punct::Question => Ok(self.is_char_literal(tok.loc)),
+                Punct::Shl => Ok(self.is_heredoc(tok.loc)),
                 _ => Ok(false),
             },
             TokenKind::Reserved(r) => match r {
@@ -789,31 +917,15 @@ impl Parser {
             self.check_lhs(&lhs)?;
             Ok(Node::new_mul_assign(vec![lhs], mrhs))
         } else if let TokenKind::Punct(Punct::AssignOp(op)) = self.peek_no_term()?.kind {
-            match op {
-                BinOp::LOr => {
-                    self.get()?;
-                    let rhs = self.parse_arg()?;
-                    self.check_lhs(&lhs)?;
-                    if let NodeKind::Ident(id) = lhs.kind {
-                        lhs = Node::new_lvar(id, lhs.loc());
-                    };
-                    let node = Node::new_binop(
-                        BinOp::LOr,
-                        lhs.clone(),
-                        Node::new_mul_assign(vec![lhs.clone()], vec![rhs]),
-                    );
-                    Ok(node)
-                }
-                _ => {
-                    self.get()?;
-                    let rhs = self.parse_arg()?;
-                    self.check_lhs(&lhs)?;
-                    Ok(Node::new_mul_assign(
-                        vec![lhs.clone()],
-                        vec![Node::new_binop(op, lhs, rhs)],
-                    ))
-                }
-            }
+            // LHS op= ARG
+            // LHS ||= ARG / LHS &&= ARG
+            self.get()?;
+            let rhs = self.parse_arg()?;
+            self.check_lhs(&lhs)?;
+            if let NodeKind::Ident(id) = lhs.kind {
+                lhs = Node::new_lvar(id, lhs.loc());
+            };
+            Ok(Node::new_assign_op(op, lhs, rhs))
         } else {
             Ok(lhs)
         }
@@ -852,21 +964,51 @@ impl Parser {
     }
 
     fn parse_arg_range(&mut self) -> Result<Node, RubyError> {
+        // beginless range: ..end / ...end
+        let loc = self.loc();
+        for (punct, exclude_end) in &[(Punct::Range2, false), (Punct::Range3, true)] {
+            if self.consume_punct_no_term(*punct)? {
+                let rhs = self.parse_arg_logical_or()?;
+                let loc = loc.merge(rhs.loc());
+                return Ok(Node::new_range(Node::new_nil(loc), rhs, *exclude_end, loc));
+            }
+        }
         let lhs = self.parse_arg_logical_or()?;
         if self.is_line_term()? {
             return Ok(lhs);
         }
-        if self.consume_punct(Punct::Range2)? {
-            let rhs = self.parse_arg_logical_or()?;
-            let loc = lhs.loc().merge(rhs.loc());
-            Ok(Node::new_range(lhs, rhs, false, loc))
+        let exclude_end = if self.consume_punct(Punct::Range2)? {
+            false
         } else if self.consume_punct(Punct::Range3)? {
-            let rhs = self.parse_arg_logical_or()?;
-            let loc = lhs.loc().merge(rhs.loc());
-            Ok(Node::new_range(lhs, rhs, true, loc))
+            true
         } else {
-            Ok(lhs)
-        }
+            return Ok(lhs);
+        };
+        // endless range: start.. / start...
+        let rhs = if self.is_range_end()? {
+            Node::new_nil(self.prev_loc())
+        } else {
+            self.parse_arg_logical_or()?
+        };
+        let loc = lhs.loc().merge(rhs.loc());
+        Ok(Node::new_range(lhs, rhs, exclude_end, loc))
+    }
+
+    /// Examine the next token, and return true if it can not begin the end of a range.
+    fn is_range_end(&mut self) -> Result<bool, RubyError> {
+        let tok = self.peek_no_term()?;
+        Ok(match tok.kind {
+            TokenKind::EOF | TokenKind::LineTerm => true,
+            TokenKind::Punct(Punct::RParen)
+            | TokenKind::Punct(Punct::RBracket)
+            | TokenKind::Punct(Punct::RBrace)
+            | TokenKind::Punct(Punct::Semi)
+            | TokenKind::Punct(Punct::Comma) => true,
+            TokenKind::Reserved(Reserved::Then)
+            | TokenKind::Reserved(Reserved::Do)
+            | TokenKind::Reserved(Reserved::End) => true,
+            _ => false,
+        })
     }
 
     fn parse_arg_logical_or(&mut self) -> Result<Node, RubyError> {
@@ -1014,6 +1156,27 @@ impl Parser {
         Ok(lhs)
     }
 
+    /// Convert a numeric literal token into a node.
+    /// Rational and imaginary literals are desugared into `Rational(num, den)` and `Complex(0, num)`.
+    fn parse_numeric_literal(&mut self, kind: &TokenKind, loc: Loc) -> Node {
+        let (method, args) = match kind {
+            TokenKind::NumLit(num) => return Node::new_integer(*num, loc),
+            TokenKind::FloatLit(num) => return Node::new_float(*num, loc),
+            TokenKind::RationalLit(num, den) => (
+                "Rational",
+                vec![Node::new_integer(*num, loc), Node::new_integer(*den, loc)],
+            ),
+            TokenKind::ImaginaryLit(kind) => (
+                "Complex",
+                vec![Node::new_integer(0, loc), self.parse_numeric_literal(kind, loc)],
+            ),
+            _ => unreachable!(),
+        };
+        let id = self.get_ident_id(method);
+        let send_args = SendArgs { args, kw_args: vec![], kw_splat: vec![], block: None };
+        Node::new_send(Node::new_self(loc), id, send_args, true, false, loc)
+    }
+
     fn parse_unary_minus(&mut self) -> Result<Node, RubyError> {
         self.save_state();
         if self.consume_punct(Punct::Minus)? {
@@ -1069,7 +1232,7 @@ impl Parser {
         let loc = node.loc();
         if self.consume_punct_no_term(Punct::LParen)? {
             // PRIMARY-METHOD : FNAME ( ARGS ) BLOCK?
-            let ArgList{args, kw_args, mut block} = self.parse_argument_list(Punct::RParen)?;
+            let ArgList{args, kw_args, kw_splat, mut block} = self.parse_argument_list(Punct::RParen)?;
             match self.parse_block()? {
                 Some(actual_block) => {
                     if block.is_some() {return Err(self.error_unexpected(actual_block.loc(), "Both block arg and actual block given."))}
@@ -1077,23 +1240,25 @@ impl Parser {
                 }
                 None => {}
             };
-            let send_args = SendArgs {args, kw_args, block};
+            let send_args = SendArgs {args, kw_args, kw_splat, block};
 
             Ok(Node::new_send(
                 Node::new_self(loc),
                 node.as_method_name().unwrap(),
                 send_args,
                 true,
+                false,
                 loc,
             ))
         } else if let Some(block) = self.parse_block()? {
             // PRIMARY-METHOD : FNAME BLOCK
-            let send_args = SendArgs {args:vec![], kw_args:vec![], block: Some(block)};
+            let send_args = SendArgs {args:vec![], kw_args:vec![], kw_splat:vec![], block: Some(block)};
             Ok(Node::new_send(
                 Node::new_self(loc),
                 node.as_method_name().unwrap(),
                 send_args,
                 true,
+                false,
                 loc,
             ))
         } else {
@@ -1130,46 +1295,61 @@ impl Parser {
         }*/
         loop {
             //let tok = self.peek()?;
-            node = if self.consume_punct(Punct::Dot)? {
+            let safe_nav = if self.consume_punct(Punct::Dot)? {
+                Some(false)
+            } else if self.consume_punct(Punct::SafeNav)? {
+                Some(true)
+            } else {
+                None
+            };
+            node = if let Some(safe_nav) = safe_nav {
                 // PRIMARY-METHOD :
+                // | PRIMARY &. FNAME ... => nil if PRIMARY is nil
                 // | PRIMARY . FNAME BLOCK => completed: true
                 // | PRIMARY . FNAME ( ARGS ) BLOCK? => completed: true
                 // | PRIMARY . FNAME => completed: false
-                let tok = self.get()?;
-                let id = match &tok.kind {
-                    TokenKind::Ident(s, has_suffix, _) => {
-                        let name = if *has_suffix {
-                            //if self.consume_punct_no_term(Punct::Question)? {
-                            //    s.clone() + "?"
-                            //} else if self.consume_punct_no_term(Punct::Not)? {
-                            //    s.clone() + "!"
-                            //} else {
+                let id = if self.peek_no_term()?.kind == TokenKind::Punct(Punct::LParen) {
+                    // PRIMARY . ( ARGS ) is evaluated as PRIMARY . call ( ARGS )
+                    self.get_ident_id("call")
+                } else {
+                    let tok = self.get()?;
+                    match &tok.kind {
+                        TokenKind::Ident(s, has_suffix, _) => {
+                            let name = if *has_suffix {
+                                //if self.consume_punct_no_term(Punct::Question)? {
+                                //    s.clone() + "?"
+                                //} else if self.consume_punct_no_term(Punct::Not)? {
+                                //    s.clone() + "!"
+                                //} else {
+                                    s.clone()
+                                //}
+                            } else {
                                 s.clone()
-                            //}
-                        } else {
-                            s.clone()
-                        };
-                        self.get_ident_id(name)
-                    }
-                    TokenKind::Reserved(r) => {
-                        let string = self.lexer.get_string_from_reserved(*r).to_owned();
-                        self.get_ident_id(string)
-                    }
-                    TokenKind::Punct(p) => self.parse_op_definable(p)?,
-                    _ => {
-                        return Err(
-                            self.error_unexpected(tok.loc(), "method name must be an identifier.")
-                        )
+                            };
+                            self.get_ident_id(name)
+                        }
+                        TokenKind::Reserved(r) => {
+                            let string = self.lexer.get_string_from_reserved(*r).to_owned();
+                            self.get_ident_id(string)
+                        }
+                        TokenKind::Punct(p) => self.parse_op_definable(p)?,
+                        _ => {
+                            return Err(
+                                self.error_unexpected(tok.loc(), "method name must be an identifier.")
+                            )
+                        }
                     }
                 };
                 let mut args = vec![];
                 let mut kw_args = vec![];
+                let mut kw_splat = vec![];
                 let mut block = None;
                 let mut completed = false;
                 if self.consume_punct_no_term(Punct::LParen)? {
                     let res = self.parse_argument_list(Punct::RParen)?;
                     args = res.args;
                     kw_args = res.kw_args;
+                    kw_splat = res.kw_splat;
                     block = res.block;
                     completed = true;
                 }
@@ -1189,12 +1369,13 @@ impl Parser {
                     }
                     _ => node,
                 };
-                let send_args = SendArgs {args, kw_args, block};
+                let send_args = SendArgs {args, kw_args, kw_splat, block};
                 Node::new_send(
                     node,
                     id,
                     send_args,
                     completed,
+                    safe_nav,
                     loc.merge(self.prev_loc()),
                 )
             } else if self.consume_punct_no_term(Punct::Scope)? {
@@ -1215,7 +1396,7 @@ impl Parser {
     }
 
     /// Parse argument list.
-    /// arg, *splat_arg, kw: kw_arg, &block <punct>
+    /// arg, *splat_arg, kw: kw_arg, **kw_splat, &block <punct>
     /// punct: punctuator for terminating arg list. Set None for unparenthesized argument list.
     fn parse_argument_list(
         &mut self,
@@ -1227,12 +1408,16 @@ impl Parser {
         };
         let mut args = vec![];
         let mut kw_args = vec![];
+        let mut kw_splat = vec![];
         let mut block = None;
         loop {
             if flag && self.consume_punct(punct)? {
-                return Ok(ArgList {args, kw_args, block});
+                return Ok(ArgList {args, kw_args, kw_splat, block});
             }
-            if self.consume_punct(Punct::Mul)? {
+            if self.consume_punct(Punct::DMul)? {
+                // double splat argument
+                kw_splat.push(self.parse_arg()?);
+            } else if self.consume_punct(Punct::Mul)? {
                 // splat argument
                 let loc = self.prev_loc();
                 let array = self.parse_arg()?;
@@ -1266,7 +1451,7 @@ impl Parser {
         if flag {
             self.expect_punct(punct)?
         };
-        Ok(ArgList {args, kw_args, block})
+        Ok(ArgList {args, kw_args, kw_splat, block})
     }
 
     fn parse_block(&mut self) -> Result<Option<Box<Node>>, RubyError> {
@@ -1363,8 +1548,14 @@ impl Parser {
             }
             TokenKind::NumLit(num) => Ok(Node::new_integer(*num, loc)),
             TokenKind::FloatLit(num) => Ok(Node::new_float(*num, loc)),
+            TokenKind::RationalLit(_, _) | TokenKind::ImaginaryLit(_) => {
+                Ok(self.parse_numeric_literal(&tok.kind, loc))
+            }
             TokenKind::StringLit(s) => Ok(self.parse_string_literal(s)?),
-            TokenKind::OpenString(s) => Ok(self.parse_interporated_string_literal(s)?),
+            TokenKind::OpenString(s) => {
+                let node = self.parse_interporated_string_literal(s)?;
+                self.parse_adjacent_strings(node)
+            }
             TokenKind::Punct(punct) => match punct {
                 Punct::Minus => match self.get()?.kind {
                     TokenKind::NumLit(num) => Ok(Node::new_integer(-num, loc)),
@@ -1415,30 +1606,39 @@ impl Parser {
                 }
                 Punct::Arrow => {
                     // Lambda literal
-                    let mut params = vec![];
+                    // -> ( PARAMS ) { COMPSTMT } | -> PARAMS do COMPSTMT end
+                    // is evaluated as `lambda` method call with a block.
                     self.context_stack.push(Context::new_block());
-                    if self.consume_punct(Punct::LParen)? {
-                        if !self.consume_punct(Punct::RParen)? {
-                            loop {
-                                let id = self.expect_ident()?;
-                                params.push(Node::new_param(id, self.prev_loc()));
-                                self.new_param(id, self.prev_loc())?;
-                                if !self.consume_punct(Punct::Comma)? {
-                                    break;
-                                }
-                            }
+                    let params = if self.consume_punct(Punct::LParen)? {
+                        if self.consume_punct(Punct::RParen)? {
+                            vec![]
+                        } else {
+                            let params = self.parse_params(TokenKind::Punct(Punct::RParen))?;
                             self.expect_punct(Punct::RParen)?;
+                            params
+                        }
+                    } else {
+                        match self.peek()?.kind {
+                            TokenKind::Punct(Punct::LBrace) | TokenKind::Reserved(Reserved::Do) => vec![],
+                            _ => self.parse_params(TokenKind::Punct(Punct::LBrace))?,
                         }
-                    } else if let TokenKind::Ident(_, _, _) = self.peek()?.kind {
-                        let id = self.expect_ident()?;
-                        self.new_param(id, self.prev_loc())?;
-                        params.push(Node::new_param(id, self.prev_loc()));
                     };
-                    self.expect_punct(Punct::LBrace)?;
-                    let body = self.parse_comp_stmt()?;
-                    self.expect_punct(Punct::RBrace)?;
+                    let body = if self.consume_punct(Punct::LBrace)? {
+                        let body = self.parse_comp_stmt()?;
+                        self.expect_punct(Punct::RBrace)?;
+                        body
+                    } else {
+                        self.expect_reserved(Reserved::Do)?;
+                        let body = self.parse_comp_stmt()?;
+                        self.expect_reserved(Reserved::End)?;
+                        body
+                    };
                     let lvar = self.context_stack.pop().unwrap().lvar;
-                    Ok(Node::new_proc(params, body, lvar, loc))
+                    let loc = loc.merge(self.prev_loc());
+                    let block = Node::new_proc(params, body, lvar, loc);
+                    let id = self.get_ident_id("lambda");
+                    let send_args = SendArgs {args: vec![], kw_args: vec![], kw_splat: vec![], block: Some(Box::new(block))};
+                    Ok(Node::new_send(Node::new_self(loc), id, send_args, true, false, loc))
                 }
                 Punct::Scope => {
                     let id = self.expect_const()?;
@@ -1448,10 +1648,16 @@ impl Parser {
                     let node = self.parse_regexp()?;
                     Ok(node)
                 }
-                Punct::Rem => {
-                    let node = self.parse_percent_notation()?;
-                    Ok(node)
+                Punct::Question => {
+                    let tok = self.lexer.lex_char_literal()?;
+                    match tok.kind {
+                        TokenKind::StringLit(s) => Ok(Node::new_string(s, loc.merge(tok.loc))),
+                        _ => unreachable!(),
+                    }
                 }
+                Punct::Rem => self.parse_percent_literal(loc),
+                Punct::BackQuote => self.parse_command_literal('`', loc),
+                Punct::Shl => self.parse_heredoc(),
                 _ => {
                     return Err(
                         self.error_unexpected(loc, format!("Unexpected token: {:?}", tok.kind))
@@ -1510,7 +1716,14 @@ impl Parser {
             TokenKind::Reserved(Reserved::Case) => {
                 let loc = self.prev_loc();
                 let cond = self.parse_expr()?;
+                if let NodeKind::PatternTest(cond, pattern) = cond.kind {
+                    // case EXPR in PATTERN ...
+                    return self.parse_case_in(*cond, Some(*pattern), loc);
+                }
                 self.consume_term()?;
+                if self.peek()?.kind == TokenKind::Reserved(Reserved::In) {
+                    return self.parse_case_in(cond, None, loc);
+                }
                 let mut when_ = vec![];
                 while self.consume_reserved(Reserved::When)? {
                     let arg = self.parse_arg_list(None)?;
@@ -1618,6 +1831,29 @@ impl Parser {
                     Ok(Node::new_next(val, loc))
                 }
             }
+            TokenKind::Reserved(Reserved::Redo) => Ok(Node::new_redo(loc)),
+            TokenKind::Reserved(Reserved::BEGIN) => {
+                // BEGIN { COMPSTMT } is evaluated before the rest of the program.
+                if self.context_stack.len() != 1 {
+                    return Err(self.error_unexpected(loc, "BEGIN is permitted only at toplevel."));
+                }
+                self.expect_punct(Punct::LBrace)?;
+                let body = self.parse_comp_stmt()?;
+                self.expect_punct(Punct::RBrace)?;
+                self.begin_blocks.push(body);
+                Ok(Node::new_nil(loc))
+            }
+            TokenKind::Reserved(Reserved::END) => {
+                // END { COMPSTMT } is evaluated at exit, as `at_exit { COMPSTMT }`.
+                if self.peek_no_term()?.kind != TokenKind::Punct(Punct::LBrace) {
+                    let loc = self.loc();
+                    return Err(self.error_unexpected(loc, "Expected '{'."));
+                }
+                let block = self.parse_block()?;
+                let id = self.get_ident_id("at_exit");
+                let send_args = SendArgs {args: vec![], kw_args: vec![], kw_splat: vec![], block};
+                Ok(Node::new_send(Node::new_self(loc), id, send_args, true, false, loc))
+            }
             TokenKind::Reserved(Reserved::True) => Ok(Node::new_bool(true, loc)),
             TokenKind::Reserved(Reserved::False) => Ok(Node::new_bool(false, loc)),
             TokenKind::Reserved(Reserved::Nil) => Ok(Node::new_nil(loc)),
@@ -1638,6 +1874,8 @@ impl Parser {
             | TokenKind::Const(_, _, _)
             | TokenKind::NumLit(_)
             | TokenKind::FloatLit(_)
+            | TokenKind::RationalLit(_, _)
+            | TokenKind::ImaginaryLit(_)
             | TokenKind::StringLit(_)
             | TokenKind::OpenString(_) => Ok(true),
             TokenKind::Punct(p) => match p {
@@ -1645,7 +1883,11 @@ impl Parser {
                 | Punct::LBracket
                 | Punct::Colon
                 | Punct::Scope
-                | Punct::Arrow => Ok(true),
+                | Punct::Arrow
+                | Punct::BackQuote => Ok(true),
+                Punct::Rem => Ok(self.is_percent_literal(tok.loc)),
+                Punct::Question => Ok(self.is_char_literal(tok.loc)),
+                Punct::Shl => Ok(self.is_heredoc(tok.loc)),
                 _ => Ok(false),
             },
             TokenKind::Reserved(r) => match r {
@@ -1656,14 +1898,89 @@ impl Parser {
         }
     }
 
+    /// Returns true if `%` at `loc` is followed by the kind or the delimiter of a percent literal.
+    fn is_percent_literal(&self, loc: Loc) -> bool {
+        let code = &self.lexer.source_info.code;
+        let pos = loc.1 as usize + 1;
+        match code.get(pos) {
+            Some(c) if "qQwWiIrsx".contains(*c) => match code.get(pos + 1) {
+                Some(c) => c.is_ascii_punctuation(),
+                None => false,
+            },
+            Some(c) => c.is_ascii_punctuation(),
+            None => false,
+        }
+    }
+
+    /// Returns true if `?` at `loc` is followed by a character literal (?a, ?\n).
+    fn is_char_literal(&self, loc: Loc) -> bool {
+        let code = &self.lexer.source_info.code;
+        let pos = loc.1 as usize + 1;
+        match code.get(pos) {
+            Some('\\') => true,
+            Some(c) if c.is_alphanumeric() || *c == '_' => match code.get(pos + 1) {
+                Some(c) => !(c.is_alphanumeric() || *c == '_'),
+                None => true,
+            },
+            Some(c) => !c.is_whitespace(),
+            None => false,
+        }
+    }
+
+    /// Returns true if `<<` at `loc` is followed by the identifier of a heredoc (<<ID, <<~ID, <<-'ID').
+    fn is_heredoc(&self, loc: Loc) -> bool {
+        let code = &self.lexer.source_info.code;
+        let mut pos = loc.1 as usize + 1;
+        if let Some('~') | Some('-') = code.get(pos) {
+            pos += 1;
+        }
+        match code.get(pos) {
+            Some(c) => c.is_alphabetic() || *c == '_' || *c == '\'' || *c == '"',
+            None => false,
+        }
+    }
+
     fn parse_string_literal(&mut self, s: &str) -> Result<Node, RubyError> {
         let loc = self.prev_loc();
-        let mut s = s.to_string();
-        while let TokenKind::StringLit(next_s) = self.peek_no_term()?.kind {
-            self.get()?;
-            s = format!("{}{}", s, next_s);
+        self.parse_adjacent_strings(Node::new_string(s.to_string(), loc))
+    }
+
+    /// Concatenate adjacent string literals (e.g. "a" "b#{c}") to `node`.
+    fn parse_adjacent_strings(&mut self, mut node: Node) -> Result<Node, RubyError> {
+        loop {
+            let next = match self.peek_no_term()?.kind {
+                TokenKind::StringLit(s) => {
+                    self.get()?;
+                    Node::new_string(s, self.prev_loc())
+                }
+                TokenKind::OpenString(s) => {
+                    self.get()?;
+                    self.parse_interporated_string_literal(&s)?
+                }
+                _ => return Ok(node),
+            };
+            let loc = node.loc().merge(next.loc());
+            node = match (&node.kind, &next.kind) {
+                (NodeKind::String(s1), NodeKind::String(s2)) => {
+                    Node::new_string(format!("{}{}", s1, s2), loc)
+                }
+                _ => {
+                    let mut nodes = Parser::string_parts(node);
+                    nodes.append(&mut Parser::string_parts(next));
+                    Node::new_interporated_string(nodes, loc)
+                }
+            };
+        }
+    }
+
+    fn string_parts(node: Node) -> Vec<Node> {
+        match node {
+            Node {
+                kind: NodeKind::InterporatedString(nodes),
+                ..
+            } => nodes,
+            node => vec![node],
         }
-        Ok(Node::new_string(s, loc))
     }
 
     fn parse_interporated_string_literal(&mut self, s: &str) -> Result<Node, RubyError> {
@@ -1684,11 +2001,6 @@ impl Parser {
                     nodes.push(Node::new_string(s.clone(), self.loc()));
                     self.get()?;
                 }
-                TokenKind::OpenString(s) => {
-                    let s = s.clone();
-                    self.get()?;
-                    self.parse_interporated_string_literal(&s)?;
-                }
                 TokenKind::EOF => {
                     let loc = self.loc();
                     return Err(self.error_unexpected(loc, "Unexpectd EOF."));
@@ -1735,18 +2047,129 @@ impl Parser {
         }
     }
 
-    fn parse_percent_notation(&mut self) -> Result<Node, RubyError> {
-        let tok = self.lexer.lex_percent_notation()?;
-        let loc = tok.loc;
-        if let TokenKind::PercentNotation(_kind, content) = tok.kind {
-            let ary = content
-                .split(' ')
-                .map(|x| Node::new_string(x.to_string(), loc))
-                .rev()
-                .collect();
-            Ok(Node::new_array(ary, tok.loc))
-        } else {
-            panic!();
+    fn parse_heredoc(&mut self) -> Result<Node, RubyError> {
+        let tok = self.lexer.lex_heredoc()?;
+        match tok.kind {
+            TokenKind::StringLit(s) => Ok(Node::new_string(s, tok.loc)),
+            TokenKind::OpenString(s) => self.parse_interporated_string_literal(&s),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Parse command literal (`...` or %x(...)) as a call of Kernel#`.
+    fn parse_command_literal(&mut self, term: char, loc: Loc) -> Result<Node, RubyError> {
+        let tok = self.lexer.lex_command_literal(term)?;
+        let command = match tok.kind {
+            TokenKind::StringLit(s) => Node::new_string(s, tok.loc),
+            TokenKind::OpenString(s) => self.parse_interporated_string_literal(&s)?,
+            _ => return Err(self.error_unexpected(tok.loc, "Illegal command literal.")),
+        };
+        let loc = loc.merge(self.prev_loc());
+        let id = self.get_ident_id("`");
+        let send_args = SendArgs {
+            args: vec![command],
+            kw_args: vec![],
+            kw_splat: vec![],
+            block: None,
+        };
+        Ok(Node::new_send(Node::new_self(loc), id, send_args, true, false, loc))
+    }
+
+    /// Parse percent literal (%w %W %i %I %q %Q %r %s %x or %( )).
+    fn parse_percent_literal(&mut self, loc: Loc) -> Result<Node, RubyError> {
+        let mut lit = self.lexer.lex_percent_literal()?;
+        let words = self.parse_percent_words(&mut lit, loc)?;
+        let node = match lit.kind {
+            'w' | 'W' => {
+                let ary = words.into_iter().map(|word| Parser::percent_string(word, loc)).rev().collect();
+                Node::new_array(ary, loc)
+            }
+            'i' | 'I' => {
+                let mut ary = vec![];
+                for word in words {
+                    ary.push(self.percent_symbol(word, loc));
+                }
+                ary.reverse();
+                Node::new_array(ary, loc)
+            }
+            's' => {
+                let word = words.into_iter().next().unwrap();
+                self.percent_symbol(word, loc)
+            }
+            'r' => Node::new_regexp(words.into_iter().next().unwrap(), loc),
+            'x' => {
+                let command = Parser::percent_string(words.into_iter().next().unwrap(), loc);
+                let id = self.get_ident_id("`");
+                let send_args = SendArgs {
+                    args: vec![command],
+                    kw_args: vec![],
+                    kw_splat: vec![],
+                    block: None,
+                };
+                Node::new_send(Node::new_self(loc), id, send_args, true, false, loc)
+            }
+            _ => Parser::percent_string(words.into_iter().next().unwrap(), loc),
+        };
+        Ok(node)
+    }
+
+    /// Parse the body of a percent literal into words.
+    /// Each word is a list of string nodes and interpolated expressions.
+    /// Literals other than word lists always consist of one word.
+    fn parse_percent_words(&mut self, lit: &mut PercentLiteral, loc: Loc) -> Result<Vec<Vec<Node>>, RubyError> {
+        let mut words = vec![];
+        let mut word = vec![];
+        loop {
+            let (parts, interpolated) = self.lexer.lex_percent_body(lit)?;
+            for part in parts {
+                match part {
+                    PercentPart::Str(s) => {
+                        if !s.is_empty() {
+                            word.push(Node::new_string(s, loc));
+                        }
+                    }
+                    PercentPart::Sep => {
+                        if !word.is_empty() {
+                            words.push(std::mem::replace(&mut word, vec![]));
+                        }
+                    }
+                }
+            }
+            if !interpolated {
+                break;
+            }
+            word.push(self.parse_comp_stmt()?);
+            self.expect_punct(Punct::RBrace)?;
+        }
+        if !word.is_empty() || !lit.is_word_list() {
+            words.push(word);
+        }
+        Ok(words)
+    }
+
+    fn percent_string(mut word: Vec<Node>, loc: Loc) -> Node {
+        if word.is_empty() {
+            return Node::new_string("".to_string(), loc);
+        }
+        if word.len() == 1 {
+            if let NodeKind::String(_) = word[0].kind {
+                return word.remove(0);
+            }
+        }
+        Node::new_interporated_string(word, loc)
+    }
+
+    fn percent_symbol(&mut self, word: Vec<Node>, loc: Loc) -> Node {
+        let node = Parser::percent_string(word, loc);
+        match &node.kind {
+            NodeKind::String(s) => {
+                let id = self.get_ident_id(s.clone());
+                Node::new_symbol(id, loc)
+            }
+            _ => {
+                let method = self.ident_table.get_ident_id("to_sym");
+                Node::new_send_noarg(node, method, true, loc)
+            }
         }
     }
 
@@ -1788,6 +2211,361 @@ impl Parser {
         Ok(Node::new_hash(kvp, loc.merge(self.prev_loc())))
     }
 
+    fn parse_case_in(
+        &mut self,
+        cond: Node,
+        mut first: Option<Pattern>,
+        loc: Loc,
+    ) -> Result<Node, RubyError> {
+        //  case EXPR
+        //  (in PATTERN [if|unless EXPR] THEN COMPSTMT)+
+        //  [else COMPSTMT]
+        //  end
+        // `first` is the pattern of the first branch already parsed with EXPR.
+        let mut in_ = vec![];
+        while first.is_some() || self.consume_reserved(Reserved::In)? {
+            let pattern = match first.take() {
+                Some(pattern) => pattern,
+                None => self.parse_pattern_top()?,
+            };
+            let guard = if self.consume_reserved_no_skip_line_term(Reserved::If)? {
+                Some(self.parse_expr()?)
+            } else if self.consume_reserved_no_skip_line_term(Reserved::Unless)? {
+                let loc = self.prev_loc();
+                let cond = self.parse_expr()?;
+                Some(Node::new_unop(UnOp::Not, cond, loc))
+            } else {
+                None
+            };
+            self.parse_then()?;
+            let body = self.parse_comp_stmt()?;
+            in_.push(InBranch::new(pattern, guard, body));
+        }
+        let else_ = if self.consume_reserved(Reserved::Else)? {
+            Some(self.parse_comp_stmt()?)
+        } else {
+            None
+        };
+        self.expect_reserved(Reserved::End)?;
+        Ok(Node::new_case_in(cond, in_, else_, loc.merge(self.prev_loc())))
+    }
+
+    /// Parse a top-level pattern, in which brackets of array patterns and
+    /// braces of hash patterns can be omitted.
+    fn parse_pattern_top(&mut self) -> Result<Pattern, RubyError> {
+        let loc = self.loc();
+        if self.is_pattern_label()? || self.peek_no_term()?.kind == TokenKind::Punct(Punct::DMul) {
+            return self.parse_pattern_hash(None, None);
+        }
+        let first = self.parse_pattern_elem()?;
+        if !self.consume_punct_no_term(Punct::Comma)? {
+            return match first {
+                PatternElem::Pattern(pattern) => Ok(pattern),
+                PatternElem::Splat(_) => self.pattern_from_elems(None, vec![first], loc),
+            };
+        }
+        let mut elems = vec![first];
+        loop {
+            elems.push(self.parse_pattern_elem()?);
+            if !self.consume_punct_no_term(Punct::Comma)? {
+                break;
+            }
+        }
+        self.pattern_from_elems(None, elems, loc)
+    }
+
+    /// Parse a pattern.
+    /// PATTERN : PRIMARY-PATTERN [| PRIMARY-PATTERN]* [=> IDENT]
+    fn parse_pattern(&mut self) -> Result<Pattern, RubyError> {
+        let mut alt = vec![self.parse_pattern_primary()?];
+        while self.consume_punct_no_term(Punct::BitOr)? {
+            alt.push(self.parse_pattern_primary()?);
+        }
+        let mut pattern = if alt.len() == 1 {
+            alt.pop().unwrap()
+        } else {
+            Pattern::Alt(alt)
+        };
+        while self.consume_punct_no_term(Punct::FatArrow)? {
+            let id = self.expect_ident()?;
+            self.add_local_var_if_new(id);
+            pattern = Pattern::Capture(Box::new(pattern), id);
+        }
+        Ok(pattern)
+    }
+
+    fn parse_pattern_primary(&mut self) -> Result<Pattern, RubyError> {
+        let tok = self.peek()?;
+        let loc = tok.loc();
+        match &tok.kind {
+            TokenKind::Punct(Punct::LBracket) => {
+                self.get()?;
+                let elems = self.parse_pattern_elems(Punct::RBracket)?;
+                self.pattern_from_elems(None, elems, loc)
+            }
+            TokenKind::Punct(Punct::LBrace) => {
+                self.get()?;
+                self.parse_pattern_hash(None, Some(Punct::RBrace))
+            }
+            TokenKind::Punct(Punct::BitXor) => {
+                // pin: ^var, ^@ivar, ^$gvar, ^(expr)
+                self.get()?;
+                let node = if self.consume_punct_no_term(Punct::LParen)? {
+                    let node = self.parse_expr()?;
+                    self.expect_punct(Punct::RParen)?;
+                    node
+                } else {
+                    let node = self.parse_primary()?;
+                    match node.kind {
+                        NodeKind::LocalVar(_) | NodeKind::InstanceVar(_) | NodeKind::GlobalVar(_) => node,
+                        _ => return Err(self.error_unexpected(node.loc(), "Invalid pin pattern.")),
+                    }
+                };
+                Ok(Pattern::Value(Box::new(node)))
+            }
+            TokenKind::Ident(name, _, _) => {
+                let id = self.get_ident_id(name.clone());
+                self.get()?;
+                self.add_local_var_if_new(id);
+                Ok(Pattern::Bind(id))
+            }
+            TokenKind::Const(_, _, _) | TokenKind::Punct(Punct::Scope) => {
+                let mut node = if self.consume_punct(Punct::Scope)? {
+                    let id = self.expect_const()?;
+                    Node::new_const(id, true, loc)
+                } else {
+                    let id = self.expect_const()?;
+                    Node::new_const(id, false, loc)
+                };
+                while self.consume_punct_no_term(Punct::Scope)? {
+                    let id = self.expect_const()?;
+                    node = Node::new_scope(node, id, loc.merge(self.prev_loc()));
+                }
+                // Const(...) / Const[...]
+                for (open, close) in &[(Punct::LParen, Punct::RParen), (Punct::LBracket, Punct::RBracket)] {
+                    if self.consume_punct_no_term(*open)? {
+                        if self.is_pattern_label()?
+                            || self.peek()?.kind == TokenKind::Punct(Punct::DMul)
+                        {
+                            return self.parse_pattern_hash(Some(node), Some(*close));
+                        }
+                        let elems = self.parse_pattern_elems(*close)?;
+                        return self.pattern_from_elems(Some(node), elems, loc);
+                    }
+                }
+                self.parse_pattern_range(node)
+            }
+            _ => {
+                let node = self.parse_pattern_value()?;
+                self.parse_pattern_range(node)
+            }
+        }
+    }
+
+    /// Parse a value for value patterns.
+    fn parse_pattern_value(&mut self) -> Result<Node, RubyError> {
+        let loc = self.loc();
+        for (punct, exclude_end) in &[(Punct::Range2, false), (Punct::Range3, true)] {
+            // beginless range: ..end / ...end
+            if self.consume_punct(*punct)? {
+                let end = self.parse_unary_minus()?;
+                let loc = loc.merge(end.loc());
+                return Ok(Node::new_range(Node::new_nil(loc), end, *exclude_end, loc));
+            }
+        }
+        self.parse_unary_minus()
+    }
+
+    fn parse_pattern_range(&mut self, start: Node) -> Result<Pattern, RubyError> {
+        let loc = start.loc();
+        for (punct, exclude_end) in &[(Punct::Range2, false), (Punct::Range3, true)] {
+            if self.consume_punct_no_term(*punct)? {
+                // endless range: start.. / start...
+                let end = if self.is_pattern_end()? {
+                    Node::new_nil(self.prev_loc())
+                } else {
+                    self.parse_unary_minus()?
+                };
+                let loc = loc.merge(end.loc());
+                let range = Node::new_range(start, end, *exclude_end, loc);
+                return Ok(Pattern::Value(Box::new(range)));
+            }
+        }
+        Ok(Pattern::Value(Box::new(start)))
+    }
+
+    /// Parse elements of an array pattern or a find pattern until `term`.
+    fn parse_pattern_elems(&mut self, term: Punct) -> Result<Vec<PatternElem>, RubyError> {
+        let mut elems = vec![];
+        loop {
+            if self.consume_punct(term)? {
+                return Ok(elems);
+            }
+            elems.push(self.parse_pattern_elem()?);
+            if !self.consume_punct(Punct::Comma)? {
+                self.expect_punct(term)?;
+                return Ok(elems);
+            }
+        }
+    }
+
+    fn parse_pattern_elem(&mut self) -> Result<PatternElem, RubyError> {
+        if self.consume_punct(Punct::Mul)? {
+            match self.peek_no_term()?.kind {
+                TokenKind::Ident(_, _, _) => {
+                    let id = self.expect_ident()?;
+                    self.add_local_var_if_new(id);
+                    Ok(PatternElem::Splat(Some(id)))
+                }
+                _ => Ok(PatternElem::Splat(None)),
+            }
+        } else {
+            Ok(PatternElem::Pattern(self.parse_pattern()?))
+        }
+    }
+
+    fn pattern_from_elems(
+        &mut self,
+        constant: Option<Node>,
+        mut elems: Vec<PatternElem>,
+        loc: Loc,
+    ) -> Result<Pattern, RubyError> {
+        let constant = constant.map(Box::new);
+        let splats: Vec<usize> = elems
+            .iter()
+            .enumerate()
+            .filter_map(|(i, elem)| match elem {
+                PatternElem::Splat(_) => Some(i),
+                _ => None,
+            })
+            .collect();
+        let len = elems.len();
+        let unwrap = |elems: Vec<PatternElem>| -> Vec<Pattern> {
+            elems
+                .into_iter()
+                .map(|elem| match elem {
+                    PatternElem::Pattern(pattern) => pattern,
+                    PatternElem::Splat(_) => unreachable!(),
+                })
+                .collect()
+        };
+        match splats.len() {
+            0 => Ok(Pattern::Array { constant, pre: unwrap(elems), rest: None, post: vec![] }),
+            1 => {
+                let mut post = elems.split_off(splats[0]);
+                let rest = match post.remove(0) {
+                    PatternElem::Splat(rest) => rest,
+                    _ => unreachable!(),
+                };
+                Ok(Pattern::Array { constant, pre: unwrap(elems), rest: Some(rest), post: unwrap(post) })
+            }
+            2 if splats[0] == 0 && splats[1] == len - 1 && len > 2 => {
+                let post = match elems.pop().unwrap() {
+                    PatternElem::Splat(post) => post,
+                    _ => unreachable!(),
+                };
+                let pre = match elems.remove(0) {
+                    PatternElem::Splat(pre) => pre,
+                    _ => unreachable!(),
+                };
+                Ok(Pattern::Find { constant, pre, mid: unwrap(elems), post })
+            }
+            _ => Err(self.error_unexpected(loc, "Invalid pattern.")),
+        }
+    }
+
+    /// Parse a hash pattern until `term`.
+    /// Set None to `term` for a hash pattern without braces.
+    fn parse_pattern_hash(
+        &mut self,
+        constant: Option<Node>,
+        term: Option<Punct>,
+    ) -> Result<Pattern, RubyError> {
+        let mut pairs = vec![];
+        let mut rest = HashPatternRest::Any;
+        loop {
+            if let Some(term) = term {
+                if self.consume_punct(term)? {
+                    break;
+                }
+            }
+            if self.consume_punct(Punct::DMul)? {
+                // **rest / **nil
+                rest = if self.consume_reserved(Reserved::Nil)? {
+                    HashPatternRest::NoKey
+                } else {
+                    let id = self.expect_ident()?;
+                    self.add_local_var_if_new(id);
+                    HashPatternRest::Bind(id)
+                };
+            } else {
+                let tok = self.get()?;
+                let key = match &tok.kind {
+                    TokenKind::Ident(name, _, _) | TokenKind::Const(name, _, _) => {
+                        self.get_ident_id(name.clone())
+                    }
+                    _ => return Err(self.error_unexpected(tok.loc(), "Expect a key of hash pattern.")),
+                };
+                self.expect_punct(Punct::Colon)?;
+                let pattern = if self.is_pattern_end()? {
+                    // key: binds the value to the local variable `key`.
+                    self.add_local_var_if_new(key);
+                    Pattern::Bind(key)
+                } else {
+                    self.parse_pattern()?
+                };
+                pairs.push((key, pattern));
+            }
+            let comma = match term {
+                Some(_) => self.consume_punct(Punct::Comma)?,
+                None => self.consume_punct_no_term(Punct::Comma)?,
+            };
+            if !comma {
+                if let Some(term) = term {
+                    self.expect_punct(term)?;
+                }
+                break;
+            }
+        }
+        // {} matches only an empty hash.
+        if term == Some(Punct::RBrace) && pairs.is_empty() && rest == HashPatternRest::Any {
+            rest = HashPatternRest::NoKey;
+        }
+        Ok(Pattern::Hash { constant: constant.map(Box::new), pairs, rest })
+    }
+
+    /// Examine whether the next tokens are `label:` of hash patterns.
+    fn is_pattern_label(&mut self) -> Result<bool, RubyError> {
+        self.save_state();
+        let res = match self.get()?.kind {
+            TokenKind::Ident(_, _, _) | TokenKind::Const(_, _, _) => {
+                self.consume_punct_no_term(Punct::Colon)?
+            }
+            _ => false,
+        };
+        self.restore_state();
+        Ok(res)
+    }
+
+    /// Examine whether the next token terminates a pattern.
+    fn is_pattern_end(&mut self) -> Result<bool, RubyError> {
+        let tok = self.peek_no_term()?;
+        if tok.is_line_term() || tok.check_stmt_end() {
+            return Ok(true);
+        }
+        Ok(match tok.kind {
+            TokenKind::Punct(Punct::Comma)
+            | TokenKind::Punct(Punct::BitOr)
+            | TokenKind::Punct(Punct::FatArrow) => true,
+            TokenKind::Reserved(Reserved::Then)
+            | TokenKind::Reserved(Reserved::If)
+            | TokenKind::Reserved(Reserved::Unless)
+            | TokenKind::Reserved(Reserved::And)
+            | TokenKind::Reserved(Reserved::Or) => true,
+            _ => false,
+        })
+    }
+
     fn parse_if_then(&mut self) -> Result<Node, RubyError> {
         //  if EXPR THEN
         //      COMPSTMT
@@ -1877,6 +2655,26 @@ impl Parser {
             TokenKind::Punct(Punct::Plus) => self.get_ident_id("+"),
             TokenKind::Punct(Punct::Minus) => self.get_ident_id("-"),
             TokenKind::Punct(Punct::Mul) => self.get_ident_id("*"),
+            TokenKind::Punct(Punct::Div) => self.get_ident_id("/"),
+            TokenKind::Punct(Punct::Rem) => self.get_ident_id("%"),
+            TokenKind::Punct(Punct::DMul) => self.get_ident_id("**"),
+            TokenKind::Punct(Punct::Shl) => self.get_ident_id("<<"),
+            TokenKind::Punct(Punct::Shr) => self.get_ident_id(">>"),
+            TokenKind::Punct(Punct::BitAnd) => self.get_ident_id("&"),
+            TokenKind::Punct(Punct::BitOr) => self.get_ident_id("|"),
+            TokenKind::Punct(Punct::BitXor) => self.get_ident_id("^"),
+            TokenKind::Punct(Punct::BitNot) => self.get_ident_id("~"),
+            TokenKind::Punct(Punct::Not) => self.get_ident_id("!"),
+            TokenKind::Punct(Punct::Eq) => self.get_ident_id("=="),
+            TokenKind::Punct(Punct::Ne) => self.get_ident_id("!="),
+            TokenKind::Punct(Punct::TEq) => self.get_ident_id("==="),
+            TokenKind::Punct(Punct::Match) => self.get_ident_id("=~"),
+            TokenKind::Punct(Punct::Lt) => self.get_ident_id("<"),
+            TokenKind::Punct(Punct::Le) => self.get_ident_id("<="),
+            TokenKind::Punct(Punct::Gt) => self.get_ident_id(">"),
+            TokenKind::Punct(Punct::Ge) => self.get_ident_id(">="),
+            TokenKind::Punct(Punct::Cmp) => self.get_ident_id("<=>"),
+            TokenKind::Punct(Punct::BackQuote) => self.get_ident_id("`"),
             TokenKind::Punct(Punct::LBracket) => {
                 if self.consume_punct_no_term(Punct::RBracket)? {
                     if self.consume_punct_no_term(Punct::Assign)? {
@@ -1918,16 +2716,57 @@ impl Parser {
         }
 
         let mut args = vec![];
+        let mut destructs = vec![];
         let mut state = Kind::Reqired;
         loop {
             let mut loc = self.loc();
-            if self.consume_punct(Punct::BitAnd)? {
+            if self.consume_punct(Punct::LParen)? {
+                // Destructuring param: `(a, (b, *c))` is received by a hidden param
+                // and assigned at the beginning of the method or block.
+                let mlhs = self.parse_mlhs_param()?;
+                loc = loc.merge(self.prev_loc());
+                let id = self.get_ident_id(format!("(destructured {})", loc.0));
+                match state {
+                    Kind::Reqired => args.push(Node::new_param(id, loc)),
+                    Kind::PostReq | Kind::Optional | Kind::Rest => {
+                        args.push(Node::new_post_param(id, loc));
+                        state = Kind::PostReq;
+                    }
+                    _ => {
+                        return Err(self.error_unexpected(
+                            loc,
+                            "Required parameter is not allowed in ths position.",
+                        ))
+                    }
+                }
+                self.new_param(id, loc)?;
+                destructs.push(Node::new_mul_assign(mlhs, vec![Node::new_lvar(id, loc)]));
+            } else if self.consume_punct(Punct::BitAnd)? {
                 // Block param
                 let id = self.expect_ident()?;
                 loc = loc.merge(self.prev_loc());
                 args.push(Node::new_block_param(id, loc));
                 self.new_block_param(id, loc)?;
                 break;
+            } else if self.consume_punct(Punct::DMul)? {
+                // Keyword rest param
+                if self.consume_reserved(Reserved::Nil)? {
+                    loc = loc.merge(self.prev_loc());
+                    args.push(Node::new_nokw_param(loc));
+                } else {
+                    let id = self.expect_ident()?;
+                    loc = loc.merge(self.prev_loc());
+                    args.push(Node::new_kwrest_param(id, loc));
+                    self.new_param(id, loc)?;
+                }
+                if state == Kind::KWRest {
+                    return Err(self.error_unexpected(
+                        loc,
+                        "Keyword rest parameter is not allowed in ths position.",
+                    ));
+                } else {
+                    state = Kind::KWRest;
+                }
             } else if self.consume_punct(Punct::Mul)? {
                 // Splat(Rest) param
                 let id = self.expect_ident()?;
@@ -1945,7 +2784,12 @@ impl Parser {
                 let id = self.expect_ident()?;
                 if self.consume_punct(Punct::Assign)? {
                     // Optional param
-                    let default = self.parse_arg()?;
+                    // In block params, the default value must not contain `|`.
+                    let default = if terminator == TokenKind::Punct(Punct::BitOr) {
+                        self.parse_function()?
+                    } else {
+                        self.parse_arg()?
+                    };
                     loc = loc.merge(self.prev_loc());
                     match state {
                         Kind::Reqired => state = Kind::Optional,
@@ -1964,6 +2808,8 @@ impl Parser {
                     let next = self.peek_no_term()?.kind;
                     let default = if next == TokenKind::Punct(Punct::Comma) || next == terminator || next == TokenKind::LineTerm {
                         None
+                    } else if terminator == TokenKind::Punct(Punct::BitOr) {
+                        Some(self.parse_function()?)
                     } else {
                         Some(self.parse_arg()?)
                     };
@@ -2004,9 +2850,64 @@ impl Parser {
                 break;
             }
         };
+        // Local variables in destructuring params are registered after all the params.
+        for node in destructs {
+            if let NodeKind::MulAssign(mlhs, _) = &node.kind {
+                for lhs in mlhs {
+                    self.check_mlhs(lhs)?;
+                }
+            }
+            args.push(node);
+        }
         Ok(args)
     }
 
+    /// Parse destructuring param after `(`: `a, (b, c), *d)`
+    fn parse_mlhs_param(&mut self) -> Result<Vec<Node>, RubyError> {
+        let mut mlhs = vec![];
+        loop {
+            let loc = self.loc();
+            if self.consume_punct(Punct::LParen)? {
+                let nested = self.parse_mlhs_param()?;
+                mlhs.push(Node::new_mlhs(nested, loc.merge(self.prev_loc())));
+            } else if self.consume_punct(Punct::Mul)? {
+                let node = match self.peek()?.kind {
+                    TokenKind::Punct(Punct::Comma) | TokenKind::Punct(Punct::RParen) => {
+                        Node::new_nil(loc)
+                    }
+                    _ => Node::new_identifier(self.expect_ident()?, self.prev_loc()),
+                };
+                mlhs.push(Node::new_splat(node, loc));
+            } else {
+                let id = self.expect_ident()?;
+                mlhs.push(Node::new_identifier(id, self.prev_loc()));
+            }
+            if !self.consume_punct(Punct::Comma)? {
+                break;
+            }
+            if self.peek()?.kind == TokenKind::Punct(Punct::RParen) {
+                // A trailing comma works as an anonymous splat: `(a, )`
+                let loc = self.prev_loc();
+                mlhs.push(Node::new_splat(Node::new_nil(loc), loc));
+                break;
+            }
+        }
+        self.expect_punct(Punct::RParen)?;
+        if mlhs.len() == 1 && !Parser::is_splat(&mlhs[0]) {
+            // `(a)` works as `(a, *)`
+            let loc = mlhs[0].loc();
+            mlhs.push(Node::new_splat(Node::new_nil(loc), loc));
+        }
+        Ok(mlhs)
+    }
+
+    fn is_splat(node: &Node) -> bool {
+        match node.kind {
+            NodeKind::Splat(_) => true,
+            _ => false,
+        }
+    }
+
     // ( )
     // ( ident [, ident]* )
     fn parse_def_params(&mut self) -> Result<Vec<Node>, RubyError> {
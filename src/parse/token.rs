@@ -42,6 +42,8 @@ pub enum TokenKind {
     Const(String, bool, bool),
     NumLit(i64),
     FloatLit(f64),
+    RationalLit(i64, i64),
+    ImaginaryLit(Box<TokenKind>),
     StringLit(String),
     Reserved(Reserved),
     Punct(Punct),
@@ -49,7 +51,6 @@ pub enum TokenKind {
     InterString(String),
     CloseString(String),
     OpenRegex(String),
-    PercentNotation(char, String),
     Space,
     LineTerm,
 }
@@ -59,6 +60,7 @@ pub enum Reserved {
     BEGIN,
     END,
     Alias,
+    And,
     Begin,
     Break,
     Case,
@@ -77,8 +79,12 @@ pub enum Reserved {
     Module,
     Next,
     Nil,
-    Return,
+    Not,
+    Or,
+    Redo,
     Rescue,
+    Retry,
+    Return,
     Self_,
     Then,
     True,
@@ -102,6 +108,7 @@ pub enum Punct {
     Scope,
     Comma,
     Dot,
+    SafeNav,
     Question,
     Range2,
     Range3,
@@ -134,6 +141,7 @@ pub enum Punct {
     Match,
 
     Backslash,
+    BackQuote,
     Arrow,
     FatArrow,
 }
@@ -184,6 +192,14 @@ impl Token {
         Annot::new(TokenKind::FloatLit(num), loc)
     }
 
+    pub fn new_rationallit(num: i64, den: i64, loc: Loc) -> Self {
+        Annot::new(TokenKind::RationalLit(num, den), loc)
+    }
+
+    pub fn new_imaginarylit(kind: TokenKind, loc: Loc) -> Self {
+        Annot::new(TokenKind::ImaginaryLit(Box::new(kind)), loc)
+    }
+
     pub fn new_stringlit(string: impl Into<String>, loc: Loc) -> Self {
         Annot::new(TokenKind::StringLit(string.into()), loc)
     }
@@ -204,10 +220,6 @@ impl Token {
         Annot::new(TokenKind::OpenRegex(s.into()), loc)
     }
 
-    pub fn new_percent(kind: char, content: String, loc: Loc) -> Self {
-        Annot::new(TokenKind::PercentNotation(kind, content), loc)
-    }
-
     pub fn new_punct(punct: Punct, loc: Loc) -> Self {
         Annot::new(TokenKind::Punct(punct), loc)
     }
@@ -253,6 +265,7 @@ impl Token {
             TokenKind::Const(_, _, _)
             | TokenKind::Ident(_, _, _)
             | TokenKind::InstanceVar(_)
+            | TokenKind::GlobalVar(_)
             | TokenKind::Reserved(_)
             | TokenKind::StringLit(_) => true,
             _ => false,
@@ -267,6 +280,7 @@ impl Token {
                 | Reserved::Elsif
                 | Reserved::End
                 | Reserved::When
+                | Reserved::In
                 | Reserved::Rescue
                 | Reserved::Ensure => true,
                 _ => false,
@@ -117,6 +117,8 @@ pub type SourceInfoRef = Ref<SourceInfo>;
 pub struct SourceInfo {
     pub path: PathBuf,
     pub code: Vec<char>,
+    /// Position of the text following `__END__` in `code`.
+    pub data_pos: Option<usize>,
 }
 
 impl SourceInfoRef {
@@ -130,8 +132,15 @@ impl SourceInfo {
         SourceInfo {
             path: path,
             code: vec![],
+            data_pos: None,
         }
     }
+    /// Returns the byte offset of the text following `__END__` in the source file.
+    pub fn data_offset(&self) -> Option<u64> {
+        let pos = self.data_pos?;
+        Some(self.code[..pos].iter().map(|c| c.len_utf8() as u64).sum())
+    }
+
     pub fn show_file_name(&self) {
         eprintln!("{}", self.path.to_string_lossy());
     }
@@ -290,6 +299,7 @@ impl IdentId {
     pub const _GT: IdentId = id!(13);
     pub const _GE: IdentId = id!(14);
     pub const _DIV: IdentId = id!(15);
+    pub const _CMP: IdentId = id!(16);
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -322,6 +332,7 @@ impl IdentifierTable {
         table.set_ident_id(">", IdentId::_GT);
         table.set_ident_id(">=", IdentId::_GE);
         table.set_ident_id("/", IdentId::_DIV);
+        table.set_ident_id("<=>", IdentId::_CMP);
         table
     }
 
@@ -345,6 +356,18 @@ impl IdentifierTable {
         }
     }
 
+    /// Returns all registered identifiers in the order of registration.
+    pub fn get_all_ids(&self) -> Vec<IdentId> {
+        let mut ids: Vec<u32> = self
+            .table_rev
+            .keys()
+            .cloned()
+            .filter(|id| *id != 0)
+            .collect();
+        ids.sort();
+        ids.into_iter().map(IdentId::from).collect()
+    }
+
     pub fn get_name(&self, id: IdentId) -> &str {
         self.table_rev.get(&id.0.get()).unwrap()
     }
@@ -3,8 +3,28 @@ use std::collections::HashMap;
 use std::hash::Hash;
 use std::ops::Deref;
 
+#[derive(Debug, Clone)]
+pub struct HashInfo {
+    pub map: HashTable,
+    /// The value returned for a missing key.
+    pub default: Value,
+    /// The proc called with (hash, key) for a missing key.
+    pub default_proc: Option<Value>,
+    /// Keys which may have `hash` and `eql?` defined in Ruby, grouped by their hash values.
+    /// None if keys have been added without VM, and the index must be rebuilt.
+    user_keys: Option<HashMap<u64, Vec<Value>>>,
+}
+
+impl PartialEq for HashInfo {
+    // Default values are not compared, as in Hash#==.
+    fn eq(&self, other: &Self) -> bool {
+        self.map == other.map
+    }
+}
+impl Eq for HashInfo {}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub enum HashInfo {
+pub enum HashTable {
     Map(HashMap<HashKey, Value>),
     IdentMap(HashMap<IdentKey, Value>),
 }
@@ -36,6 +56,12 @@ impl Hash for HashKey {
                     }
                 }
                 ObjKind::Method(lhs) => lhs.inner().hash(state),
+                ObjKind::Time(lhs) => (lhs.secs, lhs.nsec).hash(state),
+                ObjKind::Rational(lhs) => lhs.hash(state),
+                ObjKind::Complex(lhs) => {
+                    HashKey(lhs.re).hash(state);
+                    HashKey(lhs.im).hash(state);
+                }
                 _ => self.0.hash(state),
             },
         }
@@ -59,6 +85,8 @@ impl PartialEq for HashKey {
                 (ObjKind::Range(lhs), ObjKind::Range(rhs)) => *lhs == *rhs,
                 (ObjKind::Hash(lhs), ObjKind::Hash(rhs)) => lhs.inner() == rhs.inner(),
                 (ObjKind::Method(lhs), ObjKind::Method(rhs)) => *lhs.inner() == *rhs.inner(),
+                // User-defined `eql?` is called in HashInfo::find_key().
+                (ObjKind::Ordinary, _) | (_, ObjKind::Ordinary) => false,
                 _ => lhs.kind == rhs.kind,
             },
             _ => false,
@@ -68,6 +96,24 @@ impl PartialEq for HashKey {
 
 impl Eq for HashKey {}
 
+impl HashKey {
+    /// Returns true if the hash value or the equality of the key may depend on
+    /// `hash` and `eql?` defined in Ruby.
+    fn may_call_ruby(&self) -> bool {
+        match self.as_rvalue() {
+            None => false,
+            Some(rval) => match &rval.kind {
+                ObjKind::Ordinary => true,
+                ObjKind::Array(aref) => aref.elements.iter().any(|v| HashKey(*v).may_call_ruby()),
+                ObjKind::Hash(href) => href
+                    .iter()
+                    .any(|(k, v)| HashKey(k).may_call_ruby() || HashKey(v).may_call_ruby()),
+                _ => false,
+            },
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct IdentKey(pub Value);
 
@@ -102,9 +148,9 @@ pub enum IntoIter {
 
 impl IntoIter {
     fn new(hash: HashInfo) -> IntoIter {
-        match hash {
-            HashInfo::Map(map) => IntoIter::Map(map.into_iter()),
-            HashInfo::IdentMap(map) => IntoIter::IdentMap(map.into_iter()),
+        match hash.map {
+            HashTable::Map(map) => IntoIter::Map(map.into_iter()),
+            HashTable::IdentMap(map) => IntoIter::IdentMap(map.into_iter()),
         }
     }
 }
@@ -142,16 +188,16 @@ macro_rules! define_iter_new {
         impl<'a> $ty1<'a> {
             fn new(hash: $ty2) -> $ty1 {
                 match hash {
-                    HashInfo::Map(map) => $ty1::Map(map.$method()),
-                    HashInfo::IdentMap(map) => $ty1::IdentMap(map.$method()),
+                    HashTable::Map(map) => $ty1::Map(map.$method()),
+                    HashTable::IdentMap(map) => $ty1::IdentMap(map.$method()),
                 }
             }
         }
     };
 }
 
-define_iter_new!(Iter, &HashInfo, iter);
-define_iter_new!(IterMut, &mut HashInfo, iter_mut);
+define_iter_new!(Iter, &HashTable, iter);
+define_iter_new!(IterMut, &mut HashTable, iter_mut);
 
 macro_rules! define_iterator {
     ($ty2:ident) => {
@@ -177,19 +223,19 @@ define_iterator!(Iter);
 define_iterator!(IterMut);
 
 macro_rules! define_into_iterator {
-    ($ty1:ty, $ty2:ident) => {
+    ($ty1:ty, $ty2:ident, $method:ident) => {
         impl<'a> IntoIterator for $ty1 {
             type Item = (Value, Value);
             type IntoIter = $ty2<'a>;
             fn into_iter(self) -> $ty2<'a> {
-                $ty2::new(self)
+                self.$method()
             }
         }
     };
 }
 
-define_into_iterator!(&'a HashInfo, Iter);
-define_into_iterator!(&'a mut HashInfo, IterMut);
+define_into_iterator!(&'a HashInfo, Iter, iter);
+define_into_iterator!(&'a mut HashInfo, IterMut, iter_mut);
 
 impl IntoIterator for HashInfo {
     type Item = (Value, Value);
@@ -202,70 +248,83 @@ impl IntoIterator for HashInfo {
 
 impl HashInfo {
     pub fn new(map: HashMap<HashKey, Value>) -> Self {
-        HashInfo::Map(map)
+        HashInfo {
+            map: HashTable::Map(map),
+            default: Value::nil(),
+            default_proc: None,
+            user_keys: None,
+        }
     }
 
     pub fn iter(&self) -> Iter {
-        Iter::new(self)
+        Iter::new(&self.map)
     }
 
     pub fn iter_mut(&mut self) -> IterMut {
-        IterMut::new(self)
+        IterMut::new(&mut self.map)
     }
 
     pub fn get(&self, v: &Value) -> Option<&Value> {
-        match self {
-            HashInfo::Map(map) => map.get(&HashKey(*v)),
-            HashInfo::IdentMap(map) => map.get(&IdentKey(*v)),
+        match &self.map {
+            HashTable::Map(map) => map.get(&HashKey(*v)),
+            HashTable::IdentMap(map) => map.get(&IdentKey(*v)),
         }
     }
 
     pub fn len(&self) -> usize {
-        match self {
-            HashInfo::Map(map) => map.len(),
-            HashInfo::IdentMap(map) => map.len(),
+        match &self.map {
+            HashTable::Map(map) => map.len(),
+            HashTable::IdentMap(map) => map.len(),
         }
     }
 
     pub fn clear(&mut self) {
-        match self {
-            HashInfo::Map(map) => map.clear(),
-            HashInfo::IdentMap(map) => map.clear(),
+        match &mut self.map {
+            HashTable::Map(map) => map.clear(),
+            HashTable::IdentMap(map) => map.clear(),
         }
+        self.user_keys = Some(HashMap::new());
     }
 
+    /// Insert without calling `hash` and `eql?` defined in Ruby.
+    /// Use `store` for keys which may be user-defined objects.
     pub fn insert(&mut self, k: Value, v: Value) {
-        match self {
-            HashInfo::Map(map) => map.insert(HashKey(k), v),
-            HashInfo::IdentMap(map) => map.insert(IdentKey(k), v),
+        match &mut self.map {
+            HashTable::Map(map) => {
+                if HashKey(k).may_call_ruby() {
+                    self.user_keys = None;
+                }
+                map.insert(HashKey(k), v)
+            }
+            HashTable::IdentMap(map) => map.insert(IdentKey(k), v),
         };
     }
 
     pub fn remove(&mut self, k: Value) -> Option<Value> {
-        match self {
-            HashInfo::Map(map) => map.remove(&HashKey(k)),
-            HashInfo::IdentMap(map) => map.remove(&IdentKey(k)),
+        match &mut self.map {
+            HashTable::Map(map) => map.remove(&HashKey(k)),
+            HashTable::IdentMap(map) => map.remove(&IdentKey(k)),
         }
     }
 
     pub fn contains_key(&self, k: Value) -> bool {
-        match self {
-            HashInfo::Map(map) => map.contains_key(&HashKey(k)),
-            HashInfo::IdentMap(map) => map.contains_key(&IdentKey(k)),
+        match &self.map {
+            HashTable::Map(map) => map.contains_key(&HashKey(k)),
+            HashTable::IdentMap(map) => map.contains_key(&IdentKey(k)),
         }
     }
 
     pub fn keys(&self) -> Vec<Value> {
-        match self {
-            HashInfo::Map(map) => map.keys().map(|x| x.0).collect(),
-            HashInfo::IdentMap(map) => map.keys().map(|x| x.0).collect(),
+        match &self.map {
+            HashTable::Map(map) => map.keys().map(|x| x.0).collect(),
+            HashTable::IdentMap(map) => map.keys().map(|x| x.0).collect(),
         }
     }
 
     pub fn values(&self) -> Vec<Value> {
-        match self {
-            HashInfo::Map(map) => map.values().cloned().collect(),
-            HashInfo::IdentMap(map) => map.values().cloned().collect(),
+        match &self.map {
+            HashTable::Map(map) => map.values().cloned().collect(),
+            HashTable::IdentMap(map) => map.values().cloned().collect(),
         }
     }
 
@@ -291,10 +350,199 @@ impl HashInfo {
     }
 }
 
+// Methods which call `hash` and `eql?` defined in Ruby.
+impl HashInfo {
+    /// Find the key in the table which is `eql?` to `key`.
+    /// Returns the found key (or `key` itself if not found) and the hash value of `key`
+    /// if it was computed by VM.
+    fn find_key(&mut self, vm: &mut VM, key: Value) -> Result<(Value, Option<u64>), RubyError> {
+        if let HashTable::IdentMap(_) = self.map {
+            return Ok((key, None));
+        }
+        if !HashKey(key).may_call_ruby() {
+            return Ok((key, None));
+        }
+        if self.user_keys.is_none() {
+            let mut user_keys: HashMap<u64, Vec<Value>> = HashMap::new();
+            for k in self.keys() {
+                if HashKey(k).may_call_ruby() {
+                    let hash = vm.eval_hash(k)?;
+                    user_keys.entry(hash).or_default().push(k);
+                }
+            }
+            self.user_keys = Some(user_keys);
+        }
+        let hash = vm.eval_hash(key)?;
+        let candidates = match self.user_keys.as_ref().unwrap().get(&hash) {
+            Some(candidates) => candidates.clone(),
+            None => return Ok((key, Some(hash))),
+        };
+        for k in candidates {
+            // Removed keys may remain in the index.
+            if self.contains_key(k) && vm.eval_eql(k, key)? {
+                return Ok((k, Some(hash)));
+            }
+        }
+        Ok((key, Some(hash)))
+    }
+
+    /// Get the value for `key`.
+    pub fn lookup(&mut self, vm: &mut VM, key: Value) -> Result<Option<Value>, RubyError> {
+        let (key, _) = self.find_key(vm, key)?;
+        Ok(self.get(&key).cloned())
+    }
+
+    /// Insert `val` for `key`. If an `eql?` key already exists, the existing key is kept.
+    pub fn store(&mut self, vm: &mut VM, key: Value, val: Value) -> Result<(), RubyError> {
+        let (key, hash) = self.find_key(vm, key)?;
+        match &mut self.map {
+            HashTable::Map(map) => {
+                if map.insert(HashKey(key), val).is_none() {
+                    if let (Some(hash), Some(user_keys)) = (hash, &mut self.user_keys) {
+                        user_keys.entry(hash).or_default().push(key);
+                    }
+                }
+            }
+            HashTable::IdentMap(map) => {
+                map.insert(IdentKey(key), val);
+            }
+        };
+        Ok(())
+    }
+
+    /// Remove `key` and return its value.
+    pub fn delete(&mut self, vm: &mut VM, key: Value) -> Result<Option<Value>, RubyError> {
+        let (key, _) = self.find_key(vm, key)?;
+        Ok(self.remove(key))
+    }
+
+    pub fn has_key(&mut self, vm: &mut VM, key: Value) -> Result<bool, RubyError> {
+        let (key, _) = self.find_key(vm, key)?;
+        Ok(self.contains_key(key))
+    }
+}
+
+impl VM {
+    /// The hash value of `val` as a key of Hash.
+    /// `hash` defined in Ruby is called for user-defined objects.
+    pub fn eval_hash(&mut self, val: Value) -> Result<u64, RubyError> {
+        use std::hash::Hasher;
+        let mut state = std::collections::hash_map::DefaultHasher::new();
+        match val.as_rvalue().map(|rval| &rval.kind) {
+            Some(ObjKind::Ordinary) => {
+                let id = self.globals.get_ident_id("hash");
+                match self.get_user_method(val, id) {
+                    Some(method) => {
+                        let res = self.eval_send(method, val, &Args::new0())?;
+                        match res.as_fixnum() {
+                            Some(i) => i.hash(&mut state),
+                            None => {
+                                let class = self.globals.get_class_name(res);
+                                return Err(self.error_type(format!(
+                                    "No implicit conversion of {} into Integer.",
+                                    class
+                                )));
+                            }
+                        }
+                    }
+                    None => HashKey(val).hash(&mut state),
+                }
+            }
+            Some(ObjKind::Array(aref)) => {
+                let elements = aref.elements.clone();
+                elements.len().hash(&mut state);
+                for elem in elements {
+                    self.eval_hash(elem)?.hash(&mut state);
+                }
+            }
+            Some(ObjKind::Hash(href)) => {
+                // Independent of the order of the entries.
+                let mut sum = 0u64;
+                for (k, v) in href.iter().collect::<Vec<_>>() {
+                    let pair = (self.eval_hash(k)?, self.eval_hash(v)?);
+                    let mut entry = std::collections::hash_map::DefaultHasher::new();
+                    pair.hash(&mut entry);
+                    sum = sum.wrapping_add(entry.finish());
+                }
+                sum.hash(&mut state);
+            }
+            _ => HashKey(val).hash(&mut state),
+        }
+        Ok(state.finish())
+    }
+
+    /// `lhs.eql?(rhs)` as keys of Hash.
+    /// `eql?` defined in Ruby is called for user-defined objects.
+    pub fn eval_eql(&mut self, lhs: Value, rhs: Value) -> Result<bool, RubyError> {
+        if lhs.id() == rhs.id() {
+            return Ok(true);
+        }
+        let kinds = (
+            lhs.as_rvalue().map(|rval| &rval.kind),
+            rhs.as_rvalue().map(|rval| &rval.kind),
+        );
+        match kinds {
+            (Some(ObjKind::Ordinary), _) => {
+                let id = self.globals.get_ident_id("eql?");
+                match self.get_user_method(lhs, id) {
+                    Some(method) => {
+                        let res = self.eval_send(method, lhs, &Args::new1(rhs))?;
+                        Ok(self.val_to_bool(res))
+                    }
+                    None => Ok(false),
+                }
+            }
+            (Some(ObjKind::Array(lhs)), Some(ObjKind::Array(rhs))) => {
+                if lhs.elements.len() != rhs.elements.len() {
+                    return Ok(false);
+                }
+                let pairs: Vec<_> = lhs
+                    .elements
+                    .iter()
+                    .cloned()
+                    .zip(rhs.elements.iter().cloned())
+                    .collect();
+                for (lhs, rhs) in pairs {
+                    if !self.eval_eql(lhs, rhs)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            (Some(ObjKind::Hash(lhs)), Some(ObjKind::Hash(rhs))) => {
+                if lhs.len() != rhs.len() {
+                    return Ok(false);
+                }
+                let mut rhs = *rhs;
+                for (key, lhs_val) in lhs.iter().collect::<Vec<_>>() {
+                    match rhs.lookup(self, key)? {
+                        Some(rhs_val) if self.eval_eql(lhs_val, rhs_val)? => {}
+                        _ => return Ok(false),
+                    }
+                }
+                Ok(true)
+            }
+            _ => Ok(HashKey(lhs) == HashKey(rhs)),
+        }
+    }
+}
+
 pub type HashRef = Ref<HashInfo>;
 
 impl HashRef {
     pub fn from(map: HashMap<HashKey, Value>) -> Self {
         HashRef::new(HashInfo::new(map))
     }
+
+    /// Get the value for `key`, falling back to the default value or the default proc.
+    /// `self_val` is the Hash object which owns this HashRef.
+    pub fn get_elem(&self, vm: &mut VM, self_val: Value, key: Value) -> VMResult {
+        if let Some(val) = self.inner_mut().lookup(vm, key)? {
+            return Ok(val);
+        }
+        match self.default_proc {
+            Some(procobj) => vm.eval_proc(procobj, &Args::new2(self_val, key)),
+            None => Ok(self.default),
+        }
+    }
 }
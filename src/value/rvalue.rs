@@ -27,6 +27,12 @@ pub enum ObjKind {
     Method(MethodObjRef),
     Fiber(FiberRef),
     Enumerator(EnumRef),
+    File(FileRef),
+    Time(TimeInfo),
+    Rational(RationalInfo),
+    Complex(ComplexInfo),
+    Random(RandomRef),
+    MatchData(MatchDataRef),
 }
 
 impl RValue {
@@ -47,6 +53,7 @@ impl RValue {
                 ObjKind::Class(cref) => ObjKind::Class(cref.dup()),
                 ObjKind::Enumerator(eref) => ObjKind::Enumerator(eref.dup()),
                 ObjKind::Fiber(_fref) => ObjKind::Ordinary,
+                ObjKind::File(fref) => ObjKind::File(*fref),
                 ObjKind::Integer(num) => ObjKind::Integer(*num),
                 ObjKind::Float(num) => ObjKind::Float(*num),
                 ObjKind::Hash(href) => ObjKind::Hash(href.dup()),
@@ -58,6 +65,11 @@ impl RValue {
                 ObjKind::Regexp(rref) => ObjKind::Regexp(*rref),
                 ObjKind::Splat(v) => ObjKind::Splat(*v),
                 ObjKind::String(rstr) => ObjKind::String(rstr.clone()),
+                ObjKind::Time(time) => ObjKind::Time(*time),
+                ObjKind::Rational(info) => ObjKind::Rational(*info),
+                ObjKind::Complex(info) => ObjKind::Complex(*info),
+                ObjKind::Random(rref) => ObjKind::Random(RandomRef::new((**rref).clone())),
+                ObjKind::MatchData(mref) => ObjKind::MatchData(MatchDataRef::new((**mref).clone())),
             },
         }
     }
@@ -200,6 +212,14 @@ impl RValue {
         }
     }
 
+    pub fn new_unbound_method(globals: &Globals, methodref: MethodObjRef) -> Self {
+        RValue {
+            class: globals.builtins.unbound_method,
+            var_table: Box::new(HashMap::new()),
+            kind: ObjKind::Method(methodref),
+        }
+    }
+
     pub fn new_fiber(
         globals: &Globals,
         vm: VMRef,
@@ -221,7 +241,7 @@ impl RValue {
         receiver: Value,
         mut args: Args,
     ) -> Self {
-        args.block = Some(MethodRef::from(0));
+        args.block = Some(Block::Block(MethodRef::from(0)));
         let enum_info = EnumRef::from(method, receiver, args);
         RValue {
             class: globals.builtins.enumerator,
@@ -229,6 +249,54 @@ impl RValue {
             kind: ObjKind::Enumerator(enum_info),
         }
     }
+
+    pub fn new_time(globals: &Globals, time: TimeInfo) -> Self {
+        RValue {
+            class: globals.builtins.time,
+            var_table: Box::new(HashMap::new()),
+            kind: ObjKind::Time(time),
+        }
+    }
+
+    pub fn new_rational(globals: &Globals, info: RationalInfo) -> Self {
+        RValue {
+            class: globals.builtins.rational,
+            var_table: Box::new(HashMap::new()),
+            kind: ObjKind::Rational(info),
+        }
+    }
+
+    pub fn new_complex(globals: &Globals, info: ComplexInfo) -> Self {
+        RValue {
+            class: globals.builtins.complex,
+            var_table: Box::new(HashMap::new()),
+            kind: ObjKind::Complex(info),
+        }
+    }
+
+    pub fn new_random(class: Value, rref: RandomRef) -> Self {
+        RValue {
+            class,
+            var_table: Box::new(HashMap::new()),
+            kind: ObjKind::Random(rref),
+        }
+    }
+
+    pub fn new_matchdata(class: Value, mref: MatchDataRef) -> Self {
+        RValue {
+            class,
+            var_table: Box::new(HashMap::new()),
+            kind: ObjKind::MatchData(mref),
+        }
+    }
+
+    pub fn new_file(class: Value, fileref: FileRef) -> Self {
+        RValue {
+            class,
+            var_table: Box::new(HashMap::new()),
+            kind: ObjKind::File(fileref),
+        }
+    }
 }
 
 pub type ObjectRef = Ref<RValue>;
@@ -96,6 +96,8 @@ impl std::hash::Hash for Value {
                     }
                 }
                 ObjKind::Method(lhs) => lhs.inner().hash(state),
+                ObjKind::Time(lhs) => (lhs.secs, lhs.nsec).hash(state),
+                ObjKind::Rational(lhs) => lhs.hash(state),
                 _ => self.0.hash(state),
             },
         }
@@ -129,11 +131,12 @@ impl PartialEq for Value {
             (ObjKind::Range(lhs), ObjKind::Range(rhs)) => {
                 lhs.start == rhs.start && lhs.end == rhs.end && lhs.exclude == rhs.exclude
             }
-            (ObjKind::Hash(lhs), ObjKind::Hash(rhs)) => match (lhs.inner(), rhs.inner()) {
-                (HashInfo::Map(lhs), HashInfo::Map(rhs)) => *lhs == *rhs,
-                (HashInfo::IdentMap(lhs), HashInfo::IdentMap(rhs)) => *lhs == *rhs,
-                _ => false,
-            },
+            (ObjKind::Time(lhs), ObjKind::Time(rhs)) => {
+                lhs.secs == rhs.secs && lhs.nsec == rhs.nsec
+            }
+            (ObjKind::Hash(lhs), ObjKind::Hash(rhs)) => lhs.inner() == rhs.inner(),
+            (ObjKind::Rational(lhs), ObjKind::Rational(rhs)) => *lhs == *rhs,
+            (ObjKind::Complex(lhs), ObjKind::Complex(rhs)) => *lhs == *rhs,
             (_, _) => false,
         }
     }
@@ -223,7 +226,13 @@ impl Value {
                 } else if self.is_packed_num() {
                     globals.builtins.float
                 } else if self.is_packed_symbol() {
-                    globals.builtins.object
+                    globals.builtins.symbol
+                } else if self.is_nil() {
+                    globals.builtins.nilclass
+                } else if self.is_true_val() {
+                    globals.builtins.trueclass
+                } else if self.is_false_val() {
+                    globals.builtins.falseclass
                 } else {
                     globals.builtins.object
                 }
@@ -240,6 +249,10 @@ impl Value {
         match self.unpack() {
             RV::Integer(_) => globals.builtins.integer,
             RV::Float(_) => globals.builtins.float,
+            RV::Symbol(_) => globals.builtins.symbol,
+            RV::Nil => globals.builtins.nilclass,
+            RV::Bool(true) => globals.builtins.trueclass,
+            RV::Bool(false) => globals.builtins.falseclass,
             RV::Object(info) => info.search_class(),
             _ => globals.builtins.object,
         }
@@ -300,7 +313,7 @@ impl Value {
     }
 
     pub fn is_packed_flonum(&self) -> bool {
-        self.0 & 0b10 == 2
+        self.0 & 0b11 == 2
     }
 
     pub fn is_packed_num(&self) -> bool {
@@ -542,6 +555,66 @@ impl Value {
         }
     }
 
+    pub fn as_file(&self) -> Option<FileRef> {
+        match self.is_object() {
+            Some(oref) => match oref.kind {
+                ObjKind::File(fref) => Some(fref),
+                _ => None,
+            },
+            None => None,
+        }
+    }
+
+    pub fn as_random(&self) -> Option<RandomRef> {
+        match self.is_object() {
+            Some(oref) => match oref.kind {
+                ObjKind::Random(rref) => Some(rref),
+                _ => None,
+            },
+            None => None,
+        }
+    }
+
+    pub fn as_matchdata(&self) -> Option<MatchDataRef> {
+        match self.is_object() {
+            Some(oref) => match oref.kind {
+                ObjKind::MatchData(mref) => Some(mref),
+                _ => None,
+            },
+            None => None,
+        }
+    }
+
+    pub fn as_time(&self) -> Option<TimeInfo> {
+        match self.as_rvalue() {
+            Some(rval) => match &rval.kind {
+                ObjKind::Time(time) => Some(*time),
+                _ => None,
+            },
+            None => None,
+        }
+    }
+
+    pub fn as_rational(&self) -> Option<RationalInfo> {
+        match self.as_rvalue() {
+            Some(rval) => match &rval.kind {
+                ObjKind::Rational(info) => Some(*info),
+                _ => None,
+            },
+            None => None,
+        }
+    }
+
+    pub fn as_complex(&self) -> Option<ComplexInfo> {
+        match self.as_rvalue() {
+            Some(rval) => match &rval.kind {
+                ObjKind::Complex(info) => Some(*info),
+                _ => None,
+            },
+            None => None,
+        }
+    }
+
     pub fn as_symbol(&self) -> Option<IdentId> {
         if self.is_packed_symbol() {
             Some(self.as_packed_symbol())
@@ -692,10 +765,28 @@ impl Value {
         Value::object(RValue::new_proc(globals, ProcRef::from(context)))
     }
 
-    pub fn method(globals: &Globals, name: IdentId, receiver: Value, method: MethodRef) -> Self {
+    pub fn method(
+        globals: &Globals,
+        name: IdentId,
+        receiver: Value,
+        method: MethodRef,
+        owner: Value,
+    ) -> Self {
         Value::object(RValue::new_method(
             globals,
-            MethodObjRef::from(name, receiver, method),
+            MethodObjRef::from(name, receiver, method, owner),
+        ))
+    }
+
+    pub fn unbound_method(
+        globals: &Globals,
+        name: IdentId,
+        method: MethodRef,
+        owner: Value,
+    ) -> Self {
+        Value::object(RValue::new_unbound_method(
+            globals,
+            MethodObjRef::from(name, Value::nil(), method, owner),
         ))
     }
 
@@ -712,6 +803,30 @@ impl Value {
     pub fn enumerator(globals: &Globals, method: IdentId, receiver: Value, args: Args) -> Self {
         Value::object(RValue::new_enumerator(globals, method, receiver, args))
     }
+
+    pub fn time(globals: &Globals, time: TimeInfo) -> Self {
+        Value::object(RValue::new_time(globals, time))
+    }
+
+    pub fn rational(globals: &Globals, info: RationalInfo) -> Self {
+        Value::object(RValue::new_rational(globals, info))
+    }
+
+    pub fn complex(globals: &Globals, info: ComplexInfo) -> Self {
+        Value::object(RValue::new_complex(globals, info))
+    }
+
+    pub fn file(class: Value, fileref: FileRef) -> Self {
+        Value::object(RValue::new_file(class, fileref))
+    }
+
+    pub fn random(class: Value, rref: RandomRef) -> Self {
+        Value::object(RValue::new_random(class, rref))
+    }
+
+    pub fn matchdata(globals: &Globals, mref: MatchDataRef) -> Self {
+        Value::object(RValue::new_matchdata(globals.builtins.matchdata, mref))
+    }
 }
 
 impl Value {
@@ -732,7 +847,7 @@ impl Value {
                     _ => return false,
                 }
             }
-            return false;
+            return self.numeric_equal(other).unwrap_or(false);
         };
         match (&self.rvalue().kind, &other.rvalue().kind) {
             (ObjKind::Integer(lhs), ObjKind::Integer(rhs)) => *lhs == *rhs,
@@ -744,9 +859,36 @@ impl Value {
             (ObjKind::Range(lhs), ObjKind::Range(rhs)) => {
                 lhs.start.equal(rhs.start) && lhs.end.equal(rhs.end) && lhs.exclude == rhs.exclude
             }
+            (ObjKind::Time(lhs), ObjKind::Time(rhs)) => {
+                lhs.secs == rhs.secs && lhs.nsec == rhs.nsec
+            }
             (ObjKind::Hash(lhs), ObjKind::Hash(rhs)) => lhs.inner() == rhs.inner(),
-            (_, _) => false,
+            (_, _) => self.numeric_equal(other).unwrap_or(false),
+        }
+    }
+
+    /// Compare numeric values when either of them is a Rational or a Complex.
+    fn numeric_equal(self, other: Value) -> Option<bool> {
+        if let Some(lhs) = self.as_complex() {
+            return Some(match other.as_complex() {
+                Some(rhs) => lhs.re.equal(rhs.re) && lhs.im.equal(rhs.im),
+                None => lhs.im.equal(Value::fixnum(0)) && lhs.re.equal(other),
+            });
+        }
+        if let Some(lhs) = self.as_rational() {
+            if let Some(rhs) = other.as_rational() {
+                return Some(lhs == rhs);
+            } else if let Some(rhs) = other.as_fixnum() {
+                return Some(lhs.den == 1 && lhs.num == rhs);
+            } else if let Some(rhs) = other.as_flonum() {
+                return Some(lhs.to_f() == rhs);
+            }
+            return None;
+        }
+        if other.as_complex().is_some() || other.as_rational().is_some() {
+            return other.numeric_equal(self);
         }
+        None
     }
 
     pub fn to_ordering(&self) -> std::cmp::Ordering {
@@ -3,6 +3,7 @@ mod class;
 mod codegen;
 mod context;
 mod executor;
+mod globalvar;
 mod method;
 #[cfg(feature = "perf")]
 #[cfg_attr(tarpaulin, skip)]
@@ -14,4 +15,5 @@ pub use class::*;
 pub use codegen::{Codegen, ISeq, ISeqPos};
 pub use context::*;
 pub use executor::*;
+pub use globalvar::*;
 pub use method::*;
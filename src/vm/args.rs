@@ -6,7 +6,7 @@ const ARG_ARRAY_SIZE: usize = 8;
 
 #[derive(Debug, Clone)]
 pub struct Args {
-    pub block: Option<MethodRef>,
+    pub block: Option<Block>,
     pub kw_arg: Option<Value>,
     elems: ArgsArray,
 }
@@ -48,12 +48,7 @@ impl Args {
         }
     }
 
-    pub fn new3(
-        block: impl Into<Option<MethodRef>>,
-        arg0: Value,
-        arg1: Value,
-        arg2: Value,
-    ) -> Self {
+    pub fn new3(block: impl Into<Option<Block>>, arg0: Value, arg1: Value, arg2: Value) -> Self {
         Args {
             block: block.into(),
             kw_arg: None,
@@ -65,6 +60,18 @@ impl Args {
         self.elems.len()
     }
 
+    /// Returns the keyword arguments as a Hash.
+    pub fn kw_hash(&self) -> Option<HashRef> {
+        self.kw_arg.and_then(|kw| kw.as_hash())
+    }
+
+    /// Returns the value of the keyword argument `name`, or None if not given.
+    pub fn get_kwarg(&self, globals: &mut Globals, name: &str) -> Option<Value> {
+        let href = self.kw_hash()?;
+        let id = globals.get_ident_id(name);
+        href.get(&Value::symbol(id)).cloned()
+    }
+
     pub fn into_vec(self) -> Vec<Value> {
         match self.elems {
             ArgsArray::Array { ary, len } => ary[0..len].to_vec(),
@@ -8,6 +8,8 @@ pub struct ClassInfo {
     pub superclass: Value,
     pub include: Vec<Value>,
     pub is_singleton: bool,
+    /// Files to be required when the constants are referred first.
+    pub autoload: HashMap<IdentId, String>,
 }
 
 impl ClassInfo {
@@ -18,6 +20,7 @@ impl ClassInfo {
             superclass,
             include: vec![],
             is_singleton: false,
+            autoload: HashMap::new(),
         }
     }
 }
@@ -1,6 +1,6 @@
 use super::vm_inst::*;
 use crate::error::{ParseErrKind, RubyError, RuntimeErrKind};
-use crate::parse::node::{BinOp, Node, NodeKind, UnOp};
+use crate::parse::node::{BinOp, HashPatternRest, Node, NodeKind, Pattern, UnOp};
 use crate::*;
 use std::collections::HashMap;
 
@@ -19,6 +19,8 @@ pub struct Codegen {
 struct LoopInfo {
     state: LoopState,
     escape: Vec<EscapeInfo>,
+    /// The start of the loop body or the block body, where `redo` jumps to.
+    redo: Option<ISeqPos>,
 }
 
 impl LoopInfo {
@@ -26,6 +28,7 @@ impl LoopInfo {
         LoopInfo {
             state: LoopState::Top,
             escape: vec![],
+            redo: None,
         }
     }
 
@@ -33,6 +36,7 @@ impl LoopInfo {
         LoopInfo {
             state: LoopState::Loop,
             escape: vec![],
+            redo: None,
         }
     }
 }
@@ -280,12 +284,22 @@ impl Codegen {
         iseq.push(Inst::SPLAT);
     }
 
+    fn gen_merge_kwargs(&mut self, iseq: &mut ISeq) {
+        iseq.push(Inst::MERGE_KWARGS);
+    }
+
     fn gen_jmp_if_false(&mut self, iseq: &mut ISeq) -> ISeqPos {
         iseq.push(Inst::JMP_IF_FALSE);
         Codegen::push32(iseq, 0);
         ISeqPos(iseq.len())
     }
 
+    fn gen_jmp_if_nil(&mut self, iseq: &mut ISeq) -> ISeqPos {
+        iseq.push(Inst::JMP_IF_NIL);
+        Codegen::push32(iseq, 0);
+        ISeqPos(iseq.len())
+    }
+
     fn gen_jmp_back(&mut self, iseq: &mut ISeq, pos: ISeqPos) {
         let disp = Codegen::current(iseq).disp(pos) - 5;
         iseq.push(Inst::JMP);
@@ -411,6 +425,16 @@ impl Codegen {
         Codegen::push32(iseq, id.into());
     }
 
+    fn gen_check_const(&mut self, iseq: &mut ISeq, id: IdentId) {
+        iseq.push(Inst::CHECK_CONST);
+        Codegen::push32(iseq, id.into());
+    }
+
+    fn gen_check_scope(&mut self, iseq: &mut ISeq, id: IdentId) {
+        iseq.push(Inst::CHECK_SCOPE);
+        Codegen::push32(iseq, id.into());
+    }
+
     fn gen_get_const_top(&mut self, iseq: &mut ISeq, id: IdentId) {
         self.save_cur_loc(iseq);
         iseq.push(Inst::GET_CONST_TOP);
@@ -486,11 +510,28 @@ impl Codegen {
                 self.gen_set_const(iseq, *id);
             }
             NodeKind::InstanceVar(id) => self.gen_set_instance_var(iseq, *id),
-            NodeKind::GlobalVar(id) => self.gen_set_global_var(iseq, *id),
+            NodeKind::GlobalVar(id) => {
+                let name = globals.get_ident_name(*id);
+                let is_match_ref = match name {
+                    "$&" | "$`" | "$'" | "$+" => true,
+                    _ => name[1..].parse::<usize>().map_or(false, |i| i > 0),
+                };
+                if is_match_ref {
+                    return Err(
+                        self.error_syntax(format!("Can't set variable {}.", name), lhs.loc())
+                    );
+                }
+                self.gen_set_global_var(iseq, *id)
+            }
             NodeKind::Scope(parent, id) => {
                 self.gen(globals, iseq, parent, true)?;
                 self.gen_set_const(iseq, *id);
             }
+            NodeKind::Send { safe_nav: true, .. } => {
+                return Err(
+                    self.error_syntax("&. inside multiple assignment destination.", lhs.loc())
+                )
+            }
             NodeKind::Send {
                 receiver, method, ..
             } => {
@@ -509,11 +550,495 @@ impl Codegen {
                 self.save_loc(iseq, lhs.loc());
                 self.gen_set_array_elem(iseq, index.len());
             }
+            // `*` in MLHS discards the values.
+            NodeKind::Splat(box Node {
+                kind: NodeKind::Nil,
+                ..
+            }) => self.gen_pop(iseq),
+            NodeKind::Splat(lhs) => self.gen_assign(globals, iseq, lhs)?,
+            NodeKind::MulAssign(mlhs, mrhs) if mrhs.is_empty() => {
+                self.gen_mlhs_assign(globals, iseq, mlhs)?
+            }
+            _ => return Err(self.error_syntax(format!("Unimplemented LHS form."), lhs.loc())),
+        }
+        Ok(())
+    }
+
+    /// Generate code for assigning the value on the stack to `mlhs`.
+    /// The value is expanded by `to_ary` and `*lhs` receives the rest of the elements.
+    fn gen_mlhs_assign(
+        &mut self,
+        globals: &mut Globals,
+        iseq: &mut ISeq,
+        mlhs: &[Node],
+    ) -> Result<(), RubyError> {
+        let splat_pos = mlhs.iter().position(|lhs| match lhs.kind {
+            NodeKind::Splat(_) => true,
+            _ => false,
+        });
+        match splat_pos {
+            Some(pos) => self.gen_expand_array(iseq, pos, mlhs.len() - pos - 1),
+            None => self.gen_take(iseq, mlhs.len()),
+        }
+        for lhs in mlhs.iter().rev() {
+            self.gen_assign(globals, iseq, lhs)?;
+        }
+        Ok(())
+    }
+
+    /// Generate code for `lhs op= rhs`, `lhs ||= rhs` and `lhs &&= rhs`.
+    /// The receiver and the index of `lhs` are evaluated only once.
+    fn gen_assign_op(
+        &mut self,
+        globals: &mut Globals,
+        iseq: &mut ISeq,
+        op: BinOp,
+        lhs: &Node,
+        rhs: &Node,
+    ) -> Result<(), RubyError> {
+        // Push the operands of lhs.
+        let mut nil_pos = None;
+        let operands = match &lhs.kind {
+            NodeKind::Send {
+                receiver, safe_nav, ..
+            } => {
+                self.gen(globals, iseq, receiver, true)?;
+                // receiver&.method op= rhs: the result is nil if receiver is nil.
+                if *safe_nav {
+                    self.gen_dup(iseq, 1);
+                    nil_pos = Some(self.gen_jmp_if_nil(iseq));
+                }
+                1
+            }
+            NodeKind::Scope(parent, _) => {
+                self.gen(globals, iseq, parent, true)?;
+                1
+            }
+            NodeKind::ArrayMember { array, index } => {
+                self.gen(globals, iseq, array, true)?;
+                for i in index {
+                    self.gen(globals, iseq, i, true)?;
+                }
+                index.len() + 1
+            }
+            _ => 0,
+        };
+        // Get the current value of lhs.
+        if operands != 0 {
+            self.gen_dup(iseq, operands);
+        }
+        match &lhs.kind {
+            NodeKind::Ident(id) | NodeKind::LocalVar(id) => self.gen_get_local(iseq, *id)?,
+            NodeKind::InstanceVar(id) => self.gen_get_instance_var(iseq, *id),
+            NodeKind::GlobalVar(id) => self.gen_get_global_var(iseq, *id),
+            NodeKind::Const { toplevel, id } => {
+                // CONST ||= val must not raise for an undefined constant.
+                let undef = if op == BinOp::LOr {
+                    self.gen_check_const(iseq, *id);
+                    Some(self.gen_jmp_if_false(iseq))
+                } else {
+                    None
+                };
+                if *toplevel {
+                    self.gen_get_const_top(iseq, *id);
+                } else {
+                    self.gen_get_const(iseq, *id);
+                }
+                if let Some(undef) = undef {
+                    let end = Codegen::gen_jmp(iseq);
+                    Codegen::write_disp_from_cur(iseq, undef);
+                    self.gen_push_nil(iseq);
+                    Codegen::write_disp_from_cur(iseq, end);
+                }
+            }
+            NodeKind::Scope(_, id) => {
+                let undef = if op == BinOp::LOr {
+                    self.gen_dup(iseq, 1);
+                    self.gen_check_scope(iseq, *id);
+                    Some(self.gen_jmp_if_false(iseq))
+                } else {
+                    None
+                };
+                self.gen_get_scope(iseq, *id, lhs.loc());
+                if let Some(undef) = undef {
+                    let end = Codegen::gen_jmp(iseq);
+                    Codegen::write_disp_from_cur(iseq, undef);
+                    self.gen_pop(iseq);
+                    self.gen_push_nil(iseq);
+                    Codegen::write_disp_from_cur(iseq, end);
+                }
+            }
+            NodeKind::Send { method, .. } => {
+                self.loc = lhs.loc();
+                self.gen_send(globals, iseq, *method, 0, 0, None);
+            }
+            NodeKind::ArrayMember { index, .. } => {
+                self.save_loc(iseq, lhs.loc());
+                self.gen_get_array_elem(iseq, index.len());
+            }
             _ => return Err(self.error_syntax(format!("Unimplemented LHS form."), lhs.loc())),
         }
+        match op {
+            BinOp::LOr => {
+                self.gen_dup(iseq, 1);
+                let assign = self.gen_jmp_if_false(iseq);
+                self.gen_discard_operands(iseq, operands);
+                let end = Codegen::gen_jmp(iseq);
+                Codegen::write_disp_from_cur(iseq, assign);
+                self.gen_pop(iseq);
+                self.gen(globals, iseq, rhs, true)?;
+                self.gen_store(globals, iseq, lhs, operands)?;
+                Codegen::write_disp_from_cur(iseq, end);
+            }
+            BinOp::LAnd => {
+                self.gen_dup(iseq, 1);
+                let keep = self.gen_jmp_if_false(iseq);
+                self.gen_pop(iseq);
+                self.gen(globals, iseq, rhs, true)?;
+                self.gen_store(globals, iseq, lhs, operands)?;
+                let end = Codegen::gen_jmp(iseq);
+                Codegen::write_disp_from_cur(iseq, keep);
+                self.gen_discard_operands(iseq, operands);
+                Codegen::write_disp_from_cur(iseq, end);
+            }
+            _ => {
+                let loc = lhs.loc().merge(rhs.loc());
+                self.gen(globals, iseq, rhs, true)?;
+                self.save_loc(iseq, loc);
+                self.gen_binop_inst(globals, iseq, op, loc)?;
+                self.gen_store(globals, iseq, lhs, operands)?;
+            }
+        }
+        if let Some(nil_pos) = nil_pos {
+            // The nil receiver is left on the stack as the result.
+            Codegen::write_disp_from_cur(iseq, nil_pos);
+        }
         Ok(())
     }
 
+    /// Generate code for `receiver&.method = rhs`.
+    /// Neither `rhs` nor the setter is evaluated if receiver is nil.
+    fn gen_safe_nav_assign(
+        &mut self,
+        globals: &mut Globals,
+        iseq: &mut ISeq,
+        lhs: &Node,
+        rhs: &Node,
+        use_value: bool,
+    ) -> Result<(), RubyError> {
+        let (receiver, method) = match &lhs.kind {
+            NodeKind::Send {
+                receiver, method, ..
+            } => (receiver, method),
+            _ => unreachable!(),
+        };
+        let name = globals.get_ident_name(*method).to_string() + "=";
+        let assign_id = globals.get_ident_id(name);
+        self.gen(globals, iseq, receiver, true)?;
+        self.gen_dup(iseq, 1);
+        let nil_pos = self.gen_jmp_if_nil(iseq);
+        self.gen(globals, iseq, rhs, true)?;
+        if use_value {
+            // [receiver, val] => [val, val, receiver]
+            self.gen_dup(iseq, 1);
+            self.gen_sinkn(iseq, 2);
+        }
+        self.gen_sinkn(iseq, 1);
+        self.loc = lhs.loc();
+        self.gen_send(globals, iseq, assign_id, 1, 0, None);
+        self.gen_pop(iseq);
+        let end = Codegen::gen_jmp(iseq);
+        Codegen::write_disp_from_cur(iseq, nil_pos);
+        if !use_value {
+            self.gen_pop(iseq);
+        }
+        Codegen::write_disp_from_cur(iseq, end);
+        Ok(())
+    }
+
+    /// Store the value on the top of the stack to `lhs` whose `operands` are
+    /// placed just under the value, leaving only the value on the stack.
+    fn gen_store(
+        &mut self,
+        globals: &mut Globals,
+        iseq: &mut ISeq,
+        lhs: &Node,
+        operands: usize,
+    ) -> Result<(), RubyError> {
+        self.gen_dup(iseq, 1);
+        if operands == 0 {
+            return self.gen_assign(globals, iseq, lhs);
+        }
+        // [operands, val, val] => [val, val, operands]
+        self.gen_sinkn(iseq, operands + 1);
+        self.gen_sinkn(iseq, operands);
+        match &lhs.kind {
+            NodeKind::Send { method, .. } => {
+                let name = globals.get_ident_name(*method).to_string() + "=";
+                let assign_id = globals.get_ident_id(name);
+                self.loc = lhs.loc();
+                self.gen_send(globals, iseq, assign_id, 1, 0, None);
+                self.gen_pop(iseq);
+            }
+            NodeKind::Scope(_, id) => self.gen_set_const(iseq, *id),
+            NodeKind::ArrayMember { index, .. } => {
+                self.save_loc(iseq, lhs.loc());
+                self.gen_set_array_elem(iseq, index.len());
+            }
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+
+    /// Discard `operands` values placed under the top of the stack.
+    fn gen_discard_operands(&mut self, iseq: &mut ISeq, operands: usize) {
+        if operands == 0 {
+            return;
+        }
+        self.gen_sinkn(iseq, operands);
+        for _ in 0..operands {
+            self.gen_pop(iseq);
+        }
+    }
+
+    /// Generate an instruction for binary operator `op` which takes
+    /// the two values on the top of the stack.
+    fn gen_binop_inst(
+        &mut self,
+        globals: &mut Globals,
+        iseq: &mut ISeq,
+        op: BinOp,
+        loc: Loc,
+    ) -> Result<(), RubyError> {
+        match op {
+            BinOp::Add => self.gen_add(iseq, globals),
+            BinOp::Sub => self.gen_sub(iseq, globals),
+            BinOp::Mul => {
+                iseq.push(Inst::MUL);
+                Codegen::push32(iseq, globals.add_inline_cache_entry() as u32);
+            }
+            BinOp::Div => {
+                iseq.push(Inst::DIV);
+                Codegen::push32(iseq, globals.add_inline_cache_entry() as u32);
+            }
+            BinOp::Exp => iseq.push(Inst::POW),
+            BinOp::Rem => iseq.push(Inst::REM),
+            BinOp::Shr => iseq.push(Inst::SHR),
+            BinOp::Shl => {
+                iseq.push(Inst::SHL);
+                Codegen::push32(iseq, globals.add_inline_cache_entry() as u32);
+            }
+            BinOp::BitOr => iseq.push(Inst::BIT_OR),
+            BinOp::BitAnd => iseq.push(Inst::BIT_AND),
+            BinOp::BitXor => iseq.push(Inst::BIT_XOR),
+            _ => return Err(self.error_syntax(format!("Unsupported operator {:?}.", op), loc)),
+        }
+        Ok(())
+    }
+
+    /// Generate code for matching the value on the top of the stack with `pattern`.
+    /// The value is replaced with true if matched, false otherwise.
+    fn gen_pattern(
+        &mut self,
+        globals: &mut Globals,
+        iseq: &mut ISeq,
+        pattern: &Pattern,
+    ) -> Result<(), RubyError> {
+        // Jump sources on failure with the number of values to be discarded.
+        let mut fails = vec![];
+        match pattern {
+            Pattern::Value(node) => {
+                self.gen(globals, iseq, node, true)?;
+                self.save_loc(iseq, node.loc());
+                iseq.push(Inst::TEQ);
+                return Ok(());
+            }
+            Pattern::Bind(id) => {
+                self.gen_set_local(iseq, *id);
+                iseq.push(Inst::PUSH_TRUE);
+                return Ok(());
+            }
+            Pattern::Capture(pattern, id) => {
+                self.gen_dup(iseq, 1);
+                self.gen_pattern(globals, iseq, pattern)?;
+                fails.push((self.gen_jmp_if_false(iseq), 1));
+                self.gen_set_local(iseq, *id);
+            }
+            Pattern::Alt(alt) => {
+                let mut end = vec![];
+                let (last, alt) = alt.split_last().unwrap();
+                for pattern in alt {
+                    self.gen_dup(iseq, 1);
+                    self.gen_pattern(globals, iseq, pattern)?;
+                    let next = self.gen_jmp_if_false(iseq);
+                    self.gen_pop(iseq);
+                    iseq.push(Inst::PUSH_TRUE);
+                    end.push(Codegen::gen_jmp(iseq));
+                    Codegen::write_disp_from_cur(iseq, next);
+                }
+                self.gen_pattern(globals, iseq, last)?;
+                for dest in end {
+                    Codegen::write_disp_from_cur(iseq, dest);
+                }
+                return Ok(());
+            }
+            Pattern::Array {
+                constant,
+                pre,
+                rest,
+                post,
+            } => {
+                self.gen_pattern_constant(globals, iseq, constant, &mut fails)?;
+                // [val] => [post.., rest, ..pre, true] or [false]
+                let len = pre.len() + post.len();
+                let rest_pos = match rest {
+                    Some(_) => pre.len() as u32,
+                    None => std::u32::MAX,
+                };
+                iseq.push(Inst::DECONSTRUCT);
+                Codegen::push32(iseq, len as u32);
+                Codegen::push32(iseq, rest_pos);
+                fails.push((self.gen_jmp_if_false(iseq), 0));
+                let mut remain = len + if rest.is_some() { 1 } else { 0 };
+                for pattern in pre {
+                    remain -= 1;
+                    self.gen_pattern(globals, iseq, pattern)?;
+                    fails.push((self.gen_jmp_if_false(iseq), remain));
+                }
+                if let Some(rest) = rest {
+                    remain -= 1;
+                    match rest {
+                        Some(id) => self.gen_set_local(iseq, *id),
+                        None => self.gen_pop(iseq),
+                    }
+                }
+                for pattern in post {
+                    remain -= 1;
+                    self.gen_pattern(globals, iseq, pattern)?;
+                    fails.push((self.gen_jmp_if_false(iseq), remain));
+                }
+            }
+            Pattern::Find {
+                constant,
+                pre,
+                mid,
+                post,
+            } => {
+                self.gen_pattern_constant(globals, iseq, constant, &mut fails)?;
+                // [val] => [ary, true] or [false]
+                iseq.push(Inst::DECONSTRUCT);
+                Codegen::push32(iseq, 0);
+                Codegen::push32(iseq, 0);
+                fails.push((self.gen_jmp_if_false(iseq), 0));
+                // [ary, index]
+                self.gen_fixnum(iseq, 0);
+                let loop_start = Codegen::current(iseq);
+                // [ary, index] => [ary, index, post, pre, ..mid, true] or [ary, index, false]
+                self.gen_topn(iseq, 1);
+                self.gen_topn(iseq, 1);
+                iseq.push(Inst::FIND_SLICE);
+                Codegen::push32(iseq, mid.len() as u32);
+                fails.push((self.gen_jmp_if_false(iseq), 2));
+                let mut next = vec![];
+                let mut remain = mid.len() + 2;
+                for pattern in mid {
+                    remain -= 1;
+                    self.gen_pattern(globals, iseq, pattern)?;
+                    next.push((self.gen_jmp_if_false(iseq), remain));
+                }
+                for id in &[pre, post] {
+                    match id {
+                        Some(id) => self.gen_set_local(iseq, *id),
+                        None => self.gen_pop(iseq),
+                    }
+                }
+                self.gen_pop(iseq);
+                self.gen_pop(iseq);
+                iseq.push(Inst::PUSH_TRUE);
+                let end = Codegen::gen_jmp(iseq);
+                // Try the next position.
+                self.gen_pop_ladder(iseq, next);
+                self.gen_addi(iseq, 1);
+                self.gen_jmp_back(iseq, loop_start);
+                self.gen_pop_ladder(iseq, fails);
+                iseq.push(Inst::PUSH_FALSE);
+                Codegen::write_disp_from_cur(iseq, end);
+                return Ok(());
+            }
+            Pattern::Hash {
+                constant,
+                pairs,
+                rest,
+            } => {
+                self.gen_pattern_constant(globals, iseq, constant, &mut fails)?;
+                // [val, keys] => [rest, ..values, true] or [false]
+                for (key, _) in pairs.iter().rev() {
+                    self.gen_symbol(iseq, *key);
+                }
+                self.gen_create_array(iseq, pairs.len());
+                iseq.push(Inst::DECONSTRUCT_KEYS);
+                Codegen::push32(
+                    iseq,
+                    match rest {
+                        HashPatternRest::Any => 0,
+                        HashPatternRest::NoKey => 1,
+                        HashPatternRest::Bind(_) => 2,
+                    },
+                );
+                fails.push((self.gen_jmp_if_false(iseq), 0));
+                let mut remain = pairs.len();
+                if let HashPatternRest::Bind(_) = rest {
+                    remain += 1;
+                }
+                for (_, pattern) in pairs {
+                    remain -= 1;
+                    self.gen_pattern(globals, iseq, pattern)?;
+                    fails.push((self.gen_jmp_if_false(iseq), remain));
+                }
+                if let HashPatternRest::Bind(id) = rest {
+                    self.gen_set_local(iseq, *id);
+                }
+            }
+        }
+        iseq.push(Inst::PUSH_TRUE);
+        let end = Codegen::gen_jmp(iseq);
+        self.gen_pop_ladder(iseq, fails);
+        iseq.push(Inst::PUSH_FALSE);
+        Codegen::write_disp_from_cur(iseq, end);
+        Ok(())
+    }
+
+    /// Generate code for checking `constant === val` of `Const(..)` patterns.
+    fn gen_pattern_constant(
+        &mut self,
+        globals: &mut Globals,
+        iseq: &mut ISeq,
+        constant: &Option<Box<Node>>,
+        fails: &mut Vec<(ISeqPos, usize)>,
+    ) -> Result<(), RubyError> {
+        if let Some(constant) = constant {
+            self.gen_dup(iseq, 1);
+            self.gen(globals, iseq, constant, true)?;
+            self.save_loc(iseq, constant.loc());
+            iseq.push(Inst::TEQ);
+            fails.push((self.gen_jmp_if_false(iseq), 1));
+        }
+        Ok(())
+    }
+
+    /// Resolve jump sources in `fails`, discarding the given number of values for each.
+    fn gen_pop_ladder(&mut self, iseq: &mut ISeq, fails: Vec<(ISeqPos, usize)>) {
+        let max = fails.iter().map(|(_, n)| *n).max().unwrap_or(0);
+        for n in (0..=max).rev() {
+            for (src, _) in fails.iter().filter(|(_, k)| *k == n) {
+                Codegen::write_disp_from_cur(iseq, *src);
+            }
+            if n != 0 {
+                self.gen_pop(iseq);
+            }
+        }
+    }
+
     fn gen_pop(&mut self, iseq: &mut ISeq) {
         iseq.push(Inst::POP);
     }
@@ -523,11 +1048,29 @@ impl Codegen {
         Codegen::push32(iseq, len as u32);
     }
 
+    /// Push a copy of the value at `pos` from the top of the stack (0: top).
+    fn gen_topn(&mut self, iseq: &mut ISeq, pos: usize) {
+        iseq.push(Inst::TOPN);
+        Codegen::push32(iseq, pos as u32);
+    }
+
+    /// Move the top value of the stack down under `pos` values.
+    fn gen_sinkn(&mut self, iseq: &mut ISeq, pos: usize) {
+        iseq.push(Inst::SINKN);
+        Codegen::push32(iseq, pos as u32);
+    }
+
     fn gen_take(&mut self, iseq: &mut ISeq, len: usize) {
         iseq.push(Inst::TAKE);
         Codegen::push32(iseq, len as u32);
     }
 
+    fn gen_expand_array(&mut self, iseq: &mut ISeq, pre_len: usize, post_len: usize) {
+        iseq.push(Inst::EXPAND_ARRAY);
+        Codegen::push32(iseq, pre_len as u32);
+        Codegen::push32(iseq, post_len as u32);
+    }
+
     fn gen_concat(&mut self, iseq: &mut ISeq) {
         iseq.push(Inst::CONCAT_STRING);
     }
@@ -578,13 +1121,7 @@ impl Codegen {
             self.method_stack.push(methodref)
         }
         let save_loc = self.loc;
-        let mut req_params = 0;
-        let mut opt_params = 0;
-        let mut rest_param = false;
-        let mut post_params = 0;
-        let mut block_param = false;
-        let mut param_ident = vec![];
-        let mut keyword_params = HashMap::new();
+        let mut params_info = ISeqParams::default();
         let mut iseq = ISeq::new();
 
         self.context_stack
@@ -592,16 +1129,16 @@ impl Codegen {
         for (lvar_id, param) in params.iter().enumerate() {
             match &param.kind {
                 NodeKind::Param(id) => {
-                    param_ident.push(*id);
-                    req_params += 1;
+                    params_info.param_ident.push(*id);
+                    params_info.req_params += 1;
                 }
                 NodeKind::PostParam(id) => {
-                    param_ident.push(*id);
-                    post_params += 1;
+                    params_info.param_ident.push(*id);
+                    params_info.post_params += 1;
                 }
                 NodeKind::OptionalParam(id, default) => {
-                    param_ident.push(*id);
-                    opt_params += 1;
+                    params_info.param_ident.push(*id);
+                    params_info.opt_params += 1;
                     self.gen_check_local(&mut iseq, *id)?;
                     let src1 = self.gen_jmp_if_false(&mut iseq);
                     self.gen(globals, &mut iseq, default, true)?;
@@ -609,12 +1146,14 @@ impl Codegen {
                     Codegen::write_disp_from_cur(&mut iseq, src1);
                 }
                 NodeKind::RestParam(id) => {
-                    param_ident.push(*id);
-                    rest_param = true;
+                    params_info.param_ident.push(*id);
+                    params_info.rest_param = true;
                 }
                 NodeKind::KeywordParam(id, default) => {
-                    param_ident.push(*id);
-                    keyword_params.insert(*id, LvarId::from_usize(lvar_id));
+                    params_info.param_ident.push(*id);
+                    params_info
+                        .keyword_params
+                        .insert(*id, LvarId::from_usize(lvar_id));
                     match &**default {
                         Some(default) => {
                             self.gen_check_local(&mut iseq, *id)?;
@@ -623,17 +1162,29 @@ impl Codegen {
                             self.gen_set_local(&mut iseq, *id);
                             Codegen::write_disp_from_cur(&mut iseq, src1);
                         }
-                        None => {}
+                        None => params_info.req_keyword_params.push(*id),
                     }
                 }
+                NodeKind::KWRestParam(id) => {
+                    params_info.param_ident.push(*id);
+                    params_info.kwrest_param = Some(LvarId::from_usize(lvar_id));
+                }
+                NodeKind::NoKWParam => {
+                    params_info.no_keyword_param = true;
+                }
                 NodeKind::BlockParam(id) => {
-                    param_ident.push(*id);
-                    block_param = true;
+                    params_info.param_ident.push(*id);
+                    params_info.block_param = true;
                 }
+                // Destructuring param: `(a, b)`
+                NodeKind::MulAssign(..) => self.gen(globals, &mut iseq, param, false)?,
                 _ => return Err(self.error_syntax("Parameters should be identifier.", param.loc)),
             }
         }
 
+        if kind == ContextKind::Block {
+            self.loop_stack.last_mut().unwrap().redo = Some(Codegen::current(&iseq));
+        }
         self.gen(globals, &mut iseq, node, use_value)?;
         let context = self.context_stack.pop().unwrap();
         let iseq_sourcemap = context.iseq_sourcemap;
@@ -643,13 +1194,7 @@ impl Codegen {
         let info = MethodInfo::RubyFunc {
             iseq: ISeqRef::new(ISeqInfo::new(
                 methodref,
-                req_params,
-                opt_params,
-                rest_param,
-                post_params,
-                block_param,
-                param_ident,
-                keyword_params,
+                params_info,
                 iseq,
                 lvar_collector.clone(),
                 iseq_sourcemap,
@@ -1097,6 +1642,7 @@ impl Codegen {
                         self.gen_get_local(iseq, id)?;
                         iseq.push(if *exclude_end { Inst::GT } else { Inst::GE });
                         let src = self.gen_jmp_if_false(iseq);
+                        self.loop_stack.last_mut().unwrap().redo = Some(Codegen::current(iseq));
                         self.gen(globals, iseq, body, false)?;
                         loop_continue = Codegen::current(iseq);
                         self.gen_get_local(iseq, id)?;
@@ -1132,6 +1678,7 @@ impl Codegen {
                 let loop_start = Codegen::current(iseq);
                 self.gen(globals, iseq, cond, true)?;
                 let src = self.gen_jmp_if_false(iseq);
+                self.loop_stack.last_mut().unwrap().redo = Some(Codegen::current(iseq));
                 self.gen(globals, iseq, body, false)?;
                 self.gen_jmp_back(iseq, loop_start);
                 Codegen::write_disp_from_cur(iseq, src);
@@ -1244,17 +1791,84 @@ impl Codegen {
                     Codegen::write_disp_from_cur(iseq, dest);
                 }
             }
+            NodeKind::CaseIn { cond, in_, else_ } => {
+                let mut end = vec![];
+                self.gen(globals, iseq, cond, true)?;
+                for branch in in_ {
+                    self.gen_dup(iseq, 1);
+                    self.gen_pattern(globals, iseq, &branch.pattern)?;
+                    let mut next = vec![self.gen_jmp_if_false(iseq)];
+                    if let Some(guard) = &branch.guard {
+                        self.gen(globals, iseq, guard, true)?;
+                        next.push(self.gen_jmp_if_false(iseq));
+                    }
+                    self.gen_pop(iseq);
+                    self.gen(globals, iseq, &branch.body, use_value)?;
+                    end.push(Codegen::gen_jmp(iseq));
+                    for src in next {
+                        Codegen::write_disp_from_cur(iseq, src);
+                    }
+                }
+                match else_ {
+                    Some(else_) => {
+                        self.gen_pop(iseq);
+                        self.gen(globals, iseq, else_, use_value)?;
+                    }
+                    None => {
+                        self.save_loc(iseq, cond.loc());
+                        iseq.push(Inst::NO_MATCHING_PATTERN);
+                    }
+                }
+                for dest in end {
+                    Codegen::write_disp_from_cur(iseq, dest);
+                }
+            }
+            NodeKind::PatternTest(expr, pattern) => {
+                self.gen(globals, iseq, expr, true)?;
+                self.gen_pattern(globals, iseq, pattern)?;
+                if !use_value {
+                    self.gen_pop(iseq);
+                }
+            }
+            NodeKind::AssignOp(op, lhs, rhs) => match (op, &lhs.kind) {
+                (BinOp::LOr, _) | (BinOp::LAnd, _) => {
+                    self.gen_assign_op(globals, iseq, *op, lhs, rhs)?;
+                    if !use_value {
+                        self.gen_pop(iseq);
+                    }
+                }
+                (_, NodeKind::LocalVar(_))
+                | (_, NodeKind::InstanceVar(_))
+                | (_, NodeKind::GlobalVar(_)) => {
+                    // Variables can be evaluated twice: lhs = lhs op rhs
+                    let lhs = lhs.as_ref().clone();
+                    let rhs = Node::new_binop(*op, lhs.clone(), rhs.as_ref().clone());
+                    let node = Node::new_mul_assign(vec![lhs], vec![rhs]);
+                    self.gen(globals, iseq, &node, use_value)?;
+                }
+                _ => {
+                    self.gen_assign_op(globals, iseq, *op, lhs, rhs)?;
+                    if !use_value {
+                        self.gen_pop(iseq);
+                    }
+                }
+            },
             NodeKind::MulAssign(mlhs, mrhs) => {
+                if mrhs.is_empty() {
+                    return Err(self.error_syntax("Unexpected multiple assignment.", node.loc()));
+                }
                 let lhs_len = mlhs.len();
                 let rhs_len = mrhs.len();
-                let splat_flag = mrhs.iter().any(|x| {
+                let is_splat = |x: &Node| {
                     if let NodeKind::Splat(_) = x.kind {
                         true
                     } else {
                         false
                     }
-                });
-                if lhs_len == rhs_len && !splat_flag {
+                };
+                let splat_flag = mrhs.iter().any(is_splat);
+                let lhs_splat_flag = mlhs.iter().any(is_splat);
+                if lhs_len == rhs_len && !splat_flag && !lhs_splat_flag {
                     if lhs_len == 1 {
                         match (&mlhs[0].kind, &mrhs[0].kind) {
                             (
@@ -1275,6 +1889,11 @@ impl Codegen {
                                 self.save_loc(iseq, loc);
                                 self.gen_ivar_addi(iseq, *id1, *i as i32 as u32, use_value);
                             }
+                            (NodeKind::Send { safe_nav: true, .. }, _) => {
+                                self.gen_safe_nav_assign(
+                                    globals, iseq, &mlhs[0], &mrhs[0], use_value,
+                                )?;
+                            }
                             _ => {
                                 self.gen(globals, iseq, &mrhs[0], true)?;
                                 if use_value {
@@ -1297,7 +1916,7 @@ impl Codegen {
                             self.gen_create_array(iseq, rhs_len);
                         };
                     }
-                } else if lhs_len == 1 {
+                } else if lhs_len == 1 && !lhs_splat_flag {
                     for rhs in mrhs.iter().rev() {
                         self.gen(globals, iseq, rhs, true)?;
                     }
@@ -1316,30 +1935,39 @@ impl Codegen {
                     if use_value {
                         self.gen_dup(iseq, 1);
                     };
-                    self.gen_take(iseq, lhs_len);
-
-                    for lhs in mlhs.iter().rev() {
-                        self.gen_assign(globals, iseq, lhs)?;
-                    }
+                    self.gen_mlhs_assign(globals, iseq, mlhs)?;
                 }
             }
             NodeKind::Send {
                 receiver,
                 method,
                 send_args,
+                safe_nav,
                 ..
             } => {
                 let loc = self.loc;
+                // receiver&.method: skip the call (and its arguments) if receiver is nil.
+                let safe_nav = if *safe_nav && NodeKind::SelfValue != receiver.kind {
+                    self.gen(globals, iseq, receiver, true)?;
+                    self.gen_dup(iseq, 1);
+                    Some(self.gen_jmp_if_nil(iseq))
+                } else {
+                    None
+                };
                 for arg in &send_args.args {
                     self.gen(globals, iseq, arg, true)?;
                 }
-                let kw_flag = send_args.kw_args.len() != 0;
+                let kw_flag = send_args.kw_args.len() != 0 || send_args.kw_splat.len() != 0;
                 if kw_flag {
                     for (id, default) in &send_args.kw_args {
                         self.gen_symbol(iseq, *id);
                         self.gen(globals, iseq, default, true)?;
                     }
                     self.gen_create_hash(iseq, send_args.kw_args.len());
+                    for hash in &send_args.kw_splat {
+                        self.gen(globals, iseq, hash, true)?;
+                        self.gen_merge_kwargs(iseq);
+                    }
                 }
                 let mut block_flag = false;
                 let block_ref = match &send_args.block {
@@ -1366,7 +1994,25 @@ impl Codegen {
                     },
                     None => None,
                 };
-                if NodeKind::SelfValue == receiver.kind {
+                if let Some(nil_pos) = safe_nav {
+                    let items = send_args.args.len()
+                        + if kw_flag { 1 } else { 0 }
+                        + if block_flag { 1 } else { 0 };
+                    self.gen_topn(iseq, items);
+                    self.loc = loc;
+                    self.gen_send(
+                        globals,
+                        iseq,
+                        *method,
+                        send_args.args.len(),
+                        create_flag(kw_flag, block_flag),
+                        block_ref,
+                    );
+                    // [receiver, result] => [result]
+                    self.gen_sinkn(iseq, 1);
+                    self.gen_pop(iseq);
+                    Codegen::write_disp_from_cur(iseq, nil_pos);
+                } else if NodeKind::SelfValue == receiver.kind {
                     self.loc = loc;
                     self.gen_send_self(
                         globals,
@@ -1555,6 +2201,17 @@ impl Codegen {
                     x.escape.push(EscapeInfo::new(src, EscapeKind::Next));
                 }
             }
+            NodeKind::Redo => {
+                let loc = node.loc();
+                let info = self.loop_stack.last().unwrap();
+                let in_block = self.context().kind == ContextKind::Block;
+                match info.redo {
+                    Some(pos) if info.state == LoopState::Loop || in_block => {
+                        self.gen_jmp_back(iseq, pos)
+                    }
+                    _ => return Err(self.error_syntax("Invalid redo.", loc.merge(self.loc))),
+                }
+            }
             NodeKind::Proc { params, body, lvar } => {
                 self.loop_stack.push(LoopInfo::new_top());
                 let methodref =
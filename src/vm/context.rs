@@ -1,4 +1,5 @@
 pub use crate::*;
+use std::collections::HashMap;
 use std::ops::{Index, IndexMut, Range};
 
 const LVAR_ARRAY_SIZE: usize = 32;
@@ -7,7 +8,7 @@ const LVAR_ARRAY_SIZE: usize = 32;
 pub struct Context {
     pub is_fiber: bool,
     pub self_value: Value,
-    pub block: Option<MethodRef>,
+    pub block: Option<Block>,
     lvar_ary: [Value; LVAR_ARRAY_SIZE],
     lvar_vec: Vec<Value>,
     pub iseq_ref: ISeqRef,
@@ -16,6 +17,10 @@ pub struct Context {
     pub on_stack: bool,
     pub stack_len: usize,
     pub kind: ISeqKind,
+    /// The frame-local $_.
+    pub last_line: Value,
+    /// The frame-local $~.
+    pub last_match: Value,
 }
 
 pub type ContextRef = Ref<Context>;
@@ -69,7 +74,7 @@ impl IndexMut<usize> for Context {
 impl Context {
     pub fn new(
         self_value: Value,
-        block: Option<MethodRef>,
+        block: Option<Block>,
         iseq_ref: ISeqRef,
         outer: Option<ContextRef>,
     ) -> Self {
@@ -91,6 +96,8 @@ impl Context {
             on_stack: true,
             stack_len: 0,
             kind: iseq_ref.kind.clone(),
+            last_line: Value::nil(),
+            last_match: Value::nil(),
         }
     }
 
@@ -100,15 +107,33 @@ impl Context {
         iseq: ISeqRef,
         args: &Args,
         outer: Option<ContextRef>,
+    ) -> Result<Self, RubyError> {
+        Context::from_args_with_check(vm, self_value, iseq, args, outer, !iseq.is_block())
+    }
+
+    /// Create a new context with `args`.
+    /// If `strict` is true, the number of arguments is checked and an array argument is not expanded,
+    /// as methods and lambdas do.
+    pub fn from_args_with_check(
+        vm: &mut VM,
+        self_value: Value,
+        iseq: ISeqRef,
+        args: &Args,
+        outer: Option<ContextRef>,
+        strict: bool,
     ) -> Result<Self, RubyError> {
         let mut context = Context::new(self_value, args.block, iseq, outer);
         let params = &iseq.params;
-        let kw = if params.keyword_params.is_empty() {
-            args.kw_arg
-        } else {
+        // An empty keyword hash (e.g. `**{}`) is treated as no keyword argument.
+        let kw_arg = args
+            .kw_arg
+            .filter(|kw| kw.as_hash().map_or(true, |href| href.len() != 0));
+        let kw = if params.accepts_keywords() {
             None
+        } else {
+            kw_arg
         };
-        if !iseq.is_block() {
+        if strict {
             let len = args.len() + if kw.is_some() { 1 } else { 0 };
             let min = params.req_params + params.post_params;
             if params.rest_param {
@@ -117,54 +142,82 @@ impl Context {
                 vm.check_args_range(len, min, min + params.opt_params)?;
             }
         }
-        context.set_arguments(&vm.globals, args, kw);
-        match args.kw_arg {
-            Some(kw_arg) if kw.is_none() => {
-                let keyword = kw_arg.as_hash().unwrap();
-                for (k, v) in keyword.iter() {
-                    let id = k.as_symbol().unwrap();
-                    match params.keyword_params.get(&id) {
-                        Some(lvar) => {
-                            context[*lvar] = v;
-                        }
-                        None => return Err(vm.error_argument("Undefined keyword.")),
-                    };
-                }
-            }
-            _ => {}
-        };
+        // A block with several parameters expands a single Array (or `to_ary`-able) argument.
+        let expanded =
+            if !strict && iseq.is_block() && args.len() == 1 && params.expands_array_arg() {
+                vm.to_ary(args[0])?
+            } else {
+                None
+            };
+        match expanded {
+            Some(args) => context.fill_arguments(&vm.globals, &args, args.len(), iseq, kw),
+            None => context.fill_arguments(&vm.globals, args, args.len(), iseq, kw),
+        }
+        if params.accepts_keywords() {
+            context.set_keyword_arguments(vm, kw_arg)?;
+        }
         if let Some(id) = iseq.lvar.block_param() {
             context[id] = match args.block {
-                Some(block) => {
-                    let proc_context = vm.create_block_context(block)?;
+                Some(Block::Block(method)) => {
+                    let proc_context = vm.create_block_context(method)?;
                     Value::procobj(&vm.globals, proc_context)
                 }
+                Some(Block::Proc(procobj)) => procobj,
                 None => Value::nil(),
             }
         }
         Ok(context)
     }
 
-    fn set_arguments(&mut self, globals: &Globals, args: &Args, kw_arg: Option<Value>) {
+    /// Set keyword parameters and `**rest` parameter from the keyword hash `kw_arg`.
+    /// Returns ArgumentError for unknown or missing keywords.
+    fn set_keyword_arguments(
+        &mut self,
+        vm: &mut VM,
+        kw_arg: Option<Value>,
+    ) -> Result<(), RubyError> {
         let iseq = self.iseq_ref;
-        let req_len = iseq.params.req_params;
-        let post_len = iseq.params.post_params;
-
-        match self.kind {
-            ISeqKind::Block(_) if args.len() == 1 && req_len + post_len > 1 => {
-                match args[0].as_array() {
-                    Some(ary) => {
-                        let args = &ary.elements;
-                        self.fill_arguments(globals, args, args.len(), iseq, kw_arg);
-                        return;
+        let params = &iseq.params;
+        let mut rest = HashMap::new();
+        if let Some(kw_arg) = kw_arg {
+            if params.no_keyword_param {
+                return Err(vm.error_argument("No keywords accepted."));
+            }
+            let mut unknown = vec![];
+            for (k, v) in kw_arg.as_hash().unwrap().iter() {
+                match k.as_symbol().and_then(|id| params.keyword_params.get(&id)) {
+                    Some(lvar) => self[*lvar] = v,
+                    None if params.kwrest_param.is_some() => {
+                        rest.insert(HashKey(k), v);
                     }
-                    _ => {}
+                    None => unknown.push(vm.val_inspect(k)),
                 }
             }
-            _ => {}
+            if !unknown.is_empty() {
+                return Err(vm.error_argument(format!(
+                    "Unknown keyword{}: {}.",
+                    if unknown.len() > 1 { "s" } else { "" },
+                    unknown.join(", ")
+                )));
+            }
         }
-
-        self.fill_arguments(globals, args, args.len(), iseq, kw_arg);
+        let missing: Vec<String> = params
+            .req_keyword_params
+            .iter()
+            .filter(|id| self[params.keyword_params[id]].is_uninitialized())
+            .map(|id| format!(":{}", vm.globals.get_ident_name(*id)))
+            .collect();
+        if !missing.is_empty() {
+            return Err(vm.error_argument(format!(
+                "Missing keyword{}: {}.",
+                if missing.len() > 1 { "s" } else { "" },
+                missing.join(", ")
+            )));
+        }
+        if let Some(lvar) = params.kwrest_param {
+            self[lvar] = Value::hash(&vm.globals, HashRef::from(rest));
+        }
+        Ok(())
     }
 
     fn fill_arguments(
@@ -231,7 +284,7 @@ impl Context {
 impl ContextRef {
     pub fn from(
         self_value: Value,
-        block: Option<MethodRef>,
+        block: Option<Block>,
         iseq_ref: ISeqRef,
         outer: Option<ContextRef>,
     ) -> Self {
@@ -1,4 +1,6 @@
 use super::codegen::ContextKind;
+use crate::builtin::float::float_to_s;
+use crate::builtin::range::create_range;
 use crate::*;
 
 #[cfg(feature = "perf")]
@@ -80,21 +82,39 @@ impl VM {
         set_builtin_class!("Object", object);
         set_builtin_class!("Module", module);
         set_builtin_class!("Class", class);
+        set_builtin_class!("Numeric", numeric);
         set_builtin_class!("Integer", integer);
         set_builtin_class!("Float", float);
+        set_builtin_class!("Rational", rational);
+        set_builtin_class!("Complex", complex);
         set_builtin_class!("Array", array);
         set_builtin_class!("Proc", procobj);
         set_builtin_class!("Range", range);
         set_builtin_class!("String", string);
         set_builtin_class!("Hash", hash);
         set_builtin_class!("Method", method);
+        set_builtin_class!("UnboundMethod", unbound_method);
         set_builtin_class!("Regexp", regexp);
         set_builtin_class!("Fiber", fiber);
         set_builtin_class!("Enumerator", enumerator);
-
+        set_builtin_class!("Time", time);
+        set_builtin_class!("Random", random);
+        set_builtin_class!("MatchData", matchdata);
+        set_builtin_class!("Symbol", symbol);
+        set_builtin_class!("NilClass", nilclass);
+        set_builtin_class!("TrueClass", trueclass);
+        set_builtin_class!("FalseClass", falseclass);
+
+        // ArgumentError must be defined before Math::DomainError.
+        set_class!("ArgumentError", errorobj::init_argument_error(&mut globals));
         set_class!("Math", math::init_math(&mut globals));
         set_class!("File", file::init_file(&mut globals));
+        set_class!("Dir", dir::init_dir(&mut globals));
+        set_class!("IO", io::init_io(&mut globals));
+        set_class!("FileUtils", fileutils::init_fileutils(&mut globals));
         set_class!("Process", process::init_process(&mut globals));
+        set_class!("Open3", open3::init_open3(&mut globals));
+        set_class!("ENV", env::init_env(&mut globals));
         set_class!("Struct", structobj::init_struct(&mut globals));
         set_class!("StandardError", Value::class(&globals, globals.class_class));
         set_class!("RuntimeError", errorobj::init_error(&mut globals));
@@ -138,6 +158,19 @@ impl VM {
         *self.exec_context.last().unwrap()
     }
 
+    /// The context of the current method (or the top level), skipping contexts of blocks.
+    /// Returns None if no context is running.
+    pub fn method_context(&self) -> Option<ContextRef> {
+        let mut context = *self.exec_context.last()?;
+        while let ISeqKind::Block(_) = context.kind {
+            match context.outer {
+                Some(outer) => context = outer,
+                None => break,
+            }
+        }
+        Some(context)
+    }
+
     pub fn caller_context(&self) -> ContextRef {
         let len = self.exec_context.len();
         if len < 2 {
@@ -147,7 +180,11 @@ impl VM {
     }
 
     pub fn source_info(&self) -> SourceInfoRef {
-        self.context().iseq_ref.source_info
+        match self.exec_context.last() {
+            Some(context) => context.iseq_ref.source_info,
+            // Methods may be called from Rust when no context is running.
+            None => SourceInfoRef::empty(),
+        }
     }
 
     pub fn fiberstate_created(&mut self) {
@@ -335,6 +372,10 @@ impl VM {
 
     pub fn run(&mut self, path: PathBuf, program: &str, self_value: Option<Value>) -> VMResult {
         let method = self.parse_program(path, program)?;
+        // DATA is defined only for the main program, not for required files.
+        if self.root_path.len() <= 1 {
+            self.set_data_const(method)?;
+        }
         let self_value = match self_value {
             Some(val) => val,
             None => self.globals.main_object,
@@ -358,6 +399,20 @@ impl VM {
         Ok(val)
     }
 
+    /// Define DATA as a File positioned at the text following `__END__`.
+    fn set_data_const(&mut self, method: MethodRef) -> Result<(), RubyError> {
+        let source_info = self.get_iseq(method)?.source_info;
+        let offset = match source_info.data_offset() {
+            Some(offset) => offset,
+            None => return Ok(()),
+        };
+        if let Some(data) = builtin::file::open_data(&mut self.globals, &source_info.path, offset) {
+            let id = self.globals.get_ident_id("DATA");
+            self.globals.builtins.object.set_var(id, data);
+        }
+        Ok(())
+    }
+
     pub fn run_repl(&mut self, result: &ParseResult, mut context: ContextRef) -> VMResult {
         #[cfg(feature = "perf")]
         #[cfg_attr(tarpaulin, skip)]
@@ -449,7 +504,7 @@ impl VM {
         self.context_push(context);
         self.pc = context.pc;
         let iseq = &context.iseq_ref.iseq;
-        let mut self_oref = context.self_value.as_object();
+        let self_value = context.self_value;
         loop {
             #[cfg(feature = "perf")]
             #[cfg_attr(tarpaulin, skip)]
@@ -566,8 +621,11 @@ impl VM {
                 }
                 Inst::PUSH_STRING => {
                     let id = self.read_id(iseq, 1);
-                    let string = self.globals.get_ident_name(id).to_string();
-                    self.stack_push(Value::string(&self.globals, string));
+                    let val = match RString::from_literal(self.globals.get_ident_name(id)) {
+                        RString::Str(s) => Value::string(&self.globals, s),
+                        RString::Bytes(b) => Value::bytes(&self.globals, b),
+                    };
+                    self.stack_push(val);
                     self.pc += 5;
                 }
                 Inst::PUSH_SYMBOL => {
@@ -604,64 +662,55 @@ impl VM {
                 Inst::MUL => {
                     let lhs = self.stack_pop();
                     let rhs = self.stack_pop();
-                    let val = self.eval_mul(lhs, rhs, iseq)?;
-                    self.stack_push(val);
+                    try_err!(self, self.eval_mul(lhs, rhs, iseq));
                     self.pc += 5;
                 }
                 Inst::POW => {
                     let lhs = self.stack_pop();
                     let rhs = self.stack_pop();
-                    let val = self.eval_exp(lhs, rhs)?;
-                    self.stack_push(val);
+                    try_err!(self, self.eval_exp(lhs, rhs));
                     self.pc += 1;
                 }
                 Inst::DIV => {
                     let lhs = self.stack_pop();
                     let rhs = self.stack_pop();
-                    let val = self.eval_div(lhs, rhs, iseq)?;
-                    self.stack_push(val);
+                    try_err!(self, self.eval_div(lhs, rhs, iseq));
                     self.pc += 5;
                 }
                 Inst::REM => {
                     let lhs = self.stack_pop();
                     let rhs = self.stack_pop();
-                    let val = self.eval_rem(lhs, rhs)?;
-                    self.stack_push(val);
+                    try_err!(self, self.eval_rem(lhs, rhs));
                     self.pc += 1;
                 }
                 Inst::SHR => {
                     let lhs = self.stack_pop();
                     let rhs = self.stack_pop();
-                    let val = self.eval_shr(lhs, rhs)?;
-                    self.stack_push(val);
+                    try_err!(self, self.eval_shr(lhs, rhs));
                     self.pc += 1;
                 }
                 Inst::SHL => {
                     let lhs = self.stack_pop();
                     let rhs = self.stack_pop();
-                    let val = self.eval_shl(lhs, rhs, iseq)?;
-                    self.stack_push(val);
+                    try_err!(self, self.eval_shl(lhs, rhs, iseq));
                     self.pc += 5;
                 }
                 Inst::BIT_AND => {
                     let lhs = self.stack_pop();
                     let rhs = self.stack_pop();
-                    let val = self.eval_bitand(lhs, rhs)?;
-                    self.stack_push(val);
+                    try_err!(self, self.eval_bitand(lhs, rhs));
                     self.pc += 1;
                 }
                 Inst::BIT_OR => {
                     let lhs = self.stack_pop();
                     let rhs = self.stack_pop();
-                    let val = self.eval_bitor(lhs, rhs)?;
-                    self.stack_push(val);
+                    try_err!(self, self.eval_bitor(lhs, rhs));
                     self.pc += 1;
                 }
                 Inst::BIT_XOR => {
                     let lhs = self.stack_pop();
                     let rhs = self.stack_pop();
-                    let val = self.eval_bitxor(lhs, rhs)?;
-                    self.stack_push(val);
+                    try_err!(self, self.eval_bitxor(lhs, rhs));
                     self.pc += 1;
                 }
                 Inst::BIT_NOT => {
@@ -673,15 +722,15 @@ impl VM {
                 Inst::EQ => {
                     let lhs = self.stack_pop();
                     let rhs = self.stack_pop();
-                    let val = Value::bool(self.eval_eq(rhs, lhs)?);
-                    self.stack_push(val);
+                    let res = self.eval_eq(rhs, lhs).map(Value::bool);
+                    try_err!(self, res);
                     self.pc += 1;
                 }
                 Inst::NE => {
                     let lhs = self.stack_pop();
                     let rhs = self.stack_pop();
-                    let val = Value::bool(!self.eval_eq(rhs, lhs)?);
-                    self.stack_push(val);
+                    let res = self.eval_ne(rhs, lhs).map(Value::bool);
+                    try_err!(self, res);
                     self.pc += 1;
                 }
                 Inst::TEQ => {
@@ -726,7 +775,12 @@ impl VM {
                         (Some(lhs), Some(rhs)) => {
                             Value::string(&self.globals, format!("{}{}", lhs, rhs))
                         }
-                        (_, _) => unreachable!("Illegal CAONCAT_STRING arguments."),
+                        (_, _) => match (lhs.as_bytes(), rhs.as_bytes()) {
+                            (Some(lhs), Some(rhs)) => {
+                                Value::bytes(&self.globals, [lhs, rhs].concat())
+                            }
+                            (_, _) => unreachable!("Illegal CAONCAT_STRING arguments."),
+                        },
                     };
                     self.stack_push(val);
                     self.pc += 1;
@@ -777,35 +831,50 @@ impl VM {
                     let id = self.read_id(iseq, 1);
                     let val = match self.get_env_const(id) {
                         Some(val) => val,
-                        None => self.get_super_const(self.class(), id)?,
+                        None => self.get_super_const_autoload(self.class(), id)?,
                     };
                     self.stack_push(val);
                     self.pc += 5;
                 }
+                Inst::CHECK_CONST => {
+                    let id = self.read_id(iseq, 1);
+                    let val = self.get_env_const(id).is_some()
+                        || self.get_super_const(self.class(), id).is_ok();
+                    self.stack_push(Value::bool(val));
+                    self.pc += 5;
+                }
+                Inst::CHECK_SCOPE => {
+                    let parent = self.stack_pop();
+                    let id = self.read_id(iseq, 1);
+                    let val = self.get_super_const(parent, id).is_ok();
+                    self.stack_push(Value::bool(val));
+                    self.pc += 5;
+                }
                 Inst::GET_CONST_TOP => {
                     let id = self.read_id(iseq, 1);
                     let class = self.globals.builtins.object;
-                    let val = self.get_super_const(class, id)?;
+                    let val = self.get_super_const_autoload(class, id)?;
                     self.stack_push(val);
                     self.pc += 5;
                 }
                 Inst::GET_SCOPE => {
                     let parent = self.stack_pop();
                     let id = self.read_id(iseq, 1);
-                    let val = self.get_super_const(parent, id)?;
+                    let val = self.get_super_const_autoload(parent, id)?;
                     self.stack_push(val);
                     self.pc += 5;
                 }
                 Inst::SET_IVAR => {
                     let var_id = self.read_id(iseq, 1);
                     let new_val = self.stack_pop();
+                    let mut self_oref = self.expect_ivar_receiver(self_value)?;
                     self_oref.set_var(var_id, new_val);
                     self.pc += 5;
                 }
                 Inst::GET_IVAR => {
                     let var_id = self.read_id(iseq, 1);
-                    let val = match self_oref.get_var(var_id) {
-                        Some(val) => val.clone(),
+                    let val = match self_value.is_object() {
+                        Some(oref) => oref.get_var(var_id).unwrap_or_default(),
                         None => Value::nil(),
                     };
                     self.stack_push(val);
@@ -814,6 +883,7 @@ impl VM {
                 Inst::IVAR_ADDI => {
                     let var_id = self.read_id(iseq, 1);
                     let i = self.read32(iseq, 5) as i32;
+                    let mut self_oref = self.expect_ivar_receiver(self_value)?;
                     match self_oref.get_mut_var(var_id) {
                         Some(val) => {
                             let new_val = self.eval_addi(*val, i)?;
@@ -830,7 +900,10 @@ impl VM {
                 Inst::SET_GVAR => {
                     let var_id = self.read_id(iseq, 1);
                     let new_val = self.stack_pop();
-                    self.set_global_var(var_id, new_val);
+                    if let Err(err) = self.assign_global_var(var_id, new_val) {
+                        let err: VMResult = Err(err);
+                        try_err!(self, err);
+                    }
                     self.pc += 5;
                 }
                 Inst::GET_GVAR => {
@@ -851,8 +924,24 @@ impl VM {
                                     args.push(val);
                                     aref.set_elem(self, &args)?;
                                 }
-                                ObjKind::Hash(mut href) => href.insert(args[0], val),
-                                _ => return Err(self.error_undefined_method("[]=", receiver)),
+                                ObjKind::Hash(mut href) => {
+                                    if let Err(err) = href.store(self, args[0], val) {
+                                        let err: VMResult = Err(err);
+                                        try_err!(self, err);
+                                    }
+                                }
+                                _ => {
+                                    let id = self.globals.get_ident_id("[]=");
+                                    match self.get_method(receiver, id) {
+                                        Ok(mref) => {
+                                            args.push(val);
+                                            self.eval_send(mref, receiver, &args)?;
+                                        }
+                                        Err(_) => {
+                                            return Err(self.error_undefined_method("[]=", receiver))
+                                        }
+                                    }
+                                }
                             };
                         }
                         None => return Err(self.error_undefined_method("[]=", receiver)),
@@ -863,45 +952,9 @@ impl VM {
                 Inst::GET_INDEX => {
                     let arg_num = self.read_usize(iseq, 1);
                     let args = self.pop_args_to_ary(arg_num);
-                    let arg_num = args.len();
                     let receiver = self.stack_pop();
-                    let val = match receiver.is_object() {
-                        Some(oref) => match &oref.kind {
-                            ObjKind::Array(aref) => aref.get_elem(self, &args)?,
-                            ObjKind::Hash(href) => {
-                                self.check_args_range(arg_num, 1, 1)?;
-                                match href.get(&args[0]) {
-                                    Some(val) => *val,
-                                    None => Value::nil(),
-                                }
-                            }
-                            ObjKind::Method(mref) => {
-                                self.eval_send(mref.method, mref.receiver, &args)?
-                            }
-                            _ => {
-                                let id = self.globals.get_ident_id("[]");
-                                match self.get_method(receiver, id) {
-                                    Ok(mref) => self.eval_send(mref, receiver, &args)?,
-                                    Err(_) => {
-                                        return Err(self.error_undefined_method("[]", receiver))
-                                    }
-                                }
-                            }
-                        },
-                        None if receiver.is_packed_fixnum() => {
-                            let i = receiver.as_packed_fixnum();
-                            self.check_args_range(arg_num, 1, 1)?;
-                            let index = args[0].expect_integer(&self, "Index")?;
-                            let val = if index < 0 || 63 < index {
-                                0
-                            } else {
-                                (i >> index) & 1
-                            };
-                            Value::fixnum(val)
-                        }
-                        _ => return Err(self.error_undefined_method("[]", receiver)),
-                    };
-                    self.stack_push(val);
+                    let res = self.get_index(receiver, &args);
+                    try_err!(self, res);
                     self.pc += 5;
                 }
                 Inst::SPLAT => {
@@ -913,13 +966,9 @@ impl VM {
                 Inst::CREATE_RANGE => {
                     let start = self.stack_pop();
                     let end = self.stack_pop();
-                    if !start.is_packed_fixnum() || !end.is_packed_fixnum() {
-                        return Err(self.error_argument("Bad value for range."));
-                    };
                     let exclude_val = self.stack_pop();
                     let exclude_end = self.val_to_bool(exclude_val);
-                    let range = Value::range(&self.globals, start, end, exclude_end);
-                    self.stack_push(range);
+                    try_err!(self, create_range(self, start, end, exclude_end));
                     self.pc += 1;
                 }
                 Inst::CREATE_ARRAY => {
@@ -931,15 +980,14 @@ impl VM {
                 }
                 Inst::CREATE_PROC => {
                     let method = self.read_methodref(iseq, 1);
-                    let proc_obj = self.create_proc(method)?;
+                    let proc_obj = self.create_proc(Block::Block(method))?;
                     self.stack_push(proc_obj);
                     self.pc += 5;
                 }
                 Inst::CREATE_HASH => {
                     let arg_num = self.read_usize(iseq, 1);
-                    let key_value = self.pop_key_value_pair(arg_num);
-                    let hash = Value::hash(&self.globals, HashRef::from(key_value));
-                    self.stack_push(hash);
+                    let res = self.pop_key_value_pair(arg_num);
+                    try_err!(self, res);
                     self.pc += 5;
                 }
                 Inst::CREATE_REGEXP => {
@@ -961,6 +1009,54 @@ impl VM {
                         self.jump_pc(5, disp);
                     }
                 }
+                Inst::JMP_IF_NIL => {
+                    let val = self.stack_pop();
+                    if val.is_nil() {
+                        let disp = self.read_disp(iseq, 1);
+                        self.jump_pc(5, disp);
+                    } else {
+                        self.jump_pc(5, 0);
+                    }
+                }
+                Inst::DECONSTRUCT => {
+                    let len = self.read_usize(iseq, 1);
+                    let rest_pos = self.read32(iseq, 5);
+                    let val = self.stack_pop();
+                    try_err!(self, self.push_array_pattern_elems(val, len, rest_pos));
+                    self.pc += 9;
+                }
+                Inst::DECONSTRUCT_KEYS => {
+                    let rest_mode = self.read32(iseq, 1);
+                    let keys = self.stack_pop();
+                    let val = self.stack_pop();
+                    try_err!(self, self.push_hash_pattern_values(val, keys, rest_mode));
+                    self.pc += 5;
+                }
+                Inst::FIND_SLICE => {
+                    let len = self.read_usize(iseq, 1);
+                    let index = self.stack_pop().as_fixnum().unwrap() as usize;
+                    let ary = self.stack_pop().as_array().unwrap();
+                    if index + len > ary.elements.len() {
+                        self.stack_push(Value::false_val());
+                    } else {
+                        let elems = &ary.elements;
+                        let post = elems[index + len..].to_vec();
+                        let pre = elems[..index].to_vec();
+                        self.stack_push(Value::array_from(&self.globals, post));
+                        self.stack_push(Value::array_from(&self.globals, pre));
+                        for elem in elems[index..index + len].iter().rev() {
+                            self.stack_push(*elem);
+                        }
+                        self.stack_push(Value::true_val());
+                    }
+                    self.pc += 5;
+                }
+                Inst::NO_MATCHING_PATTERN => {
+                    let val = self.stack_pop();
+                    let inspect = self.val_inspect(val);
+                    let err: VMResult = Err(self.error_no_matching_pattern(inspect));
+                    try_err!(self, err);
+                }
                 Inst::OPT_CASE => {
                     let val = self.stack_pop();
                     let map = self.globals.get_case_dispatch_map(self.read32(iseq, 1));
@@ -1063,6 +1159,12 @@ impl VM {
                     self.stack_push(res);
                     self.pc += 1;
                 }
+                Inst::MERGE_KWARGS => {
+                    let val = self.stack_pop();
+                    let hash = self.stack_pop();
+                    try_err!(self, self.merge_kwargs(hash, val));
+                    self.pc += 1;
+                }
                 Inst::POP => {
                     self.stack_pop();
                     self.pc += 1;
@@ -1076,36 +1178,49 @@ impl VM {
                     }
                     self.pc += 5;
                 }
+                Inst::TOPN => {
+                    let pos = self.read_usize(iseq, 1);
+                    let val = self.exec_stack[self.exec_stack.len() - 1 - pos];
+                    self.stack_push(val);
+                    self.pc += 5;
+                }
+                Inst::SINKN => {
+                    let pos = self.read_usize(iseq, 1);
+                    let val = self.stack_pop();
+                    let len = self.exec_stack.len();
+                    self.exec_stack.insert(len - pos, val);
+                    self.pc += 5;
+                }
                 Inst::TAKE => {
                     let len = self.read_usize(iseq, 1);
                     let val = self.stack_pop();
-                    match val.as_array() {
-                        Some(info) => {
-                            let elem = &info.elements;
-                            let ary_len = elem.len();
-                            if len <= ary_len {
-                                for i in 0..len {
-                                    self.stack_push(elem[i]);
-                                }
-                            } else {
-                                for i in 0..ary_len {
-                                    self.stack_push(elem[i]);
-                                }
-                                for _ in ary_len..len {
-                                    self.stack_push(Value::nil());
-                                }
-                            }
-                        }
-                        None => {
-                            self.stack_push(val);
-                            for _ in 0..len - 1 {
-                                self.stack_push(Value::nil());
-                            }
-                        }
+                    try_err!(self, self.to_ary_for_assign(val));
+                    let ary = self.stack_pop().as_array().unwrap();
+                    let elem = &ary.elements;
+                    for i in 0..len {
+                        self.stack_push(elem.get(i).cloned().unwrap_or_default());
                     }
-
                     self.pc += 5;
                 }
+                Inst::EXPAND_ARRAY => {
+                    // [pre.., rest.., post..] => pre.., [rest..], post..
+                    let pre_len = self.read_usize(iseq, 1);
+                    let post_len = self.read_usize(iseq, 5);
+                    let val = self.stack_pop();
+                    try_err!(self, self.to_ary_for_assign(val));
+                    let ary = self.stack_pop().as_array().unwrap();
+                    let elem = &ary.elements;
+                    for i in 0..pre_len {
+                        self.stack_push(elem.get(i).cloned().unwrap_or_default());
+                    }
+                    let post_pos = std::cmp::max(pre_len, elem.len().saturating_sub(post_len));
+                    let rest = elem.get(pre_len..post_pos).unwrap_or(&[]).to_vec();
+                    self.stack_push(Value::array_from(&self.globals, rest));
+                    for i in post_pos..post_pos + post_len {
+                        self.stack_push(elem.get(i).cloned().unwrap_or_default());
+                    }
+                    self.pc += 9;
+                }
                 _ => return Err(self.error_unimplemented("Unimplemented instruction.")),
             }
         }
@@ -1202,11 +1317,44 @@ impl VM {
         RubyError::new_runtime_err(RuntimeErrKind::Index(msg.into()), self.source_info(), loc)
     }
 
+    pub fn error_zero_division(&self) -> RubyError {
+        let loc = self.get_loc();
+        RubyError::new_runtime_err(
+            RuntimeErrKind::ZeroDivision("divided by 0".to_string()),
+            self.source_info(),
+            loc,
+        )
+    }
+
+    pub fn error_domain(&self, msg: impl Into<String>) -> RubyError {
+        let loc = self.get_loc();
+        RubyError::new_runtime_err(RuntimeErrKind::Domain(msg.into()), self.source_info(), loc)
+    }
+
+    pub fn error_range(&self, msg: impl Into<String>) -> RubyError {
+        let loc = self.get_loc();
+        RubyError::new_runtime_err(RuntimeErrKind::Range(msg.into()), self.source_info(), loc)
+    }
+
     pub fn error_fiber(&self, msg: impl Into<String>) -> RubyError {
         let loc = self.get_loc();
         RubyError::new_runtime_err(RuntimeErrKind::Fiber(msg.into()), self.source_info(), loc)
     }
 
+    pub fn error_no_matching_pattern(&self, msg: impl Into<String>) -> RubyError {
+        let loc = self.get_loc();
+        RubyError::new_runtime_err(
+            RuntimeErrKind::NoMatchingPattern(msg.into()),
+            self.source_info(),
+            loc,
+        )
+    }
+
+    pub fn error_load(&self, msg: impl Into<String>) -> RubyError {
+        let loc = self.get_loc();
+        RubyError::new_runtime_err(RuntimeErrKind::Load(msg.into()), self.source_info(), loc)
+    }
+
     pub fn error_method_return(&self, method: MethodRef) -> RubyError {
         let loc = self.get_loc();
         RubyError::new_method_return(method, self.source_info(), loc)
@@ -1249,10 +1397,37 @@ impl VM {
             )))
         }
     }
+
+    /// Check that all keyword arguments in `args` are included in `accepted`.
+    pub fn check_kwargs(&mut self, args: &Args, accepted: &[&str]) -> Result<(), RubyError> {
+        let href = match args.kw_hash() {
+            Some(href) => href,
+            None => return Ok(()),
+        };
+        for (k, _) in href.iter() {
+            let known = match k.as_symbol() {
+                Some(id) => accepted.contains(&self.globals.get_ident_name(id)),
+                None => false,
+            };
+            if !known {
+                let inspect = self.val_inspect(k);
+                return Err(self.error_argument(format!("Unknown keyword: {}.", inspect)));
+            }
+        }
+        Ok(())
+    }
 }
 
 impl VM {
-    pub fn expect_block(&self, block: Option<MethodRef>) -> Result<MethodRef, RubyError> {
+    /// Immediate values such as Integer or Symbol can not have instance variables.
+    fn expect_ivar_receiver(&self, val: Value) -> Result<ObjectRef, RubyError> {
+        val.is_object().ok_or_else(|| {
+            let class = self.globals.get_class_name(val);
+            self.error_type(format!("Can't modify frozen {}.", class))
+        })
+    }
+
+    pub fn expect_block(&self, block: Option<Block>) -> Result<Block, RubyError> {
         match block {
             Some(method) => Ok(method),
             None => return Err(self.error_argument("Currently, needs block.")),
@@ -1345,7 +1520,11 @@ impl VM {
 
 impl VM {
     fn get_loc(&self) -> Loc {
-        let sourcemap = &self.context().iseq_ref.iseq_sourcemap;
+        let context = match self.exec_context.last() {
+            Some(context) => context,
+            None => return Loc(0, 0),
+        };
+        let sourcemap = &context.iseq_ref.iseq_sourcemap;
         sourcemap
             .iter()
             .find(|x| x.0 == ISeqPos::from(self.pc))
@@ -1418,15 +1597,51 @@ impl VM {
         }
     }
 
-    pub fn get_global_var(&self, id: IdentId) -> Value {
-        match self.globals.global_var.get(&id) {
-            Some(val) => val.clone(),
-            None => Value::nil(),
+    /// Search class inheritance chain for the constant.
+    /// If a file is registered by autoload for the constant, requires it and searches again.
+    pub fn get_super_const_autoload(&mut self, class: Value, id: IdentId) -> VMResult {
+        match self.get_super_const(class, id) {
+            Ok(val) => Ok(val),
+            Err(err) => {
+                if self.autoload_const(class, id)? {
+                    self.get_super_const(class, id)
+                } else {
+                    Err(err)
+                }
+            }
+        }
+    }
+
+    /// Require the file registered by autoload for the constant `id` in `class`,
+    /// its ancestors or Object. Returns false if no file is registered.
+    fn autoload_const(&mut self, class: Value, id: IdentId) -> Result<bool, RubyError> {
+        let mut class = Some(class);
+        let object = self.globals.builtins.object;
+        while let Some(module) = class {
+            let mut cref = match module.as_module() {
+                Some(cref) => cref,
+                None => break,
+            };
+            if let Some(file_name) = cref.autoload.remove(&id) {
+                self.require_feature(&file_name)?;
+                return Ok(true);
+            }
+            class = match module.superclass() {
+                Some(superclass) => Some(superclass),
+                None if module != object => Some(object),
+                None => None,
+            };
         }
+        Ok(false)
     }
 
-    pub fn set_global_var(&mut self, id: IdentId, val: Value) {
-        self.globals.global_var.insert(id, val);
+    /// Run procs registered by Kernel#at_exit in reverse order of registration.
+    pub fn exec_at_exit(&mut self) {
+        while let Some(procobj) = self.globals.at_exit.pop() {
+            if let Err(err) = self.eval_proc(procobj, &Args::new0()) {
+                err.show_err();
+            }
+        }
     }
 }
 
@@ -1518,6 +1733,19 @@ macro_rules! eval_op {
     };
 }
 
+macro_rules! eval_op_values {
+    ($vm:ident, $rhs:expr, $lhs:expr, $op:ident, $id:expr) => {
+        let val = match ($lhs.unpack(), $rhs.unpack()) {
+            (RV::Integer(lhs), RV::Integer(rhs)) => Value::fixnum(lhs.$op(rhs)),
+            (RV::Integer(lhs), RV::Float(rhs)) => Value::flonum((lhs as f64).$op(rhs)),
+            (RV::Float(lhs), RV::Integer(rhs)) => Value::flonum(lhs.$op(rhs as f64)),
+            (RV::Float(lhs), RV::Float(rhs)) => Value::flonum(lhs.$op(rhs)),
+            _ => return $vm.fallback_to_method($id, $lhs, $rhs),
+        };
+        return Ok(val);
+    };
+}
+
 impl VM {
     fn eval_add(&mut self, rhs: Value, lhs: Value, iseq: &ISeq) -> VMResult {
         use std::ops::Add;
@@ -1545,8 +1773,42 @@ impl VM {
     }
 
     fn eval_div(&mut self, rhs: Value, lhs: Value, iseq: &ISeq) -> VMResult {
-        use std::ops::Div;
-        eval_op!(self, iseq, rhs, lhs, div, IdentId::_DIV);
+        use divrem::*;
+        let val = match (lhs.unpack(), rhs.unpack()) {
+            (RV::Integer(_), RV::Integer(0)) => return Err(self.error_zero_division()),
+            (RV::Integer(lhs), RV::Integer(rhs)) => Value::fixnum(lhs.div_floor(rhs)),
+            (RV::Integer(lhs), RV::Float(rhs)) => Value::flonum(lhs as f64 / rhs),
+            (RV::Float(lhs), RV::Integer(rhs)) => Value::flonum(lhs / rhs as f64),
+            (RV::Float(lhs), RV::Float(rhs)) => Value::flonum(lhs / rhs),
+            _ => {
+                let cache = self.read32(iseq, 1);
+                return self.fallback_to_method_with_cache(lhs, rhs, IdentId::_DIV, cache);
+            }
+        };
+        Ok(val)
+    }
+
+    /// Evaluate `lhs + rhs` outside of the instruction sequence.
+    pub fn eval_add_values(&mut self, lhs: Value, rhs: Value) -> VMResult {
+        use std::ops::Add;
+        eval_op_values!(self, rhs, lhs, add, IdentId::_ADD);
+    }
+
+    /// Evaluate `lhs - rhs` outside of the instruction sequence.
+    pub fn eval_sub_values(&mut self, lhs: Value, rhs: Value) -> VMResult {
+        use std::ops::Sub;
+        eval_op_values!(self, rhs, lhs, sub, IdentId::_SUB);
+    }
+
+    /// Evaluate `lhs * rhs` outside of the instruction sequence.
+    pub fn eval_mul_values(&mut self, lhs: Value, rhs: Value) -> VMResult {
+        use std::ops::Mul;
+        eval_op_values!(self, rhs, lhs, mul, IdentId::_MUL);
+    }
+
+    /// Evaluate `lhs / rhs` outside of the instruction sequence.
+    pub fn eval_div_values(&mut self, lhs: Value, rhs: Value) -> VMResult {
+        self.fallback_to_method(IdentId::_DIV, lhs, rhs)
     }
 
     fn eval_rem(&mut self, rhs: Value, lhs: Value) -> VMResult {
@@ -1561,6 +1823,7 @@ impl VM {
         }
         use divrem::*;
         let val = match (lhs.unpack(), rhs.unpack()) {
+            (RV::Integer(_), RV::Integer(0)) => return Err(self.error_zero_division()),
             (RV::Integer(lhs), RV::Integer(rhs)) => Value::fixnum(lhs.rem_floor(rhs)),
             (RV::Integer(lhs), RV::Float(rhs)) => Value::flonum(rem_floorf64(lhs as f64, rhs)),
             (RV::Float(lhs), RV::Integer(rhs)) => Value::flonum(rem_floorf64(lhs, rhs as f64)),
@@ -1575,6 +1838,15 @@ impl VM {
             (RV::Integer(lhs), RV::Integer(rhs)) => {
                 if 0 <= rhs && rhs <= std::u32::MAX as i64 {
                     Value::fixnum(lhs.pow(rhs as u32))
+                } else if rhs < 0 {
+                    // Integer ** negative Integer is a Rational.
+                    if lhs == 0 {
+                        return Err(self.error_zero_division());
+                    }
+                    match RationalInfo::new(lhs, 1).pow(rhs) {
+                        Some(info) => Value::rational(&self.globals, info),
+                        None => Value::flonum((lhs as f64).powf(rhs as f64)),
+                    }
                 } else {
                     Value::flonum((lhs as f64).powf(rhs as f64))
                 }
@@ -1624,7 +1896,10 @@ impl VM {
         }
         match (lhs.unpack(), rhs.unpack()) {
             (RV::Integer(lhs), RV::Integer(rhs)) => Ok(Value::fixnum(lhs >> rhs)),
-            (_, _) => return Err(self.error_undefined_op(">>", rhs, lhs)),
+            (_, _) => {
+                let id = self.globals.get_ident_id(">>");
+                self.fallback_to_method(id, lhs, rhs)
+            }
         }
     }
 
@@ -1636,7 +1911,10 @@ impl VM {
         }
         match (lhs.unpack(), rhs.unpack()) {
             (RV::Integer(lhs), RV::Integer(rhs)) => Ok(Value::fixnum(lhs & rhs)),
-            (_, _) => return Err(self.error_undefined_op("&", rhs, lhs)),
+            (_, _) => {
+                let id = self.globals.get_ident_id("&");
+                self.fallback_to_method(id, lhs, rhs)
+            }
         }
     }
 
@@ -1648,7 +1926,10 @@ impl VM {
         }
         match (lhs.unpack(), rhs.unpack()) {
             (RV::Integer(lhs), RV::Integer(rhs)) => Ok(Value::fixnum(lhs | rhs)),
-            (_, _) => return Err(self.error_undefined_op("|", rhs, lhs)),
+            (_, _) => {
+                let id = self.globals.get_ident_id("|");
+                self.fallback_to_method(id, lhs, rhs)
+            }
         }
     }
 
@@ -1660,7 +1941,10 @@ impl VM {
         }
         match (lhs.unpack(), rhs.unpack()) {
             (RV::Integer(lhs), RV::Integer(rhs)) => Ok(Value::fixnum(lhs ^ rhs)),
-            (_, _) => return Err(self.error_undefined_op("^", rhs, lhs)),
+            (_, _) => {
+                let id = self.globals.get_ident_id("^");
+                self.fallback_to_method(id, lhs, rhs)
+            }
         }
     }
 
@@ -1685,33 +1969,145 @@ macro_rules! eval_cmp {
 }
 
 impl VM {
-    pub fn eval_eq(&self, rhs: Value, lhs: Value) -> Result<bool, RubyError> {
-        Ok(rhs.equal(lhs))
+    /// Evaluate `lhs == rhs`, calling `==` of `lhs` if it is defined in Ruby.
+    pub fn eval_eq(&mut self, lhs: Value, rhs: Value) -> Result<bool, RubyError> {
+        if lhs.is_packed_value() {
+            return Ok(lhs.equal(rhs));
+        }
+        if let Some(method) = self.get_user_method(lhs, IdentId::_EQ) {
+            let res = self.eval_send(method, lhs, &Args::new1(rhs))?;
+            return Ok(self.val_to_bool(res));
+        }
+        if rhs.is_packed_value() {
+            return Ok(lhs.equal(rhs));
+        }
+        if lhs.id() == rhs.id() {
+            return Ok(true);
+        }
+        match (&lhs.rvalue().kind, &rhs.rvalue().kind) {
+            (ObjKind::Array(lhs), ObjKind::Array(rhs)) => {
+                if lhs.elements.len() != rhs.elements.len() {
+                    return Ok(false);
+                }
+                for (lhs, rhs) in lhs.elements.iter().zip(rhs.elements.iter()) {
+                    if !self.eval_eq(*lhs, *rhs)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            (ObjKind::Hash(lhs), ObjKind::Hash(rhs)) => {
+                if lhs.len() != rhs.len() {
+                    return Ok(false);
+                }
+                let mut rhs = *rhs;
+                for (key, lhs_val) in lhs.iter() {
+                    let rhs_val = match rhs.lookup(self, key)? {
+                        Some(val) => val,
+                        None => return Ok(false),
+                    };
+                    if !self.eval_eq(lhs_val, rhs_val)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            _ => Ok(lhs.equal(rhs)),
+        }
+    }
+
+    /// Evaluate `lhs != rhs`, calling `!=` of `lhs` if it is defined in Ruby.
+    pub fn eval_ne(&mut self, lhs: Value, rhs: Value) -> Result<bool, RubyError> {
+        if !lhs.is_packed_value() {
+            if let Some(method) = self.get_user_method(lhs, IdentId::_NEQ) {
+                let res = self.eval_send(method, lhs, &Args::new1(rhs))?;
+                return Ok(self.val_to_bool(res));
+            }
+        }
+        Ok(!self.eval_eq(lhs, rhs)?)
     }
 
+    /// Evaluate `lhs === rhs` (e.g. `when` clause of case expression),
+    /// calling `===` of `lhs` if it is defined in Ruby.
     pub fn eval_teq(&mut self, rhs: Value, lhs: Value) -> Result<bool, RubyError> {
-        match lhs.is_object() {
-            Some(oref) => match oref.kind {
-                ObjKind::Class(_) => {
-                    let res = rhs.get_class_object(&self.globals).id() == lhs.id();
-                    Ok(res)
-                }
-                ObjKind::Regexp(re) => {
-                    let given = match rhs.unpack() {
-                        RV::Symbol(sym) => self.globals.get_ident_name(sym),
-                        RV::Object(_) => match rhs.as_string() {
-                            Some(s) => s,
-                            None => return Ok(false),
-                        },
-                        _ => return Ok(false),
-                    }
-                    .to_owned();
-                    let res = Regexp::find_one(self, &re.regexp, &given)?.is_some();
-                    Ok(res)
+        let oref = match lhs.is_object() {
+            Some(oref) => oref,
+            None => return self.eval_eq(lhs, rhs),
+        };
+        let id = self.globals.get_ident_id("===");
+        if let Some(method) = self.get_user_method(lhs, id) {
+            let res = self.eval_send(method, lhs, &Args::new1(rhs))?;
+            return Ok(self.val_to_bool(res));
+        }
+        match &oref.kind {
+            ObjKind::Class(_) | ObjKind::Module(_) => Ok(self.is_kind_of(rhs, lhs)),
+            ObjKind::Regexp(re) => {
+                let given = match rhs.unpack() {
+                    RV::Symbol(sym) => self.globals.get_ident_name(sym),
+                    RV::Object(_) => match rhs.as_string() {
+                        Some(s) => s,
+                        None => return Ok(false),
+                    },
+                    _ => return Ok(false),
                 }
-                _ => Ok(self.eval_eq(lhs, rhs).unwrap_or(false)),
+                .to_owned();
+                let res = Regexp::find_one(self, &re.regexp, &given)?.is_some();
+                Ok(res)
+            }
+            ObjKind::Range(range) => self.range_include(range, rhs),
+            ObjKind::Proc(_) => {
+                let res = self.eval_proc(lhs, &Args::new1(rhs))?;
+                Ok(self.val_to_bool(res))
+            }
+            _ => self.eval_eq(lhs, rhs),
+        }
+    }
+
+    /// Returns true if `val` is an instance of `module` or its descendants,
+    /// including classes which include `module`.
+    pub fn is_kind_of(&self, val: Value, module: Value) -> bool {
+        fn includes(class: Value, module: Value) -> bool {
+            match class.as_module() {
+                Some(cref) => cref
+                    .include
+                    .iter()
+                    .any(|included| included.id() == module.id() || includes(*included, module)),
+                None => false,
+            }
+        }
+        let mut class = val.get_class_object(&self.globals);
+        loop {
+            if class.id() == module.id() || includes(class, module) {
+                return true;
+            }
+            class = match class.superclass() {
+                Some(superclass) => superclass,
+                None => return false,
+            };
+        }
+    }
+
+    /// Returns true if `val` is in `range`, comparing with `<=>`.
+    /// A nil bound is treated as unbounded on that side.
+    pub fn range_include(&mut self, range: &RangeInfo, val: Value) -> Result<bool, RubyError> {
+        // start <=> val
+        if !range.start.is_nil() {
+            match self.eval_cmp(val, range.start) {
+                Ok(ord) if ord.as_fixnum().map_or(false, |ord| ord <= 0) => {}
+                _ => return Ok(false),
+            };
+        }
+        if range.end.is_nil() {
+            return Ok(true);
+        }
+        // val <=> end
+        match self.eval_cmp(range.end, val) {
+            Ok(ord) => match ord.as_fixnum() {
+                Some(ord) if range.exclude => Ok(ord < 0),
+                Some(ord) => Ok(ord <= 0),
+                None => Ok(false),
             },
-            None => Ok(self.eval_eq(lhs, rhs).unwrap_or(false)),
+            Err(_) => Ok(false),
         }
     }
 
@@ -1728,12 +2124,12 @@ impl VM {
             RV::Integer(lhs) => match rhs.unpack() {
                 RV::Integer(rhs) => lhs.partial_cmp(&rhs),
                 RV::Float(rhs) => (lhs as f64).partial_cmp(&rhs),
-                _ => return Ok(Value::nil()),
+                _ => return self.fallback_to_method(IdentId::_CMP, Value::fixnum(lhs), rhs),
             },
             RV::Float(lhs) => match rhs.unpack() {
                 RV::Integer(rhs) => lhs.partial_cmp(&(rhs as f64)),
                 RV::Float(rhs) => lhs.partial_cmp(&rhs),
-                _ => return Ok(Value::nil()),
+                _ => return self.fallback_to_method(IdentId::_CMP, Value::flonum(lhs), rhs),
             },
             _ => {
                 let id = self.globals.get_ident_id("<=>");
@@ -1785,6 +2181,169 @@ impl VM {
         };
         self.create_regexp_from_string(&arg)
     }
+
+    /// Merge `val` given as a double splat argument into the keyword hash `hash`.
+    /// `nil` is ignored, and a non-Hash object is converted by `to_hash`.
+    /// Push elements of `val` for array patterns: [..post, rest, ..pre]
+    /// Return false without pushing anything if `val` does not match.
+    fn push_array_pattern_elems(&mut self, val: Value, len: usize, rest_pos: u32) -> VMResult {
+        let elems = match self.deconstruct(val)? {
+            Some(elems) if rest_pos == std::u32::MAX && elems.len() == len => elems,
+            Some(mut elems) if rest_pos != std::u32::MAX && elems.len() >= len => {
+                // [pre.., rest.., post..] => [pre.., [rest..], post..]
+                let rest_pos = rest_pos as usize;
+                let mut post = elems.split_off(rest_pos);
+                let rest = post.drain(0..post.len() + rest_pos - len).collect();
+                elems.push(Value::array_from(&self.globals, rest));
+                elems.append(&mut post);
+                elems
+            }
+            _ => return Ok(Value::false_val()),
+        };
+        for elem in elems.iter().rev() {
+            self.stack_push(*elem);
+        }
+        Ok(Value::true_val())
+    }
+
+    /// Push values of `val` for hash patterns: [rest, ..values]
+    /// `rest_mode` 0: other keys are allowed. 1: **nil. 2: **rest.
+    /// Return false without pushing anything if `val` does not match.
+    fn push_hash_pattern_values(&mut self, val: Value, keys: Value, rest_mode: u32) -> VMResult {
+        // deconstruct_keys receives nil when the pattern has **rest.
+        let arg = if rest_mode == 2 { Value::nil() } else { keys };
+        let keys = keys.as_array().unwrap().elements.clone();
+        let hash = match self.deconstruct_keys(val, arg)? {
+            Some(hash) if keys.iter().all(|k| hash.contains_key(*k)) => hash,
+            _ => return Ok(Value::false_val()),
+        };
+        match rest_mode {
+            1 if hash.len() != keys.len() => return Ok(Value::false_val()),
+            2 => {
+                let mut rest = HashMap::new();
+                for (k, v) in hash.iter() {
+                    if !keys.contains(&k) {
+                        rest.insert(HashKey(k), v);
+                    }
+                }
+                self.stack_push(Value::hash_from(&self.globals, rest));
+            }
+            _ => {}
+        }
+        for k in keys.iter().rev() {
+            let val = *hash.get(k).unwrap();
+            self.stack_push(val);
+        }
+        Ok(Value::true_val())
+    }
+
+    /// Convert `val` into an Array for multiple assignment.
+    /// `val` is converted by `to_ary`, or wrapped in an Array if it does not respond to `to_ary`.
+    fn to_ary_for_assign(&mut self, val: Value) -> VMResult {
+        if val.as_array().is_some() {
+            return Ok(val);
+        }
+        let ary = match self.to_ary(val)? {
+            Some(elems) => elems,
+            None => vec![val],
+        };
+        Ok(Value::array_from(&self.globals, ary))
+    }
+
+    /// Convert `val` into elements by `to_ary`.
+    /// Return None if `val` is not an Array and does not respond to `to_ary`, or `to_ary` returns nil.
+    pub fn to_ary(&mut self, val: Value) -> Result<Option<Vec<Value>>, RubyError> {
+        if let Some(aref) = val.as_array() {
+            return Ok(Some(aref.elements.clone()));
+        }
+        let id = self.globals.get_ident_id("to_ary");
+        let method = match self.get_method(val, id) {
+            Ok(method) => method,
+            Err(_) => return Ok(None),
+        };
+        let res = self.eval_send(method, val, &Args::new0())?;
+        if res.is_nil() {
+            return Ok(None);
+        }
+        match res.as_array() {
+            Some(aref) => Ok(Some(aref.elements.clone())),
+            None => {
+                let class = self.val_inspect(val.get_class_object(&self.globals));
+                let res_class = self.val_inspect(res.get_class_object(&self.globals));
+                Err(self.error_type(format!(
+                    "Can't convert {} to Array ({}#to_ary gives {}).",
+                    class, class, res_class
+                )))
+            }
+        }
+    }
+
+    /// Convert `val` into elements for array patterns and find patterns.
+    /// Return None if `val` does not respond to `deconstruct`.
+    fn deconstruct(&mut self, val: Value) -> Result<Option<Vec<Value>>, RubyError> {
+        if let Some(aref) = val.as_array() {
+            return Ok(Some(aref.elements.clone()));
+        }
+        let id = self.globals.get_ident_id("deconstruct");
+        let method = match self.get_method(val, id) {
+            Ok(method) => method,
+            Err(_) => return Ok(None),
+        };
+        let res = self.eval_send(method, val, &Args::new0())?;
+        match res.as_array() {
+            Some(aref) => Ok(Some(aref.elements.clone())),
+            None => Err(self.error_type("deconstruct must return Array.")),
+        }
+    }
+
+    /// Convert `val` into a Hash for hash patterns.
+    /// Return None if `val` does not respond to `deconstruct_keys`.
+    fn deconstruct_keys(&mut self, val: Value, keys: Value) -> Result<Option<HashRef>, RubyError> {
+        if let Some(href) = val.as_hash() {
+            return Ok(Some(href));
+        }
+        let id = self.globals.get_ident_id("deconstruct_keys");
+        let method = match self.get_method(val, id) {
+            Ok(method) => method,
+            Err(_) => return Ok(None),
+        };
+        let res = self.eval_send(method, val, &Args::new1(keys))?;
+        match res.as_hash() {
+            Some(href) => Ok(Some(href)),
+            None => Err(self.error_type("deconstruct_keys must return Hash.")),
+        }
+    }
+
+    fn merge_kwargs(&mut self, hash: Value, val: Value) -> VMResult {
+        if val.is_nil() {
+            return Ok(hash);
+        }
+        let src = match val.as_hash() {
+            Some(href) => href,
+            None => {
+                let id = self.globals.get_ident_id("to_hash");
+                let converted = match self.get_method(val, id) {
+                    Ok(method) => self.eval_send(method, val, &Args::new0())?.as_hash(),
+                    Err(_) => None,
+                };
+                match converted {
+                    Some(href) => href,
+                    None => {
+                        let inspect = self.val_inspect(val);
+                        return Err(self.error_type(format!(
+                            "No implicit conversion of {} into Hash.",
+                            inspect
+                        )));
+                    }
+                }
+            }
+        };
+        let mut dest = hash.as_hash().unwrap();
+        for (k, v) in src.iter() {
+            dest.insert(k, v);
+        }
+        Ok(hash)
+    }
 }
 
 // API's for handling values.
@@ -1803,13 +2362,7 @@ impl VM {
                 false => "false".to_string(),
             },
             RV::Integer(i) => i.to_string(),
-            RV::Float(f) => {
-                if f.fract() == 0.0 {
-                    format!("{:.1}", f)
-                } else {
-                    f.to_string()
-                }
-            }
+            RV::Float(f) => float_to_s(f),
             RV::Symbol(i) => format!("{}", self.globals.get_ident_name(i)),
             RV::Object(oref) => match &oref.kind {
                 ObjKind::String(s) => s.to_s(),
@@ -1822,6 +2375,10 @@ impl VM {
                 ObjKind::Range(rinfo) => rinfo.to_s(self),
                 ObjKind::Regexp(rref) => format!("({})", rref.regexp.as_str().to_string()),
                 ObjKind::Hash(href) => href.to_s(self),
+                ObjKind::Time(time) => time.to_s(),
+                ObjKind::Rational(info) => info.to_s(),
+                ObjKind::Complex(info) => info.to_s(self),
+                ObjKind::MatchData(mref) => mref.group(0).unwrap_or("").to_string(),
                 _ => format!("{:?}", oref.kind),
             },
         }
@@ -1836,13 +2393,7 @@ impl VM {
                 false => "false".to_string(),
             },
             RV::Integer(i) => i.to_string(),
-            RV::Float(f) => {
-                if f.fract() == 0.0 {
-                    format!("{:.1}", f)
-                } else {
-                    f.to_string()
-                }
-            }
+            RV::Float(f) => float_to_s(f),
             RV::Symbol(sym) => format!(":{}", self.globals.get_ident_name(sym)),
             RV::Object(oref) => match &oref.kind {
                 ObjKind::String(s) => s.inspect(),
@@ -1858,8 +2409,15 @@ impl VM {
                 ObjKind::Array(aref) => aref.to_s(self),
                 ObjKind::Regexp(rref) => format!("/{}/", rref.regexp.as_str().to_string()),
                 ObjKind::Ordinary => oref.inspect(self),
-                ObjKind::Proc(pref) => format!("#<Proc:0x{:x}>", pref.id()),
+                ObjKind::Proc(pref) => format!(
+                    "#<Proc:0x{:x}{}>",
+                    pref.id(),
+                    if pref.is_lambda { " (lambda)" } else { "" }
+                ),
                 ObjKind::Hash(href) => href.to_s(self),
+                ObjKind::Time(time) => time.inspect(),
+                ObjKind::Rational(info) => info.inspect(),
+                ObjKind::Complex(info) => info.inspect(self),
                 _ => {
                     let id = self.globals.get_ident_id("inspect");
                     self.send0(val, id)
@@ -1889,27 +2447,36 @@ impl VM {
         let block = self.read32(iseq, 13);
         let methodref = self.get_method_from_cache(cache_slot, receiver, method_id)?;
 
-        let keyword = if flag & 0b01 == 1 {
+        // The block argument is pushed after the arguments and the keyword arguments.
+        let block = if block != 0 {
+            Some(Block::Block(MethodRef::from(block)))
+        } else if flag & 0b10 == 2 {
             let val = self.stack_pop();
-            Some(val)
+            if val.is_nil() {
+                None
+            } else {
+                let val = if val.as_proc().is_none() {
+                    // e.g. Symbol#to_proc for `&:sym`
+                    let id = self.globals.get_ident_id("to_proc");
+                    self.send0(val, id)?
+                } else {
+                    val
+                };
+                if val.as_proc().is_none() {
+                    return Err(self.error_type("Wrong argument type (expected Proc)."));
+                }
+                Some(Block::Proc(val))
+            }
         } else {
             None
         };
-        let mut args = self.pop_args_to_ary(args_num as usize);
-        let block = if block != 0 {
-            Some(MethodRef::from(block))
-        } else if flag & 0b10 == 2 {
+        let keyword = if flag & 0b01 == 1 {
             let val = self.stack_pop();
-            let method = val
-                .as_proc()
-                .ok_or_else(|| self.error_argument("Block argument must be Proc."))?
-                .context
-                .iseq_ref
-                .method;
-            Some(method)
+            Some(val)
         } else {
             None
         };
+        let mut args = self.pop_args_to_ary(args_num as usize);
         args.block = block;
         args.kw_arg = keyword;
         let val = self.eval_send(methodref, receiver, &args)?;
@@ -1923,10 +2490,85 @@ impl VM {
         self.eval_method(methodref, self_val, None, args)
     }
 
-    /// Evaluate method with self_val of current context, current context as outer context, and given `args`.
-    pub fn eval_block(&mut self, methodref: MethodRef, args: &Args) -> VMResult {
-        let context = self.context();
-        self.eval_method(methodref, context.self_value, Some(context), args)
+    /// Evaluate the Proc object `procobj` with `args` in its captured context.
+    pub fn eval_proc(&mut self, procobj: Value, args: &Args) -> VMResult {
+        let self_val = match procobj.as_proc() {
+            Some(pref) => pref.context.self_value,
+            None => return Err(self.error_argument("Expected Proc object.")),
+        };
+        self.eval_proc_with_self(procobj, self_val, args)
+    }
+
+    fn eval_proc_with_self(&mut self, procobj: Value, self_val: Value, args: &Args) -> VMResult {
+        let pref = match procobj.as_proc() {
+            Some(pref) => pref,
+            None => return Err(self.error_argument("Expected Proc object.")),
+        };
+        if let Some(id) = pref.symbol {
+            return self.send_symbol(id, args);
+        }
+        let context = Context::from_args_with_check(
+            self,
+            self_val,
+            pref.context.iseq_ref,
+            args,
+            pref.context.outer,
+            pref.is_lambda,
+        )?;
+        if !pref.is_lambda {
+            return self.run_context(ContextRef::from_local(&context));
+        }
+        let stack_len = self.exec_stack.len();
+        match self.run_context(ContextRef::from_local(&context)) {
+            // `return` in a lambda returns from the lambda itself.
+            Err(RubyError {
+                kind: RubyErrorKind::MethodReturn(method),
+                ..
+            }) if ISeqKind::Block(method) == pref.context.iseq_ref.kind => {
+                let val = self.stack_pop();
+                self.exec_stack.truncate(stack_len);
+                Ok(val)
+            }
+            res => res,
+        }
+    }
+
+    /// Evaluate `block` with given `args`.
+    /// A block literal is evaluated with self_val of current context and current context as outer context.
+    pub fn eval_block(&mut self, block: Block, args: &Args) -> VMResult {
+        match block {
+            Block::Block(methodref) => {
+                let context = self.context();
+                self.eval_method(methodref, context.self_value, Some(context), args)
+            }
+            Block::Proc(procobj) => self.eval_proc(procobj, args),
+        }
+    }
+
+    /// Send the method `id` to the first element of `args` with the rest of `args`.
+    fn send_symbol(&mut self, id: IdentId, args: &Args) -> VMResult {
+        if args.len() == 0 {
+            return Err(self.error_argument("No receiver given."));
+        }
+        let receiver = args[0];
+        let mut new_args = Args::new(args.len() - 1);
+        for i in 0..args.len() - 1 {
+            new_args[i] = args[i + 1];
+        }
+        new_args.block = args.block;
+        let method = self.get_method(receiver, id)?;
+        self.eval_send(method, receiver, &new_args)
+    }
+
+    /// Evaluate `block` with given `self_val` and `args`.
+    pub fn eval_block_with_self(&mut self, block: Block, self_val: Value, args: &Args) -> VMResult {
+        match block {
+            Block::Block(methodref) => {
+                let context = self.context();
+                self.eval_method(methodref, self_val, Some(context), args)
+            }
+            Block::Proc(procobj) => self.eval_proc_with_self(procobj, self_val, args),
+        }
     }
 
     /// Evaluate method with self_val of current context, caller context as outer context, and given `args`.
@@ -1942,16 +2584,18 @@ impl VM {
                 .outer
                 .ok_or_else(|| self.error_unimplemented("No block given."))?;
         }
-        let method = context
+        let block = context
             .block
             .ok_or_else(|| self.error_unimplemented("No block given."))?;
-        let res = self.eval_method(
-            method,
-            self.context().self_value,
-            Some(self.caller_context()),
-            &args,
-        )?;
-        Ok(res)
+        match block {
+            Block::Block(method) => self.eval_method(
+                method,
+                self.context().self_value,
+                Some(self.caller_context()),
+                &args,
+            ),
+            Block::Proc(procobj) => self.eval_proc(procobj, &args),
+        }
     }
 
     /// Evaluate method with given `self_val`, `outer` context, and `args`.
@@ -1996,6 +2640,22 @@ impl VM {
                 }
                 val
             }
+            MethodInfo::BuiltinClosure { func, .. } => {
+                #[cfg(feature = "perf")]
+                #[cfg_attr(tarpaulin, skip)]
+                {
+                    self.perf.get_perf(Perf::EXTERN);
+                }
+                // The method table may grow while the closure is running.
+                let func = func.clone();
+                let val = func(self, self_val, args)?;
+                #[cfg(feature = "perf")]
+                #[cfg_attr(tarpaulin, skip)]
+                {
+                    self.perf.get_perf_no_count(inst);
+                }
+                val
+            }
             MethodInfo::AttrReader { id } => match self_val.is_object() {
                 Some(oref) => match oref.get_var(*id) {
                     Some(v) => v,
@@ -2097,6 +2757,15 @@ impl VM {
         Ok(method)
     }
 
+    /// Get method(MethodRef) for receiver only if the method is defined in Ruby.
+    pub fn get_user_method(&mut self, receiver: Value, method_id: IdentId) -> Option<MethodRef> {
+        let method = self.get_method(receiver, method_id).ok()?;
+        match self.globals.get_method_info(method) {
+            MethodInfo::RubyFunc { .. } => Some(method),
+            _ => None,
+        }
+    }
+
     /// Get instance method(MethodRef) for the class object.
     pub fn get_instance_method(
         &mut self,
@@ -2140,6 +2809,33 @@ impl VM {
         }
     }
 
+    /// Get the class or module which defines instance method `method` for the class object.
+    pub fn get_method_owner(&self, class: Value, method: IdentId) -> Option<Value> {
+        fn owner(module: Value, method: IdentId) -> Option<Value> {
+            let cref = module.as_module()?;
+            if cref.method_table.contains_key(&method) {
+                return Some(module);
+            }
+            cref.include.iter().find_map(|m| owner(*m, method))
+        }
+        let original_class = class;
+        let mut class = class;
+        let mut singleton_flag = original_class.as_class().is_singleton;
+        loop {
+            if let Some(module) = owner(class, method) {
+                return Some(module);
+            }
+            class = match class.superclass() {
+                Some(superclass) => superclass,
+                None if singleton_flag => {
+                    singleton_flag = false;
+                    original_class.as_object().class()
+                }
+                None => return None,
+            };
+        }
+    }
+
     pub fn get_singleton_class(&mut self, obj: Value) -> VMResult {
         self.globals
             .get_singleton_class(obj)
@@ -2195,14 +2891,60 @@ impl VM {
         }
     }
 
-    fn pop_key_value_pair(&mut self, arg_num: usize) -> HashMap<HashKey, Value> {
-        let mut hash = HashMap::new();
+    /// Evaluate `receiver[args]`.
+    fn get_index(&mut self, receiver: Value, args: &Args) -> VMResult {
+        let arg_num = args.len();
+        let val = match receiver.is_object() {
+            Some(oref) => match &oref.kind {
+                ObjKind::Array(aref) => aref.get_elem(self, &args)?,
+                ObjKind::Hash(href) => {
+                    self.check_args_range(arg_num, 1, 1)?;
+                    href.get_elem(self, receiver, args[0])?
+                }
+                ObjKind::Method(mref) => self.eval_send(mref.method, mref.receiver, &args)?,
+                _ => {
+                    let id = self.globals.get_ident_id("[]");
+                    match self.get_method(receiver, id) {
+                        Ok(mref) => self.eval_send(mref, receiver, &args)?,
+                        Err(_) => return Err(self.error_undefined_method("[]", receiver)),
+                    }
+                }
+            },
+            None if receiver.is_packed_fixnum() => {
+                let i = receiver.as_packed_fixnum();
+                self.check_args_range(arg_num, 1, 1)?;
+                let index = args[0].expect_integer(&self, "Index")?;
+                let val = if index < 0 || 63 < index {
+                    0
+                } else {
+                    (i >> index) & 1
+                };
+                Value::fixnum(val)
+            }
+            _ => {
+                let id = self.globals.get_ident_id("[]");
+                match self.get_method(receiver, id) {
+                    Ok(mref) => self.eval_send(mref, receiver, &args)?,
+                    Err(_) => return Err(self.error_undefined_method("[]", receiver)),
+                }
+            }
+        };
+        Ok(val)
+    }
+
+    /// Pop `arg_num` pairs of a key and a value, and create a Hash.
+    fn pop_key_value_pair(&mut self, arg_num: usize) -> VMResult {
+        let mut pairs = vec![];
         for _ in 0..arg_num {
             let value = self.stack_pop();
             let key = self.stack_pop();
-            hash.insert(HashKey(key), value);
+            pairs.push((key, value));
+        }
+        let mut hash = HashRef::from(HashMap::new());
+        for (key, value) in pairs.into_iter().rev() {
+            hash.store(self, key, value)?;
         }
-        hash
+        Ok(Value::hash(&self.globals, hash))
     }
 
     fn pop_args_to_ary(&mut self, arg_num: usize) -> Args {
@@ -2242,31 +2984,49 @@ impl VM {
         args
     }
 
-    /// Create new Proc object from `method`,
+    /// Create new Proc object from `block`,
     /// moving outer `Context`s on stack to heap.
-    pub fn create_proc(&mut self, method: MethodRef) -> VMResult {
-        self.move_outer_to_heap();
-        let context = self.create_block_context(method)?;
-        Ok(Value::procobj(&self.globals, context))
+    /// A Proc object given as `block` is returned as is.
+    pub fn create_proc(&mut self, block: Block) -> VMResult {
+        match block {
+            Block::Block(method) => {
+                self.move_outer_to_heap();
+                let context = self.create_block_context(method)?;
+                Ok(Value::procobj(&self.globals, context))
+            }
+            Block::Proc(procobj) => Ok(procobj),
+        }
     }
 
-    /// Move outer execution contexts on the stack to the heap.
+    /// Move the current execution context and its outer contexts on the stack to the heap.
+    /// Other contexts in `exec_context` may also refer to the moved contexts as their outer
+    /// (e.g. a block which called the current method), so their outer links are fixed up by identity.
     fn move_outer_to_heap(&mut self) {
-        let mut prev_ctx: Option<ContextRef> = None;
-        for context in self.exec_context.iter_mut().rev() {
+        let mut moved: Vec<(ContextRef, ContextRef)> = vec![];
+        let mut next = self.exec_context.last().cloned();
+        while let Some(context) = next {
             if !context.on_stack {
                 break;
-            };
+            }
             let mut heap_context = context.dup();
             heap_context.on_stack = false;
-            *context = heap_context;
-            if let Some(mut ctx) = prev_ctx {
-                ctx.outer = Some(heap_context);
-            };
-            if heap_context.outer.is_none() {
-                break;
+            if let Some(&(_, mut prev)) = moved.last() {
+                prev.outer = Some(heap_context);
+            }
+            moved.push((context, heap_context));
+            next = context.outer;
+        }
+        if moved.is_empty() {
+            return;
+        }
+        for context in self.exec_context.iter_mut() {
+            if let Some((_, heap_context)) = moved.iter().find(|(ctx, _)| *ctx == *context) {
+                *context = *heap_context;
+            } else if let Some(outer) = context.outer {
+                if let Some((_, heap_outer)) = moved.iter().find(|(ctx, _)| *ctx == outer) {
+                    context.outer = Some(*heap_outer);
+                }
             }
-            prev_ctx = Some(heap_context);
         }
     }
 
@@ -2282,6 +3042,16 @@ impl VM {
         self.globals.get_method_info(method).as_iseq(&self)
     }
 
+    pub fn get_block_iseq(&self, block: Block) -> Result<ISeqRef, RubyError> {
+        match block {
+            Block::Block(method) => self.get_iseq(method),
+            Block::Proc(procobj) => match procobj.as_proc() {
+                Some(pref) => Ok(pref.context.iseq_ref),
+                None => Err(self.error_argument("Expected Proc object.")),
+            },
+        }
+    }
+
     /// Create new Regexp object from `string`.
     /// Regular expression meta characters are handled as is.
     /// Returns RubyError if `string` was invalid regular expression.
@@ -0,0 +1,292 @@
+use crate::*;
+use std::collections::HashMap;
+
+/// Global variables which behave differently from ordinary ones.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpecialVar {
+    /// $_: the last line read by `gets`. Local to the method frame.
+    LastLine,
+    /// $~: the last MatchData. Local to the method frame.
+    LastMatch,
+    /// $&, $`, $', $+ and $1, $2, ...: derived from $~.
+    MatchRef(MatchRef),
+    /// $!: the current exception.
+    Error,
+    /// $@: the backtrace of the current exception.
+    ErrorPosition,
+    /// $stdout and $stderr: must have `write` method.
+    Output,
+    /// $/, $;, $, and $\: must be a String or nil.
+    Separator,
+    /// $0: must be a String.
+    ProgramName,
+    /// $VERBOSE: one of nil, false and true.
+    Verbose,
+    /// Variables which can not be assigned from Ruby code.
+    ReadOnly,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MatchRef {
+    Whole,
+    PreMatch,
+    PostMatch,
+    LastGroup,
+    Nth(usize),
+}
+
+#[derive(Debug, Clone)]
+pub struct GlobalVars {
+    table: ValueTable,
+    /// Alias names and their original names.
+    alias: HashMap<IdentId, IdentId>,
+    special: HashMap<IdentId, SpecialVar>,
+    /// Procs registered by Kernel#trace_var.
+    trace: HashMap<IdentId, Vec<Value>>,
+}
+
+impl GlobalVars {
+    pub fn new(ident_table: &mut IdentifierTable) -> Self {
+        let mut alias = HashMap::new();
+        for (name, orig) in &[
+            ("$PROGRAM_NAME", "$0"),
+            ("$:", "$LOAD_PATH"),
+            ("$-I", "$LOAD_PATH"),
+            ("$\"", "$LOADED_FEATURES"),
+            ("$-d", "$DEBUG"),
+            ("$-v", "$VERBOSE"),
+            ("$-w", "$VERBOSE"),
+            ("$-0", "$/"),
+            ("$-F", "$;"),
+        ] {
+            let id = ident_table.get_ident_id(*name);
+            let orig = ident_table.get_ident_id(*orig);
+            alias.insert(id, orig);
+        }
+        let mut special = HashMap::new();
+        for (name, kind) in &[
+            ("$_", SpecialVar::LastLine),
+            ("$~", SpecialVar::LastMatch),
+            ("$&", SpecialVar::MatchRef(MatchRef::Whole)),
+            ("$`", SpecialVar::MatchRef(MatchRef::PreMatch)),
+            ("$'", SpecialVar::MatchRef(MatchRef::PostMatch)),
+            ("$+", SpecialVar::MatchRef(MatchRef::LastGroup)),
+            ("$!", SpecialVar::Error),
+            ("$@", SpecialVar::ErrorPosition),
+            ("$stdout", SpecialVar::Output),
+            ("$stderr", SpecialVar::Output),
+            ("$/", SpecialVar::Separator),
+            ("$;", SpecialVar::Separator),
+            ("$,", SpecialVar::Separator),
+            ("$\\", SpecialVar::Separator),
+            ("$0", SpecialVar::ProgramName),
+            ("$VERBOSE", SpecialVar::Verbose),
+            ("$$", SpecialVar::ReadOnly),
+            ("$?", SpecialVar::ReadOnly),
+            ("$LOAD_PATH", SpecialVar::ReadOnly),
+            ("$LOADED_FEATURES", SpecialVar::ReadOnly),
+        ] {
+            let id = ident_table.get_ident_id(*name);
+            special.insert(id, *kind);
+        }
+        GlobalVars {
+            table: HashMap::new(),
+            alias,
+            special,
+            trace: HashMap::new(),
+        }
+    }
+
+    /// Get the original name of `id` if `id` is an alias.
+    pub fn resolve(&self, id: IdentId) -> IdentId {
+        match self.alias.get(&id) {
+            Some(orig) => *orig,
+            None => id,
+        }
+    }
+
+    pub fn get(&self, id: IdentId) -> Option<Value> {
+        self.table.get(&self.resolve(id)).cloned()
+    }
+
+    pub fn insert(&mut self, id: IdentId, val: Value) {
+        self.table.insert(self.resolve(id), val);
+    }
+
+    pub fn special(&self, id: IdentId) -> Option<SpecialVar> {
+        self.special.get(&self.resolve(id)).cloned()
+    }
+
+    pub fn add_trace(&mut self, id: IdentId, procobj: Value) {
+        self.trace
+            .entry(self.resolve(id))
+            .or_insert_with(|| vec![])
+            .push(procobj);
+    }
+
+    /// Remove `procobj` (or all procs if None) from the hooks of `id`.
+    /// Returns removed procs.
+    pub fn remove_trace(&mut self, id: IdentId, procobj: Option<Value>) -> Vec<Value> {
+        let id = self.resolve(id);
+        let procs = match self.trace.get_mut(&id) {
+            Some(procs) => procs,
+            None => return vec![],
+        };
+        match procobj {
+            Some(procobj) => {
+                let len = procs.len();
+                procs.retain(|p| *p != procobj);
+                if procs.len() == len {
+                    vec![]
+                } else {
+                    vec![procobj]
+                }
+            }
+            None => std::mem::replace(procs, vec![]),
+        }
+    }
+
+    pub fn traces(&self, id: IdentId) -> Vec<Value> {
+        match self.trace.get(&self.resolve(id)) {
+            Some(procs) => procs.clone(),
+            None => vec![],
+        }
+    }
+}
+
+impl VM {
+    fn special_var(&self, id: IdentId) -> Option<SpecialVar> {
+        match self.globals.global_var.special(id) {
+            Some(kind) => Some(kind),
+            None => {
+                let name = self.globals.get_ident_name(id);
+                match name[1..].parse::<usize>() {
+                    Ok(i) if i > 0 => Some(SpecialVar::MatchRef(MatchRef::Nth(i))),
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    pub fn get_last_line(&self) -> Value {
+        match self.method_context() {
+            Some(context) => context.last_line,
+            None => Value::nil(),
+        }
+    }
+
+    pub fn set_last_line(&mut self, val: Value) {
+        if let Some(mut context) = self.method_context() {
+            context.last_line = val;
+        }
+    }
+
+    pub fn get_last_match(&self) -> Value {
+        match self.method_context() {
+            Some(context) => context.last_match,
+            None => Value::nil(),
+        }
+    }
+
+    pub fn set_last_match(&mut self, val: Value) {
+        if let Some(mut context) = self.method_context() {
+            context.last_match = val;
+        }
+    }
+
+    fn get_match_ref(&self, kind: MatchRef) -> Value {
+        let mref = match self.get_last_match().as_matchdata() {
+            Some(mref) => mref,
+            None => return Value::nil(),
+        };
+        let s = match kind {
+            MatchRef::Whole => mref.group(0),
+            MatchRef::PreMatch => Some(mref.pre_match()),
+            MatchRef::PostMatch => Some(mref.post_match()),
+            MatchRef::LastGroup => mref.last_group(),
+            MatchRef::Nth(i) => mref.group(i),
+        };
+        match s {
+            Some(s) => Value::string(&self.globals, s.to_string()),
+            None => Value::nil(),
+        }
+    }
+
+    pub fn get_global_var(&self, id: IdentId) -> Value {
+        match self.special_var(id) {
+            Some(SpecialVar::LastLine) => self.get_last_line(),
+            Some(SpecialVar::LastMatch) => self.get_last_match(),
+            Some(SpecialVar::MatchRef(kind)) => self.get_match_ref(kind),
+            // Exceptions can not be rescued yet, so there is no current exception.
+            Some(SpecialVar::Error) | Some(SpecialVar::ErrorPosition) => Value::nil(),
+            _ => match self.globals.global_var.get(id) {
+                Some(val) => val,
+                None => Value::nil(),
+            },
+        }
+    }
+
+    /// Set a global variable without any check.
+    pub fn set_global_var(&mut self, id: IdentId, val: Value) {
+        match self.special_var(id) {
+            Some(SpecialVar::LastLine) => self.set_last_line(val),
+            Some(SpecialVar::LastMatch) => self.set_last_match(val),
+            _ => self.globals.global_var.insert(id, val),
+        }
+    }
+
+    /// Assign `val` to a global variable from Ruby code.
+    /// Checks whether the assignment is valid, and calls the procs registered by trace_var.
+    pub fn assign_global_var(&mut self, id: IdentId, val: Value) -> Result<(), RubyError> {
+        let name = self.globals.get_ident_name(id).to_string();
+        let val = match self.special_var(id) {
+            Some(SpecialVar::MatchRef(_)) => {
+                return Err(self.error_name(format!("Can't set variable {}.", name)))
+            }
+            Some(SpecialVar::Error) | Some(SpecialVar::ReadOnly) => {
+                return Err(self.error_name(format!("{} is a read-only variable.", name)))
+            }
+            Some(SpecialVar::ErrorPosition) => return Err(self.error_argument("$! not set.")),
+            Some(SpecialVar::LastMatch) => {
+                if !val.is_nil() && val.as_matchdata().is_none() {
+                    let class = self.globals.get_class_name(val);
+                    return Err(self.error_type(format!(
+                        "Wrong argument type {} (expected MatchData).",
+                        class
+                    )));
+                }
+                val
+            }
+            Some(SpecialVar::Output) => {
+                let write = self.globals.get_ident_id("write");
+                if self.get_method(val, write).is_err() {
+                    let class = self.globals.get_class_name(val);
+                    return Err(self
+                        .error_type(format!("{} must have write method, {} given.", name, class)));
+                }
+                val
+            }
+            Some(SpecialVar::Separator) => {
+                if !val.is_nil() && val.as_string().is_none() {
+                    return Err(self.error_type(format!("Value of {} must be String.", name)));
+                }
+                val
+            }
+            Some(SpecialVar::ProgramName) => {
+                if val.as_string().is_none() {
+                    let class = self.globals.get_class_name(val);
+                    return Err(self
+                        .error_type(format!("No implicit conversion of {} into String.", class)));
+                }
+                val
+            }
+            Some(SpecialVar::Verbose) if !val.is_nil() => Value::bool(self.val_to_bool(val)),
+            _ => val,
+        };
+        self.set_global_var(id, val);
+        for procobj in self.globals.global_var.traces(id) {
+            self.eval_proc(procobj, &Args::new1(val))?;
+        }
+        Ok(())
+    }
+}
@@ -3,6 +3,9 @@ use std::collections::HashMap;
 
 pub type BuiltinFunc = fn(vm: &mut VM, self_val: Value, args: &Args) -> VMResult;
 
+/// A method implemented by a Rust closure. Used by the embedding API.
+pub type BuiltinClosure = std::rc::Rc<dyn Fn(&mut VM, Value, &Args) -> VMResult>;
+
 pub type MethodTable = HashMap<IdentId, MethodRef>;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -32,12 +35,38 @@ impl MethodRef {
     }
 }
 
+/// A block given to a method call.
+/// `Block` is a block literal which is evaluated in the context of the caller,
+/// and `Proc` is a Proc object which is evaluated in its own captured context.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Block {
+    Block(MethodRef),
+    Proc(Value),
+}
+
+impl Block {
+    /// Returns true if `self` is the dummy block given by Enumerator.
+    pub fn is_none(&self) -> bool {
+        match self {
+            Block::Block(method) => method.is_none(),
+            Block::Proc(_) => false,
+        }
+    }
+}
+
+impl From<MethodRef> for Block {
+    fn from(method: MethodRef) -> Self {
+        Block::Block(method)
+    }
+}
+
 #[derive(Clone)]
 pub enum MethodInfo {
     RubyFunc { iseq: ISeqRef },
     AttrReader { id: IdentId },
     AttrWriter { id: IdentId },
     BuiltinFunc { name: String, func: BuiltinFunc },
+    BuiltinClosure { name: String, func: BuiltinClosure },
 }
 
 impl MethodInfo {
@@ -69,6 +98,7 @@ impl std::fmt::Debug for MethodInfo {
             MethodInfo::AttrReader { id } => write!(f, "AttrReader {:?}", id),
             MethodInfo::AttrWriter { id } => write!(f, "AttrWriter {:?}", id),
             MethodInfo::BuiltinFunc { name, .. } => write!(f, "BuiltinFunc {:?}", name),
+            MethodInfo::BuiltinClosure { name, .. } => write!(f, "BuiltinClosure {:?}", name),
         }
     }
 }
@@ -101,6 +131,55 @@ pub struct ISeqParams {
     pub block_param: bool,
     pub param_ident: Vec<IdentId>,
     pub keyword_params: HashMap<IdentId, LvarId>,
+    /// Keyword parameters without a default value.
+    pub req_keyword_params: Vec<IdentId>,
+    /// The local variable for `**rest` parameter.
+    pub kwrest_param: Option<LvarId>,
+    /// True if keyword arguments are rejected by `**nil`.
+    pub no_keyword_param: bool,
+}
+
+impl ISeqParams {
+    pub fn default() -> Self {
+        ISeqParams {
+            req_params: 0,
+            opt_params: 0,
+            rest_param: false,
+            post_params: 0,
+            block_param: false,
+            param_ident: vec![],
+            keyword_params: HashMap::new(),
+            req_keyword_params: vec![],
+            kwrest_param: None,
+            no_keyword_param: false,
+        }
+    }
+
+    /// Returns true if the method takes keyword arguments separately from positional arguments.
+    pub fn accepts_keywords(&self) -> bool {
+        !self.keyword_params.is_empty() || self.kwrest_param.is_some() || self.no_keyword_param
+    }
+
+    /// Returns true if a single Array argument of a block is expanded to the parameters,
+    /// i.e. the block has more than one positional parameter.
+    pub fn expands_array_arg(&self) -> bool {
+        let rest = if self.rest_param { 1 } else { 0 };
+        self.req_params + self.opt_params + rest + self.post_params > 1
+    }
+
+    /// Returns the number of mandatory parameters,
+    /// or -n-1 if optional or rest parameters exist where n is the number of mandatory parameters.
+    /// Required keyword parameters are counted as one mandatory parameter.
+    pub fn arity(&self) -> i64 {
+        let req_kw = !self.req_keyword_params.is_empty();
+        let opt_kw = !req_kw && (!self.keyword_params.is_empty() || self.kwrest_param.is_some());
+        let req = (self.req_params + self.post_params) as i64 + if req_kw { 1 } else { 0 };
+        if self.opt_params > 0 || self.rest_param || opt_kw {
+            -req - 1
+        } else {
+            req
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -129,13 +208,7 @@ pub enum ISeqKind {
 impl ISeqInfo {
     pub fn new(
         method: MethodRef,
-        req_params: usize,
-        opt_params: usize,
-        rest_param: bool,
-        post_params: usize,
-        block_param: bool,
-        param_ident: Vec<IdentId>,
-        keyword_params: HashMap<IdentId, LvarId>,
+        params: ISeqParams,
         iseq: ISeq,
         lvar: LvarCollector,
         iseq_sourcemap: Vec<(ISeqPos, Loc)>,
@@ -145,15 +218,7 @@ impl ISeqInfo {
         let lvars = lvar.len();
         ISeqInfo {
             method,
-            params: ISeqParams {
-                req_params,
-                opt_params,
-                rest_param,
-                post_params,
-                block_param,
-                param_ident,
-                keyword_params,
-            },
+            params,
             iseq,
             lvar,
             lvars,
@@ -167,13 +232,7 @@ impl ISeqInfo {
     pub fn default(method: MethodRef) -> Self {
         ISeqInfo::new(
             method,
-            0,
-            0,
-            false,
-            0,
-            false,
-            vec![],
-            std::collections::HashMap::new(),
+            ISeqParams::default(),
             vec![],
             LvarCollector::new(),
             vec![],
@@ -238,16 +297,20 @@ impl GlobalMethodTable {
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct MethodObjInfo {
     pub name: IdentId,
+    /// The receiver of the method. nil for UnboundMethod.
     pub receiver: Value,
     pub method: MethodRef,
+    /// The class or module which defines the method.
+    pub owner: Value,
 }
 
 impl MethodObjInfo {
-    pub fn new(name: IdentId, receiver: Value, method: MethodRef) -> Self {
+    pub fn new(name: IdentId, receiver: Value, method: MethodRef, owner: Value) -> Self {
         MethodObjInfo {
             name,
             receiver,
             method,
+            owner,
         }
     }
 }
@@ -255,7 +318,7 @@ impl MethodObjInfo {
 pub type MethodObjRef = Ref<MethodObjInfo>;
 
 impl MethodObjRef {
-    pub fn from(name: IdentId, receiver: Value, method: MethodRef) -> Self {
-        MethodObjRef::new(MethodObjInfo::new(name, receiver, method))
+    pub fn from(name: IdentId, receiver: Value, method: MethodRef, owner: Value) -> Self {
+        MethodObjRef::new(MethodObjInfo::new(name, receiver, method, owner))
     }
 }
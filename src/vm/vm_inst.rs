@@ -50,6 +50,8 @@ impl Inst {
     pub const SET_INDEX: u8 = 51;
 
     pub const CHECK_LOCAL: u8 = 52;
+    pub const CHECK_CONST: u8 = 53;
+    pub const CHECK_SCOPE: u8 = 54;
 
     pub const SEND: u8 = 60;
     pub const SEND_SELF: u8 = 61;
@@ -66,6 +68,10 @@ impl Inst {
     pub const SPLAT: u8 = 83;
     pub const CONCAT_STRING: u8 = 84;
     pub const TO_S: u8 = 85;
+    pub const MERGE_KWARGS: u8 = 86;
+    pub const TOPN: u8 = 87;
+    pub const SINKN: u8 = 88;
+    pub const EXPAND_ARRAY: u8 = 89;
 
     pub const DEF_CLASS: u8 = 90;
     pub const DEF_METHOD: u8 = 91;
@@ -78,6 +84,12 @@ impl Inst {
     pub const OPT_CASE: u8 = 104;
     pub const MRETURN: u8 = 105;
     pub const YIELD: u8 = 106;
+    pub const JMP_IF_NIL: u8 = 107;
+
+    pub const DECONSTRUCT: u8 = 110;
+    pub const DECONSTRUCT_KEYS: u8 = 111;
+    pub const FIND_SLICE: u8 = 112;
+    pub const NO_MATCHING_PATTERN: u8 = 113;
 }
 
 #[allow(dead_code)]
@@ -135,6 +147,8 @@ impl Inst {
             Inst::SEND_SELF => "SEND_SELF",
 
             Inst::CHECK_LOCAL => "CHECK_LOCAL",
+            Inst::CHECK_CONST => "CHECK_CONST",
+            Inst::CHECK_SCOPE => "CHECK_SCOPE",
 
             Inst::CREATE_RANGE => "CREATE_RANGE",
             Inst::CREATE_ARRAY => "CREATE_ARRAY",
@@ -148,6 +162,10 @@ impl Inst {
             Inst::SPLAT => "SPLAT",
             Inst::CONCAT_STRING => "CONCAT_STR",
             Inst::TO_S => "TO_S",
+            Inst::MERGE_KWARGS => "MERGE_KWARGS",
+            Inst::TOPN => "TOPN",
+            Inst::SINKN => "SINKN",
+            Inst::EXPAND_ARRAY => "EXPAND_ARRAY",
 
             Inst::DEF_CLASS => "DEF_CLASS",
             Inst::DEF_METHOD => "DEF_METHOD",
@@ -155,12 +173,18 @@ impl Inst {
 
             Inst::JMP => "JMP",
             Inst::JMP_IF_FALSE => "JMP_IF_FALSE",
+            Inst::JMP_IF_NIL => "JMP_IF_NIL",
             Inst::END => "END",
             Inst::RETURN => "RETURN",
             Inst::OPT_CASE => "OPT_CASE",
             Inst::MRETURN => "MRETURN",
             Inst::YIELD => "YIELD",
 
+            Inst::DECONSTRUCT => "DECONSTRUCT",
+            Inst::DECONSTRUCT_KEYS => "DECONSTRUCT_KEYS",
+            Inst::FIND_SLICE => "FIND_SLICE",
+            Inst::NO_MATCHING_PATTERN => "NO_MATCHING_PATTERN",
+
             _ => "undefined",
         }
     }
@@ -189,16 +213,20 @@ impl Inst {
             | Inst::CREATE_RANGE
             | Inst::CREATE_REGEXP
             | Inst::TO_S
+            | Inst::MERGE_KWARGS
             | Inst::SPLAT
             | Inst::POP
             | Inst::RETURN
-            | Inst::MRETURN => 1,
+            | Inst::MRETURN
+            | Inst::NO_MATCHING_PATTERN => 1,
                                         // operand
             Inst::PUSH_STRING           // IdentId: u32
             | Inst::PUSH_SYMBOL         // IdentId: u32
             | Inst::GET_CONST           // IdentId: u32
             | Inst::SET_CONST           // IdentId: u32
             | Inst::GET_CONST_TOP       // IdentId: u32
+            | Inst::CHECK_CONST         // IdentId: u32
+            | Inst::CHECK_SCOPE         // IdentId: u32
             | Inst::GET_SCOPE           // IdentId: u32
             | Inst::GET_IVAR            // IdentId: u32
             | Inst::SET_IVAR            // IdentId: u32
@@ -210,8 +238,11 @@ impl Inst {
             | Inst::CREATE_PROC
             | Inst::JMP                 // disp: u32
             | Inst::JMP_IF_FALSE        // disp: u32
+            | Inst::JMP_IF_NIL          // disp: u32
             | Inst::DUP                 // number of items: u32
             | Inst::TAKE                // number of items: u32
+            | Inst::TOPN                // position: u32
+            | Inst::SINKN               // position: u32
             | Inst::ADD                 // inline cache: u32
             | Inst::SUB                 // inline cache: u32
             | Inst::MUL                 // inline cache: u32
@@ -221,6 +252,8 @@ impl Inst {
             | Inst::SHL                 // inline cache: u32
             | Inst::CREATE_HASH         // number of items: u32
             | Inst::YIELD               // number of items: u32
+            | Inst::DECONSTRUCT_KEYS    // rest of hash pattern: u32
+            | Inst::FIND_SLICE          // number of items: u32
             => 5,
 
             Inst::PUSH_FIXNUM
@@ -230,6 +263,8 @@ impl Inst {
             | Inst::DEF_METHOD
             | Inst::DEF_SMETHOD
             | Inst::OPT_CASE
+            | Inst::DECONSTRUCT         // number of items: u32, position of rest: u32
+            | Inst::EXPAND_ARRAY        // number of items before/after splat: u32, u32
             | Inst::IVAR_ADDI => 9,
             Inst::DEF_CLASS => 10,
             Inst::SEND | Inst::SEND_SELF => 17,
@@ -267,8 +302,10 @@ impl Inst {
             | Inst::CREATE_REGEXP
             | Inst::RETURN
             | Inst::TO_S
+            | Inst::MERGE_KWARGS
             | Inst::SPLAT
             | Inst::POP
+            | Inst::NO_MATCHING_PATTERN
             | Inst::YIELD => format!("{}", Inst::inst_name(iseq[pc])),
             Inst::PUSH_STRING => format!("PUSH_STRING {}", Inst::read32(iseq, pc + 1) as i32),
             Inst::PUSH_SYMBOL => format!("PUSH_SYMBOL {}", Inst::read32(iseq, pc + 1) as i32),
@@ -287,11 +324,15 @@ impl Inst {
                 "JMP_IF_FALSE {:>05x}",
                 pc as i32 + 5 + Inst::read32(iseq, pc + 1) as i32
             ),
+            Inst::JMP_IF_NIL => format!(
+                "JMP_IF_NIL {:>05x}",
+                pc as i32 + 5 + Inst::read32(iseq, pc + 1) as i32
+            ),
             Inst::OPT_CASE => {
                 //let val = Value::from(Inst::read64(iseq, pc + 1));
                 //let info = val.as_hash().unwrap();
-                /*let map = match info.inner_mut() {
-                    HashInfo::Map(map) => map,
+                /*let map = match &mut info.inner_mut().map {
+                    HashTable::Map(map) => map,
                     _ => panic!(),
                 };*/
                 format!(
@@ -325,6 +366,12 @@ impl Inst {
                 "GET_CONST_TOP '{}'",
                 Inst::ident_name(globals, iseq, pc + 1)
             ),
+            Inst::CHECK_CONST => {
+                format!("CHECK_CONST '{}'", Inst::ident_name(globals, iseq, pc + 1))
+            }
+            Inst::CHECK_SCOPE => {
+                format!("CHECK_SCOPE '{}'", Inst::ident_name(globals, iseq, pc + 1))
+            }
             Inst::SET_CONST => format!("SET_CONST '{}'", Inst::ident_name(globals, iseq, pc + 1)),
             Inst::GET_SCOPE => format!("GET_SCOPE '{}'", Inst::ident_name(globals, iseq, pc + 1)),
             Inst::GET_IVAR => format!("GET_IVAR '{}'", Inst::ident_name(globals, iseq, pc + 1)),
@@ -347,6 +394,22 @@ impl Inst {
             Inst::CREATE_HASH => format!("CREATE_HASH {} items", Inst::read32(iseq, pc + 1)),
             Inst::DUP => format!("DUP {}", Inst::read32(iseq, pc + 1)),
             Inst::TAKE => format!("TAKE {}", Inst::read32(iseq, pc + 1)),
+            Inst::TOPN => format!("TOPN {}", Inst::read32(iseq, pc + 1)),
+            Inst::DECONSTRUCT => format!(
+                "DECONSTRUCT {} items rest:{}",
+                Inst::read32(iseq, pc + 1),
+                Inst::read32(iseq, pc + 5) as i32
+            ),
+            Inst::DECONSTRUCT_KEYS => {
+                format!("DECONSTRUCT_KEYS rest:{}", Inst::read32(iseq, pc + 1))
+            }
+            Inst::FIND_SLICE => format!("FIND_SLICE {} items", Inst::read32(iseq, pc + 1)),
+            Inst::SINKN => format!("SINKN {}", Inst::read32(iseq, pc + 1)),
+            Inst::EXPAND_ARRAY => format!(
+                "EXPAND_ARRAY pre:{} post:{}",
+                Inst::read32(iseq, pc + 1),
+                Inst::read32(iseq, pc + 5)
+            ),
             Inst::DEF_CLASS => format!(
                 "DEF_CLASS {} '{}' method:{}",
                 if Inst::read8(iseq, pc + 1) == 1 {
@@ -0,0 +1,22 @@
+use std::process::Command;
+
+fn run_ruruby(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_ruruby"))
+        .args(args)
+        .output()
+        .expect("Failed to execute ruruby.");
+    assert!(output.status.success(), "{:?}", output);
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn cli_option_e() {
+    assert_eq!("1\n2\n", run_ruruby(&["-e", "p 1, 2"]));
+    assert_eq!("3\n", run_ruruby(&["-e", "a = 1", "-e", "p a + 2"]));
+}
+
+#[test]
+fn cli_option_comma() {
+    let stdout = run_ruruby(&["-I", "a,b", "-e", "p $LOAD_PATH[0]"]);
+    assert_eq!("\"a,b\"\n", stdout);
+}
@@ -0,0 +1,33 @@
+extern crate ruruby;
+use ruruby::test::assert_script;
+
+// Dir.chdir changes the working directory of the whole process,
+// so it is tested in its own test binary, not in parallel with the tests using relative paths.
+#[test]
+fn dir_chdir() {
+    let dir = std::env::temp_dir().join("ruruby_chdir_test");
+    std::fs::create_dir_all(&dir).unwrap();
+    let dir = dir.canonicalize().unwrap();
+    let prev = std::env::current_dir().unwrap();
+    let program = format!(
+        r#"
+        dir = "{}"
+        prev = Dir.pwd
+        res = Dir.chdir(dir) do |path|
+          assert dir, path
+          assert dir, Dir.pwd
+          100
+        end
+        assert 100, res
+        assert prev, Dir.pwd
+        assert 0, Dir.chdir(dir)
+        assert dir, Dir.pwd
+        Dir.chdir(prev)
+        assert prev, Dir.pwd
+        "#,
+        dir.to_string_lossy()
+    );
+    assert_script(program);
+    std::env::set_current_dir(prev).unwrap();
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -137,3 +137,90 @@ fn splat_argument() {
     "#;
     assert_script(program);
 }
+
+#[test]
+fn keyword_param() {
+    let program = r#"
+        def fn(a, b:, c: 2)
+            [a, b, c]
+        end
+
+        assert [1, 3, 2], fn(1, b: 3)
+        assert [1, 4, 5], fn(1, c: 5, b: 4)
+        assert_error { fn(1) }
+        assert_error { fn(1, c: 3) }
+        assert_error { fn(1, b: 1, d: 3) }
+        assert_error { fn(b: 1) }
+        assert 2, method(:fn).arity
+
+        def fx(a, b = 2, *c, k: 0)
+            [a, b, c, k]
+        end
+
+        assert [1, 2, [], 0], fx(1)
+        assert [1, 5, [6, 7], 1], fx(1, 5, 6, 7, k: 1)
+        assert(-2, method(:fx).arity)
+    "#;
+    assert_script(program);
+}
+
+#[test]
+fn kwrest_param() {
+    let program = r#"
+        def fn(a, k: 0, **opts)
+            [a, k, opts]
+        end
+
+        assert [1, 0, {}], fn(1)
+        assert [1, 3, {x: 1, y: 2}], fn(1, x: 1, k: 3, y: 2)
+        assert(-2, method(:fn).arity)
+
+        def fx(a, **nil)
+            a
+        end
+
+        assert 1, fx(1)
+        assert_error { fx(1, a: 2) }
+        assert [[:req, :a], [:nokey]], ->(a, **nil) {}.parameters
+        assert [[:req, :a], [:keyreq, :b], [:key, :c], [:keyrest, :d], [:block, :e]], ->(a, b:, c: 1, **d, &e) {}.parameters
+
+        def pos(a, b = 1)
+            [a, b]
+        end
+
+        assert [1, {x: 2}], pos(1, x: 2)
+    "#;
+    assert_script(program);
+}
+
+#[test]
+fn double_splat_argument() {
+    let program = r#"
+        def fn(a, k: 0, **opts)
+            [a, k, opts]
+        end
+
+        h = {k: 9, y: 8}
+        assert [1, 9, {y: 8}], fn(1, **h)
+        assert [1, 9, {y: 8, z: 0}], fn(1, **h, z: 0)
+        assert [1, 0, {}], fn(1, **nil)
+        assert [1, 0, {}], fn(1, **{})
+        assert({k: 9, y: 8}, h)
+
+        def pos(a, b = 1)
+            [a, b]
+        end
+
+        assert [1, 1], pos(1, **{})
+
+        class Opts
+            def to_hash
+                {k: 5}
+            end
+        end
+
+        assert [1, 5, {}], fn(1, **Opts.new)
+        assert_error { fn(1, **1) }
+    "#;
+    assert_script(program);
+}
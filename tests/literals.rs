@@ -75,6 +75,224 @@ fn array_lit1() {
 fn percent_notation() {
     let program = r#"
     assert(%w(We are the champions), ["We", "are", "the", "champions"])
+    assert(["a b", "c", "d"], %w[a\ b  c
+      d])
+    assert(["a", "{b}", "c"], %w{a {b} c})
+    assert(["a", "b"], %w<a b>)
+    assert(["a", "b"], %w|a b|)
+    assert([], %w())
+    assert(["a\\tb"], %w(a\tb))
+    x = 5
+    assert(["a5b", "c", "5"], %W(a#{x}b c #{x}))
+    assert(["a\tb", "1 2"], %W(a\tb #{[1, 2].join(" ")}))
+    assert([:foo, :bar], %i(foo bar))
+    assert([:a5, :b], %I(a#{x} b))
+    assert(:sym, %s(sym))
     "#;
     assert_script(program);
 }
+
+#[test]
+fn percent_string() {
+    let program = r#"
+    x = 5
+    assert("it's (nested) \\ )", %q(it's (nested) \\ \)))
+    assert("a\\nb", %q(a\nb))
+    assert("v=5 (y)\n", %Q(v=#{x} (y)\n))
+    assert("hello 5", %(hello #{x}))
+    assert("", %q())
+    assert("a!b", %Q!a\!b!)
+    "#;
+    assert_script(program);
+}
+
+#[test]
+fn percent_regexp() {
+    let program = r#"
+    x = 5
+    assert(1, "/a/b" =~ %r{a/b})
+    assert(0, "ABC" =~ %r(abc)i)
+    assert(0, "12-5" =~ %r{(\d+)-#{x}})
+    assert(["3-5"], "a3-5".scan(%r{\d-#{x}}))
+    "#;
+    assert_script(program);
+}
+
+#[test]
+fn char_lit() {
+    let program = r#"
+    assert("a", ?a)
+    assert("\n", ?\n)
+    assert("ab", ?a + ?b)
+    assert(["x", "y"], [?x, ?y])
+    assert("t", true ? ?t : ?f)
+    assert(["?"], [?? ])
+    "#;
+    assert_script(program);
+}
+
+#[test]
+fn integer_lit_radix() {
+    let program = "
+    assert(15, 0o17)
+    assert(15, 0O17)
+    assert(15, 017)
+    assert(15, 0_17)
+    assert(99, 0d99)
+    assert(31, 0X1f)
+    assert(5, 0B101)
+    assert(0, 0)
+    assert(0.5, 0.5)
+    assert_error { eval(\"019\") }
+    ";
+    assert_script(program);
+}
+
+#[test]
+fn numeric_lit_exponent_and_suffix() {
+    let program = "
+    assert(0.000000001, 1e-9)
+    assert(1000.0, 1E3)
+    assert(200.0, 2e+2)
+    assert(Rational(3, 2), 1.5r)
+    assert(Complex(0, 3), 3i)
+    assert(Complex(0, Rational(2, 1)), 2ri)
+    ";
+    assert_script(program);
+}
+
+#[test]
+fn end_of_program() {
+    let program = "
+    assert(3, 1 + 2)
+__END__
+    this is not a program
+    ";
+    assert_script(program);
+}
+
+#[test]
+fn data_after_end() {
+    let program = "x = DATA.gets
+assert(\"hello\\n\", x)
+assert(\"world\\n\", DATA.read)
+assert(true, DATA.eof?)
+__END__
+hello
+world
+";
+    let path = std::env::temp_dir().join("ruruby_data_after_end.rb");
+    std::fs::write(&path, program).unwrap();
+    let mut vm = VM::new();
+    let res = vm.run(path.clone(), program, None);
+    std::fs::remove_file(&path).unwrap();
+    if let Err(err) = res {
+        err.show_err();
+        panic!("Got error: {:?}", err);
+    }
+}
+
+#[test]
+fn string_interpolation_nested() {
+    let program = r##"
+    x = 5
+    assert("ab5cd", "a#{"b#{x}c"}d")
+    assert("x}y", "x#{"}"}y")
+    assert("[2, 4]", "#{[1, 2].map { |i| i * 2 }}")
+    assert("1", "#{{a: 1}[:a]}")
+    assert("A5", "#{"a#{x}".upcase}")
+    "##;
+    assert_script(program);
+}
+
+#[test]
+fn string_interpolation_variables() {
+    let program = r##"
+    @iv = 3
+    $gv = 4
+    assert("iv=3 gv=4", "iv=#@iv gv=#$gv")
+    assert("3!", "#@iv!")
+    assert("#@ #$ #", "#@ #$ #")
+    assert(0, "3" =~ /#@iv/)
+    "##;
+    assert_script(program);
+}
+
+#[test]
+fn string_escapes() {
+    let program = r##"
+    assert("😀", "\u{1F600}")
+    assert("AB", "\u{41 42}")
+    assert("A", "A")
+    assert("A", "\x41")
+    assert([7], "\x7".bytes)
+    assert("A", "\101")
+    assert([0], "\0".bytes)
+    assert([27, 32], "\e\s".bytes)
+    assert([1, 1, 127], "\C-a\ca\c?".bytes)
+    assert("\M-a", "\M-a")
+    assert("ab", "a\
+b")
+    assert("\x01", ?\C-a)
+    assert("\u3042", "\xE3\x81\x82")
+    assert("\u3042", "\343\201\202")
+    assert("a\u3042b", "a\xE3\x81\x82b")
+    assert("\u3042\u3044", "\xE3\x81\x82\u3044")
+    assert([225], "\M-a".bytes)
+    assert(1, "\M-a".size)
+    assert([225], "\xE1".bytes)
+    assert([97, 225, 98], "a\M-ab".bytes)
+    assert([225, 49], "\xE1#{1}".bytes)
+    "##;
+    assert_script(program);
+}
+
+#[test]
+fn string_adjacent_literals() {
+    let program = r##"
+    x = 5
+    assert("ab", "a" "b")
+    assert("ab5c", "a" "b" "#{x}" "c")
+    assert("5ab", "#{x}" "a" "b")
+    "##;
+    assert_script(program);
+}
+
+#[test]
+fn heredoc() {
+    let program = r##"
+    x = 5
+    s = <<EOS
+hello #{x}
+  world\tend
+EOS
+    assert("hello 5\n  world\tend\n", s)
+    s = <<-EOS
+    indented #{x}
+    EOS
+    assert("    indented 5\n", s)
+    s = <<~EOS
+      a
+        b #{x}
+
+      c
+    EOS
+    assert("a\n  b 5\n\nc\n", s)
+    s = <<~'EOS'
+      raw #{x}\n
+    EOS
+    assert("raw \#{x}\\n\n", s)
+    assert(["one\n", "two\n"], [<<~A, <<~B])
+      one
+    A
+      two
+    B
+    assert("xinner 5\ny", "x#{<<EOS}y")
+inner #{x}
+EOS
+    assert("UP\n", <<~EOS.upcase)
+      up
+    EOS
+    "##;
+    assert_script(program);
+}
@@ -229,7 +229,8 @@ fn objects() {
         assert(nil, Object.superclass)
         assert(Object, Module.superclass)
         assert(Module, Class.superclass)
-        assert(Object, Integer.superclass)
+        assert(Numeric, Integer.superclass)
+        assert(Object, Numeric.superclass)
         assert(Object, Regexp.superclass)
         assert(Object, String.superclass)
         assert(Object, Range.superclass)
@@ -458,6 +459,57 @@ fn until2() {
     assert_script(program);
 }
 
+#[test]
+fn and_or_not() {
+    let program = "
+        def f(x); x; end
+        x = f(nil) or 5
+        assert(nil, x)
+        y = f(3) and 7
+        assert(3, y)
+        assert(5, (f(nil) or 5))
+        assert(false, (not true))
+        assert(true, (not nil))
+        assert(true, (true and not false))
+        def g(v)
+          v > 2 and return :big
+          :small
+        end
+        assert(:big, g(3))
+        assert(:small, g(1))
+    ";
+    assert_script(program);
+}
+
+#[test]
+fn redo() {
+    let program = "
+        i = 0
+        cnt = 0
+        while i < 3
+          i += 1
+          cnt += 1
+          redo if cnt == 2
+        end
+        assert([3, 3], [i, cnt])
+        n = 0
+        assert([2, 6], [1, 2].map { |v| n += 1; redo if n == 1; v * n })
+        assert_error { eval(\"redo\") }
+        assert_error { eval(\"retry\") }
+    ";
+    assert_script(program);
+}
+
+#[test]
+fn begin_end_block() {
+    let program = "
+        assert(1, $a)
+        BEGIN { $a = 1 }
+        assert_error { eval(\"def foo; BEGIN { 1 }; end\") }
+    ";
+    assert_script(program);
+}
+
 #[test]
 fn case0() {
     let program = "
@@ -497,6 +549,226 @@ fn case1() {
     assert_script(program);
 }
 
+#[test]
+fn case2() {
+    let program = "
+        module Greet; end
+        class Foo; include Greet; end
+        def kind(x)
+          case x
+          when 1..5 then :small
+          when ->(v) { v.is_a?(Integer) && v > 100 } then :large
+          when Integer then :integer
+          when String, Array then :text
+          when Greet then :greet
+          when /ab/ then :regexp
+          else :other
+          end
+        end
+        assert :small, kind(3)
+        assert :large, kind(200)
+        assert :integer, kind(50)
+        assert :text, kind(\"x\")
+        assert :text, kind([])
+        assert :greet, kind(Foo.new)
+        assert :other, kind(nil)
+        assert true, (1..5) === 5
+        assert false, (1...5) === 5
+        assert true, Integer === 3
+        assert true, Greet === Foo.new
+        assert true, Foo.new.is_a?(Greet)
+    ";
+    assert_script(program);
+}
+
+#[test]
+fn pattern_match_case_in() {
+    let program = "
+        def m(v)
+          case v
+          in Integer | Float => n if n > 100
+            [:big, n]
+          in Integer => n
+            [:int, n]
+          in []
+            :empty
+          in [1, *rest]
+            [:one, rest]
+          in [x, y]
+            [:pair, x, y]
+          in [*, :needle, *post]
+            [:found, post]
+          in {name: String => name, age: 18.. => age}
+            [:adult, name, age]
+          in {name:, **nil}
+            [:only_name, name]
+          in {type: :point, **rest}
+            [:point, rest]
+          in {}
+            :empty_hash
+          in nil
+            :nil
+          in \"str\" | :sym
+            :lit
+          else
+            :other
+          end
+        end
+        assert [:big, 500], m(500)
+        assert [:int, 5], m(5)
+        assert :empty, m([])
+        assert [:one, [2, 3]], m([1, 2, 3])
+        assert [:pair, 4, 5], m([4, 5])
+        assert [:found, [9]], m([3, 4, :needle, 9])
+        assert [:adult, \"Ann\", 20], m({name: \"Ann\", age: 20})
+        assert [:only_name, \"Bob\"], m({name: \"Bob\"})
+        assert :other, m({name: \"Bob\", age: 3})
+        assert [:point, {x: 1}], m({type: :point, x: 1})
+        assert :empty_hash, m({})
+        assert :nil, m(nil)
+        assert :lit, m(:sym)
+        assert :other, m(:zzz)
+        x = 5
+        assert :pinned, (case 5 in ^x then :pinned end)
+        assert :guard, (case 7 in Integer unless 7 > 10 then :guard end)
+        case [1, [2, 3]]
+        in [a, [b, c]]
+          assert [1, 2, 3], [a, b, c]
+        end
+        case {a: 1, b: 2}
+        in a: Integer => q, **others
+          assert [1, {b: 2}], [q, others]
+        end
+        case [1, 2, 3]
+        in Integer, *tail
+          assert [2, 3], tail
+        end
+        assert_error { case 5; in String; end }
+    ";
+    assert_script(program);
+}
+
+#[test]
+fn pattern_match_deconstruct() {
+    let program = "
+        class Point
+          attr_reader :x, :y
+          def initialize(x, y); @x = x; @y = y; end
+          def deconstruct; [x, y]; end
+          def deconstruct_keys(keys); {x: x, y: y}; end
+        end
+        case Point.new(1, 2)
+        in Point(x:, y: 2)
+          assert 1, x
+        end
+        case Point.new(1, 2)
+        in Point[a, b]
+          assert [1, 2], [a, b]
+        end
+        assert false, (Point.new(1, 2) in Point(_, 3))
+        assert false, (5 in [_])
+    ";
+    assert_script(program);
+}
+
+#[test]
+fn pattern_match_standalone() {
+    let program = "
+        assert true, (1 in Integer)
+        assert false, (\"a\" in Integer)
+        {name: \"Z\", roles: [:admin]} => {name:, roles: [*, :admin, *]}
+        assert \"Z\", name
+        [1, [2, 3]] => [_, [_, last]]
+        assert 3, last
+        assert_error { 5 => String }
+    ";
+    assert_script(program);
+}
+
+#[test]
+fn user_defined_eq() {
+    let program = "
+        class Point
+          attr_reader :x, :y
+          def initialize(x, y); @x = x; @y = y; end
+          def ==(other); x == other.x && y == other.y; end
+          def eql?(other); self == other; end
+          def hash; x * 31 + y; end
+        end
+        class Even
+          def ===(other); other % 2 == 0; end
+        end
+        assert true, Point.new(1, 2) == Point.new(1, 2)
+        assert false, Point.new(1, 2) != Point.new(1, 2)
+        assert true, Point.new(1, 2) != Point.new(2, 1)
+        assert true, [Point.new(1, 2)] == [Point.new(1, 2)]
+        assert true, [Point.new(1, 2)].include?(Point.new(1, 2))
+        r = case 4
+            when Even.new then :even
+            else :odd
+            end
+        assert :even, r
+        h = {}
+        h[Point.new(1, 2)] = :a
+        assert :a, h[Point.new(1, 2)]
+        assert nil, h[Point.new(2, 1)]
+        o = Object.new
+        h[o] = :o
+        assert :o, h[o]
+        assert nil, h[Object.new]
+        h = {Point.new(1, 2) => 1, Point.new(1, 2) => 2}
+        assert 1, h.size
+        assert 2, h[Point.new(1, 2)]
+        assert true, h.key?(Point.new(1, 2))
+        assert 2, h.fetch(Point.new(1, 2))
+        assert 2, h.delete(Point.new(1, 2))
+        assert 0, h.size
+        h = {[Point.new(1, 2)] => :ary, {Point.new(3, 4) => 5} => :hash}
+        assert :ary, h[[Point.new(1, 2)]]
+        assert :hash, h[{Point.new(3, 4) => 5}]
+        assert nil, h[[Point.new(2, 1)]]
+        assert true, {Point.new(1, 2) => 1} == {Point.new(1, 2) => 1}
+        assert false, {Point.new(1, 2) => 1} == {Point.new(2, 1) => 1}
+        assert true, {Point.new(1, 2) => 1}.send(:\"==\", {Point.new(1, 2) => 1})
+        class Money
+          attr_reader :v
+          def initialize(v); @v = v; end
+          def ==(other); other == v; end
+        end
+        assert true, Money.new(1) == 1
+        assert false, Money.new(1) != 1
+        assert true, Money.new(1) != 2
+        assert true, Money.new(nil) == nil
+        assert true, [Money.new(1)] == [1]
+        assert true, {a: Money.new(1)} == {a: 1}
+    ";
+    assert_script(program);
+}
+
+#[test]
+fn user_defined_hash_error() {
+    let mut ruby = ruruby::Ruby::new();
+    ruby.eval(
+        "
+        class Foo
+          def hash; raise \"hash\"; end
+        end
+        class Bar
+          def hash; 1; end
+          def eql?(other); raise \"eql?\"; end
+        end
+        ",
+    )
+    .unwrap();
+    assert!(ruby.eval("{Foo.new => 1}").is_err());
+    assert!(ruby.eval("h = {}; h[Foo.new] = 1").is_err());
+    assert!(ruby.eval("{}[Foo.new]").is_err());
+    assert!(ruby.eval("{[Foo.new] => 1}").is_err());
+    assert!(ruby.eval("{Bar.new => 1, Bar.new => 2}").is_err());
+    assert!(ruby.eval("{Bar.new => 1}.key?(Bar.new)").is_err());
+    assert_eq!(3, ruby.eval_as::<i64>("1 + 2").unwrap());
+}
+
 #[test]
 fn block_break() {
     let program = "
@@ -544,6 +816,47 @@ fn proc_return() {
     assert_script(program);
 }
 
+#[test]
+fn block_pass() {
+    let program = "
+        def yielder(x)
+            yield x
+        end
+        def takes(&block)
+            block
+        end
+        class Doubler
+            def to_proc
+                Proc.new { |x| x * 2 }
+            end
+        end
+        assert(8, yielder(4, &Doubler.new))
+        assert([2, 4], [1, 2].map(&Doubler.new))
+        assert(nil, takes)
+        assert(nil, takes(&nil))
+        pr = takes { |x| x + 1 }
+        assert(Proc, pr.class)
+        assert(6, pr.call(5))
+        assert(pr, takes(&pr))
+        class Counter
+            def initialize
+                @count = 10
+            end
+            def counter
+                Proc.new { |x| @count += x }
+            end
+        end
+        counter = Counter.new.counter
+        assert(13, yielder(3, &counter))
+        assert(15, counter.call(2))
+        base = 100
+        add = Proc.new { |x| base + x }
+        assert(107, yielder(7, &add))
+        assert_error { yielder(1, &Object.new) }
+        ";
+    assert_script(program);
+}
+
 #[test]
 fn local_var1() {
     let program = "
@@ -645,6 +958,233 @@ fn mul_assign7() {
     assert_script(program);
 }
 
+#[test]
+fn mul_assign_nested() {
+    let program = "
+        a, (b, c), *d = 1, [2, 3], 4, 5
+        assert([1, 2, 3, [4, 5]], [a, b, c, d])
+        first, *rest = [1, 2, 3]
+        assert([1, [2, 3]], [first, rest])
+        *init, last = [1, 2, 3]
+        assert([[1, 2], 3], [init, last])
+        a, *b, c = 1
+        assert([1, [], nil], [a, b, c])
+        x, = [9, 8]
+        assert(9, x)
+        h, * = [3, 4]
+        assert(3, h)
+        *s = 1
+        assert([1], s)
+        *s = [1, 2]
+        assert([1, 2], s)
+        (m, n), o = [5, 6], 7
+        assert([5, 6, 7], [m, n, o])
+        a, (b, (c, *d)) = 0, [1, [2, 3, 4]]
+        assert([0, 1, 2, [3, 4]], [a, b, c, d])
+        d = (a, *b = 1, 2, 3)
+        assert([1, 2, 3], d)
+        ";
+    assert_script(program);
+}
+
+#[test]
+fn mul_assign_to_ary() {
+    let program = "
+        class Foo
+          def to_ary
+            [7, 8]
+          end
+        end
+        class Bar
+          def to_ary
+            1
+          end
+        end
+        a, b = Foo.new
+        assert([7, 8], [a, b])
+        a, (b, c) = 1, Foo.new
+        assert([1, 7, 8], [a, b, c])
+        a, b = 5
+        assert([5, nil], [a, b])
+        assert_error { a, b = Bar.new }
+        ";
+    assert_script(program);
+}
+
+#[test]
+fn block_param_destructuring() {
+    let program = "
+        x = []
+        [[[1, 2], 3]].each { |(k, v), i| x << [k, v, i] }
+        [[1, [2, 3]]].each { |a, (b, c)| x << [a, b, c] }
+        [[1, [2, 3]]].each { |a, (b)| x << [a, b] }
+        [[1, 2, 3, [4, 5]]].each { |a, *b, (c, d)| x << [a, b, c, d] }
+        {a: 1}.each_with_index { |(k, v), i| x << [k, v, i] }
+        assert([[1, 2, 3], [1, 2, 3], [1, 2], [1, [2, 3], 4, 5], [:a, 1, 0]], x)
+        def m((a, b), c)
+          [a, b, c]
+        end
+        assert([1, 2, 3], m([1, 2], 3))
+        ";
+    assert_script(program);
+}
+
+#[test]
+fn block_param_array_expansion() {
+    let program = "
+        class Foo
+          def to_ary
+            [7, 8]
+          end
+        end
+        def f(x)
+          yield x
+        end
+        assert([1, [2, 3]], f([1, 2, 3]) { |x, *y| [x, y] })
+        assert([[1, 2, 3]], f([1, 2, 3]) { |*y| y })
+        assert([1, 2], f([1, 2, 3]) { |x, y = 5| [x, y] })
+        assert([1, 5], f([1]) { |x, y = 5| [x, y] })
+        assert([1, 2, 3], f([1, 2, 3]) { |x| x })
+        assert([7, 8], f(Foo.new) { |a, b| [a, b] })
+        assert([4, 5], proc { |a, b| [a, b] }.call([4, 5]))
+        assert([[4, 5], nil], lambda { |a, b = nil| [a, b] }.call([4, 5]))
+        x = []
+        [Foo.new, [1, 2, 3], 4].each { |a, b| x << [a, b] }
+        assert([[7, 8], [1, 2], [4, nil]], x)
+        x = []
+        [[1, 2], [3]].each { |a, *b| x << [a, b] }
+        assert([[1, [2]], [3, []]], x)
+        ";
+    assert_script(program);
+}
+
+#[test]
+fn method_name() {
+    let program = "
+    def foo
+      [1].map { __method__ }[0]
+    end
+    assert(:foo, foo)
+    assert(nil, __method__)
+    ";
+    assert_script(program);
+}
+
+#[test]
+fn global_var_special() {
+    let program = r#"
+    assert(true, $:.equal?($LOAD_PATH))
+    assert(true, $".equal?($LOADED_FEATURES))
+    assert(true, $PROGRAM_NAME.equal?($0))
+    assert(false, $DEBUG)
+    assert("\n", $/)
+    assert(nil, $,)
+    assert(nil, $!)
+    assert(nil, $@)
+    $VERBOSE = 1
+    assert(true, $VERBOSE)
+    assert(true, $-w)
+    $VERBOSE = nil
+    assert(nil, $-v)
+    assert_error { $: = [] }
+    assert_error { $LOADED_FEATURES = [] }
+    assert_error { $$ = 1 }
+    assert_error { $! = 1 }
+    assert_error { $@ = [] }
+    assert_error { $/ = 1 }
+    assert_error { $0 = 1 }
+    assert_error { $~ = 1 }
+    assert_error { $stdout = 1 }
+    "#;
+    assert_script(program);
+}
+
+#[test]
+fn global_var_stdout() {
+    let program = r#"
+    class Buffer
+      attr_reader :buf
+      def initialize
+        @buf = ""
+      end
+      def write(s)
+        @buf += s
+        s.size
+      end
+    end
+    b = Buffer.new
+    $stdout = b
+    puts "foo", [1, 2]
+    print "bar", "baz"
+    $, = "-"
+    $\ = "!"
+    print "a", "b"
+    $, = nil
+    $\ = nil
+    p :sym
+    $stdout = STDOUT
+    assert("foo\n1\n2\nbarbaza-b!:sym\n", b.buf)
+    "#;
+    assert_script(program);
+}
+
+#[test]
+fn global_var_trace() {
+    let program = r#"
+    log = []
+    trace_var(:$foo) { |v| log << v }
+    pr = proc { |v| log << v * 10 }
+    trace_var(:$foo, pr)
+    $foo = 1
+    assert([1, 10], log)
+    assert([pr], untrace_var(:$foo, pr))
+    $foo = 2
+    assert([1, 10, 2], log)
+    assert(1, untrace_var(:$foo).size)
+    $foo = 3
+    assert([1, 10, 2], log)
+    "#;
+    assert_script(program);
+}
+
+#[test]
+fn require_load_autoload() {
+    let dir = std::env::temp_dir().join("ruruby_require_test");
+    std::fs::create_dir_all(dir.join("lib")).unwrap();
+    for (name, body) in &[
+        ("lib/feature.rb", "$feature = ($feature || 0) + 1"),
+        ("lib/auto.rb", "class Auto; def hi; 7; end; end"),
+        ("script.rb", "$script = ($script || 0) + 1; SCRIPT = 3"),
+    ] {
+        std::fs::write(dir.join(name), body).unwrap();
+    }
+    let program = format!(
+        r#"
+    dir = "{}"
+    assert_error {{ require "feature" }}
+    assert(false, require("fileutils"))
+    assert(false, require("open3.rb"))
+    $LOAD_PATH << dir + "/lib"
+    assert(true, require("feature"))
+    assert(false, require("feature"))
+    assert(false, require("feature.rb"))
+    assert(1, $feature)
+    assert(true, $LOADED_FEATURES.include?(dir + "/lib/feature.rb"))
+    assert_error {{ require "script.rb" }}
+    assert(true, load(dir + "/script.rb"))
+    load(dir + "/script.rb", true)
+    assert(2, $script)
+    assert_error {{ load "script" }}
+    autoload(:Auto, "auto")
+    assert("auto", autoload?(:Auto))
+    assert(7, Auto.new.hi)
+    assert(nil, autoload?(:Auto))
+    "#,
+        dir.canonicalize().unwrap().to_string_lossy()
+    );
+    assert_script(program);
+}
+
 #[test]
 fn const1() {
     let program = "
@@ -927,6 +1467,32 @@ fn closure3() {
     assert_script(program);
 }
 
+#[test]
+fn closure4() {
+    let program = "
+        def yield_twice
+            x = 1000
+            yield
+            f = Proc.new { x = x + 1 }
+            yield
+            f.call
+            x
+        end
+        def func
+            a = 1
+            res = yield_twice {
+                a = a + 1
+                g = Proc.new { a = a + 10 }
+                g.call
+            }
+            assert 23, a
+            res
+        end
+        assert 1001, func
+    ";
+    assert_script(program);
+}
+
 #[test]
 fn method_chain1() {
     let program = "
@@ -992,6 +1558,91 @@ fn assign_op() {
     assert_script(program);
 }
 
+#[test]
+fn assign_op_lvalues() {
+    let program = "
+        $calls = 0
+        def key; $calls += 1; :a; end
+        h = {}
+        assert [], h[key] ||= []
+        assert [], h[key] ||= [1]
+        h[key] <<= 3
+        assert({a: [3]}, h)
+        assert 3, $calls
+        a = [1, 2]
+        a[0] += 10
+        a[1] &&= 7
+        a[5] &&= 1
+        assert [11, 7], a
+        class Foo
+          attr_accessor :bar
+        end
+        def foo; $calls += 1; Foo.new; end
+        $calls = 0
+        assert 5, foo.bar ||= 5
+        assert 1, $calls
+        f = Foo.new
+        f.bar ||= 5
+        f.bar ||= 6
+        f.bar += 1
+        assert 6, f.bar
+        @memo ||= [1]
+        @memo ||= [2]
+        assert [1], @memo
+        X ||= 3
+        X ||= 4
+        assert 3, X
+        module M; end
+        M::Y ||= 10
+        M::Y ||= 11
+        assert 10, M::Y
+        x = nil
+        x &&= 1
+        assert nil, x
+        y = 2
+        y &&= 3
+        assert 3, y
+        z ||= 9
+        assert 9, z
+        ";
+    assert_script(program);
+}
+
+#[test]
+fn safe_navigation() {
+    let program = "
+        class Foo
+          def bar; :bar; end
+        end
+        f = Foo.new
+        n = nil
+        assert :bar, f&.bar
+        assert nil, n&.bar
+        assert :BAR, f&.bar&.upcase
+        assert nil, n&.bar&.upcase
+        $calls = 0
+        def count; $calls += 1; end
+        n&.foo(count)
+        assert 0, $calls
+        assert 3, [1, 2, 3]&.size
+        class Foo
+          attr_accessor :x
+        end
+        assert nil, (n&.x = count)
+        assert nil, (n&.x ||= count)
+        assert nil, (n&.x += count)
+        n&.x = count
+        assert 0, $calls
+        assert 5, (f&.x = 5)
+        assert 5, (f&.x ||= 7)
+        assert 8, (f&.x += 3)
+        f&.x = 9
+        assert 9, f.x
+        assert_error { eval(\"n&.x, a = 1, 2\") }
+        ";
+    assert_script(program);
+}
+
 #[test]
 fn singleton() {
     let program = "